@@ -0,0 +1,78 @@
+//! Benchmarks for writing a PR's commit, which `jr create`, `jr update` and
+//! `jr restack` do once per PR.
+//!
+//! `direct` reads the jj commit's tree and writes the new commit as loose
+//! objects (see [`jr::clients::git_objects`]); `subprocess` runs `git
+//! rev-parse` and `git commit-tree` instead, as jr did before.  `setup` is
+//! the first commit of a run, which also sets up the direct writer, so is
+//! what a single-PR command pays.
+//!
+//! These only need git, in a repository with one commit.
+
+use std::path::Path;
+
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use jr::clients::git::CommitId;
+use jr::clients::git::GitClient;
+use tokio::process::Command;
+
+async fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .await
+        .expect("Failed to execute git");
+    assert!(output.status.success(), "git {} failed", args.join(" "));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+async fn write_commit(git: &GitClient, parent: &CommitId) -> CommitId {
+    let tree = git.get_tree(parent).await.expect("Failed to read tree");
+    git.commit_tree(&tree, vec![parent], "Message")
+        .await
+        .expect("Failed to write commit")
+}
+
+fn bench_commit(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start runtime");
+    let dir = tempfile::tempdir().expect("Failed to create directory");
+    let path = dir.path();
+    let head = runtime.block_on(async {
+        git(path, &["init", "-q"]).await;
+        git(path, &["config", "user.name", "Bench User"]).await;
+        git(path, &["config", "user.email", "bench@example.com"]).await;
+        git(path, &["commit", "-q", "--allow-empty", "-m", "Base"]).await;
+        CommitId(git(path, &["rev-parse", "HEAD"]).await)
+    });
+
+    let mut group = c.benchmark_group("commit");
+    let client = GitClient::new(path.to_path_buf());
+    group.bench_function("direct", |b| {
+        b.iter(|| runtime.block_on(write_commit(&client, &head)))
+    });
+    group.bench_function("subprocess", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let tree = git(path, &["rev-parse", &format!("{}^{{tree}}", head.0)]).await;
+                git(
+                    path,
+                    &["commit-tree", &tree, "-p", &head.0, "-m", "Message"],
+                )
+                .await
+            })
+        })
+    });
+    group.bench_function("setup", |b| {
+        b.iter(|| {
+            let client = GitClient::new(path.to_path_buf());
+            runtime.block_on(write_commit(&client, &head))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_commit);
+criterion_main!(benches);
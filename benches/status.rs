@@ -0,0 +1,90 @@
+//! Benchmarks for `jr status` and `jr submit`.
+//!
+//! For each commit in the stack, status normalizes and compares the commit's
+//! diff with its PR's diff; `compare_diffs` runs that over synthetic diffs.
+//! `load` elaborates a stack as status does, and `submit_plan` works out what
+//! `jr submit` would do, both in a fixture repository (see [`fixtures`]) with
+//! an offline GitHub client, so they include jj and git but not GitHub.
+//! Each runs over stacks of 5, 20 and 50 commits.
+//!
+//! `tests/budget.rs` fails if these get slower than their budgets.
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use jr::commands::submit::SubmitOptions;
+use jr::diff_utils::normalize_diff;
+use jr::fixtures;
+use jr::fixtures::Shape;
+use jr::stack::Stack;
+
+const STACK_SIZES: [usize; 3] = [5, 20, 50];
+
+fn bench_compare_diffs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("status");
+    for size in STACK_SIZES {
+        let diffs = fixtures::synthetic_diffs(size);
+        group.bench_with_input(
+            BenchmarkId::new("compare_diffs", size),
+            &diffs,
+            |b, diffs| {
+                b.iter(|| {
+                    diffs
+                        .iter()
+                        .filter(|(commit_diff, pr_diff)| {
+                            normalize_diff(commit_diff) == normalize_diff(pr_diff)
+                        })
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_fixture(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start runtime");
+    let mut group = c.benchmark_group("status");
+    // Each iteration runs jj and git many times
+    group.sample_size(10);
+    for size in STACK_SIZES {
+        let dir = tempfile::tempdir().expect("Failed to create directory");
+        let fixture = runtime
+            .block_on(fixtures::build(dir.path(), &Shape::linear(size)))
+            .expect("Failed to build fixture");
+        let app = fixture.offline_app("bench/").expect("Failed to create app");
+        let commits = runtime
+            .block_on(app.jj.get_stack_ancestors("@-"))
+            .expect("Failed to list the stack");
+
+        group.bench_with_input(BenchmarkId::new("load", size), &commits, |b, commits| {
+            b.iter(|| {
+                runtime
+                    .block_on(Stack::load(
+                        commits.clone(),
+                        &app.config,
+                        &app.jj,
+                        &app.gh,
+                        &app.git,
+                    ))
+                    .expect("Failed to load the stack")
+            })
+        });
+        group.bench_function(BenchmarkId::new("submit_plan", size), |b| {
+            b.iter(|| {
+                runtime
+                    .block_on(app.cmd_submit(
+                        "::@-",
+                        &SubmitOptions::default(),
+                        &mut std::io::sink(),
+                    ))
+                    .expect("Failed to plan the submit")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_compare_diffs, bench_fixture);
+criterion_main!(benches);
@@ -0,0 +1,444 @@
+# Messages shown to jr users, keyed by stable message ID.
+#
+# Translations live in `<locale>.ftl` files in the directory named by
+# `jr.localeDir`; any message they don't define falls back to this file.
+
+## Configuration
+
+config-not-found = Config not found in .git/config. Run 'jr init' to create one.
+config-file-secret = { $key } can't be set in a config file, so it isn't committed by mistake; set it with 'jr init' instead
+config-token-not-found = GitHub token not found in $JR_GITHUB_TOKEN, $GITHUB_TOKEN, git config jr.githubToken or gh auth token. Run 'jr init' or 'jr token set' to configure one, or log in with 'gh auth login'.
+config-default-branch-not-found = Default branch not found in .git/config. Run 'jr init' to configure.
+config-save-failed = Failed to save { $key } to .git/config
+checkout-pr-message = Review #{ $number }: { $title }
+checkout-pr-done = Now at { $change }, with the changes from #{ $number } on top of { $base }. Run 'jj abandon' when you're done.
+config-invalid = Invalid jr configuration:
+config-invalid-prefix = Branch prefix '{ $prefix }' { $reason }. Fix it with: git config jr.githubBranchPrefix <prefix>/
+config-prefix-no-slash = Branch prefix '{ $prefix }' doesn't end with '/'. Fix it with: git config jr.githubBranchPrefix '{ $fixed }'
+config-default-branch-empty = Default branch is empty. Fix it with: git config jr.defaultBranch main
+config-invalid-default-branch = Default branch '{ $branch }' { $reason }. Fix it with: git config jr.defaultBranch <branch>
+config-default-branch-missing = Default branch '{ $branch }' doesn't exist on { $remote }. Run 'git fetch', push it if the repository is new, or base PRs on another branch with: git config jr.defaultBranch <branch>
+default-branch-push-prompt = Default branch '{ $branch }' doesn't exist on { $remote } yet. Push { $commit } to create it?
+default-branch-pushed = Pushed '{ $branch }' to { $remote }
+repo-moved = { $repo } has moved to { $moved_to } on GitHub
+repo-moved-prompt = Point remote '{ $remote }' at { $url }?
+repo-moved-updated = Updated remote '{ $remote }'
+repo-moved-hint = { $repo } has moved to { $moved_to } on GitHub. Point remote '{ $remote }' at it with: git remote set-url { $remote } { $url }
+first-push-summary =
+    This is jr's first push from this clone. It will push PR branches to:
+      remote:        { $remote } ({ $url })
+      repository:    { $repo }
+      branch prefix: { $prefix }
+first-push-prompt = Push PR branches there?
+first-push-unconfirmed = jr hasn't pushed PR branches to { $remote } ({ $url }) from this clone yet, and can't ask here. Run 'jr push' once to confirm, or restart with 'jr --yes mcp --allow-writes'.
+first-push-declined = Not pushing; check the remote and jr.githubBranchPrefix, or pass --yes to confirm without asking
+config-invalid-protect = jr.protectBranch '{ $pattern }' isn't a valid regex: { $error }
+config-not-number = { $key } must be a number
+config-not-seconds = { $key } must be a number of seconds
+config-not-days = { $key } must be a number of days
+config-not-lines = { $key } must be a number of lines
+config-not-project = { $key } must be a project number
+config-not-bool = { $key } must be true or false
+config-invalid-merge-method = jr.mergeMethod must be one of squash, merge or rebase
+config-invalid-auto-ready = jr.autoReady must be one of off, approved or merged
+config-invalid-policy = { $key } must be one of block, warn or off
+config-invalid-pair = jr.pair '{ $value }' must be '<branch prefix> <email>'
+config-invalid-revset-alias = jr.revsetAlias '{ $value }' must be 'name = revset'
+config-token-empty = GitHub token is empty. Run 'jr init' to configure one.
+config-write-token-invalid = GitHub write token (jr.githubWriteToken or $JR_GITHUB_WRITE_TOKEN) is empty or contains whitespace.
+config-token-whitespace = GitHub token contains whitespace; it was probably pasted with extra characters. Run 'jr init' to configure it again.
+trunk-not-found = Couldn't find the trunk commit with revset '{ $revset }', so jr can't tell where your stacks start. Fix it with: git config jr.trunkRevset <revision>, e.g. main@origin, or pass --trunk <revision>
+trunk-ambiguous = The trunk revset '{ $revset }' matches { $count } commits, but it must match one. Fix it with: git config jr.trunkRevset <revision>, or pass --trunk <revision>
+dashboard-no-stacks = No stacks
+dashboard-stack =
+    Stack { $head } ({ $count } { $count ->
+        [one] commit
+       *[other] commits
+    })
+dashboard-ci-pending = CI running
+dashboard-ci-passed = CI passed
+dashboard-ci-failed = CI failed
+create-fork-stacked = PRs from a fork can only target the default branch, since GitHub PRs can't target branches in a fork. Create a PR for the parent commit and wait for it to merge first.
+describe-command-failed = jr.describeCommand failed, so there's no suggested message: { $error }
+message-edit-hint =
+    Enter a message for these changes: a title line, then a blank line and a body.
+    Lines starting with '#' are ignored, and an empty message aborts.
+message-empty = Aborting because the message is empty
+fork-found = Using fork { $repo }
+fork-remote-added = Added remote '{ $remote }' for { $url }
+fork-done = PR branches will be pushed to '{ $remote }'
+doctor-config = Configuration
+doctor-jj = Jujutsu repository with a trunk commit
+doctor-token = GitHub token (authenticated as { $login })
+doctor-token-failed = GitHub token
+doctor-repository = GitHub repository { $repo }
+doctor-failed =
+    Found { $count } { $count ->
+        [one] problem
+       *[other] problems
+    }
+init-detected = Detected { $visibility } repository { $repo }
+init-no-gh-token = No 'gh' login found. Run 'gh auth login' first, or run 'jr init' without --from-gh.
+init-saved = Configuration saved to .git/config
+init-saved-files = Configuration saved to .jr.toml (commit it to share it) and ~/.config/jr/config.toml
+init-token-saved = Token saved to { $destination }
+
+## Policies
+
+policy-blocked = { $violation } (override with --override { $policy })
+policy-warning = { $violation } (policy '{ $policy }')
+parent-no-pr = Parent commit has no PR branch. Create parent PR first (bottom-up).
+parent-needs-restack = Cannot update PR: parent PR needs restacking. Its base branch has been updated. Run 'jr restack' on the parent first.
+parent-out-of-date = Cannot update PR: parent PR is out of date. Update parent PRs first (starting from the bottom of the stack).
+
+## Pull requests
+
+repository-archived = Repository { $repo } is archived; jr cannot create or update PRs here.
+repository-disabled = Repository { $repo } is disabled; jr cannot create or update PRs here.
+create-on-trunk = Revision { $revision } is on trunk, so there's no change to create a PR for. Start a stack with `jj new`, then run 'jr create'.
+pr-on-trunk-adopt = Revision { $revision } is on trunk, so there's no PR to adopt; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-draft = Revision { $revision } is on trunk, so there's no PR to convert to a draft; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-edit-base = Revision { $revision } is on trunk, so there's no PR to change the base of; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-graph = Revision { $revision } is on trunk, so there's no PR to graph; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-label = Revision { $revision } is on trunk, so there's no PR to label; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-merge = Revision { $revision } is on trunk, so there's no PR to merge; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-open = Revision { $revision } is on trunk, so there's no PR to open; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-ready = Revision { $revision } is on trunk, so there's no PR to mark ready; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-restack = Revision { $revision } is on trunk, so there's no PR to restack; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-submit = Revision { $revision } is on trunk, so there's no PR to submit; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-sync = Revision { $revision } is on trunk, so there's no PR to sync; it may already have been merged. Run 'jr status' to see your stack.
+pr-on-trunk-update = Revision { $revision } is on trunk, so there's no PR to update; it may already have been merged. Run 'jr status' to see your stack.
+change-divergent = Change { $change } is divergent: it has more than one visible commit, so jr can't tell which one belongs in its PR. Abandon or squash the extra commits (see `jj log -r 'change_id({ $change })'`), then try again.
+provenance-failed = Couldn't record which change the PR branch commit came from: { $error }
+why-provenance =
+    Synthesized from change { $change }, commit { $commit },
+    at jj operation { $operation } (see it with 'jj op show { $operation }')
+why-no-provenance = No jr provenance recorded for { $revision }. Fetch notes pushed from elsewhere with: git fetch { $remote } refs/notes/jr:refs/notes/jr
+why-conflicts = GitHub can't merge { $url } into { $base }; these files conflict:
+why-conflicts-not-local = GitHub can't merge { $url } into { $base }, but it merges cleanly here; run 'jj git fetch' and try again
+commit-already-merged = Commit { $commit } is an ancestor of trunk; this commit is already merged.
+pr-title-too-long = The commit title is { $length } characters long, but GitHub allows at most { $max } in a PR title. Shorten it with 'jj describe', then try again.
+pr-body-truncated = *This description was truncated, as it's longer than GitHub allows. The full text is in the comments below.*
+pr-body-part = **Full description, part { $part } of { $count }**
+stack-comment-heading = Stack, from top to bottom:
+stack-comment-failed = Failed to update the stack comments on PRs: { $error }
+pr-body-overflow =
+    The description was too long for GitHub, so it's truncated, with the full text in { $count } { $count ->
+        [one] comment
+       *[other] comments
+    }.
+pr-created = Created PR: { $url }
+create-stack-created =
+    Created { $count } { $count ->
+        [one] PR
+       *[other] PRs
+    }:
+create-stack-none = Every commit in the stack already has a PR
+pr-created-ready = Draft PRs aren't supported in this repository, so it was created ready for review
+pr-updated = Updated PR: { $url }
+pr-prepared = Prepared branch { $branch }; push it and open or update its PR with: jr push
+push-nothing = No PR branches prepared with --no-push to push
+pr-branch-exists = PR branch already exists: { $branch }
+ping-nudged =
+    nudged { $reviewers } after { $days } { $days ->
+        [one] day
+       *[other] days
+    }
+ping-would-nudge =
+    would nudge { $reviewers } after { $days } { $days ->
+        [one] day
+       *[other] days
+    }
+ping-limit = Stopping after nudging { $max } PRs; the rest will be nudged on the next run
+ping-none =
+    No PRs have waited more than { $days } { $days ->
+        [one] day
+       *[other] days
+    } for review
+pr-tip-foreign = The tip of PR branch { $branch } was committed by { $committer }, not you. Fetch and incorporate their commits first, so they aren't overwritten.
+pr-exists-up-to-date = PR already exists and is up to date: { $url }
+pr-branch-missing = PR branch { $branch } does not exist. Use 'jr create' to create a new PR.
+pr-not-open = No open PR found for branch { $branch }. The PR may have been closed or merged.
+pr-size-over-budget = ⚠ This PR changes { $lines } lines (+{ $added } -{ $removed }), over jr.sizeBudget's { $budget }; consider splitting it into reviewable chunks
+pr-size-split = e.g. { $command }  ({ $lines } lines in { $group })
+pr-conflicts = ⚠ GitHub can't merge { $url } into its base because of conflicts; run 'jr why { $branch }' to see which files
+github-repo-moved = { $repo } has moved to { $moved_to } on GitHub, and can't be found by its old name. Run 'jr doctor' to see how to update the remote
+github-rate-limited = GitHub is rate limiting requests until { $until }; try again then, or lower jr.githubJobs
+pr-empty-description = Cannot create PR with empty description
+restack-local-changes =
+    Cannot restack: commit has local changes.
+    Use 'jr update -m "<message>"' to update with your changes.
+ready-done = Marked PR ready for review: { $url }
+ready-already = PR is already ready for review: { $url }
+sync-abandoned = Abandoned { $commit }, whose PR was merged: { $url }
+sync-rebased = Rebased the stack onto trunk ({ $commit }); run 'jr restack --all' to update its PRs
+sync-conflicts = The stack has conflicts after rebasing; resolve them, then run 'jr restack --all'
+sync-nothing = The stack is up to date with trunk, and no draft PRs in it can be marked ready yet
+draft-done = Converted PR to a draft: { $url }
+draft-already = PR is already a draft: { $url }
+draft-nothing = No PRs in the stack are ready for review, so there's nothing to convert
+open-opening = Opening { $url }
+open-failed = Failed to open { $url } in the browser; use --print to list the URLs instead
+open-nothing = No commits in the stack have PRs to open
+dry-run-create = Would push a new commit to { $branch } and open a PR against { $base }: { $title }
+dry-run-prepare = Would point the local branch { $branch } at a new commit
+dry-run-push = Would push a new commit to { $branch }
+dry-run-push-merge = Would push a commit merging { $base } into { $branch }
+dry-run-retarget = Would change the base of { $url } from { $old } to { $base }
+dry-run-plan = In all: { $pushes ->
+        [one] 1 push
+       *[other] { $pushes } pushes
+    } and about { $requests } GitHub requests, taking about { $duration }
+dry-run-plan-guess = In all: { $pushes ->
+        [one] 1 push
+       *[other] { $pushes } pushes
+    } and about { $requests } GitHub requests, taking about { $duration } (a guess until jr has timed a real run)
+adopt-superseded = Superseded by { $url }, which jr keeps up to date with this change.
+adopt-closed = Closed { $duplicate }, which duplicated { $url }
+adopt-deleted = Deleted branch { $branch }
+adopt-nothing = No branches pushed by `jj git push --change` duplicate the stack's PR branches
+graph-embedded = Embedded the diagram in { $url }
+graph-no-prs = No commits in the stack have PRs to embed the diagram in
+edit-base-done = Pointed { $url } at { $base }
+edit-base-missing = Branch '{ $branch }' doesn't exist on { $remote }
+label-synced = Updated the labels of { $url }
+label-nothing = The stack's PRs already have the configured labels
+label-none-configured = No labels are configured; add them with 'git config --add jr.label <label>'
+cleanup-deleted = Deleted { $branch } ({ $url })
+cleanup-would-delete = Would delete { $branch } ({ $url })
+cleanup-deleted-stale = Deleted { $branch } (no PR, last committed { $date })
+cleanup-would-delete-stale = Would delete { $branch } (no PR, last committed { $date })
+cleanup-protected = Keeping { $branch }, which jr.protectBranch protects
+cleanup-nothing = No branches of merged or closed PRs to delete
+age-invalid = '{ $age }' isn't an age like 12h, 30d or 2w
+gc-state = PR metadata: pruned { $pruned } of { $total } merged, closed or abandoned PRs ({ $before } → { $after })
+gc-audit =
+    Audit log: pruned { $pruned } of { $total } entries from over { $days } { $days ->
+        [one] day
+       *[other] days
+    } ago ({ $before } → { $after })
+gc-run = Run manifest: removed, as it can't be resumed ({ $size })
+gc-notes =
+    Provenance notes: pruned { $pruned } { $pruned ->
+        [one] note
+       *[other] notes
+    } on commits that no longer exist
+gc-dry-run = Nothing was changed, as this is a dry run
+ready-parent-draft = Parent PR { $url } is still a draft; mark it ready first so reviewers get the stack bottom-up
+restack-base-unchanged = Base hasn't changed; no need to restack
+restack-all-changed = Commit { $commit } has changes since its PR was updated, so the PRs above it can't be restacked; run 'jr update -r { $commit }', then 'jr restack --all' again
+restack-all-no-pr = Commit { $commit } has no PR, so the PRs above it can't be restacked; run 'jr create -r { $commit }', then 'jr restack --all' again
+submit-since-none = None of the commits in '{ $revset }' were modified after or descend from { $since }
+submit-not-contiguous = The commits in '{ $revset }' aren't a contiguous range of one stack, like a::c; submit each run of them separately
+restack-all-none = All PRs in the stack are up to date; nothing to restack
+update-no-changes = No changes detected
+update-commit-unchanged = Commit unchanged; use 'jr restack' instead
+merge-method-not-allowed = This repository doesn't allow the merge method set by jr.mergeMethod, { $method }; it only allows: { $allowed }
+merge-no-methods = This repository doesn't allow any merge methods
+merge-multiple-roots = Stack has more than one bottom commit; merge one of them with -r
+merge-queue = { $branch } uses a merge queue, which jr can't merge through; add PR { $url } to the queue on GitHub
+merge-draft = PR { $url } is a draft; mark it ready with 'jr ready' first
+merge-not-synced = PR { $url } isn't up to date with its commit; run 'jr status' and update or restack it first
+merge-done = Merged PR ({ $method }): { $url }
+merge-cascade-conflict = Commit { $commit } has conflicts after rebasing; resolve them and 'jr update' its PR, then continue with 'jr merge --resume'
+merge-cascade-stopped = Commit { $commit } has changes since its PR was updated; 'jr update' its PR, then continue with 'jr merge --resume'
+run-resume-none = There's no interrupted 'jr { $command }' to resume
+run-resume-other = The interrupted run was a 'jr { $other }', not a 'jr { $command }'; resume it with 'jr { $other } --resume'
+notify-failed = Failed to send webhook notification: { $error }
+
+## Bisect
+
+bisect-on-trunk = Revision { $revision } is on trunk, so there's no stack to bisect.
+bisect-testing = Testing { $position }/{ $total } { $change } { $title }…
+bisect-passed = passed
+bisect-failed = failed
+bisect-top-passes = The top of the stack passes, so there's nothing to bisect.
+bisect-first-failing = First failing commit: { $position }/{ $total } { $change } { $title }
+bisect-ci-timeout = Gave up waiting for CI on { $commit } after { $minutes } minutes. Try again with a longer --timeout, or use --command to test locally.
+
+## Server
+
+serve-listening = Serving jr over JSON-RPC at { $url } (press Ctrl-C to stop)
+serve-token = Clients must send the header: Authorization: Bearer { $token }
+
+## Code owners
+
+owners-expected-reviewers = Expected reviewers: { $owners }
+pr-milestone-failed = Couldn't assign the PR to milestone { $milestone }: { $error }
+pr-project-failed = Couldn't add the PR to project { $project }: { $error }
+pr-participants-failed = Couldn't request reviews or add labels and assignees: { $error }
+pr-owners-request-failed = Couldn't request reviews from the code owners: { $error }
+owners-no-codeowners = No CODEOWNERS file found
+owners-none = (no owners)
+
+## Files
+
+files-in-stack = also in { $commits }
+files-in-prs =
+    also in open { $count ->
+        [one] PR
+       *[other] PRs
+    } { $prs }
+
+diff-stack-heading =
+    Combined diff of { $count } { $count ->
+        [one] commit
+       *[other] commits
+    }, from the bottom up:
+diff-empty-stack = There are no commits in the stack to diff
+
+## Navigation
+
+navigate-at-top = Already at the top of the stack
+navigate-at-bottom = Already at the bottom of the stack
+navigate-ambiguous = Multiple candidate commits; use 'jj edit' to pick one of: { $candidates }
+navigate-now-at = Now at: { $commit }
+
+## Status
+
+status-on-trunk = You're on trunk — no stack here. Start one with `jj new`.
+status-watch-header = Every { $interval }s, last at { $time }; Ctrl-C to stop
+status-no-stacks = No stacks here: every commit is on trunk or immutable.
+status-stack-header = Stack { $number } of { $count }: { $title }
+status-read-only = Repository { $repo } is read-only (archived or disabled); this stack can't be updated.
+status-jj-push-duplicates = { $count ->
+        [one] A change in the stack also has a branch
+       *[other] { $count } changes in the stack also have branches
+    } pushed by `jj git push --change`; run `jr adopt --from-jj-push` to keep only jr's
+status-multiple-heads = Warning: Multiple stack heads detected. Showing stack from rev to trunk.
+status-external-base-changed = Externally modified: { $actor } changed the base branch to { $base } on { $date }
+status-external-draft = Externally modified: { $actor } converted it to a draft on { $date }
+status-external-ready = Externally modified: { $actor } marked it ready for review on { $date }
+status-external-force-pushed = Externally modified: { $actor } force-pushed the PR branch on { $date }
+status-divergent = divergent change; resolve it before updating this PR
+status-branches-from = ╰─ branches from { $commit }
+stack-over-limit = Stack has { $count } commits, more than the limit of { $limit } (jr.stackLimit). Raise it with --limit, or use 'jr status --depth' to look at part of the stack.
+status-milestone = milestone: { $milestone }
+status-ready-before-parent = ready for review before its draft parent
+status-conflicts = ⚠ conflicts with base
+status-more-below =
+    … { $count } more { $count ->
+        [one] commit
+       *[other] commits
+    } below (not checked)
+status-more-below-synced =
+    … { $count } more synced { $count ->
+        [one] commit
+       *[other] commits
+    } below
+status-more-below-out-of-sync =
+    … { $count } more { $count ->
+        [one] commit
+       *[other] commits
+    } below, not all in sync
+
+## Report
+
+report-title = jr activity, { $since } to { $until }
+report-created = { $count ->
+        [one] 1 PR created
+       *[other] { $count } PRs created
+    }
+report-updated = { $count ->
+        [one] 1 update pushed
+       *[other] { $count } updates pushed
+    }
+report-restacked = { $count ->
+        [one] 1 restack
+       *[other] { $count } restacks
+    }
+report-merged = { $count ->
+        [one] 1 PR merged
+       *[other] { $count } PRs merged
+    }
+report-time-to-land = Average time to land: { $time }
+report-merged-heading = Merged
+report-revised-heading = Most revised
+report-iteration = { $url }: iteration { $iteration }
+audit-failed = Failed to record in the audit log: { $error }
+state-failed = Failed to save PR metadata for later commands: { $error }
+run-failed = Failed to record progress for --resume: { $error }
+
+## Tutorial
+
+token-prompt = New GitHub token
+token-empty = No token given
+token-rejected = GitHub rejected the new token: { $error }
+token-no-push = The new token can't push to { $repo }. It needs write access to contents and pull requests.
+token-git-scope = { $scope } git config
+token-shadowed-by-source = Saved the new token to { $destination }, but jr still reads the token from { $source }, which takes precedence
+token-shadowed = Saved the new token to { $destination }, but jr still reads the token from { $shadowed_by } git config. Remove it with: git config --{ $shadowed_by } --unset jr.githubToken
+token-rotated = Token for { $login } saved to { $destination }
+token-deleted = Deleted the token from the system keychain
+token-delete-none = There's no token in the system keychain
+review-stack-other-repo = { $repo } isn't this repository ({ $this }). Run jr review-stack from a clone of { $repo }.
+review-stack-header =
+    Stack of { $count } { $count ->
+        [one] PR
+       *[other] PRs
+    } ending at #{ $number }, from the top:
+review-state-closed = closed
+review-state-draft = draft
+review-state-approved = approved
+review-state-changes-requested = changes requested
+review-state-review-required = review required
+review-state-behind =
+    { $count } { $count ->
+        [one] commit
+       *[other] commits
+    } behind its base
+review-order = Suggested review order: { $prs }
+review-order-none = Nothing left to review
+tutorial-continue = Press Enter to continue…
+tutorial-done = That's it!  Run 'jr --help' for the full list of commands.
+tutorial-building = Building a sandbox repository in { $path }…
+tutorial-sandbox-failed = Couldn't build the tutorial's sandbox repository; jj and git must be installed
+tutorial-intro-title = Stacks and PRs
+tutorial-intro =
+    jr turns each Jujutsu commit between trunk and @ into its own GitHub PR.
+    Each PR's base is the PR of the commit below it, so reviewers see one
+    change at a time.
+
+    This tutorial runs jr in a sandbox: a temporary repository with a stack
+    of two commits, parser and cli.  Its GitHub is pretend, so nothing leaves
+    your machine.  Neither commit has a PR yet:
+tutorial-create-title = Creating PRs: jr create
+tutorial-create =
+    Describe a commit, then create its PR:
+
+        jj new -m "Add feature"
+        jr create
+
+    jr pushes a PR branch named after the change ID and opens a draft PR.
+    Create PRs from the bottom of the stack up; a commit's parent must have
+    a PR first.  In the sandbox, jr says what it would do, and the tutorial
+    plays GitHub's part:
+tutorial-created = Both commits now have PRs.
+tutorial-status-title = Checking the stack: jr status
+tutorial-status =
+    jr status shows each commit from the top down:
+
+        ✓  the PR is in sync with the commit
+        ✗  the commit has changed since its PR was last updated
+        ↻  the PR's base has moved on and it needs restacking
+        ?  the commit has no PR yet
+
+    Both of the sandbox's PRs are in sync:
+tutorial-update-title = Responding to review: jr update
+tutorial-update =
+    Edit the commit as usual, e.g. with jj edit or jj squash, then run
+    jr update.  jr adds a new commit to the PR branch rather than
+    force-pushing, so reviewers can see exactly what changed since their
+    last review.
+
+    The tutorial has edited parser, so its PR is out of date:
+tutorial-restack-title = Keeping children up to date: jr restack
+tutorial-restack =
+    Updating a PR leaves the PRs above it marked ↻.  Run jr restack on each
+    of them, from the bottom up.  jr merges the new base into the PR branch,
+    again without rewriting history.
+tutorial-restacked = The whole stack is in sync again:
+tutorial-navigate-title = Moving around: jr next, prev, top and bottom
+tutorial-navigate =
+    jr next and jr prev move @ up and down the stack, and jr top and
+    jr bottom jump to either end, showing each commit's PR as you go.
@@ -1,108 +1,824 @@
 use std::path;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
 
+use anyhow::Context;
 use anyhow::Result;
 use anyhow::bail;
-use futures_util::future::try_join_all;
+use colored::Colorize;
+use log::debug;
+use log::warn;
+use tokio::io::AsyncWriteExt as _;
 
+use crate::audit;
+use crate::audit::AuditEntry;
+use crate::audit::AuditEvent;
+use crate::clients::git::CommitId;
 use crate::clients::git::GitClient;
 use crate::clients::github::GithubClient;
+use crate::clients::jujutsu::GITHUB_CHANGE_ID_LENGTH;
 use crate::clients::jujutsu::JujutsuClient;
+use crate::clients::jujutsu::JujutsuCommit;
+use crate::clock;
+use crate::clock::Clock;
+use crate::clock::SystemClock;
 use crate::commit::CommitInfo;
 use crate::commit::SyncStatus;
+use crate::config;
 use crate::config::Config;
+use crate::i18n::tr;
+use crate::message;
+use crate::notify;
+use crate::notify::StackEvent;
+use crate::plan;
+use crate::plan::Plan;
+use crate::policy::Policy;
+use crate::pr_size;
+use crate::provenance;
+use crate::provenance::Provenance;
+use crate::run;
+use crate::run::Run;
+use crate::run::RunCommand;
+use crate::stack::Stack;
+use crate::stack_comment;
+use crate::state;
+use crate::state::State;
+
+/// File in the git directory recording the push remote URL the user
+/// confirmed pushing to.
+const PUSH_CONFIRMED_FILE: &str = "jr-push-confirmed";
+
+/// Times to ask GitHub whether a just-pushed PR can be merged, a second
+/// apart, before giving up.
+const MERGEABLE_POLLS: u32 = 5;
 
 pub struct App {
     pub config: Arc<Config>,
     pub gh: Arc<GithubClient>,
     pub jj: Arc<JujutsuClient>,
     pub git: Arc<GitClient>,
+    /// Source of time, for waiting and polling (see [`clock`]).
+    pub clock: Arc<dyn Clock>,
+    /// What a dry run would push and send to GitHub, so far.
+    plan: Mutex<Plan>,
 }
 
 impl App {
     pub fn new(config: Config, gh: GithubClient, path: path::PathBuf) -> Self {
+        let mut git = GitClient::new(path.clone()).with_remote(config.remote());
+        if let Some(remote) = &config.push_remote {
+            git = git.with_push_remote(remote);
+        }
+        if gh.is_dry_run() {
+            git = git.with_dry_run();
+        }
+        let mut jj = JujutsuClient::new(path).with_remote(config.remote());
+        if let Some(revset) = &config.trunk_revset {
+            jj = jj.with_trunk(revset);
+        }
         Self {
             config: Arc::new(config),
             gh: Arc::new(gh),
-            jj: Arc::new(JujutsuClient::new(path.clone())),
-            git: Arc::new(GitClient::new(path)),
+            jj: Arc::new(jj),
+            git: Arc::new(git),
+            clock: Arc::new(SystemClock),
+            plan: Mutex::default(),
         }
     }
+
+    /// Read the time from `clock`, e.g. a [`clock::FakeClock`] in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether this is a dry run, which only reports what would change: git
+    /// and GitHub refuse changes (see [`GithubClient::with_dry_run`]).
+    pub fn is_dry_run(&self) -> bool {
+        self.gh.is_dry_run()
+    }
+
+    /// How long to wait between polls of GitHub, from `jr.pollInterval`.
+    pub(crate) fn poll_interval(&self) -> Duration {
+        self.config
+            .poll_interval
+            .map_or(clock::DEFAULT_POLL_INTERVAL, Duration::from_secs)
+    }
 }
 
 /// Shared helper methods for App
 impl App {
-    /// Check if any parent PRs in the stack are outdated or need restacking.
+    /// Fail if the repository is archived or disabled, since GitHub would
+    /// reject any PR changes.
+    pub(crate) async fn ensure_repository_writable(&self) -> Result<()> {
+        let repository = self.gh.repository().await?;
+        if repository.archived {
+            bail!(tr!("repository-archived", repo = self.gh.full_name()));
+        }
+        if repository.disabled {
+            bail!(tr!("repository-disabled", repo = self.gh.full_name()));
+        }
+        Ok(())
+    }
+
+    /// Start fetching our open PRs in the background, so that GitHub's latency
+    /// overlaps with local work.
+    pub async fn start_prefetch(&self) {
+        let prefixes = self.config.branch_prefixes().map(str::to_string).collect();
+        self.gh.start_prefetch(prefixes).await;
+    }
+
+    /// Check the config for problems, including whether the default branch
+    /// exists on the remote.
+    pub(crate) async fn config_problems(&self) -> Vec<String> {
+        let mut problems = self.config.problems();
+        let branch = &self.config.default_branch;
+        if !branch.is_empty() && self.git.get_branch_tip(branch).await.is_err() {
+            problems.push(tr!(
+                "config-default-branch-missing",
+                branch = branch.as_str(),
+                remote = self.git.remote()
+            ));
+        }
+        problems
+    }
+
+    /// The first time jr pushes from this clone, or after the push remote
+    /// changes, show where PR branches will go and ask before pushing, unless
+    /// `yes`.  The confirmed remote URL is kept in `.git/jr-push-confirmed`,
+    /// so a fresh clone asks again.
+    pub async fn confirm_first_push(
+        &self,
+        yes: bool,
+        stdin: &mut impl std::io::BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let path = self.git.get_git_dir().await?.join(PUSH_CONFIRMED_FILE);
+        let url = self.git.get_remote_url().await?;
+        if is_push_confirmed(&path, &url) {
+            return Ok(());
+        }
+
+        if !yes {
+            writeln!(
+                stdout,
+                "{}",
+                tr!(
+                    "first-push-summary",
+                    remote = self.git.push_remote(),
+                    url = url.as_str(),
+                    repo = self.gh.full_name(),
+                    prefix = self.config.github_branch_prefix.as_str()
+                )
+            )?;
+            write!(stdout, "{} [y/N]: ", tr!("first-push-prompt"))?;
+            stdout.flush()?;
+            let mut answer = String::new();
+            stdin.read_line(&mut answer)?;
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                bail!(tr!("first-push-declined"));
+            }
+        }
+
+        std::fs::write(&path, format!("{url}\n"))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Fail unless pushing from this clone to the push remote has been
+    /// confirmed (see [`App::confirm_first_push`]), for `jr mcp`, which can't
+    /// ask since stdin and stdout carry the protocol.
+    pub(crate) async fn ensure_push_confirmed(&self) -> Result<()> {
+        let path = self.git.get_git_dir().await?.join(PUSH_CONFIRMED_FILE);
+        let url = self.git.get_remote_url().await?;
+        if !is_push_confirmed(&path, &url) {
+            bail!(tr!(
+                "first-push-unconfirmed",
+                remote = self.git.push_remote(),
+                url = url.as_str()
+            ));
+        }
+        Ok(())
+    }
+
+    /// In a brand-new repository, where the default branch doesn't exist on
+    /// the remote yet, offer to push it so PRs have a base, from the local
+    /// bookmark of the same name or else trunk.
+    pub async fn offer_to_push_default_branch(
+        &self,
+        stdin: &mut impl std::io::BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let branch = &self.config.default_branch;
+        if branch.is_empty() || self.git.get_branch_tip(branch).await.is_ok() {
+            return Ok(());
+        }
+        let commit = match self
+            .jj
+            .get_commit(&format!("bookmarks(exact:{branch:?})"))
+            .await
+        {
+            Ok(commit) => commit,
+            Err(_) => match self.jj.get_trunk().await {
+                Ok(commit) if !commit.commit_id.is_root() => commit,
+                _ => return Ok(()),
+            },
+        };
+
+        write!(
+            stdout,
+            "{} [y/N]: ",
+            tr!(
+                "default-branch-push-prompt",
+                branch = branch.as_str(),
+                remote = self.git.remote(),
+                commit = commit.commit_id.to_string()
+            )
+        )?;
+        stdout.flush()?;
+        let mut answer = String::new();
+        stdin.read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Ok(());
+        }
+
+        self.git
+            .create_remote_branch(&commit.commit_id, branch)
+            .await?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "default-branch-pushed",
+                branch = branch.as_str(),
+                remote = self.git.remote()
+            )
+        )?;
+        Ok(())
+    }
+
+    /// If GitHub has redirected a request because the repository has been
+    /// renamed or transferred, offer to point the remote at its new name, or
+    /// just say how if not `interactive`.
+    pub async fn offer_to_update_moved_remote(
+        &self,
+        interactive: bool,
+        stdin: &mut impl std::io::BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if !self.gh.redirected() {
+            return Ok(());
+        }
+        let Some(hint) = self.moved_remote_hint().await? else {
+            return Ok(());
+        };
+        let (moved_to, remote, url) = hint;
+        if !interactive {
+            writeln!(
+                stdout,
+                "{}",
+                tr!(
+                    "repo-moved-hint",
+                    repo = self.gh.full_name(),
+                    moved_to = moved_to,
+                    remote = remote.as_str(),
+                    url = url.as_str()
+                )
+                .yellow()
+            )?;
+            return Ok(());
+        }
+
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "repo-moved",
+                repo = self.gh.full_name(),
+                moved_to = moved_to
+            )
+            .yellow()
+        )?;
+        write!(
+            stdout,
+            "{} [y/N]: ",
+            tr!(
+                "repo-moved-prompt",
+                remote = remote.as_str(),
+                url = url.as_str()
+            )
+        )?;
+        stdout.flush()?;
+        let mut answer = String::new();
+        stdin.read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Ok(());
+        }
+
+        self.git.set_remote_url(&remote, &url).await?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!("repo-moved-updated", remote = remote.as_str())
+        )?;
+        Ok(())
+    }
+
+    /// If the repository has moved, its new `owner/repo` name, along with the
+    /// remote to update and the URL to point it at.
+    pub(crate) async fn moved_remote_hint(&self) -> Result<Option<(String, String, String)>> {
+        let Some(moved_to) = self.gh.moved_to().await? else {
+            return Ok(None);
+        };
+        let remote = self.git.remote().to_string();
+        let url = self.git.get_url_of(&remote).await?;
+        let url = self.gh.host().moved_remote_url(&url, &moved_to);
+        Ok(Some((moved_to, remote, url)))
+    }
+
+    /// Fail with every problem found by [`App::config_problems`], if any,
+    /// or if there's no trunk commit to build stacks on.
+    pub async fn validate_config(&self) -> Result<()> {
+        config::ensure_no_problems(&self.config_problems().await)?;
+        self.jj.get_trunk().await?;
+        Ok(())
+    }
+
+    /// Whether a revision is on trunk, i.e. there is no stack to work with.
+    pub(crate) async fn is_on_trunk(&self, revision: &str) -> Result<bool> {
+        Ok(self.jj.get_stack_ancestors(revision).await?.is_empty())
+    }
+
+    /// Fail if a revision or any of its ancestors in the stack has a divergent
+    /// change, since we can't tell which of its commits belongs in the PR.
+    pub(crate) async fn ensure_not_divergent(&self, revision: &str) -> Result<()> {
+        let commits = self.jj.get_stack_ancestors(revision).await?;
+        if let Some(commit) = commits.iter().find(|commit| commit.divergent) {
+            let change = &commit.change_id.0;
+            bail!(tr!(
+                "change-divergent",
+                change = &change[..GITHUB_CHANGE_ID_LENGTH.min(change.len())]
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the commits of the stack containing a revision, from child to
+    /// parent: up to the stack's head if it has just one, or else up to the
+    /// revision.
+    pub(crate) async fn get_stack(&self, revision: &str) -> Result<Vec<JujutsuCommit>> {
+        let heads = self.jj.get_stack_heads(revision).await?;
+        if heads.len() == 1 {
+            self.jj.get_stack_ancestors(&heads[0].commit_id.0).await
+        } else {
+            warn!("{}", tr!("status-multiple-heads"));
+            self.jj.get_stack_ancestors(revision).await
+        }
+    }
+
+    /// Check if any parent PRs in the stack are outdated or need restacking,
+    /// subject to the `parent-up-to-date` policy.
     pub(crate) async fn check_parent_prs_up_to_date(&self, revision: &str) -> Result<()> {
+        let policies = &self.config.policies;
+        if !policies.is_enabled(Policy::ParentUpToDate) {
+            return Ok(());
+        }
+
         let commit = self.jj.get_commit(revision).await?;
         let stack_changes = self
             .jj
             .get_stack_ancestors_exclusive(&commit.commit_id.0)
             .await?;
 
-        // Build CommitInfo for each commit
-        let commit_futures = stack_changes
-            .into_iter()
-            .map(|commit| CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git));
-        let commit_infos = try_join_all(commit_futures).await?;
-
-        // Calculate sync statuses with propagation from parent to child
-        // Iterate from parent to child (oldest to youngest)
-        let commits_rev = commit_infos.iter().rev().collect::<Vec<_>>();
-        let mut statuses: Vec<SyncStatus> = vec![];
-        let mut restack = false;
-
-        for commit_info in commits_rev.iter() {
-            let status = commit_info.status();
-
-            // If any ancestor needs restacking, all descendants need restacking
-            match status {
-                SyncStatus::Unknown | SyncStatus::Changed | SyncStatus::Restack => {
-                    restack = true;
-                    statuses.push(status);
+        let stack = Stack::load(stack_changes, &self.config, &self.jj, &self.gh, &self.git).await?;
+        let statuses = stack.statuses();
+
+        policies.enforce(Policy::ParentUpToDate, check_parent_statuses(&statuses))
+    }
+
+    /// Check that the tip of a PR branch was committed by the user (as jr
+    /// does) or a pairing co-author, rather than pushed by a collaborator,
+    /// subject to the `foreign-push` policy.
+    pub(crate) async fn check_pr_tip_is_mine(&self, pr_tip: &CommitId, branch: &str) -> Result<()> {
+        let policies = &self.config.policies;
+        if !policies.is_enabled(Policy::ForeignPush) {
+            return Ok(());
+        }
+        let Some(me) = self.git.get_user_email().await? else {
+            return Ok(());
+        };
+
+        let committer = self.git.get_committer_email(pr_tip).await?;
+        let mut mine = std::iter::once(me.as_str())
+            .chain(self.config.pairs.iter().map(|pair| pair.email.as_str()));
+        let check = if mine.any(|email| committer.eq_ignore_ascii_case(email)) {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(tr!(
+                "pr-tip-foreign",
+                branch = branch,
+                committer = committer
+            )))
+        };
+        policies.enforce(Policy::ForeignPush, check)
+    }
+
+    /// Push a synthesized commit to a remote PR branch, running the configured
+    /// pre-push command first (subject to the `pre-push` policy), and record
+    /// the jj commit it was synthesized from.
+    pub(crate) async fn push_pr_branch(
+        &self,
+        commit_id: &CommitId,
+        source: &JujutsuCommit,
+        branch: &str,
+    ) -> Result<()> {
+        let policies = &self.config.policies;
+        if let Some(command) = &self.config.pre_push_command
+            && policies.is_enabled(Policy::PrePush)
+        {
+            let check = self
+                .git
+                .run_pre_push_command(command, commit_id, branch)
+                .await;
+            policies.enforce(Policy::PrePush, check)?;
+        }
+        self.git.push_commit_to_branch(commit_id, branch).await?;
+        self.record_provenance(commit_id, source).await;
+        Ok(())
+    }
+
+    /// Note which jj commit a synthesized commit came from (see
+    /// [`provenance`]), pushing the notes if `jr.pushNotes` is set.  Failures
+    /// are logged rather than returned, since the push has already happened.
+    async fn record_provenance(&self, commit_id: &CommitId, source: &JujutsuCommit) {
+        let result = async {
+            let provenance = Provenance {
+                change_id: source.change_id.0.clone(),
+                commit_id: source.commit_id.0.clone(),
+                operation: self.jj.get_operation_id().await?,
+            };
+            self.git
+                .add_note(provenance::NOTES_REF, commit_id, &provenance.to_note())
+                .await?;
+            if self.config.push_notes {
+                self.git.push_ref(provenance::NOTES_REF).await?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(err) = result {
+            warn!("{}", tr!("provenance-failed", error = format!("{err:#}")));
+        }
+    }
+
+    /// Compose a commit message in the user's editor, prefilled with the
+    /// describe command's suggestion for a diff, or else with `default`.
+    /// Fails if the result is empty.
+    pub(crate) async fn compose_message(&self, diff: &str, default: &str) -> Result<String> {
+        let suggestion = match &self.config.describe_command {
+            Some(command) => match message::suggest(command, diff, &std::env::current_dir()?).await
+            {
+                Ok(suggestion) if !suggestion.is_empty() => suggestion,
+                Ok(_) => default.to_string(),
+                Err(err) => {
+                    warn!(
+                        "{}",
+                        tr!("describe-command-failed", error = err.to_string())
+                    );
+                    default.to_string()
                 }
-                SyncStatus::Synced => {
-                    if restack {
-                        statuses.push(SyncStatus::Restack);
-                    } else {
-                        statuses.push(SyncStatus::Synced);
-                    }
+            },
+            None => default.to_string(),
+        };
+
+        let git_dir = self.git.get_git_dir().await?;
+        let message = message::edit(&suggestion, &tr!("message-edit-hint"), &git_dir).await?;
+        if message.is_empty() {
+            bail!(tr!("message-empty"));
+        }
+        Ok(message)
+    }
+
+    /// Append an entry to the audit log (see [`audit`]).  Failures are logged
+    /// rather than returned, like notifications.
+    pub(crate) async fn record_audit(&self, event: AuditEvent, branch: &str, url: &str) {
+        let entry = AuditEntry {
+            time: self.clock.now(),
+            event,
+            branch: branch.to_string(),
+            url: url.to_string(),
+        };
+        let result = async {
+            let path = self.git.get_git_dir().await?.join(audit::FILE_NAME);
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            file.write_all(format!("{}\n", entry.to_line()).as_bytes())
+                .await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            warn!("{}", tr!("audit-failed", error = err.to_string()));
+        }
+    }
+
+    /// Load the PR metadata kept between commands (see [`state`]), or an
+    /// empty state if there's none.
+    pub(crate) async fn load_state(&self) -> State {
+        let Ok(git_dir) = self.git.get_git_dir().await else {
+            return State::default();
+        };
+        match tokio::fs::read_to_string(git_dir.join(state::FILE_NAME)).await {
+            Ok(json) => State::parse(&json),
+            Err(_) => State::default(),
+        }
+    }
+
+    /// Save the PR metadata kept between commands.  Failures are logged
+    /// rather than returned, like the audit log's.
+    pub(crate) async fn save_state(&self, state: &State) {
+        let result = async {
+            let path = self.git.get_git_dir().await?.join(state::FILE_NAME);
+            let dir = path.parent().expect("The state file is in a directory");
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(path, state.to_json()).await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            warn!("{}", tr!("state-failed", error = err.to_string()));
+        }
+    }
+
+    /// Count a push to a PR branch in a dry run's plan (see [`plan`]), with
+    /// `requests` GitHub requests besides those for stack comments.
+    pub(crate) fn plan_push(&self, requests: u64) {
+        let requests = if self.config.stack_comments {
+            requests + plan::STACK_COMMENT_REQUESTS
+        } else {
+            requests
+        };
+        self.plan.lock().expect("plan lock poisoned").push(requests);
+    }
+
+    /// Sum up what a dry run would push and send to GitHub, and roughly how
+    /// long that would take from the timings of earlier runs.
+    pub async fn report_plan(&self, stdout: &mut impl std::io::Write) -> Result<()> {
+        let plan = *self.plan.lock().expect("plan lock poisoned");
+        if plan.is_empty() {
+            return Ok(());
+        }
+        let (duration, recorded) = plan.estimate(&self.load_state().await.timings);
+        let duration = plan::format_duration(duration);
+        let message = if recorded {
+            tr!(
+                "dry-run-plan",
+                pushes = plan.pushes,
+                requests = plan.requests,
+                duration = duration
+            )
+        } else {
+            tr!(
+                "dry-run-plan-guess",
+                pushes = plan.pushes,
+                requests = plan.requests,
+                duration = duration
+            )
+        };
+        writeln!(stdout, "{message}")?;
+        Ok(())
+    }
+
+    /// Keep how long this run's pushes and GitHub requests took, for
+    /// estimating dry runs (see [`plan`]).
+    pub async fn record_timings(&self) {
+        let (pushes, requests) = (self.git.push_timing(), self.gh.timing());
+        if pushes.count == 0 && requests.count == 0 {
+            return;
+        }
+        let mut state = self.load_state().await;
+        state.timings.pushes.add(pushes);
+        state.timings.requests.add(requests);
+        self.save_state(&state).await;
+    }
+
+    /// Load the steps an interrupted `jr submit` or `jr merge` left to do
+    /// (see [`run`]), failing if there are none for `command`.
+    pub(crate) async fn load_run(&self, command: RunCommand) -> Result<Run> {
+        let path = self.git.get_git_dir().await?.join(run::FILE_NAME);
+        let run = tokio::fs::read_to_string(&path)
+            .await
+            .ok()
+            .and_then(|json| Run::parse(&json));
+        match run {
+            Some(run) if run.command == command => Ok(run),
+            Some(run) => bail!(tr!(
+                "run-resume-other",
+                command = command.name(),
+                other = run.command.name()
+            )),
+            None => bail!(tr!("run-resume-none", command = command.name())),
+        }
+    }
+
+    /// Save a run's progress, for `--resume`.  Failures are logged rather
+    /// than returned, like the state's; a dry run saves nothing.
+    pub(crate) async fn save_run(&self, run: &Run) {
+        if self.is_dry_run() {
+            return;
+        }
+        let result = async {
+            let path = self.git.get_git_dir().await?.join(run::FILE_NAME);
+            let dir = path.parent().expect("The run file is in a directory");
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(path, run.to_json()).await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            warn!("{}", tr!("run-failed", error = err.to_string()));
+        }
+    }
+
+    /// Forget a finished run.
+    pub(crate) async fn clear_run(&self) {
+        if self.is_dry_run() {
+            return;
+        }
+        if let Ok(git_dir) = self.git.get_git_dir().await {
+            let _ = tokio::fs::remove_file(git_dir.join(run::FILE_NAME)).await;
+        }
+    }
+
+    /// Warn if GitHub can't merge a just-pushed PR into its base, so
+    /// conflicts are noticed before reviewers do.  GitHub works this out in
+    /// the background, so this gives up if it takes more than a few seconds.
+    /// Failures are logged rather than returned.
+    pub(crate) async fn warn_if_unmergeable(
+        &self,
+        pr_branch: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let pr = match self.gh.get_pr(pr_branch).await {
+            Ok(Some(pr)) => pr,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                debug!("Can't check whether {pr_branch} can be merged: {err:#}");
+                return Ok(());
+            }
+        };
+        for _ in 0..MERGEABLE_POLLS {
+            match self.gh.pr_mergeable(pr.number).await {
+                Ok(Some(true)) => return Ok(()),
+                Ok(Some(false)) => {
+                    let warning = tr!("pr-conflicts", url = pr.url.as_str(), branch = pr_branch);
+                    writeln!(stdout, "{}", warning.yellow())?;
+                    return Ok(());
+                }
+                Ok(None) => self.clock.sleep(Duration::from_secs(1)).await,
+                Err(err) => {
+                    debug!("Can't check whether {pr_branch} can be merged: {err:#}");
+                    return Ok(());
                 }
             }
         }
+        Ok(())
+    }
 
-        // Reverse statuses to match original commit order (child to parent)
-        statuses.reverse();
+    /// Warn if a commit's PR changes more lines than `jr.sizeBudget`,
+    /// suggesting how to split it (see [`pr_size`]).  Failures are logged
+    /// rather than returned.
+    pub(crate) async fn warn_if_oversized(
+        &self,
+        commit: &JujutsuCommit,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let Some(budget) = self.config.size_budget else {
+            return Ok(());
+        };
+        let diff = match self.git.get_commit_diff(&commit.commit_id).await {
+            Ok(diff) => diff,
+            Err(err) => {
+                debug!("Can't measure {}: {err:#}", commit.change_id.0);
+                return Ok(());
+            }
+        };
+        let files = pr_size::file_sizes(&diff);
+        let added = files.iter().map(|file| file.added).sum::<usize>();
+        let removed = files.iter().map(|file| file.removed).sum::<usize>();
+        if added + removed <= budget {
+            return Ok(());
+        }
+        let warning = tr!(
+            "pr-size-over-budget",
+            lines = added + removed,
+            added = added,
+            removed = removed,
+            budget = budget
+        );
+        writeln!(stdout, "{}", warning.yellow())?;
+        let change_id = &commit.change_id.0;
+        let change_id = &change_id[..GITHUB_CHANGE_ID_LENGTH.min(change_id.len())];
+        for group in pr_size::split_candidates(&files) {
+            let command = format!("jj split -r {change_id} {}", group.filesets.join(" "));
+            let suggestion = tr!(
+                "pr-size-split",
+                command = command,
+                lines = group.lines,
+                group = group.name
+            );
+            writeln!(stdout, "  {suggestion}")?;
+        }
+        Ok(())
+    }
 
-        // Check statuses in order from parent to child (oldest to youngest)
-        for status in statuses.iter().rev() {
-            match status {
-                SyncStatus::Unknown => {
-                    bail!("Parent commit has no PR branch. Create parent PR first (bottom-up).",);
-                }
-                SyncStatus::Restack => {
-                    // bail!(
-                    //     "Cannot update PR: parent PR {} needs restacking. Its base branch '{}' has been updated. Run 'jr restack' on the parent first.",
-                    //     expected_branch,
-                    //     base_branch
-                    // );
-                    bail!(
-                        "Cannot update PR: parent PR needs restacking. Its base branch has been updated. Run 'jr restack' on the parent first.",
-                    );
+    /// Add or update the stack navigation comment (see [`stack_comment`]) on
+    /// each PR in the stack containing a revision.  Failures are logged
+    /// rather than returned, like notifications.
+    pub(crate) async fn update_stack_comments(&self, revision: &str) {
+        if !self.config.stack_comments {
+            return;
+        }
+        let result = async {
+            let mut prs = vec![];
+            for commit in self.get_stack(revision).await?.iter().rev() {
+                let branch =
+                    CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+                if let Some(pr) = self.gh.get_pr(&branch).await?
+                    && pr.is_open()
+                {
+                    prs.push(pr.number);
                 }
-                SyncStatus::Changed => {
-                    // bail!(
-                    //     "Cannot update PR: parent PR {} is out of date. Update parent PRs first (starting from the bottom of the stack).",
-                    //     expected_branch
-                    // );
-                    bail!(
-                        "Cannot update PR: parent PR is out of date. Update parent PRs first (starting from the bottom of the stack).",
-                    );
+            }
+            for &number in &prs {
+                let comments = self.gh.pr_comments(number).await?;
+                let existing = stack_comment::find(&comments);
+                let body = stack_comment::render(&prs, number);
+                match existing {
+                    Some(comment) if comment.body.as_deref() == Some(body.as_str()) => {}
+                    Some(comment) => self.gh.pr_comment_edit(comment.id, &body).await?,
+                    // A lone PR needs no navigation
+                    None if prs.len() < 2 => {}
+                    None => self.gh.pr_comment(number, &body).await?,
                 }
-                SyncStatus::Synced => {}
             }
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            warn!("{}", tr!("stack-comment-failed", error = err.to_string()));
         }
+    }
 
-        Ok(())
+    /// Notify the configured webhook, if any, of a stack event.  Failures are
+    /// logged rather than returned; a notification should never fail a command.
+    pub(crate) async fn notify(&self, event: StackEvent, title: &str, url: &str, branch: &str) {
+        let Some(webhook_url) = &self.config.webhook_url else {
+            return;
+        };
+        let template = self
+            .config
+            .webhook_template
+            .as_deref()
+            .unwrap_or(notify::DEFAULT_TEMPLATE);
+        let payload = notify::render_payload(template, event, title, url, branch);
+        if let Err(err) = self.gh.post_webhook(webhook_url, &payload).await {
+            warn!("{}", tr!("notify-failed", error = err.to_string()));
+        }
     }
 }
+
+/// Check the statuses of a revision's parents, ordered from parent to child
+/// (oldest to youngest), failing if any of them isn't in sync.
+fn check_parent_statuses(statuses: &[SyncStatus]) -> Result<()> {
+    for status in statuses {
+        match status {
+            SyncStatus::Unknown => {
+                bail!(tr!("parent-no-pr"));
+            }
+            SyncStatus::Restack => {
+                // bail!(
+                //     "Cannot update PR: parent PR {} needs restacking. Its base branch '{}' has been updated. Run 'jr restack' on the parent first.",
+                //     expected_branch,
+                //     base_branch
+                // );
+                bail!(tr!("parent-needs-restack"));
+            }
+            SyncStatus::Changed => {
+                // bail!(
+                //     "Cannot update PR: parent PR {} is out of date. Update parent PRs first (starting from the bottom of the stack).",
+                //     expected_branch
+                // );
+                bail!(tr!("parent-out-of-date"));
+            }
+            SyncStatus::Synced => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether pushing to the remote at `url` has been confirmed, in the file at
+/// `path`.
+fn is_push_confirmed(path: &path::Path, url: &str) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|confirmed| confirmed.trim() == url)
+}
@@ -0,0 +1,296 @@
+//! A local log of what jr has done to PRs, and summaries of it.
+//!
+//! Each change jr makes to a PR is appended to `.git/jr-audit.jsonl`, one
+//! JSON [`AuditEntry`] per line.  The log never leaves the machine: `jr
+//! report` summarizes it as markdown, for personal retrospectives.
+//!
+//! A PR's iteration is the number of versions jr has pushed to it: one for
+//! creating it, and one more for each update.  Restacks only merge in the
+//! base, so don't count.  A PR on its fifth iteration may be churning, and
+//! better served by a design conversation than another round of review.
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::i18n::tr;
+
+/// Name of the log file, in the `.git` directory.
+pub const FILE_NAME: &str = "jr-audit.jsonl";
+
+/// Something jr did to a PR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditEvent {
+    Created,
+    Updated,
+    Restacked,
+    Merged,
+}
+
+/// A line of the log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    pub event: AuditEvent,
+    /// The PR branch.
+    pub branch: String,
+    pub url: String,
+}
+
+impl AuditEntry {
+    /// Format as a line of the log, without its newline.
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("entries serialize")
+    }
+}
+
+/// Each PR branch's iteration, as of the last of `entries`.
+pub fn iterations(entries: &[AuditEntry]) -> HashMap<&str, usize> {
+    let mut iterations = HashMap::new();
+    for entry in entries {
+        if matches!(entry.event, AuditEvent::Created | AuditEvent::Updated) {
+            *iterations.entry(entry.branch.as_str()).or_default() += 1;
+        }
+    }
+    iterations
+}
+
+/// Parse a log, skipping lines that aren't entries, e.g. from a newer jr.
+pub fn parse_log(log: &str) -> Vec<AuditEntry> {
+    log.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Drop the entries before `since` from a log, returning what's left and how
+/// many were dropped.  Lines that aren't entries are kept.
+pub fn prune(log: &str, since: DateTime<Utc>) -> (String, usize) {
+    let mut kept = String::new();
+    let mut pruned = 0;
+    for line in log.lines() {
+        match serde_json::from_str::<AuditEntry>(line) {
+            Ok(entry) if entry.time < since => pruned += 1,
+            _ => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+    (kept, pruned)
+}
+
+/// A summary of the entries in a period.
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub counts: HashMap<AuditEvent, usize>,
+    /// Average time from creating a PR to merging it, for PRs merged in the
+    /// period that were created with jr.
+    pub average_time_to_land: Option<TimeDelta>,
+    /// PRs merged in the period.
+    pub merged: Vec<String>,
+    /// PRs pushed to in the period that are past their first iteration, with
+    /// their iteration, most first.
+    pub revised: Vec<(String, usize)>,
+}
+
+impl Report {
+    /// Summarize the entries from `since` up to `until`.  Earlier entries are
+    /// still used to find when PRs merged in the period were created.
+    pub fn new(entries: &[AuditEntry], since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        let in_period = |entry: &&AuditEntry| entry.time >= since && entry.time < until;
+        let mut counts = HashMap::new();
+        for entry in entries.iter().filter(in_period) {
+            *counts.entry(entry.event).or_default() += 1;
+        }
+
+        let mut created = HashMap::new();
+        for entry in entries {
+            if entry.event == AuditEvent::Created {
+                created.entry(entry.branch.as_str()).or_insert(entry.time);
+            }
+        }
+        let merged_entries = entries
+            .iter()
+            .filter(in_period)
+            .filter(|entry| entry.event == AuditEvent::Merged)
+            .collect::<Vec<_>>();
+        let times_to_land = merged_entries
+            .iter()
+            .filter_map(|entry| Some(entry.time - *created.get(entry.branch.as_str())?))
+            .collect::<Vec<_>>();
+        let average_time_to_land = (!times_to_land.is_empty())
+            .then(|| times_to_land.iter().copied().sum::<TimeDelta>() / times_to_land.len() as i32);
+
+        let iterations = iterations(entries);
+        let mut revised = vec![];
+        for entry in entries.iter().filter(in_period).rev() {
+            let iteration = iterations.get(entry.branch.as_str()).copied();
+            if matches!(entry.event, AuditEvent::Created | AuditEvent::Updated)
+                && let Some(iteration) = iteration.filter(|&iteration| iteration > 1)
+                && !revised.iter().any(|(url, _)| *url == entry.url)
+            {
+                revised.push((entry.url.clone(), iteration));
+            }
+        }
+        revised.sort_by_key(|(_, iteration)| std::cmp::Reverse(*iteration));
+
+        Self {
+            since,
+            until,
+            counts,
+            average_time_to_land,
+            revised,
+            merged: merged_entries
+                .iter()
+                .map(|entry| entry.url.clone())
+                .collect(),
+        }
+    }
+
+    fn count(&self, event: AuditEvent) -> usize {
+        self.counts.get(&event).copied().unwrap_or(0)
+    }
+
+    /// Format as markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# {}\n\n",
+            tr!(
+                "report-title",
+                since = self.since.format("%Y-%m-%d").to_string(),
+                until = self.until.format("%Y-%m-%d").to_string()
+            )
+        );
+        for line in [
+            tr!("report-created", count = self.count(AuditEvent::Created)),
+            tr!("report-updated", count = self.count(AuditEvent::Updated)),
+            tr!(
+                "report-restacked",
+                count = self.count(AuditEvent::Restacked)
+            ),
+            tr!("report-merged", count = self.count(AuditEvent::Merged)),
+        ] {
+            markdown.push_str(&format!("- {line}\n"));
+        }
+        if let Some(average) = self.average_time_to_land {
+            markdown.push_str(&format!(
+                "- {}\n",
+                tr!("report-time-to-land", time = format_duration(average))
+            ));
+        }
+        if !self.merged.is_empty() {
+            markdown.push_str(&format!("\n## {}\n\n", tr!("report-merged-heading")));
+            for url in &self.merged {
+                markdown.push_str(&format!("- {url}\n"));
+            }
+        }
+        if !self.revised.is_empty() {
+            markdown.push_str(&format!("\n## {}\n\n", tr!("report-revised-heading")));
+            for (url, iteration) in &self.revised {
+                let line = tr!(
+                    "report-iteration",
+                    url = url.as_str(),
+                    iteration = *iteration
+                );
+                markdown.push_str(&format!("- {line}\n"));
+            }
+        }
+        markdown
+    }
+}
+
+/// Format a duration in days and hours, or hours and minutes.
+fn format_duration(duration: TimeDelta) -> String {
+    if duration.num_days() > 0 {
+        format!("{}d {}h", duration.num_days(), duration.num_hours() % 24)
+    } else {
+        format!("{}h {}m", duration.num_hours(), duration.num_minutes() % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, hour: u32, event: AuditEvent, branch: &str) -> AuditEntry {
+        AuditEntry {
+            time: format!("2026-03-{day:02}T{hour:02}:00:00Z")
+                .parse()
+                .unwrap(),
+            event,
+            branch: branch.to_string(),
+            url: format!("https://github.com/o/r/pull/{}", branch.len()),
+        }
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let entry = entry(1, 9, AuditEvent::Created, "me/abc");
+        let log = format!(
+            "{}\nnot json\n{{\"event\": \"teleported\"}}\n",
+            entry.to_line()
+        );
+        assert_eq!(parse_log(&log), [entry]);
+    }
+
+    #[test]
+    fn test_prune() {
+        let old = entry(1, 9, AuditEvent::Created, "me/abc");
+        let new = entry(3, 9, AuditEvent::Merged, "me/abc");
+        let log = format!("{}\nnot json\n{}\n", old.to_line(), new.to_line());
+        let since = "2026-03-02T00:00:00Z".parse().unwrap();
+        let (kept, pruned) = prune(&log, since);
+        assert_eq!(pruned, 1);
+        assert_eq!(kept, format!("not json\n{}\n", new.to_line()));
+    }
+
+    #[test]
+    fn test_report() {
+        use AuditEvent::*;
+        let entries = [
+            // Created the week before, merged this week
+            entry(1, 9, Created, "me/a"),
+            entry(9, 9, Updated, "me/a"),
+            entry(10, 9, Merged, "me/a"),
+            entry(9, 10, Created, "me/bb"),
+            entry(9, 12, Restacked, "me/bb"),
+            entry(9, 16, Merged, "me/bb"),
+            // After the period
+            entry(20, 9, Created, "me/ccc"),
+        ];
+        let since = "2026-03-08T00:00:00Z".parse().unwrap();
+        let until = "2026-03-15T00:00:00Z".parse().unwrap();
+        let report = Report::new(&entries, since, until);
+        assert_eq!(report.count(Created), 1);
+        assert_eq!(report.count(Merged), 2);
+        // (9 days + 6 hours) / 2
+        assert_eq!(
+            report.average_time_to_land,
+            Some(TimeDelta::hours(9 * 24 + 6) / 2)
+        );
+
+        assert_eq!(
+            report.to_markdown(),
+            "# jr activity, 2026-03-08 to 2026-03-15\n\n\
+             - 1 PR created\n\
+             - 1 update pushed\n\
+             - 1 restack\n\
+             - 2 PRs merged\n\
+             - Average time to land: 4d 15h\n\
+             \n\
+             ## Merged\n\n\
+             - https://github.com/o/r/pull/4\n\
+             - https://github.com/o/r/pull/5\n\
+             \n\
+             ## Most revised\n\n\
+             - https://github.com/o/r/pull/4: iteration 2\n"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Marking draft PRs ready for review once their parent is (see `jr sync`).
+//!
+//! PRs are created as drafts, and reviewers should receive a stack bottom-up.
+//! With `jr.autoReady` (or `jr sync --auto-ready` for a single stack), a
+//! draft PR is marked ready once its parent PR is approved or merged, so the
+//! stack reaches reviewers in order without anyone tending its draft states.
+
+use clap::ValueEnum;
+
+use crate::model::PullRequest;
+use crate::model::ReviewDecision;
+
+/// When a draft PR is marked ready for review.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AutoReady {
+    /// Never; PRs are only marked ready with `jr ready`.
+    Off,
+    /// Once its parent PR is approved, or merged.
+    Approved,
+    /// Once its parent PR is merged.
+    Merged,
+}
+
+/// Whether to mark a PR ready for review, given the PR its base branch
+/// belongs to.
+pub fn should_mark_ready(auto_ready: AutoReady, pr: &PullRequest, parent: &PullRequest) -> bool {
+    if !pr.is_open() || !pr.draft {
+        return false;
+    }
+    let merged = parent.merged_at.is_some();
+    let approved = parent.is_open()
+        && !parent.draft
+        && parent.review_decision == Some(ReviewDecision::Approved);
+    match auto_ready {
+        AutoReady::Off => false,
+        AutoReady::Approved => merged || approved,
+        AutoReady::Merged => merged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(state: &str, draft: bool, merged: bool, decision: Option<&str>) -> PullRequest {
+        serde_json::from_value(serde_json::json!({
+            "number": 1,
+            "node_id": "PR_1",
+            "html_url": "https://github.com/o/r/pull/1",
+            "state": state,
+            "draft": draft,
+            "title": "Title",
+            "body": null,
+            "base": { "ref": "main", "sha": "0" },
+            "head": { "ref": "me/abc", "sha": "1" },
+            "milestone": null,
+            "merged_at": merged.then_some("2026-03-01T09:00:00Z"),
+            "review_decision": decision,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_should_mark_ready() {
+        use AutoReady::*;
+        let draft = pr("open", true, false, None);
+        let approved = pr("open", false, false, Some("APPROVED"));
+        let merged = pr("closed", false, true, None);
+        let unreviewed = pr("open", false, false, Some("REVIEW_REQUIRED"));
+
+        assert!(should_mark_ready(Approved, &draft, &approved));
+        assert!(should_mark_ready(Approved, &draft, &merged));
+        assert!(!should_mark_ready(Approved, &draft, &unreviewed));
+        assert!(!should_mark_ready(Merged, &draft, &approved));
+        assert!(should_mark_ready(Merged, &draft, &merged));
+        assert!(!should_mark_ready(Off, &draft, &merged));
+
+        // Only open drafts are marked ready
+        assert!(!should_mark_ready(Approved, &unreviewed, &merged));
+        let closed = pr("closed", true, false, None);
+        assert!(!should_mark_ready(Approved, &closed, &merged));
+        // A draft can't be approved into readiness, however it was reviewed
+        let approved_draft = pr("open", true, false, Some("APPROVED"));
+        assert!(!should_mark_ready(Approved, &draft, &approved_draft));
+    }
+}
@@ -0,0 +1,49 @@
+//! Developer tasks for jr.  Install with
+//!
+//!   cargo install --path . --features fixtures --bin jr-xtask
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clap::Subcommand;
+use jr::fixtures;
+use jr::fixtures::Shape;
+
+#[derive(Parser)]
+#[command(name = "jr-xtask")]
+#[command(about = "Developer tasks for jr")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Build a colocated jj+git repository with stacks of commits, tracking
+    /// `main` from a local bare `origin`
+    Fixture {
+        /// Empty directory to build the repository in, as `repo` and
+        /// `origin.git`
+        dir: PathBuf,
+        /// Stack shape: comma-separated paths of dash-separated commit names
+        /// from trunk up, e.g. `a-b-c,b-d` for a stack branching at `b`
+        #[arg(short, long, default_value = "a-b-c")]
+        shape: Shape,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Commands::Fixture { dir, shape } => {
+            let fixture = fixtures::build(&dir, &shape).await?;
+            println!("Built {}", fixture.repo.display());
+            for (name, _) in &shape.commits {
+                println!("  {name}: {}", fixture.change_ids[name]);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Finding the first commit in a stack that fails a test.
+//!
+//! Position `k` stands for the stack's prefix from the bottom commit up to
+//! and including commit `k`, which is exactly what commit `k`'s tree
+//! contains.  Trunk is assumed to pass and the top of the stack to fail, so
+//! a binary search finds the first failing position in about `log2(n)`
+//! tests.
+
+/// The state of a binary search for the first failing position in a stack.
+#[derive(Debug)]
+pub struct Bisection {
+    /// The highest position known to pass; `None` is trunk.
+    good: Option<usize>,
+    /// The lowest position known to fail.
+    bad: usize,
+}
+
+impl Bisection {
+    /// Start a search in a stack of `len` commits whose top fails.
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "stacks aren't empty");
+        Self {
+            good: None,
+            bad: len - 1,
+        }
+    }
+
+    /// The next position to test, or `None` once the first failing position
+    /// is known.
+    pub fn next(&self) -> Option<usize> {
+        let low = self.good.map_or(0, |good| good + 1);
+        (low < self.bad).then(|| low + (self.bad - low) / 2)
+    }
+
+    /// Record the result of testing a position.
+    pub fn record(&mut self, position: usize, passed: bool) {
+        if passed {
+            self.good = Some(position);
+        } else {
+            self.bad = position;
+        }
+    }
+
+    /// The first failing position, once [`Bisection::next`] returns `None`.
+    pub fn first_failing(&self) -> usize {
+        self.bad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_commit() {
+        let bisection = Bisection::new(1);
+        assert_eq!(bisection.next(), None);
+        assert_eq!(bisection.first_failing(), 0);
+    }
+
+    #[test]
+    fn test_finds_first_failing() {
+        for len in 1..20 {
+            for first_failing in 0..len {
+                let mut bisection = Bisection::new(len);
+                let mut tested = vec![];
+                while let Some(position) = bisection.next() {
+                    tested.push(position);
+                    bisection.record(position, position < first_failing);
+                }
+                assert_eq!(bisection.first_failing(), first_failing, "{len} commits");
+                // Never more tests than a binary search needs
+                assert!(tested.len() <= len.ilog2() as usize + 1, "{tested:?}");
+                assert!(!tested.contains(&(len - 1)), "top is already known to fail");
+            }
+        }
+    }
+}
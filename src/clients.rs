@@ -3,14 +3,16 @@
 //! This module contains the integration layers for the three systems that `jr` coordinates:
 //!
 //! - [`git`]: Low-level Git operations (tree parsing, commit creation, branch updates, pushing)
+//! - [`git_objects`]: Reading and writing loose Git objects, to create commits without Git
 //! - [`github`]: GitHub PR management via GitHub CLI
-//! - [`github_curl`]: Curl-based HTTP client for making GitHub API requests
+//! - [`github_http`]: HTTP client for making GitHub API requests
 //! - [`jujutsu`]: Jujutsu operations for extracting commit and change IDs
 //!
 //! Each submodule provides trait-based abstractions with real and mock implementations
 //! to support both production use and testing.
 
 pub mod git;
+pub mod git_objects;
 pub mod github;
-pub mod github_curl;
+pub mod github_http;
 pub mod jujutsu;
@@ -1,10 +1,24 @@
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::process::Stdio;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::bail;
 use anyhow::ensure;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt as _;
 use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::clients::git_objects;
+use crate::clients::git_objects::ObjectStore;
+use crate::plan::Stopwatch;
+use crate::plan::Timing;
 
 // -----------------------------------------------------------------------------
 // Types
@@ -12,11 +26,80 @@ use tokio::process::Command;
 /// Git client.
 pub struct GitClient {
     path: std::path::PathBuf,
+    /// Remote the repository is fetched from and PRs are merged into.
+    remote: String,
+    /// Remote that PR branches are pushed to: `remote`, or a fork.
+    push_remote: String,
+    /// Writes PR commits without running git, if the repository allows (see
+    /// [`git_objects`]).  Set up on first use.
+    commit_writer: OnceCell<Option<CommitWriter>>,
+    /// The `.git` directory, once looked up.
+    git_dir: OnceCell<std::path::PathBuf>,
+    /// Times pushes of PR branches, for estimating how long a dry run's
+    /// would take (see [`crate::plan`]).
+    pushes: Stopwatch,
+    /// Refuse to change the repository or its remotes, for `--dry-run`.
+    dry_run: bool,
+}
+
+/// What's needed to write commits directly.
+#[derive(Debug)]
+struct CommitWriter {
+    store: ObjectStore,
+    author: Identity,
+    committer: Identity,
+}
+
+/// A commit author or committer.
+#[derive(Debug)]
+struct Identity {
+    /// `Name <email>`.
+    name_email: String,
+    /// `timestamp timezone`, if fixed by e.g. `GIT_COMMITTER_DATE`; otherwise
+    /// the time of each commit.
+    date: Option<String>,
+}
+
+impl Identity {
+    /// Parse `git var` output, keeping its date only if `date_var` is set.
+    fn parse(ident: &str, date_var: &str) -> Option<Self> {
+        let (name_email, date) = ident.trim().rsplit_once("> ")?;
+        Some(Self {
+            name_email: format!("{name_email}>"),
+            date: std::env::var_os(date_var).map(|_| date.to_string()),
+        })
+    }
+
+    /// The identity as recorded in a commit made now.
+    fn stamp(&self) -> String {
+        let date = self
+            .date
+            .clone()
+            .unwrap_or_else(|| chrono::Local::now().format("%s %z").to_string());
+        format!("{} {date}", self.name_email)
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct CommitId(pub String);
 
+/// A single line of `git blame` output.
+#[derive(Clone, Debug)]
+pub struct BlameLine {
+    /// The commit that last modified this line.
+    pub commit_id: CommitId,
+    /// The line contents, without the trailing newline.
+    pub content: String,
+}
+
+impl CommitId {
+    /// Whether this is jj's virtual root commit, which has no contents.
+    pub fn is_root(&self) -> bool {
+        self.0.bytes().all(|b| b == b'0')
+    }
+}
+
 impl Display for CommitId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.0)
@@ -28,13 +111,63 @@ impl Display for CommitId {
 
 impl GitClient {
     pub fn new(path: std::path::PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            remote: "origin".to_string(),
+            push_remote: "origin".to_string(),
+            commit_writer: OnceCell::new(),
+            git_dir: OnceCell::new(),
+            pushes: Stopwatch::default(),
+            dry_run: false,
+        }
     }
 
-    pub async fn get_tree(&self, commit_id: &CommitId) -> Result<String> {
+    /// The commit writer, if commits can be written directly.
+    async fn commit_writer(&self) -> Option<&CommitWriter> {
+        self.commit_writer
+            .get_or_init(|| async { self.new_commit_writer().await.ok().flatten() })
+            .await
+            .as_ref()
+    }
+
+    /// Set up a commit writer, reusing the `.git` directory if it's been
+    /// looked up already, and otherwise running git just once, for the
+    /// identities and config that `git var -l` lists together.
+    async fn new_commit_writer(&self) -> Result<Option<CommitWriter>> {
+        if std::env::var_os("GIT_OBJECT_DIRECTORY").is_some() {
+            return Ok(None);
+        }
+        let git_dir = self.get_git_dir().await?;
+        let vars = parse_vars(&self.output(&["var", "-l"]).await?);
+        let var = |name: &str| vars.get(name).map(String::as_str);
+        if var("extensions.objectformat").is_some_and(|format| format != "sha1") {
+            return Ok(None);
+        }
+        // git signs commits with this set, which we can't
+        if var("commit.gpgsign")
+            .is_some_and(|sign| ["true", "yes", "on", "1"].contains(&sign.to_lowercase().as_str()))
+        {
+            return Ok(None);
+        }
+        let (Some(author), Some(committer)) = (
+            var("GIT_AUTHOR_IDENT").and_then(|ident| Identity::parse(ident, "GIT_AUTHOR_DATE")),
+            var("GIT_COMMITTER_IDENT")
+                .and_then(|ident| Identity::parse(ident, "GIT_COMMITTER_DATE")),
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(CommitWriter {
+            store: ObjectStore::new(git_dir.join("objects")),
+            author,
+            committer,
+        }))
+    }
+
+    /// Run git, returning its stdout.
+    async fn output(&self, args: &[&str]) -> Result<String> {
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(["rev-parse", &format!("{}^{{tree}}", commit_id)])
+            .args(args)
             .output()
             .await
             .context("Failed to execute git command")?;
@@ -46,13 +179,61 @@ impl GitClient {
             );
         }
 
-        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+        Ok(String::from_utf8(output.stdout)?)
     }
 
-    pub async fn get_branch_tip(&self, branch: &str) -> Result<CommitId> {
+    /// Use another remote than `origin`, pushing PR branches to it too unless
+    /// [`Self::with_push_remote`] says otherwise.
+    pub fn with_remote(mut self, remote: &str) -> Self {
+        self.remote = remote.to_string();
+        self.push_remote = remote.to_string();
+        self
+    }
+
+    /// Refuse to change the repository or its remotes, so that a bug in a
+    /// `--dry-run` can't change anything.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Fail if this is a dry run, before doing `action`.
+    fn ensure_writable(&self, action: &str) -> Result<()> {
+        if self.dry_run {
+            bail!("Refusing to {action} during a dry run");
+        }
+        Ok(())
+    }
+
+    /// The remote the repository is fetched from.
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    /// Push PR branches to another remote, e.g. a fork.
+    pub fn with_push_remote(mut self, remote: &str) -> Self {
+        self.push_remote = remote.to_string();
+        self
+    }
+
+    /// The remote PR branches are pushed to.
+    pub fn push_remote(&self) -> &str {
+        &self.push_remote
+    }
+
+    pub async fn get_tree(&self, commit_id: &CommitId) -> Result<String> {
+        // jj's commits are usually loose objects, which we can read
+        if let Some(writer) = self.commit_writer().await
+            && let Ok(Some((kind, content))) = writer.store.read(&commit_id.0)
+            && kind == "commit"
+            && let Some(tree) = git_objects::commit_tree(&content)
+        {
+            return Ok(tree.to_string());
+        }
+
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(["rev-parse", &format!("origin/{}", branch)])
+            .args(["rev-parse", &format!("{}^{{tree}}", commit_id)])
             .output()
             .await
             .context("Failed to execute git command")?;
@@ -64,9 +245,32 @@ impl GitClient {
             );
         }
 
-        Ok(CommitId(
-            String::from_utf8(output.stdout)?.trim().to_string(),
-        ))
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Get the tip of a branch on the remote.
+    pub async fn get_branch_tip(&self, branch: &str) -> Result<CommitId> {
+        self.get_remote_branch_tip(&self.remote, branch).await
+    }
+
+    /// Get the tip of a PR branch on the push remote.
+    pub async fn get_pr_branch_tip(&self, branch: &str) -> Result<CommitId> {
+        self.get_remote_branch_tip(&self.push_remote, branch).await
+    }
+
+    /// Get the tip of a local branch, e.g. a PR branch prepared by
+    /// `jr create --no-push`.
+    pub async fn get_local_branch_tip(&self, branch: &str) -> Result<CommitId> {
+        self.rev_parse(&format!("refs/heads/{branch}")).await
+    }
+
+    async fn get_remote_branch_tip(&self, remote: &str, branch: &str) -> Result<CommitId> {
+        self.rev_parse(&format!("{remote}/{branch}")).await
+    }
+
+    async fn rev_parse(&self, revision: &str) -> Result<CommitId> {
+        let output = self.output(&["rev-parse", revision]).await?;
+        Ok(CommitId(output.trim().to_string()))
     }
 
     pub async fn commit_tree(
@@ -75,6 +279,22 @@ impl GitClient {
         parents: Vec<&CommitId>,
         message: &str,
     ) -> Result<CommitId> {
+        self.ensure_writable("write a commit")?;
+        if let Some(writer) = self.commit_writer().await {
+            let parents = parents
+                .iter()
+                .map(|parent| parent.0.as_str())
+                .collect::<Vec<_>>();
+            let content = git_objects::encode_commit(
+                tree,
+                &parents,
+                &writer.author.stamp(),
+                &writer.committer.stamp(),
+                message,
+            );
+            return writer.store.write("commit", &content).map(CommitId);
+        }
+
         let mut args = vec!["commit-tree".to_string(), tree.to_string()];
         for parent in &parents {
             args.push("-p".to_string());
@@ -102,13 +322,378 @@ impl GitClient {
         ))
     }
 
+    /// Create a branch on the remote, e.g. the default branch of a brand-new
+    /// repository.  Fails if the branch already exists.
+    pub async fn create_remote_branch(&self, commit_id: &CommitId, branch: &str) -> Result<()> {
+        self.ensure_writable(&format!("create branch {branch}"))?;
+        let refspec = format!("{}:refs/heads/{}", commit_id.0, branch);
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["push", &self.remote, &refspec])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
     /// Push a commit directly to a remote branch without creating a local branch
     pub async fn push_commit_to_branch(&self, commit_id: &CommitId, branch: &str) -> Result<()> {
+        self.ensure_writable(&format!("push to {branch}"))?;
         ensure!(!["main", "master", "dev", "development", "stage", "staging"].contains(&branch));
         let refspec = format!("{}:refs/heads/{}", commit_id.0, branch);
+        let start = Instant::now();
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["push", "-u", &self.push_remote, &refspec])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        self.pushes.record(start.elapsed());
+
+        Ok(())
+    }
+
+    /// How long the pushes of PR branches so far took.
+    pub fn push_timing(&self) -> Timing {
+        self.pushes.timing()
+    }
+
+    /// Run a pre-push validation command before pushing a commit to a remote
+    /// branch.
+    ///
+    /// The command is run through `sh` with the same interface as a git
+    /// pre-push hook: it receives the remote name and URL as arguments, and a
+    /// line "<local ref> <local sha> <remote ref> <remote sha>" on stdin.
+    /// Since we push commits rather than local branches, the local ref is
+    /// reported as `refs/heads/<branch>` so that branch naming policies apply.
+    /// The push is rejected if and only if the command fails.
+    pub async fn run_pre_push_command(
+        &self,
+        command: &str,
+        commit_id: &CommitId,
+        branch: &str,
+    ) -> Result<()> {
+        let remote_url = self.get_remote_url().await?;
+        let remote_sha = self
+            .get_pr_branch_tip(branch)
+            .await
+            .map(|tip| tip.0)
+            .unwrap_or_else(|_| "0".repeat(40));
+        let stdin = format!("refs/heads/{branch} {commit_id} refs/heads/{branch} {remote_sha}\n");
+
+        let mut child = Command::new("sh")
+            .current_dir(&self.path)
+            .args([
+                "-c",
+                &format!("{command} \"$@\""),
+                "sh",
+                &self.push_remote,
+                &remote_url,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute pre-push command")?;
+        let mut child_stdin = child.stdin.take().context("Failed to open stdin")?;
+        // Hooks needn't read their input, and may exit before it's written,
+        // so only their exit status counts
+        match child_stdin.write_all(stdin.as_bytes()).await {
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {}
+            result => result.context("Failed to write to pre-push command")?,
+        }
+        drop(child_stdin);
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            bail!(
+                "Pre-push command rejected push to {}:\n{}{}",
+                branch,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the path of the `.git` directory, looking it up once.
+    pub async fn get_git_dir(&self) -> Result<std::path::PathBuf> {
+        let git_dir = self
+            .git_dir
+            .get_or_try_init(|| async {
+                let output = self.output(&["rev-parse", "--absolute-git-dir"]).await?;
+                anyhow::Ok(std::path::PathBuf::from(output.trim()))
+            })
+            .await?;
+        Ok(git_dir.clone())
+    }
+
+    /// Attach a note to a commit under a notes ref, replacing any existing
+    /// note.
+    pub async fn add_note(&self, notes_ref: &str, commit_id: &CommitId, note: &str) -> Result<()> {
+        self.ensure_writable(&format!("add a note to {commit_id}"))?;
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args([
+                "notes",
+                "--ref",
+                notes_ref,
+                "add",
+                "-f",
+                "-m",
+                note,
+                &commit_id.0,
+            ])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get a commit's note under a notes ref, if it has one.
+    pub async fn get_note(&self, notes_ref: &str, revision: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["notes", "--ref", notes_ref, "show", revision])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(String::from_utf8(output.stdout)?))
+    }
+
+    /// Remove the notes under a notes ref for commits that no longer exist,
+    /// e.g. from deleted PR branches once git has garbage collected them,
+    /// returning how many there were.  If `dry_run`, just count them.
+    pub async fn prune_notes(&self, notes_ref: &str, dry_run: bool) -> Result<usize> {
+        let mut args = vec!["notes", "--ref", notes_ref, "prune", "-v"];
+        if dry_run {
+            args.push("-n");
+        } else {
+            self.ensure_writable(&format!("prune {notes_ref}"))?;
+        }
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(args)
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?.lines().count())
+    }
+
+    /// Push a ref, e.g. a notes ref, to the push remote.
+    pub async fn push_ref(&self, refname: &str) -> Result<()> {
+        self.ensure_writable(&format!("push {refname}"))?;
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["push", &self.push_remote, refname])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Check out a commit into a new worktree at `path`, detached from any
+    /// branch.
+    pub async fn add_worktree(&self, path: &std::path::Path, commit_id: &CommitId) -> Result<()> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["worktree", "add", "--detach"])
+            .arg(path)
+            .arg(&commit_id.0)
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove a worktree added by [`GitClient::add_worktree`], along with any
+    /// changes made in it.
+    pub async fn remove_worktree(&self, path: &std::path::Path) -> Result<()> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["worktree", "remove", "--force"])
+            .arg(path)
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the diff between two commits.
+    pub async fn get_diff(&self, from: &CommitId, to: &CommitId) -> Result<String> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["diff", &from.0, &to.0])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Get the email address of a commit's committer.
+    pub async fn get_committer_email(&self, commit_id: &CommitId) -> Result<String> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["log", "-1", "--format=%ce", &commit_id.0])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Get the configured `user.email`, if any.
+    pub async fn get_user_email(&self) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["config", "--get", "user.email"])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        let email = String::from_utf8(output.stdout)?.trim().to_string();
+        Ok((output.status.success() && !email.is_empty()).then_some(email))
+    }
+
+    /// Get the URL of the push remote.
+    pub async fn get_remote_url(&self) -> Result<String> {
+        self.get_url_of(&self.push_remote).await
+    }
+
+    /// Get the URL of a remote.
+    pub async fn get_url_of(&self, remote: &str) -> Result<String> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["config", "--get", &format!("remote.{remote}.url")])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!("No git remote '{}' configured", remote);
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Add a remote.
+    pub async fn add_remote(&self, name: &str, url: &str) -> Result<()> {
+        self.ensure_writable(&format!("add remote {name}"))?;
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["remote", "add", name, url])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Point a local branch at a commit, creating it if needed.
+    pub async fn set_local_branch(&self, branch: &str, commit_id: &CommitId) -> Result<()> {
+        self.ensure_writable(&format!("set branch {branch}"))?;
+        self.output(&["update-ref", &format!("refs/heads/{branch}"), &commit_id.0])
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a local branch, if it exists.
+    pub async fn delete_local_branch(&self, branch: &str) -> Result<()> {
+        self.ensure_writable(&format!("delete branch {branch}"))?;
+        self.output(&["update-ref", "-d", &format!("refs/heads/{branch}")])
+            .await?;
+        Ok(())
+    }
+
+    /// Point a remote at another URL.
+    pub async fn set_remote_url(&self, name: &str, url: &str) -> Result<()> {
+        self.ensure_writable(&format!("set the URL of remote {name}"))?;
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(["push", "-u", "origin", &refspec])
+            .args(["remote", "set-url", name, url])
             .output()
             .await
             .context("Failed to execute git command")?;
@@ -125,9 +710,10 @@ impl GitClient {
 
     /// Delete a remote branch
     pub async fn delete_branch(&self, branch: &str) -> Result<()> {
+        self.ensure_writable(&format!("delete branch {branch}"))?;
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(["push", "origin", "--delete", branch])
+            .args(["push", &self.push_remote, "--delete", branch])
             .output()
             .await
             .context("Failed to execute git command")?;
@@ -157,6 +743,40 @@ impl GitClient {
         Ok(output.status.success())
     }
 
+    /// The files that conflict when merging two commits, as GitHub would to
+    /// merge a PR into its base.  Nothing is written to the working copy.
+    pub async fn merge_conflicts(&self, base: &CommitId, head: &CommitId) -> Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args([
+                "merge-tree",
+                "--write-tree",
+                "--name-only",
+                "--no-messages",
+                &base.0,
+                &head.0,
+            ])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        // Exit code 0 means it merges cleanly, 1 that there are conflicts,
+        // listed after the merged tree's ID
+        match output.status.code() {
+            Some(0) => Ok(vec![]),
+            Some(1) => Ok(String::from_utf8(output.stdout)?
+                .lines()
+                .skip(1)
+                .take_while(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()),
+            _ => bail!(
+                "git merge-tree failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        }
+    }
+
     /// Get a canonical representation of the changes introduced by a commit.
     /// Returns a string representing the diff (file names and status) that can be compared.
     pub async fn get_commit_diff(&self, commit_id: &CommitId) -> Result<String> {
@@ -181,8 +801,55 @@ impl GitClient {
         Ok(String::from_utf8(output.stdout)?)
     }
 
+    /// Get the paths of the files changed by a commit.
+    pub async fn get_changed_files(&self, commit_id: &CommitId) -> Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args([
+                "diff-tree",
+                "-r",
+                // A root commit's files are all added, rather than none
+                "--root",
+                "--name-only",
+                "--no-commit-id",
+                &commit_id.0,
+            ])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(|line| line.to_string())
+            .collect())
+    }
+
+    /// Get the contents of a file as of a commit, returns None if the file
+    /// doesn't exist.
+    pub async fn get_file(&self, commit_id: &CommitId, path: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["show", &format!("{}:{}", commit_id, path)])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(String::from_utf8(output.stdout)?))
+    }
+
     /// Get the remote git branches for a commit.
-    /// Returns branch names with "origin/" prefix stripped (e.g., ["main", "test/abc12345"])
+    /// Returns branch names with the push remote stripped (e.g., ["main", "test/abc12345"])
     pub async fn get_git_remote_branches(&self, commit_id: &CommitId) -> Result<Vec<String>> {
         let output = Command::new("git")
             .current_dir(&self.path)
@@ -207,18 +874,48 @@ impl GitClient {
         let output_str = String::from_utf8(output.stdout)?.trim().to_string();
 
         // Parse git refs and filter for remote branches only
+        let remote = format!("{}/", self.push_remote);
         let branches: Vec<String> = output_str
             .lines()
-            .filter_map(|line| line.strip_prefix("origin/").map(|s| s.to_string()))
+            .filter_map(|line| line.strip_prefix(&remote).map(|s| s.to_string()))
             .collect();
 
         Ok(branches)
     }
 
-    /// Find remote branches matching a prefix.
-    /// Returns branch names with "origin/" prefix stripped (e.g., ["test/abc123", "test/xyz789"])
+    /// Find branches on the push remote matching a prefix.
+    /// Returns branch names with the remote stripped (e.g., ["test/abc123", "test/xyz789"])
+    /// Get when the tip of each branch with a prefix on the push remote was
+    /// committed.
+    pub async fn get_branch_tip_dates(
+        &self,
+        prefix: &str,
+    ) -> Result<HashMap<String, DateTime<Utc>>> {
+        let pattern = format!("refs/remotes/{}/{}*", self.push_remote, prefix);
+        let output = self
+            .output(&[
+                "for-each-ref",
+                "--format=%(refname:short) %(committerdate:iso-strict)",
+                &pattern,
+            ])
+            .await?;
+        let remote = format!("{}/", self.push_remote);
+        let mut dates = HashMap::new();
+        for line in output.lines() {
+            let Some((branch, date)) = line.split_once(' ') else {
+                continue;
+            };
+            if let Some(branch) = branch.strip_prefix(&remote) {
+                let date = DateTime::parse_from_rfc3339(date)
+                    .with_context(|| format!("Unexpected commit date for {branch}: {date}"))?;
+                dates.insert(branch.to_string(), date.to_utc());
+            }
+        }
+        Ok(dates)
+    }
+
     pub async fn find_branches_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
-        let pattern = format!("refs/remotes/origin/{}", prefix);
+        let pattern = format!("refs/remotes/{}/{}", self.push_remote, prefix);
         let output = Command::new("git")
             .current_dir(&self.path)
             .args([
@@ -239,20 +936,21 @@ impl GitClient {
 
         let output_str = String::from_utf8(output.stdout)?.trim().to_string();
 
+        let remote = format!("{}/", self.push_remote);
         let branches: Vec<String> = output_str
             .lines()
-            .filter_map(|line| line.strip_prefix("origin/").map(|s| s.to_string()))
+            .filter_map(|line| line.strip_prefix(&remote).map(|s| s.to_string()))
             .collect();
 
         Ok(branches)
     }
 
     /// Get the default branch name from the remote.
-    /// Returns the branch name (e.g., "main" or "master") without the "origin/" prefix.
+    /// Returns the branch name (e.g., "main" or "master") without the remote prefix.
     pub async fn get_default_branch(&self) -> Result<String> {
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(["ls-remote", "--symref", "origin", "HEAD"])
+            .args(["ls-remote", "--symref", &self.remote, "HEAD"])
             .output()
             .await
             .context("Failed to execute git command")?;
@@ -277,4 +975,144 @@ impl GitClient {
 
         bail!("Could not determine default branch from git ls-remote output");
     }
+
+    /// Get the commit that last modified each line of a file, as of the given
+    /// commit.
+    pub async fn blame(&self, commit_id: &CommitId, file: &str) -> Result<Vec<BlameLine>> {
+        let output = Command::new("git")
+            .current_dir(&self.path)
+            .args(["blame", "--porcelain", &commit_id.0, "--", file])
+            .output()
+            .await
+            .context("Failed to execute git command")?;
+
+        if !output.status.success() {
+            bail!(
+                "git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        // The porcelain format emits a header line "<sha> <orig> <final> [<n>]"
+        // for every line, followed by optional metadata lines, followed by the
+        // line contents prefixed with a tab.
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut lines = Vec::new();
+        let mut current: Option<CommitId> = None;
+        for line in output_str.lines() {
+            if let Some(content) = line.strip_prefix('\t') {
+                let commit_id = current.take().context("Unexpected git blame output")?;
+                lines.push(BlameLine {
+                    commit_id,
+                    content: content.to_string(),
+                });
+            } else if current.is_none()
+                && let Some(sha) = line.split(' ').next()
+                && sha.len() == 40
+                && sha.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                current = Some(CommitId(sha.to_string()));
+            }
+        }
+
+        Ok(lines)
+    }
+}
+
+/// Parse `git var -l` output: config, with lowercase section and key names,
+/// then git's variables.  Later values win, as with git config, and a key
+/// without a value is true.
+fn parse_vars(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (line.to_string(), "true".to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A git repository with an `origin` remote, which needn't exist.
+    async fn repo() -> (tempfile::TempDir, GitClient) {
+        let dir = tempfile::tempdir().unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["remote", "add", "origin", "https://github.com/o/r.git"],
+        ] {
+            let status = Command::new("git")
+                .current_dir(dir.path())
+                .args(args)
+                .status()
+                .await
+                .unwrap();
+            assert!(status.success());
+        }
+        let git = GitClient::new(dir.path().to_path_buf());
+        (dir, git)
+    }
+
+    #[tokio::test]
+    async fn test_pre_push_command() {
+        let (_dir, git) = repo().await;
+        let commit = CommitId("a".repeat(40));
+        let run = async |command: &str| git.run_pre_push_command(command, &commit, "pr/x").await;
+
+        // Passes, having been given the push like a git hook
+        let hook = r#"hook() {
+            read local_ref local_sha remote_ref remote_sha &&
+            test "$1 $2 $local_ref $remote_ref" = \
+                "origin https://github.com/o/r.git refs/heads/pr/x refs/heads/pr/x"
+        }; hook"#;
+        run(hook).await.unwrap();
+
+        // Rejects the push
+        let err = run("echo 'not on a Friday' >&2; exit 1").await.unwrap_err();
+        assert!(err.to_string().contains("not on a Friday"), "{err}");
+
+        // Ignores its input, closing it before it's written
+        run("exec <&-; exit 0").await.unwrap();
+        run("exec <&-; exit 1").await.unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_vars() {
+        let vars = parse_vars(
+            "commit.gpgsign=false\ncommit.gpgsign\nuser.name=A = B\n\
+             GIT_AUTHOR_IDENT=A <a@example.com> 1700000000 +0000\n",
+        );
+        assert_eq!(vars["commit.gpgsign"], "true");
+        assert_eq!(vars["user.name"], "A = B");
+        assert_eq!(
+            vars["GIT_AUTHOR_IDENT"],
+            "A <a@example.com> 1700000000 +0000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commit_writer() {
+        let (dir, git) = repo().await;
+        let config = async |key: &str, value: &str| {
+            let status = Command::new("git")
+                .current_dir(dir.path())
+                .args(["config", key, value])
+                .status()
+                .await
+                .unwrap();
+            assert!(status.success());
+        };
+        config("user.name", "Test User").await;
+        config("user.email", "test@example.com").await;
+        let writer = git.commit_writer().await.unwrap();
+        assert_eq!(writer.author.name_email, "Test User <test@example.com>");
+        assert!(writer.store.read(&"0".repeat(40)).unwrap().is_none());
+
+        // Signed commits are left to git
+        config("commit.gpgSign", "yes").await;
+        let git = GitClient::new(dir.path().to_path_buf());
+        assert!(git.commit_writer().await.is_none());
+    }
 }
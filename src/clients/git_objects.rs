@@ -0,0 +1,151 @@
+//! A minimal git object store, for synthesizing PR commits without running
+//! git.
+//!
+//! Creating or updating a PR reuses the tree of the jj commit in a new commit
+//! object.  With git, that's a subprocess to find the tree and another to
+//! write the commit, whose startup dominates on Windows and NFS homes.  jj
+//! writes its commits as loose objects, so the tree can usually be read
+//! directly, and the commit written as another loose object.  Anything this
+//! doesn't handle (packed objects, SHA-256 repositories, signed commits) is
+//! left to git.
+
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+/// Distinguishes the temporary files of objects written at once.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Reads and writes loose objects in a repository's object directory.
+#[derive(Debug)]
+pub struct ObjectStore {
+    objects_dir: PathBuf,
+}
+
+impl ObjectStore {
+    pub fn new(objects_dir: PathBuf) -> Self {
+        Self { objects_dir }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        if id.len() != 40 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("Invalid object ID: {id}");
+        }
+        Ok(self.objects_dir.join(&id[..2]).join(&id[2..]))
+    }
+
+    /// Read a loose object's type and content, or `None` if it isn't loose,
+    /// e.g. because it's packed.
+    pub fn read(&self, id: &str) -> Result<Option<(String, Vec<u8>)>> {
+        let compressed = match std::fs::read(self.path(id)?) {
+            Ok(compressed) => compressed,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context("Failed to read git object"),
+        };
+        let mut data = vec![];
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut data)
+            .context("Corrupt git object")?;
+        let nul = data
+            .iter()
+            .position(|&b| b == 0)
+            .context("Corrupt git object header")?;
+        let header = std::str::from_utf8(&data[..nul]).context("Corrupt git object header")?;
+        let (kind, _size) = header
+            .split_once(' ')
+            .context("Corrupt git object header")?;
+        Ok(Some((kind.to_string(), data[nul + 1..].to_vec())))
+    }
+
+    /// Write a loose object, returning its ID.
+    pub fn write(&self, kind: &str, content: &[u8]) -> Result<String> {
+        let mut data = format!("{kind} {}\0", content.len()).into_bytes();
+        data.extend_from_slice(content);
+        let id = sha1_smol::Sha1::from(&data).digest().to_string();
+
+        let path = self.path(&id)?;
+        if path.exists() {
+            return Ok(id);
+        }
+        let dir = path.parent().expect("Objects are in a subdirectory");
+        std::fs::create_dir_all(dir).context("Failed to create git object directory")?;
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&data)?;
+        // Write then rename, so readers never see a partial object
+        let temp = dir.join(format!(
+            "tmp_obj_{}_{}",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&temp, encoder.finish()?).context("Failed to write git object")?;
+        std::fs::rename(&temp, &path).context("Failed to write git object")?;
+        Ok(id)
+    }
+}
+
+/// The tree of a commit object's content.
+pub fn commit_tree(content: &[u8]) -> Option<&str> {
+    let line = content.split(|&b| b == b'\n').next()?;
+    std::str::from_utf8(line.strip_prefix(b"tree ")?).ok()
+}
+
+/// Encode a commit object's content, as `git commit-tree` would.  The
+/// identities are of the form `Name <email> timestamp timezone`, as printed
+/// by `git var`.
+pub fn encode_commit(
+    tree: &str,
+    parents: &[&str],
+    author: &str,
+    committer: &str,
+    message: &str,
+) -> Vec<u8> {
+    let mut content = format!("tree {tree}\n");
+    for parent in parents {
+        content.push_str(&format!("parent {parent}\n"));
+    }
+    content.push_str(&format!(
+        "author {author}\ncommitter {committer}\n\n{message}"
+    ));
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    #[test]
+    fn test_encode_commit() {
+        let content = encode_commit(
+            EMPTY_TREE,
+            &[],
+            "A <a@x> 1700000000 +0100",
+            "C <c@x> 1700000000 +0100",
+            "Title\n\nBody",
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let store = ObjectStore::new(dir.path().to_path_buf());
+        // The ID `git commit-tree` gives the same commit
+        let id = store.write("commit", &content).unwrap();
+        assert_eq!(id, "fe80639f2152b1d357d392c1f9f0381dd58a3115");
+
+        let (kind, read) = store.read(&id).unwrap().unwrap();
+        assert_eq!(kind, "commit");
+        assert_eq!(read, content);
+        assert_eq!(commit_tree(&read), Some(EMPTY_TREE));
+        assert_eq!(store.read(EMPTY_TREE).unwrap(), None);
+    }
+}
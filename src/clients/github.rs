@@ -1,16 +1,37 @@
 use std::collections::HashMap;
 use std::path;
+use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::bail;
+use chrono::DateTime;
+use chrono::Utc;
+use log::debug;
 use serde::Deserialize;
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use tokio::process::Command;
+use tokio::sync::RwLock;
 use tracing::instrument;
 
-use super::github_curl::GithubCurlClient;
+use super::github_http::GithubHttpClient;
+use crate::i18n::tr;
+use crate::merge::MergeMethod;
+use crate::model::Account;
+use crate::model::BranchRef;
+use crate::model::CiState;
+use crate::model::IssueComment;
+use crate::model::Label;
+use crate::model::Milestone;
+use crate::model::PullRequest;
+use crate::model::PullRequestState;
+use crate::model::Review;
+use crate::model::ReviewDecision;
+use crate::model::TimelineEvent;
+use crate::plan::Timing;
 
 // -----------------------------------------------------------------------------
 // Types
@@ -22,20 +43,250 @@ use super::github_curl::GithubCurlClient;
 pub struct GithubClient {
     owner: String,
     repo: String,
-    http_client: GithubCurlClient,
+    /// Owner of the repository PR branches are pushed to: `owner`, or the
+    /// owner of a fork.
+    head_owner: String,
+    /// The GitHub instance the repository is on.
+    host: GithubHost,
+    http_client: GithubHttpClient,
     // Local caching, significantly speeds up integration tests where we reuse
     // the same GitHub client.  Assumes that each branch is associated with a
     // single PR (true for us).
     branch_to_pr: Mutex<HashMap<String, Option<PullRequest>>>,
     // Cached PR diff.  Invalidated on PR update.
     pr_number_to_diff: Mutex<HashMap<u64, String>>,
+    // Cached repository metadata.
+    repository: Mutex<Option<Repository>>,
+    // Cached capabilities, probed once per run.
+    capabilities: Mutex<Option<Capabilities>>,
+    // Held for writing while PRs are prefetched, so that PR lookups wait for
+    // the prefetch rather than duplicating its requests.
+    prefetch: Arc<RwLock<()>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct PullRequest {
-    number: u64,
-    html_url: String,
+/// The GitHub instance a repository is on: github.com, or a GitHub Enterprise
+/// Server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubHost {
+    /// Hostname of the web UI and git remotes, e.g. `github.com`.
+    pub name: String,
+    /// Base URL of the REST API, without a trailing slash.
+    pub api_url: String,
+}
+
+impl Default for GithubHost {
+    fn default() -> Self {
+        Self::new("github.com", None)
+    }
+}
+
+impl GithubHost {
+    /// A host, whose API is at `api_url` if given, and otherwise where GitHub
+    /// puts it: `api.github.com` for github.com, and under `/api/v3` for
+    /// GitHub Enterprise Server.
+    pub fn new(name: &str, api_url: Option<&str>) -> Self {
+        let api_url = match api_url {
+            Some(api_url) => api_url.trim_end_matches('/').to_string(),
+            None if name.eq_ignore_ascii_case("github.com") => "https://api.github.com".to_string(),
+            None => format!("https://{name}/api/v3"),
+        };
+        Self {
+            name: name.to_string(),
+            api_url,
+        }
+    }
+
+    /// URL of the GraphQL API, which GitHub Enterprise Server serves beside
+    /// the REST API rather than under it.
+    fn graphql_url(&self) -> String {
+        match self.api_url.strip_suffix("/v3") {
+            Some(api_url) => format!("{api_url}/graphql"),
+            None => format!("{}/graphql", self.api_url),
+        }
+    }
+
+    /// Parse the owner and repo from the URL of a git remote on this host,
+    /// e.g. `git@github.com:owner/repo.git`, `ssh://git@github.com/owner/repo`
+    /// or `https://github.com/owner/repo.git`.
+    pub fn parse_remote_url(&self, url: &str) -> Option<(String, String)> {
+        let (authority, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            // scp-like syntax
+            None => url.split_once(':')?,
+        };
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = host.split(':').next()?;
+        if !host.eq_ignore_ascii_case(&self.name) {
+            return None;
+        }
+
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let mut split = path.split('/');
+        let (owner, repo) = (split.next()?, split.next()?);
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        Some((owner.to_string(), repo.to_string()))
+    }
+
+    /// The URL of a git remote on this host once its repository has moved to
+    /// `full_name`, keeping the URL's form.
+    pub fn moved_remote_url(&self, url: &str, full_name: &str) -> String {
+        if let Some((owner, repo)) = self.parse_remote_url(url) {
+            let old = format!("{owner}/{repo}");
+            if let Some(start) = url.rfind(&old) {
+                let end = start + old.len();
+                return format!("{}{full_name}{}", &url[..start], &url[end..]);
+            }
+        }
+        format!("https://{}/{full_name}.git", self.name)
+    }
+}
+
+/// Repository metadata.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Repository {
+    /// The repository's current `owner/repo` name.
+    #[serde(default)]
+    pub full_name: String,
+    pub default_branch: String,
+    /// One of `public`, `private` or `internal`.
+    pub visibility: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    /// The authenticated user's permissions on the repository.
+    #[serde(default)]
+    pub permissions: RepositoryPermissions,
+    /// Which merge methods PRs can be merged with.  These are only returned
+    /// to users who can push, so default to allowed.
+    #[serde(default = "default_true")]
+    pub allow_squash_merge: bool,
+    #[serde(default = "default_true")]
+    pub allow_merge_commit: bool,
+    #[serde(default = "default_true")]
+    pub allow_rebase_merge: bool,
+    /// Whether PRs can be set to merge automatically once checks pass.
+    #[serde(default)]
+    pub allow_auto_merge: bool,
+}
+
+/// Features that vary between repositories and GitHub versions, so commands
+/// can work around a missing one up front rather than fail partway through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Draft PRs, which e.g. private repositories on GitHub Free lack.
+    /// There's no way to ask, so this is assumed until GitHub rejects a
+    /// draft.
+    pub drafts: bool,
+    /// Auto-merge, if the repository allows it.
+    pub auto_merge: bool,
+    /// A merge queue on the default branch, which PRs must be merged through.
+    pub merge_queue: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A fork of the repository.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fork {
+    pub full_name: String,
+    pub clone_url: String,
+    pub ssh_url: String,
+    #[serde(default)]
+    parent: Option<ForkParent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ForkParent {
+    full_name: String,
+}
+
+/// What the authenticated user can do in a repository.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RepositoryPermissions {
+    #[serde(default)]
+    pub pull: bool,
+    #[serde(default)]
+    pub push: bool,
+}
+
+impl Repository {
+    /// Whether PRs can't be created or updated in this repository.
+    pub fn is_read_only(&self) -> bool {
+        self.archived || self.disabled
+    }
+
+    /// The merge methods PRs can be merged with, in order of preference.
+    pub fn allowed_merge_methods(&self) -> Vec<MergeMethod> {
+        [
+            (self.allow_squash_merge, MergeMethod::Squash),
+            (self.allow_merge_commit, MergeMethod::Merge),
+            (self.allow_rebase_merge, MergeMethod::Rebase),
+        ]
+        .into_iter()
+        .filter_map(|(allowed, method)| allowed.then_some(method))
+        .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestFile {
+    filename: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+impl CheckRun {
+    fn state(&self) -> CiState {
+        match (self.status.as_str(), self.conclusion.as_deref()) {
+            ("completed", Some("success" | "neutral" | "skipped")) => CiState::Success,
+            ("completed", _) => CiState::Failure,
+            _ => CiState::Pending,
+        }
+    }
+}
+
+/// The combined state of a commit's (non-check-run) statuses.
+#[derive(Debug, Deserialize)]
+struct CombinedStatus {
     state: String,
+    total_count: u64,
+}
+
+impl CombinedStatus {
+    fn state(&self) -> CiState {
+        match self.state.as_str() {
+            "success" => CiState::Success,
+            "pending" => CiState::Pending,
+            _ => CiState::Failure,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Comparison {
+    behind_by: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -47,71 +298,370 @@ struct CreatePullRequest {
     draft: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct CreateComment<'a> {
+    body: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct AddAssignees<'a> {
+    assignees: &'a [String],
+}
+
+#[derive(Debug, Serialize)]
+struct UpdatePullRequestState<'a> {
+    state: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdatePullRequestBody<'a> {
+    body: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct SetLabels<'a> {
+    labels: &'a [String],
+}
+
 #[derive(Debug, Serialize)]
 struct UpdatePullRequest {
     base: String,
 }
 
+#[derive(Debug, Serialize)]
+struct MergePullRequest<'a> {
+    merge_method: MergeMethod,
+    /// Only merge if the PR's head is still this commit.
+    sha: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeResult {
+    merged: bool,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateIssueMilestone {
+    milestone: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct GraphqlRequest<'a> {
+    query: &'a str,
+    variables: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestReviewers {
+    reviewers: Vec<String>,
+    team_reviewers: Vec<String>,
+}
+
 // -----------------------------------------------------------------------------
 // GithubClient impl
 
 impl GithubClient {
-    pub async fn new(token: String, path: path::PathBuf) -> Result<Self> {
-        let (owner, repo) = Self::detect_owner_and_repo(&path).await?;
-        let http_client = GithubCurlClient::new(token);
+    /// A client for the repository a git remote (normally `origin`) points
+    /// at.
+    pub async fn new(
+        token: String,
+        path: path::PathBuf,
+        host: GithubHost,
+        remote: &str,
+    ) -> Result<Self> {
+        let (owner, repo) = Self::detect_owner_and_repo(&path, remote, &host).await?;
+        let http_client = GithubHttpClient::new(token)?;
 
         Ok(Self {
+            head_owner: owner.clone(),
             owner,
             repo,
+            host,
             http_client,
             branch_to_pr: Mutex::new(HashMap::new()),
             pr_number_to_diff: Mutex::new(HashMap::new()),
+            repository: Mutex::new(None),
+            capabilities: Mutex::new(None),
+            prefetch: Arc::new(RwLock::new(())),
         })
     }
 
+    /// A client that never contacts GitHub, e.g. for a sandbox repository: it
+    /// only knows the PRs given to [`Self::seed_pr`], and the repository,
+    /// with `default_branch`, as writable.  Changes are refused as in a dry
+    /// run.
+    pub fn offline(owner: &str, repo: &str, default_branch: &str) -> Result<Self> {
+        let repository = serde_json::from_value(serde_json::json!({
+            "full_name": format!("{owner}/{repo}"),
+            "default_branch": default_branch,
+            "visibility": "private",
+            "permissions": { "pull": true, "push": true },
+        }))?;
+        Ok(Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            head_owner: owner.to_string(),
+            host: GithubHost::new("github.com", None),
+            http_client: GithubHttpClient::new(String::new())?.with_offline(),
+            branch_to_pr: Mutex::new(HashMap::new()),
+            pr_number_to_diff: Mutex::new(HashMap::new()),
+            repository: Mutex::new(Some(repository)),
+            capabilities: Mutex::new(None),
+            prefetch: Arc::new(RwLock::new(())),
+        })
+    }
+
+    /// Open PRs from branches pushed to another remote, e.g. a fork, whose
+    /// owner is detected from its URL.
+    pub async fn with_push_remote(mut self, path: &path::Path, remote: &str) -> Result<Self> {
+        (self.head_owner, _) = Self::detect_owner_and_repo(path, remote, &self.host).await?;
+        Ok(self)
+    }
+
+    /// Time out GitHub API requests after `timeout`, rather than the default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.http_client = self.http_client.with_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// Also trust the CA certificates in a PEM file.
+    pub fn with_ca_bundle(mut self, path: &path::Path) -> Result<Self> {
+        self.http_client = self.http_client.with_ca_bundle(path)?;
+        Ok(self)
+    }
+
+    /// Refuse to change anything on GitHub, for `--dry-run`.
+    pub fn with_dry_run(mut self) -> Self {
+        self.http_client = self.http_client.with_dry_run();
+        self
+    }
+
+    /// Make changes to GitHub with a separate token, keeping the client's
+    /// own token for reads.
+    pub fn with_write_token(mut self, token: String) -> Self {
+        self.http_client = self.http_client.with_write_token(token);
+        self
+    }
+
+    /// POST a JSON payload to a webhook, with the same proxy and TLS
+    /// settings as GitHub requests.  Refused during a dry run.
+    pub async fn post_webhook(&self, url: &str, payload: &str) -> Result<()> {
+        self.http_client.post_webhook(url, payload).await
+    }
+
+    /// Whether changes to GitHub are refused (see [`Self::with_dry_run`]).
+    pub fn is_dry_run(&self) -> bool {
+        self.http_client.is_dry_run()
+    }
+
+    /// Have at most `jobs` GitHub requests in flight at once.
+    pub fn with_max_requests(mut self, jobs: usize) -> Self {
+        self.http_client = self.http_client.with_max_requests(jobs);
+        self
+    }
+
     /// Detect owner and repo from git remote URL
-    async fn detect_owner_and_repo(path: &path::Path) -> Result<(String, String)> {
+    async fn detect_owner_and_repo(
+        path: &path::Path,
+        remote: &str,
+        host: &GithubHost,
+    ) -> Result<(String, String)> {
         let output = Command::new("git")
             .current_dir(path)
-            .args(["config", "--get", "remote.origin.url"])
+            .args(["config", "--get", &format!("remote.{remote}.url")])
             .output()
             .await
             .context("Failed to get git remote URL")?;
 
         if !output.status.success() {
-            bail!("No git remote 'origin' configured");
+            bail!("No git remote '{}' configured", remote);
         }
 
         let url = String::from_utf8(output.stdout)?.trim().to_string();
+        match host.parse_remote_url(&url) {
+            Some(owner_and_repo) => Ok(owner_and_repo),
+            None => bail!(
+                "Remote URL is not a {} URL: {} (set jr.githubHost for GitHub Enterprise Server)",
+                host.name,
+                url
+            ),
+        }
+    }
 
-        // Parse URLs like:
-        // git@github.com:owner/repo.git
-        // https://github.com/owner/repo.git
-        let parts = if url.starts_with("git@github.com:") {
-            url.strip_prefix("git@github.com:")
-                .context("Invalid GitHub URL format")?
-        } else if url.starts_with("https://github.com/") {
-            url.strip_prefix("https://github.com/")
-                .context("Invalid GitHub URL format")?
-        } else {
-            bail!("Remote URL is not a GitHub URL: {}", url);
+    /// The GitHub instance the repository is on.
+    pub fn host(&self) -> &GithubHost {
+        &self.host
+    }
+
+    /// Get the token the `gh` CLI is logged in to a host with, if any.
+    pub async fn detect_gh_token(host: &GithubHost) -> Option<String> {
+        let output = Command::new("gh")
+            .args(["auth", "token", "--hostname", &host.name])
+            .output()
+            .await
+            .ok()?;
+        let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        (output.status.success() && !token.is_empty()).then_some(token)
+    }
+
+    /// Get the login of the authenticated user.
+    #[instrument(skip_all)]
+    pub async fn current_user(&self) -> Result<String> {
+        let response = self
+            .http_client
+            .get(
+                &format!("{}/user", self.host.api_url),
+                "application/vnd.github+json",
+            )
+            .await?;
+        let user: User = serde_json::from_str(&response)?;
+        Ok(user.login)
+    }
+
+    /// Get the repository's metadata.
+    #[instrument(skip_all)]
+    pub async fn repository(&self) -> Result<Repository> {
+        if let Some(repository) = &*self.repository.lock().expect("Shouldn't fail") {
+            return Ok(repository.clone());
+        }
+
+        let url = format!("{}/repos/{}/{}", self.host.api_url, self.owner, self.repo);
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let repository: Repository = serde_json::from_str(&response)?;
+
+        *self.repository.lock().expect("Shouldn't fail") = Some(repository.clone());
+        Ok(repository)
+    }
+
+    /// Get the repository's capabilities, probing them on first use.
+    #[instrument(skip_all)]
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        if let Some(capabilities) = *self.capabilities.lock().expect("Shouldn't fail") {
+            return Ok(capabilities);
+        }
+
+        let repository = self.repository().await?;
+        // GitHub versions without merge queues reject the field
+        let merge_queue = self
+            .graphql::<serde_json::Value>(
+                r#"query($owner: String!, $name: String!, $branch: String!) {
+                    repository(owner: $owner, name: $name) { mergeQueue(branch: $branch) { id } }
+                }"#,
+                serde_json::json!({
+                    "owner": self.owner,
+                    "name": self.repo,
+                    "branch": repository.default_branch,
+                }),
+            )
+            .await
+            .is_ok_and(|data| data["repository"]["mergeQueue"].is_object());
+        let capabilities = Capabilities {
+            drafts: true,
+            auto_merge: repository.allow_auto_merge,
+            merge_queue,
         };
 
-        let parts = parts.strip_suffix(".git").unwrap_or(parts);
-        let mut split = parts.split('/');
-        let owner = split
-            .next()
-            .context("Could not parse owner from GitHub URL")?
-            .to_string();
-        let repo = split
-            .next()
-            .context("Could not parse repo from GitHub URL")?
-            .to_string();
+        *self.capabilities.lock().expect("Shouldn't fail") = Some(capabilities);
+        Ok(capabilities)
+    }
 
-        Ok((owner, repo))
+    /// Forget cached PRs and diffs, so they're fetched again.
+    pub fn clear_cache(&self) {
+        self.branch_to_pr.lock().expect("Shouldn't fail").clear();
+        self.pr_number_to_diff
+            .lock()
+            .expect("Shouldn't fail")
+            .clear();
     }
 
-    /// Create a new PR and return the PR URL
+    /// Find the user's fork of the repository, creating it if it doesn't
+    /// exist.  GitHub creates forks asynchronously, so a new fork may take a
+    /// few seconds to become available.
+    #[instrument(skip_all)]
+    pub async fn find_or_create_fork(&self, login: &str) -> Result<Fork> {
+        let url = format!("{}/repos/{}/{}", self.host.api_url, login, self.repo);
+        if let Ok(response) = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await
+        {
+            let fork: Fork = serde_json::from_str(&response)?;
+            if fork.parent.as_ref().map(|parent| parent.full_name.as_str())
+                == Some(self.full_name().as_str())
+            {
+                return Ok(fork);
+            }
+            bail!(
+                "{}/{} exists but isn't a fork of {}",
+                login,
+                self.repo,
+                self.full_name()
+            );
+        }
+
+        let url = format!(
+            "{}/repos/{}/{}/forks",
+            self.host.api_url, self.owner, self.repo
+        );
+        let response = self.http_client.post(&url, "{}").await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// The repository's `owner/repo` name.
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    /// The repository's owner.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// The repository's name, without its owner.
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    /// Whether GitHub has redirected a request, e.g. because the repository
+    /// has been renamed or transferred.
+    pub fn redirected(&self) -> bool {
+        self.http_client.redirected()
+    }
+
+    /// How long the requests sent so far took (see [`crate::plan`]).
+    pub fn timing(&self) -> Timing {
+        self.http_client.timing()
+    }
+
+    /// The repository's new `owner/repo` name, if it has been renamed or
+    /// transferred since the remote was set up.  GitHub redirects REST
+    /// requests for the old name, but GraphQL queries and PR lookups by head
+    /// owner don't follow it.
+    pub async fn moved_to(&self) -> Result<Option<String>> {
+        let full_name = self.repository().await?.full_name;
+        let moved = !full_name.is_empty() && !full_name.eq_ignore_ascii_case(&self.full_name());
+        Ok(moved.then_some(full_name))
+    }
+
+    /// Create a new PR and return the PR URL.  With `draft`, it's a draft if
+    /// the repository supports drafts; if GitHub turns out not to, it's
+    /// created ready for review and [`Capabilities::drafts`] is cleared.
     #[instrument(skip_all)]
     pub async fn pr_create(
         &self,
@@ -119,22 +669,37 @@ impl GithubClient {
         base_branch: &str,
         title: &str,
         body: &str,
+        draft: bool,
     ) -> Result<String> {
         let url = format!(
-            "https://api.github.com/repos/{}/{}/pulls",
-            self.owner, self.repo
+            "{}/repos/{}/{}/pulls",
+            self.host.api_url, self.owner, self.repo
         );
 
         let request_body = CreatePullRequest {
             title: title.to_string(),
             body: body.to_string(),
-            head: pr_branch.to_string(),
+            head: format!("{}:{}", self.head_owner, pr_branch),
             base: base_branch.to_string(),
-            draft: true,
+            draft: draft && self.capabilities().await?.drafts,
         };
 
         let json_data = serde_json::to_string(&request_body)?;
-        let response = self.http_client.post(&url, &json_data).await?;
+        let response = match self.http_client.post(&url, &json_data).await {
+            Err(err) if request_body.draft && is_drafts_unsupported(&err) => {
+                if let Some(capabilities) = &mut *self.capabilities.lock().expect("Shouldn't fail")
+                {
+                    capabilities.drafts = false;
+                }
+                let request_body = CreatePullRequest {
+                    draft: false,
+                    ..request_body
+                };
+                let json_data = serde_json::to_string(&request_body)?;
+                self.http_client.post(&url, &json_data).await?
+            }
+            response => response?,
+        };
         let pr: PullRequest = serde_json::from_str(&response)?;
 
         self.branch_to_pr
@@ -142,7 +707,7 @@ impl GithubClient {
             .expect("Shouldn't fail")
             .insert(pr_branch.into(), Some(pr.clone()));
 
-        Ok(pr.html_url)
+        Ok(pr.url)
     }
 
     /// Edit an existing PR and return the PR URL
@@ -159,8 +724,8 @@ impl GithubClient {
             .remove(&pr_number);
 
         let url = format!(
-            "https://api.github.com/repos/{}/{}/pulls/{}",
-            self.owner, self.repo, pr_number
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
         );
 
         let request_body = UpdatePullRequest {
@@ -170,7 +735,235 @@ impl GithubClient {
         let json_data = serde_json::to_string(&request_body)?;
         let response = self.http_client.patch(&url, &json_data).await?;
         let pr: PullRequest = serde_json::from_str(&response)?;
-        Ok(pr.html_url)
+
+        self.branch_to_pr
+            .lock()
+            .expect("Shouldn't fail")
+            .insert(pr_branch.into(), Some(pr.clone()));
+
+        Ok(pr.url)
+    }
+
+    /// Merge a PR, as long as its head is still `sha`.
+    #[instrument(skip_all)]
+    pub async fn pr_merge(&self, pr_number: u64, method: MergeMethod, sha: &str) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}/merge",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let request_body = MergePullRequest {
+            merge_method: method,
+            sha,
+        };
+        let json_data = serde_json::to_string(&request_body)?;
+        let response = self.http_client.put(&url, &json_data).await?;
+        let result: MergeResult = serde_json::from_str(&response)?;
+        if !result.merged {
+            bail!("Failed to merge PR #{pr_number}: {}", result.message);
+        }
+        Ok(())
+    }
+
+    /// Request reviews on a PR.  Reviewers are GitHub handles, either `@user`
+    /// or `@org/team`; anything else (e.g. email addresses) is ignored.
+    #[instrument(skip_all)]
+    pub async fn pr_request_reviewers(&self, pr_branch: &str, reviewers: &[String]) -> Result<()> {
+        let pr_number = self
+            .pr_number(pr_branch)
+            .await?
+            .context("PR not found for branch")?;
+
+        let mut request_body = RequestReviewers {
+            reviewers: vec![],
+            team_reviewers: vec![],
+        };
+        for reviewer in reviewers {
+            let Some(handle) = reviewer.strip_prefix('@') else {
+                continue;
+            };
+            match handle.split_once('/') {
+                Some((_org, team)) => request_body.team_reviewers.push(team.to_string()),
+                None => request_body.reviewers.push(handle.to_string()),
+            }
+        }
+        if request_body.reviewers.is_empty() && request_body.team_reviewers.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}/requested_reviewers",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+
+        let json_data = serde_json::to_string(&request_body)?;
+        self.http_client.post(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Assign a PR to the open milestone with the given title.
+    #[instrument(skip_all)]
+    pub async fn pr_set_milestone(&self, pr_branch: &str, title: &str) -> Result<()> {
+        let pr_number = self
+            .pr_number(pr_branch)
+            .await?
+            .context("PR not found for branch")?;
+
+        let url = format!(
+            "{}/repos/{}/{}/milestones?state=open&per_page=100",
+            self.host.api_url, self.owner, self.repo
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let milestones: Vec<Milestone> = serde_json::from_str(&response)?;
+        let Some(milestone) = milestones.into_iter().find(|m| m.title == title) else {
+            bail!("No open milestone named '{}'", title);
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let request_body = UpdateIssueMilestone {
+            milestone: milestone.number,
+        };
+        let json_data = serde_json::to_string(&request_body)?;
+        self.http_client.patch(&url, &json_data).await?;
+
+        if let Some(Some(pr)) = self
+            .branch_to_pr
+            .lock()
+            .expect("Shouldn't fail")
+            .get_mut(pr_branch)
+        {
+            pr.milestone = Some(milestone);
+        }
+
+        Ok(())
+    }
+
+    /// Add a PR to one of the repository owner's projects, identified by its
+    /// project number.
+    #[instrument(skip_all)]
+    pub async fn pr_add_to_project(&self, pr_branch: &str, project_number: u64) -> Result<()> {
+        let pr = self
+            .get_pr(pr_branch)
+            .await?
+            .context("PR not found for branch")?;
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            repository_owner: Option<Owner>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Owner {
+            project_v2: Option<Project>,
+        }
+        #[derive(Deserialize)]
+        struct Project {
+            id: String,
+        }
+
+        let data: Data = self
+            .graphql(
+                r#"query($owner: String!, $number: Int!) {
+                    repositoryOwner(login: $owner) {
+                        ... on Organization { projectV2(number: $number) { id } }
+                        ... on User { projectV2(number: $number) { id } }
+                    }
+                }"#,
+                serde_json::json!({ "owner": self.owner, "number": project_number }),
+            )
+            .await?;
+        let Some(project) = data.repository_owner.and_then(|owner| owner.project_v2) else {
+            bail!("No project #{} found for {}", project_number, self.owner);
+        };
+
+        let _: serde_json::Value = self
+            .graphql(
+                r#"mutation($project: ID!, $content: ID!) {
+                    addProjectV2ItemById(input: {projectId: $project, contentId: $content}) {
+                        item { id }
+                    }
+                }"#,
+                serde_json::json!({ "project": project.id, "content": pr.node_id }),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mark a PR as a draft, or as ready for review.
+    #[instrument(skip_all)]
+    pub async fn pr_set_draft(&self, pr_branch: &str, draft: bool) -> Result<()> {
+        let pr = self
+            .get_pr(pr_branch)
+            .await?
+            .context("PR not found for branch")?;
+
+        let query = if draft {
+            r#"mutation($id: ID!) {
+                convertPullRequestToDraft(input: {pullRequestId: $id}) { pullRequest { id } }
+            }"#
+        } else {
+            r#"mutation($id: ID!) {
+                markPullRequestReadyForReview(input: {pullRequestId: $id}) { pullRequest { id } }
+            }"#
+        };
+        let _: serde_json::Value = self
+            .graphql(query, serde_json::json!({ "id": pr.node_id }))
+            .await?;
+
+        if let Some(Some(pr)) = self
+            .branch_to_pr
+            .lock()
+            .expect("Shouldn't fail")
+            .get_mut(pr_branch)
+        {
+            pr.draft = draft;
+        }
+
+        Ok(())
+    }
+
+    /// Convert several PRs to drafts in a single request, so a stack is pulled
+    /// back from review all at once.
+    #[instrument(skip_all)]
+    pub async fn prs_convert_to_draft(&self, prs: &[PullRequest]) -> Result<()> {
+        if prs.is_empty() {
+            return Ok(());
+        }
+        let variables = prs
+            .iter()
+            .enumerate()
+            .map(|(i, pr)| (format!("id{i}"), pr.node_id.clone().into()))
+            .collect::<serde_json::Map<_, _>>();
+        let _: serde_json::Value = self
+            .graphql(&draft_mutation(prs.len()), variables.into())
+            .await?;
+
+        let mut branch_to_pr = self.branch_to_pr.lock().expect("Shouldn't fail");
+        for pr in prs {
+            if let Some(Some(cached)) = branch_to_pr.get_mut(&pr.head.name) {
+                cached.draft = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the title of a PR's milestone, returns None if the PR doesn't exist
+    /// or has no milestone.
+    #[instrument(skip_all)]
+    pub async fn pr_milestone(&self, branch: &str) -> Result<Option<String>> {
+        Ok(self
+            .get_pr(branch)
+            .await?
+            .and_then(|pr| pr.milestone)
+            .map(|milestone| milestone.title))
     }
 
     /// Get the diff for a PR.  This is the cumulative diff from the base to
@@ -192,8 +985,8 @@ impl GithubClient {
         }
 
         let url = format!(
-            "https://api.github.com/repos/{}/{}/pulls/{}",
-            self.owner, self.repo, pr_number
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
         );
 
         let diff = self
@@ -209,16 +1002,16 @@ impl GithubClient {
         Ok(diff)
     }
 
-    /// Get PR number from branch.
+    /// Get PR number from branch, returns None if no PR exists.
     #[instrument(skip_all)]
-    async fn pr_number(&self, branch: &str) -> Result<Option<u64>> {
+    pub async fn pr_number(&self, branch: &str) -> Result<Option<u64>> {
         Ok(self.get_pr(branch).await?.map(|pr| pr.number))
     }
 
     /// Get the PR URL for a branch, returns None if no PR exists
     #[instrument(skip_all)]
     pub async fn pr_url(&self, branch: &str) -> Result<Option<String>> {
-        Ok(self.get_pr(branch).await?.map(|pr| pr.html_url.clone()))
+        Ok(self.get_pr(branch).await?.map(|pr| pr.url))
     }
 
     /// Check if an open PR exists for a branch.
@@ -227,25 +1020,367 @@ impl GithubClient {
         Ok(self
             .get_pr(branch)
             .await?
-            .map(|pr| pr.state == "open")
+            .map(|pr| pr.is_open())
             .unwrap_or_default())
     }
 
-    /// Helper to get PR from branch name
+    /// List the repository's open PRs, most recently updated first.  Only the
+    /// first 100 are returned.
+    #[instrument(skip_all)]
+    pub async fn open_prs(&self) -> Result<Vec<PullRequest>> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls?state=open&sort=updated&direction=desc&per_page=100",
+            self.host.api_url, self.owner, self.repo
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// List the files changed by a PR.  Only the first 100 are returned.
+    #[instrument(skip_all)]
+    pub async fn pr_files(&self, pr_number: u64) -> Result<Vec<String>> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}/files?per_page=100",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let files: Vec<PullRequestFile> = serde_json::from_str(&response)?;
+        Ok(files.into_iter().map(|file| file.filename).collect())
+    }
+
+    /// List the reviews submitted on a PR, oldest first.  Only the first 100
+    /// are returned.
+    #[instrument(skip_all)]
+    pub async fn pr_reviews(&self, pr_number: u64) -> Result<Vec<Review>> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}/reviews?per_page=100",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// List the comments on a PR's conversation, oldest first.  Only the first
+    /// 100 are returned.
+    #[instrument(skip_all)]
+    pub async fn pr_comments(&self, pr_number: u64) -> Result<Vec<IssueComment>> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments?per_page=100",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// List the events in a PR's timeline, oldest first.  Only the first 100
+    /// are returned.
+    #[instrument(skip_all)]
+    pub async fn pr_timeline(&self, pr_number: u64) -> Result<Vec<TimelineEvent>> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/timeline?per_page=100",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Add a comment to a PR's conversation.
+    #[instrument(skip_all)]
+    pub async fn pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&CreateComment { body })?;
+        self.http_client.post(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Replace a PR's labels.  Labels that don't exist yet are created.
+    #[instrument(skip_all)]
+    pub async fn pr_set_labels(&self, pr_number: u64, labels: &[String]) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/labels",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&SetLabels { labels })?;
+        self.http_client.put(&url, &json_data).await?;
+        let mut cache = self.branch_to_pr.lock().expect("Shouldn't fail");
+        for pr in cache.values_mut().flatten() {
+            if pr.number == pr_number {
+                pr.labels = labels
+                    .iter()
+                    .map(|name| Label { name: name.clone() })
+                    .collect();
+            }
+        }
+        Ok(())
+    }
+
+    /// Add labels to a PR, keeping those it already has.
+    #[instrument(skip_all)]
+    pub async fn pr_add_labels(&self, pr_number: u64, labels: &[String]) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/labels",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&SetLabels { labels })?;
+        self.http_client.post(&url, &json_data).await?;
+        let mut cache = self.branch_to_pr.lock().expect("Shouldn't fail");
+        for pr in cache.values_mut().flatten() {
+            if pr.number == pr_number {
+                for name in labels {
+                    if !pr.labels.iter().any(|label| label.name == *name) {
+                        pr.labels.push(Label { name: name.clone() });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Assign users to a PR, keeping those already assigned.
+    #[instrument(skip_all)]
+    pub async fn pr_add_assignees(&self, pr_number: u64, assignees: &[String]) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/assignees",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&AddAssignees { assignees })?;
+        self.http_client.post(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Close a PR without merging it.
+    #[instrument(skip_all)]
+    pub async fn pr_close(&self, pr_number: u64) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&UpdatePullRequestState { state: "closed" })?;
+        self.http_client.patch(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Replace a PR's description.
+    #[instrument(skip_all)]
+    pub async fn pr_set_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let json_data = serde_json::to_string(&UpdatePullRequestBody { body })?;
+        self.http_client.patch(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Replace the body of a comment on a PR's conversation.
+    #[instrument(skip_all)]
+    pub async fn pr_comment_edit(&self, comment_id: u64, body: &str) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/comments/{}",
+            self.host.api_url, self.owner, self.repo, comment_id
+        );
+        let json_data = serde_json::to_string(&CreateComment { body })?;
+        self.http_client.patch(&url, &json_data).await?;
+        Ok(())
+    }
+
+    /// Get a PR by number, including its review decision.
+    #[instrument(skip_all)]
+    pub async fn get_pr_by_number(&self, pr_number: u64) -> Result<PullRequest> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let mut pr: PullRequest = serde_json::from_str(&response)?;
+        pr.review_decision = self.pr_review_decision(&pr.node_id).await?;
+        Ok(pr)
+    }
+
+    /// Whether a PR can be merged into its base, or None while GitHub is
+    /// still working it out, e.g. just after a push.
     #[instrument(skip_all)]
-    async fn get_pr(&self, branch: &str) -> Result<Option<PullRequest>> {
-        if let Some(pr) = self
+    pub async fn pr_mergeable(&self, pr_number: u64) -> Result<Option<bool>> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}",
+            self.host.api_url, self.owner, self.repo, pr_number
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let pr: PullRequest = serde_json::from_str(&response)?;
+        if let Some(Some(cached)) = self
             .branch_to_pr
             .lock()
             .expect("Shouldn't fail")
-            .get(branch)
+            .get_mut(&pr.head.name)
         {
-            return Ok(pr.clone());
+            cached.mergeable = pr.mergeable;
+        }
+        Ok(pr.mergeable)
+    }
+
+    /// Get a PR's review decision, given its GraphQL node ID.  This is None if
+    /// the repository doesn't require reviews.
+    #[instrument(skip_all)]
+    pub async fn pr_review_decision(&self, node_id: &str) -> Result<Option<ReviewDecision>> {
+        #[derive(Deserialize)]
+        struct Data {
+            node: Option<Node>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Node {
+            review_decision: Option<ReviewDecision>,
         }
 
+        let data: Data = self
+            .graphql(
+                r#"query($id: ID!) {
+                    node(id: $id) { ... on PullRequest { reviewDecision } }
+                }"#,
+                serde_json::json!({ "id": node_id }),
+            )
+            .await?;
+        Ok(data.node.and_then(|node| node.review_decision))
+    }
+
+    /// Get the combined state of a commit's CI check runs and statuses.  Only
+    /// the first 100 check runs are considered.
+    #[instrument(skip_all)]
+    pub async fn ci_state(&self, sha: &str) -> Result<CiState> {
         let url = format!(
-            "https://api.github.com/repos/{}/{}/pulls?head={}:{}&state=all",
-            self.owner, self.repo, self.owner, branch
+            "{}/repos/{}/{}/commits/{}/check-runs?per_page=100",
+            self.host.api_url, self.owner, self.repo, sha
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let check_runs: CheckRuns = serde_json::from_str(&response)?;
+
+        let url = format!(
+            "{}/repos/{}/{}/commits/{}/status",
+            self.host.api_url, self.owner, self.repo, sha
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let status: CombinedStatus = serde_json::from_str(&response)?;
+
+        Ok(check_runs
+            .check_runs
+            .iter()
+            .map(CheckRun::state)
+            .chain((status.total_count > 0).then(|| status.state()))
+            .fold(CiState::None, CiState::combine))
+    }
+
+    /// Count the commits on a base branch that a head commit doesn't have.
+    #[instrument(skip_all)]
+    pub async fn commits_behind(&self, base_branch: &str, head_sha: &str) -> Result<u64> {
+        let url = format!(
+            "{}/repos/{}/{}/compare/{}...{}",
+            self.host.api_url, self.owner, self.repo, base_branch, head_sha
+        );
+        let response = self
+            .http_client
+            .get(&url, "application/vnd.github+json")
+            .await?;
+        let comparison: Comparison = serde_json::from_str(&response)?;
+        Ok(comparison.behind_by)
+    }
+
+    /// Start fetching the open PRs whose branches have any of the given
+    /// prefixes in the background, caching them for [`Self::get_pr`].
+    ///
+    /// This overlaps GitHub's latency with local work, at the cost of a single
+    /// request.  PRs that aren't open (or beyond the first 100) are still
+    /// looked up individually.
+    pub async fn start_prefetch(self: &Arc<Self>, prefixes: Vec<String>) {
+        let guard = self.prefetch.clone().write_owned().await;
+        let gh = self.clone();
+        tokio::spawn(async move {
+            match gh.open_prs().await {
+                Ok(prs) => {
+                    let mut cache = gh.branch_to_pr.lock().expect("Shouldn't fail");
+                    for pr in prs {
+                        if prefixes
+                            .iter()
+                            .any(|prefix| pr.head.name.starts_with(prefix))
+                        {
+                            cache.entry(pr.head.name.clone()).or_insert(Some(pr));
+                        }
+                    }
+                }
+                Err(err) => debug!("Prefetching PRs failed: {err}"),
+            }
+            drop(guard);
+        });
+    }
+
+    /// Use a PR, and its diff, looked up by an earlier run (see
+    /// [`crate::state`]) rather than requesting them again.
+    pub fn seed_pr(&self, pr: PullRequest, diff: Option<String>) {
+        if let Some(diff) = diff {
+            self.pr_number_to_diff
+                .lock()
+                .expect("Shouldn't fail")
+                .insert(pr.number, diff);
+        }
+        self.branch_to_pr
+            .lock()
+            .expect("Shouldn't fail")
+            .insert(pr.head.name.clone(), Some(pr));
+    }
+
+    /// Get the PR for a branch, returns None if no PR exists.
+    #[instrument(skip_all)]
+    pub async fn get_pr(&self, branch: &str) -> Result<Option<PullRequest>> {
+        let cached = || {
+            self.branch_to_pr
+                .lock()
+                .expect("Shouldn't fail")
+                .get(branch)
+                .cloned()
+        };
+        // Prefetching never replaces a PR already known, e.g. from
+        // `seed_pr`, so those needn't wait for it
+        if let Some(pr) = cached() {
+            return Ok(pr);
+        }
+        // Wait for any prefetch to finish, since it may have this PR
+        drop(self.prefetch.read().await);
+        if let Some(pr) = cached() {
+            return Ok(pr);
+        }
+
+        let url = format!(
+            "{}/repos/{}/{}/pulls?head={}:{}&state=all",
+            self.host.api_url, self.owner, self.repo, self.head_owner, branch
         );
 
         let response = self
@@ -262,4 +1397,378 @@ impl GithubClient {
 
         Ok(pr.cloned())
     }
+
+    /// Look up the PRs of several branches up front, with a GraphQL query per
+    /// [`PR_BATCH_SIZE`] branches rather than a REST request each, so that
+    /// [`Self::get_pr`] answers from the cache.  The PRs come with their
+    /// review decisions and mergeability.  If GraphQL fails, e.g. on a host
+    /// without it, lookups fall back to REST.
+    #[instrument(skip_all)]
+    pub async fn prefetch_branches(&self, branches: &[String]) {
+        // Wait for any prefetch to finish, since it may have these PRs
+        drop(self.prefetch.read().await);
+        let uncached = {
+            let cache = self.branch_to_pr.lock().expect("Shouldn't fail");
+            let mut uncached = branches
+                .iter()
+                .filter(|branch| !cache.contains_key(*branch))
+                .cloned()
+                .collect::<Vec<_>>();
+            uncached.dedup();
+            uncached
+        };
+        for batch in uncached.chunks(PR_BATCH_SIZE) {
+            match self.branch_prs(batch).await {
+                Ok(prs) => {
+                    let mut cache = self.branch_to_pr.lock().expect("Shouldn't fail");
+                    for (branch, pr) in batch.iter().zip(prs) {
+                        cache.entry(branch.clone()).or_insert(pr);
+                    }
+                }
+                Err(err) => {
+                    debug!("Looking up PRs with GraphQL failed: {err}");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The latest PR from each branch, in a single GraphQL query.
+    async fn branch_prs(&self, branches: &[String]) -> Result<Vec<Option<PullRequest>>> {
+        #[derive(Deserialize)]
+        struct Data {
+            repository: HashMap<String, Connection>,
+        }
+        #[derive(Deserialize)]
+        struct Connection {
+            nodes: Vec<GraphqlPullRequest>,
+        }
+
+        let mut variables = serde_json::Map::new();
+        variables.insert("owner".to_string(), self.owner.clone().into());
+        variables.insert("repo".to_string(), self.repo.clone().into());
+        for (i, branch) in branches.iter().enumerate() {
+            variables.insert(format!("head{i}"), branch.clone().into());
+        }
+        let mut data: Data = self
+            .graphql(&branch_prs_query(branches.len()), variables.into())
+            .await?;
+        Ok((0..branches.len())
+            .map(|i| {
+                let nodes = data.repository.remove(&format!("pr{i}"))?.nodes;
+                // Branches of the same name in other forks aren't ours
+                nodes
+                    .into_iter()
+                    .find(|pr| {
+                        pr.head_repository_owner
+                            .as_ref()
+                            .is_some_and(|owner| owner.login.eq_ignore_ascii_case(&self.head_owner))
+                    })
+                    .map(GraphqlPullRequest::into_pull_request)
+            })
+            .collect())
+    }
+
+    /// Run a GraphQL query or mutation and return its data.
+    async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T> {
+        let request_body = GraphqlRequest { query, variables };
+        let json_data = serde_json::to_string(&request_body)?;
+        let response = if query.trim_start().starts_with("mutation") {
+            self.http_client
+                .post(&self.host.graphql_url(), &json_data)
+                .await?
+        } else {
+            self.http_client
+                .query(&self.host.graphql_url(), &json_data)
+                .await?
+        };
+        let response: GraphqlResponse<T> = serde_json::from_str(&response)?;
+        if let Some(error) = response.errors.first() {
+            if error
+                .message
+                .starts_with("Could not resolve to a Repository")
+                && let Some(moved_to) = self.moved_to().await?
+            {
+                bail!(tr!(
+                    "github-repo-moved",
+                    repo = self.full_name(),
+                    moved_to = moved_to
+                ));
+            }
+            bail!("GitHub GraphQL error: {}", error.message);
+        }
+        response.data.context("GitHub GraphQL response has no data")
+    }
+}
+
+/// Whether GitHub rejected a PR for being a draft, in a repository without
+/// drafts.
+fn is_drafts_unsupported(err: &anyhow::Error) -> bool {
+    err.to_string()
+        .contains("Draft pull requests are not supported")
+}
+
+/// Branches whose PRs are looked up in a single GraphQL query, at most.
+const PR_BATCH_SIZE: usize = 50;
+
+/// A GraphQL query for the latest PRs from `count` branches, whose names are
+/// the variables `head0`, `head1`, etc.
+fn branch_prs_query(count: usize) -> String {
+    let (params, fields): (Vec<_>, Vec<_>) = (0..count)
+        .map(|i| {
+            (
+                format!("$head{i}: String!"),
+                format!(
+                    "pr{i}: pullRequests(headRefName: $head{i}, first: 5, \
+                     orderBy: {{field: CREATED_AT, direction: DESC}}) {{ nodes {{ ...pr }} }}"
+                ),
+            )
+        })
+        .unzip();
+    format!(
+        "query($owner: String!, $repo: String!, {}) {{ \
+         repository(owner: $owner, name: $repo) {{ {} }} }} {GRAPHQL_PR_FIELDS}",
+        params.join(", "),
+        fields.join(" ")
+    )
+}
+
+/// The fields of [`GraphqlPullRequest`].
+const GRAPHQL_PR_FIELDS: &str = "fragment pr on PullRequest { \
+    id number url state isDraft title body \
+    baseRefName baseRefOid headRefName headRefOid headRepositoryOwner { login } \
+    mergeable reviewDecision milestone { number title } author { login } \
+    reviewRequests(first: 20) { nodes { requestedReviewer { ... on User { login } } } } \
+    labels(first: 50) { nodes { name } } createdAt mergedAt }";
+
+/// A PR, as returned by GraphQL.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlPullRequest {
+    id: String,
+    number: u64,
+    url: String,
+    state: String,
+    is_draft: bool,
+    title: String,
+    body: Option<String>,
+    base_ref_name: String,
+    base_ref_oid: String,
+    head_ref_name: String,
+    head_ref_oid: String,
+    head_repository_owner: Option<Account>,
+    mergeable: String,
+    review_decision: Option<ReviewDecision>,
+    milestone: Option<Milestone>,
+    author: Option<Account>,
+    review_requests: Option<GraphqlNodes<GraphqlReviewRequest>>,
+    labels: Option<GraphqlNodes<Label>>,
+    created_at: Option<DateTime<Utc>>,
+    merged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlNodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlReviewRequest {
+    /// Absent for teams.
+    requested_reviewer: Option<Account>,
+}
+
+impl GraphqlPullRequest {
+    fn into_pull_request(self) -> PullRequest {
+        PullRequest {
+            number: self.number,
+            node_id: self.id,
+            url: self.url,
+            state: if self.state == "OPEN" {
+                PullRequestState::Open
+            } else {
+                PullRequestState::Closed
+            },
+            draft: self.is_draft,
+            title: self.title,
+            body: self.body,
+            base: BranchRef {
+                name: self.base_ref_name,
+                sha: self.base_ref_oid,
+            },
+            head: BranchRef {
+                name: self.head_ref_name,
+                sha: self.head_ref_oid,
+            },
+            mergeable: match self.mergeable.as_str() {
+                "MERGEABLE" => Some(true),
+                "CONFLICTING" => Some(false),
+                _ => None,
+            },
+            review_decision: self.review_decision,
+            milestone: self.milestone,
+            user: self.author,
+            requested_reviewers: self
+                .review_requests
+                .map(|requests| requests.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|request| request.requested_reviewer)
+                .collect(),
+            created_at: self.created_at,
+            merged_at: self.merged_at,
+            labels: self.labels.map(|labels| labels.nodes).unwrap_or_default(),
+        }
+    }
+}
+
+/// A GraphQL mutation converting `count` PRs to drafts, whose node IDs are
+/// the variables `id0`, `id1`, etc.
+fn draft_mutation(count: usize) -> String {
+    let (params, fields): (Vec<_>, Vec<_>) = (0..count)
+        .map(|i| {
+            (
+                format!("$id{i}: ID!"),
+                format!(
+                    "pr{i}: convertPullRequestToDraft(input: {{pullRequestId: $id{i}}}) {{ pullRequest {{ id }} }}"
+                ),
+            )
+        })
+        .unzip();
+    format!("mutation({}) {{ {} }}", params.join(", "), fields.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_host() {
+        let github = GithubHost::default();
+        assert_eq!(github.api_url, "https://api.github.com");
+        assert_eq!(github.graphql_url(), "https://api.github.com/graphql");
+
+        let enterprise = GithubHost::new("ghe.example.com", None);
+        assert_eq!(enterprise.api_url, "https://ghe.example.com/api/v3");
+        assert_eq!(
+            enterprise.graphql_url(),
+            "https://ghe.example.com/api/graphql"
+        );
+
+        let proxied = GithubHost::new("ghe.example.com", Some("https://proxy.example.com/gh/"));
+        assert_eq!(proxied.api_url, "https://proxy.example.com/gh");
+        assert_eq!(
+            proxied.graphql_url(),
+            "https://proxy.example.com/gh/graphql"
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_url() {
+        let owner_and_repo = Some(("o".to_string(), "r".to_string()));
+        let github = GithubHost::default();
+        for url in [
+            "git@github.com:o/r.git",
+            "git@github.com:o/r",
+            "https://github.com/o/r.git",
+            "https://github.com/o/r/",
+            "ssh://git@github.com/o/r.git",
+        ] {
+            assert_eq!(github.parse_remote_url(url), owner_and_repo, "{url}");
+        }
+        assert_eq!(github.parse_remote_url("git@gitlab.com:o/r.git"), None);
+        assert_eq!(github.parse_remote_url("https://github.com/o"), None);
+
+        let enterprise = GithubHost::new("ghe.example.com", None);
+        for url in [
+            "git@ghe.example.com:o/r.git",
+            "ssh://git@ghe.example.com:2222/o/r.git",
+            "https://user@ghe.example.com/o/r",
+        ] {
+            assert_eq!(enterprise.parse_remote_url(url), owner_and_repo, "{url}");
+        }
+        assert_eq!(enterprise.parse_remote_url("git@github.com:o/r.git"), None);
+    }
+
+    #[test]
+    fn test_moved_remote_url() {
+        let github = GithubHost::default();
+        assert_eq!(
+            github.moved_remote_url("git@github.com:o/r.git", "n/r2"),
+            "git@github.com:n/r2.git"
+        );
+        assert_eq!(
+            github.moved_remote_url("https://github.com/o/r", "n/r2"),
+            "https://github.com/n/r2"
+        );
+        assert_eq!(
+            github.moved_remote_url("https://example.com/o/r", "n/r2"),
+            "https://github.com/n/r2.git"
+        );
+    }
+
+    #[test]
+    fn test_branch_prs_query() {
+        let query = branch_prs_query(2);
+        assert!(query.starts_with(
+            "query($owner: String!, $repo: String!, $head0: String!, $head1: String!) { \
+             repository(owner: $owner, name: $repo) { \
+             pr0: pullRequests(headRefName: $head0, first: 5, "
+        ));
+        assert!(query.contains("pr1: pullRequests(headRefName: $head1, "));
+        assert!(query.ends_with(GRAPHQL_PR_FIELDS));
+    }
+
+    #[test]
+    fn test_graphql_pull_request() {
+        let pr: GraphqlPullRequest = serde_json::from_value(serde_json::json!({
+            "id": "PR_1",
+            "number": 1,
+            "url": "https://github.com/o/r/pull/1",
+            "state": "MERGED",
+            "isDraft": false,
+            "title": "Title",
+            "body": null,
+            "baseRefName": "main",
+            "baseRefOid": "abc",
+            "headRefName": "me/klmnopqr",
+            "headRefOid": "def",
+            "headRepositoryOwner": { "login": "o" },
+            "mergeable": "CONFLICTING",
+            "reviewDecision": "APPROVED",
+            "milestone": null,
+            "author": { "login": "me" },
+            "reviewRequests": { "nodes": [
+                { "requestedReviewer": { "login": "alice" } },
+                { "requestedReviewer": null },
+            ] },
+            "labels": { "nodes": [{ "name": "stacked" }] },
+            "createdAt": "2026-03-01T09:00:00Z",
+            "mergedAt": "2026-03-02T09:00:00Z",
+        }))
+        .unwrap();
+        let pr = pr.into_pull_request();
+        assert!(!pr.is_open());
+        assert_eq!(pr.head.name, "me/klmnopqr");
+        assert_eq!(pr.mergeable, Some(false));
+        assert_eq!(pr.review_decision, Some(ReviewDecision::Approved));
+        assert_eq!(pr.requested_reviewers.len(), 1);
+        assert_eq!(pr.labels[0].name, "stacked");
+        assert!(pr.merged_at.is_some());
+    }
+
+    #[test]
+    fn test_draft_mutation() {
+        assert_eq!(
+            draft_mutation(2),
+            "mutation($id0: ID!, $id1: ID!) { \
+             pr0: convertPullRequestToDraft(input: {pullRequestId: $id0}) { pullRequest { id } } \
+             pr1: convertPullRequestToDraft(input: {pullRequestId: $id1}) { pullRequest { id } } }"
+        );
+    }
 }
@@ -0,0 +1,644 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use chrono::DateTime;
+use chrono::Utc;
+use log::debug;
+use reqwest::Method;
+use reqwest::StatusCode;
+use reqwest::header;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+
+use crate::clock::Backoff;
+use crate::clock::Clock;
+use crate::clock::SystemClock;
+use crate::i18n::tr;
+use crate::jobs;
+use crate::plan::Stopwatch;
+use crate::plan::Timing;
+
+/// How long a request may take by default, including connecting.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// GitHub's JSON media type, which responses are requested in by default.
+const GITHUB_JSON: &str = "application/vnd.github+json";
+
+/// Times a failed request is retried, at most.
+const MAX_RETRIES: u32 = 3;
+
+/// The longest jr waits to retry a request, e.g. for a rate limit to reset,
+/// rather than failing.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// HTTP client for making GitHub API requests.  Connections are reused
+/// across requests.
+pub struct GithubHttpClient {
+    token: String,
+    /// Token for requests that change something, if not `token`.
+    write_token: Option<String>,
+    client: reqwest::Client,
+    timeout: Duration,
+    /// CA certificates trusted besides the system's and the bundled roots.
+    ca_certs: Vec<reqwest::Certificate>,
+    /// Permits for requests in flight.
+    requests: Semaphore,
+    /// For waiting before retries.
+    clock: Arc<dyn Clock>,
+    /// Whether a request has been redirected, as GitHub does for renamed and
+    /// transferred repositories.
+    redirected: AtomicBool,
+    /// Times requests, for estimating how long a dry run's would take (see
+    /// [`crate::plan`]).
+    stopwatch: Stopwatch,
+    /// Refuse requests that change anything, for `--dry-run`.
+    dry_run: bool,
+    /// Refuse every request, for a client that only answers from its
+    /// caches.
+    offline: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubError {
+    message: String,
+    #[serde(default)]
+    #[allow(dead_code)]
+    documentation_url: Option<String>,
+    /// Details of validation failures.
+    #[serde(default)]
+    errors: Vec<GitHubErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubErrorDetail {
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl GithubHttpClient {
+    pub fn new(token: String) -> Result<Self> {
+        Ok(Self {
+            token,
+            write_token: None,
+            client: build_client(DEFAULT_TIMEOUT, &[])?,
+            timeout: DEFAULT_TIMEOUT,
+            ca_certs: vec![],
+            requests: Semaphore::new(jobs::DEFAULT_GITHUB_JOBS),
+            clock: Arc::new(SystemClock),
+            redirected: AtomicBool::new(false),
+            stopwatch: Stopwatch::default(),
+            dry_run: false,
+            offline: false,
+        })
+    }
+
+    /// Wait before retries with `clock`, e.g. a [`crate::clock::FakeClock`]
+    /// in tests.
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// Have at most `jobs` requests in flight at once.
+    pub fn with_max_requests(self, jobs: usize) -> Self {
+        Self {
+            requests: Semaphore::new(jobs.max(1)),
+            ..self
+        }
+    }
+
+    /// Refuse requests that change anything, so that a bug in a `--dry-run`
+    /// can't change anything on GitHub.  GraphQL queries are still allowed
+    /// (see [`Self::query`]).
+    pub fn with_dry_run(self) -> Self {
+        Self {
+            dry_run: true,
+            ..self
+        }
+    }
+
+    /// Refuse every request, as well as those that change anything, so that
+    /// nothing is sent to GitHub.
+    pub fn with_offline(self) -> Self {
+        Self {
+            dry_run: true,
+            offline: true,
+            ..self
+        }
+    }
+
+    /// Authenticate requests that change anything with `token`, keeping the
+    /// client's own token for reads, so it can be a read-only one.
+    pub fn with_write_token(self, token: String) -> Self {
+        Self {
+            write_token: Some(token),
+            ..self
+        }
+    }
+
+    /// Whether requests that change anything are refused.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Use a different timeout for each request.
+    pub fn with_timeout(self, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: build_client(timeout, &self.ca_certs)?,
+            timeout,
+            ..self
+        })
+    }
+
+    /// Also trust the CA certificates in a PEM file, e.g. an internal CA of
+    /// a GitHub Enterprise host.
+    pub fn with_ca_bundle(self, path: &Path) -> Result<Self> {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+        let ca_certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid CA bundle {}", path.display()))?;
+        if ca_certs.is_empty() {
+            bail!("No certificates in CA bundle {}", path.display());
+        }
+        Ok(Self {
+            client: build_client(self.timeout, &ca_certs)?,
+            ca_certs,
+            ..self
+        })
+    }
+
+    /// Whether a request has been redirected elsewhere.
+    pub fn redirected(&self) -> bool {
+        self.redirected.load(Ordering::Relaxed)
+    }
+
+    /// How long the requests sent so far took.
+    pub fn timing(&self) -> Timing {
+        self.stopwatch.timing()
+    }
+
+    /// Make a GET request
+    pub async fn get(&self, url: &str, accept: &str) -> Result<String> {
+        self.send(Method::GET, url, accept, None, &self.token).await
+    }
+
+    /// Make a POST request
+    pub async fn post(&self, url: &str, json_data: &str) -> Result<String> {
+        let token = self.ensure_writable(&Method::POST, url)?;
+        self.send(Method::POST, url, GITHUB_JSON, Some(json_data), token)
+            .await
+    }
+
+    /// Make a POST request that only reads, i.e. a GraphQL query rather than
+    /// a mutation, which is allowed during a dry run.
+    pub async fn query(&self, url: &str, json_data: &str) -> Result<String> {
+        self.send(Method::POST, url, GITHUB_JSON, Some(json_data), &self.token)
+            .await
+    }
+
+    /// Make a PATCH request
+    pub async fn patch(&self, url: &str, json_data: &str) -> Result<String> {
+        let token = self.ensure_writable(&Method::PATCH, url)?;
+        self.send(Method::PATCH, url, GITHUB_JSON, Some(json_data), token)
+            .await
+    }
+
+    /// Make a PUT request
+    pub async fn put(&self, url: &str, json_data: &str) -> Result<String> {
+        let token = self.ensure_writable(&Method::PUT, url)?;
+        self.send(Method::PUT, url, GITHUB_JSON, Some(json_data), token)
+            .await
+    }
+
+    /// Make a DELETE request
+    pub async fn delete(&self, url: &str) -> Result<()> {
+        let token = self.ensure_writable(&Method::DELETE, url)?;
+        self.send(Method::DELETE, url, GITHUB_JSON, None, token)
+            .await?;
+        Ok(())
+    }
+
+    /// POST a JSON payload to a webhook, which isn't GitHub, so the request
+    /// isn't authenticated with a GitHub token or retried.
+    pub async fn post_webhook(&self, url: &str, json_data: &str) -> Result<()> {
+        self.ensure_writable(&Method::POST, url)?;
+        let response = {
+            let _permit = self.requests.acquire().await?;
+            self.client
+                .post(url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(json_data.to_string())
+                .send()
+                .await
+                .with_context(|| format!("Webhook request to {url} failed"))?
+        };
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Webhook request to {url} failed with {status}");
+        }
+        Ok(())
+    }
+
+    /// Fail if this is a dry run, before a request that changes something,
+    /// or else return the token to send it with.
+    fn ensure_writable(&self, method: &Method, url: &str) -> Result<&str> {
+        if self.dry_run {
+            bail!("Refusing to send a {method} request to {url} during a dry run");
+        }
+        Ok(self.write_token.as_deref().unwrap_or(&self.token))
+    }
+
+    /// Send a request, retrying it if it fails in a way that may pass.
+    ///
+    /// Idempotent requests are retried after connection failures and gateway
+    /// errors, with exponential backoff.  Rate-limited requests weren't
+    /// processed, so are retried whatever their method, once the rate limit
+    /// resets, unless that's more than [`MAX_RETRY_WAIT`] away.
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        accept: &str,
+        json_data: Option<&str>,
+        token: &str,
+    ) -> Result<String> {
+        if self.offline {
+            bail!("Refusing to send a {method} request to {url} offline");
+        }
+        let idempotent = matches!(method, Method::GET | Method::PUT | Method::DELETE);
+        let backoff = Backoff::default();
+        let mut attempt = 0;
+        loop {
+            let response = {
+                let _permit = self.requests.acquire().await?;
+                let mut request = self
+                    .client
+                    .request(method.clone(), url)
+                    .bearer_auth(token)
+                    .header(header::ACCEPT, accept);
+                if let Some(json_data) = json_data {
+                    request = request
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(json_data.to_string());
+                }
+                let start = Instant::now();
+                let response = request.send().await;
+                self.stopwatch.record(start.elapsed());
+                response
+            };
+            let response = match response {
+                Ok(response) => response,
+                Err(err) if idempotent && attempt < MAX_RETRIES => {
+                    debug!("{method} request to {url} failed, retrying: {err}");
+                    let delay = backoff.delay(attempt, self.clock.jitter());
+                    self.clock.sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| format!("{method} request to {url} failed"));
+                }
+            };
+            if reqwest::Url::parse(url).is_ok_and(|url| url != *response.url()) {
+                debug!("{method} request to {url} redirected to {}", response.url());
+                self.redirected.store(true, Ordering::Relaxed);
+            }
+            let status = response.status();
+            let wait = retry_wait(response.headers(), self.clock.now());
+            let body = response
+                .text()
+                .await
+                .with_context(|| format!("Failed to read response to {method} {url}"))?;
+
+            let rate_limited = is_rate_limited(status, wait.is_some(), &body);
+            if rate_limited || (idempotent && is_transient(status)) {
+                let delay = wait.unwrap_or_else(|| backoff.delay(attempt, self.clock.jitter()));
+                if attempt < MAX_RETRIES && delay <= MAX_RETRY_WAIT {
+                    debug!("{method} request to {url} failed with {status}, retrying in {delay:?}");
+                    self.clock.sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                if rate_limited {
+                    let until = self.clock.now() + delay;
+                    let until = until.with_timezone(&chrono::Local).format("%H:%M:%S");
+                    bail!(tr!("github-rate-limited", until = until.to_string()));
+                }
+            }
+
+            check_status(status, &body)?;
+            return Ok(body);
+        }
+    }
+}
+
+/// How long GitHub asks us to wait before retrying, from its `Retry-After`
+/// header or, once the rate limit is used up, `X-RateLimit-Reset`.
+fn retry_wait(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok();
+    if let Some(seconds) = header("retry-after").and_then(|value| value.parse().ok()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header("x-ratelimit-remaining") == Some("0") {
+        let reset = header("x-ratelimit-reset")?.parse().ok()?;
+        let reset = DateTime::from_timestamp(reset, 0)?;
+        return Some((reset - now).to_std().unwrap_or_default());
+    }
+    None
+}
+
+/// Whether a response is GitHub refusing a request for exceeding a primary or
+/// secondary rate limit.
+fn is_rate_limited(status: StatusCode, asked_to_wait: bool, body: &str) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && (asked_to_wait || body.contains("rate limit")))
+}
+
+/// Whether a response is a gateway error, which may pass.
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Build a client trusting the system's certificate store and the bundled
+/// web PKI roots, as well as `ca_certs`.
+fn build_client(timeout: Duration, ca_certs: &[reqwest::Certificate]) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent("jr-cli")
+        .timeout(timeout);
+    for cert in ca_certs {
+        builder = builder.add_root_certificate(cert.clone());
+    }
+    builder.build().context("Failed to create HTTP client")
+}
+
+/// Fail if a response's status is an error, with GitHub's message if it
+/// gave one.
+fn check_status(status: StatusCode, body: &str) -> Result<()> {
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(());
+    }
+    if let Ok(error) = serde_json::from_str::<GitHubError>(body) {
+        let mut message = error.message;
+        for detail in error.errors.into_iter().filter_map(|detail| detail.message) {
+            message.push_str(": ");
+            message.push_str(&detail);
+        }
+        bail!("GitHub API error ({}): {}", status.as_u16(), message);
+    }
+    bail!(
+        "GitHub API request failed with status {}: {}",
+        status.as_u16(),
+        body
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_status() {
+        assert!(check_status(StatusCode::OK, "{}").is_ok());
+        assert!(check_status(StatusCode::NO_CONTENT, "").is_ok());
+
+        let body = r#"{"message": "Not Found", "documentation_url": "https://docs.github.com"}"#;
+        let err = check_status(StatusCode::NOT_FOUND, body).unwrap_err();
+        assert_eq!(err.to_string(), "GitHub API error (404): Not Found");
+
+        let body = r#"{"message": "Validation Failed", "errors": [{"code": "custom", "message": "Draft pull requests are not supported in this repository."}]}"#;
+        let err = check_status(StatusCode::UNPROCESSABLE_ENTITY, body).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GitHub API error (422): Validation Failed: Draft pull requests are not supported in this repository."
+        );
+
+        let err = check_status(StatusCode::BAD_GATEWAY, "<html>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GitHub API request failed with status 502: <html>"
+        );
+    }
+
+    #[test]
+    fn test_with_ca_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = || GithubHttpClient::new("token".to_string()).unwrap();
+        let missing = dir.path().join("missing.pem");
+        assert!(client().with_ca_bundle(&missing).is_err());
+
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "").unwrap();
+        let err = client().with_ca_bundle(&empty).err().unwrap();
+        assert!(err.to_string().starts_with("No certificates in CA bundle"));
+    }
+
+    #[test]
+    fn test_retry_wait() {
+        let now = "2026-03-01T09:00:00Z".parse().unwrap();
+        let headers = |pairs: &[(&'static str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        header::HeaderName::from_static(name),
+                        value.parse().unwrap(),
+                    )
+                })
+                .collect::<HeaderMap>()
+        };
+        assert_eq!(retry_wait(&headers(&[]), now), None);
+        assert_eq!(
+            retry_wait(&headers(&[("retry-after", "30")]), now),
+            Some(Duration::from_secs(30))
+        );
+        let reset = now.timestamp() + 90;
+        let limited = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", &reset.to_string()),
+        ]);
+        assert_eq!(retry_wait(&limited, now), Some(Duration::from_secs(90)));
+        let remaining = headers(&[
+            ("x-ratelimit-remaining", "10"),
+            ("x-ratelimit-reset", &reset.to_string()),
+        ]);
+        assert_eq!(retry_wait(&remaining, now), None);
+
+        assert!(is_rate_limited(StatusCode::TOO_MANY_REQUESTS, false, ""));
+        assert!(is_rate_limited(StatusCode::FORBIDDEN, true, ""));
+        assert!(is_rate_limited(
+            StatusCode::FORBIDDEN,
+            false,
+            "You have exceeded a secondary rate limit"
+        ));
+        assert!(!is_rate_limited(StatusCode::FORBIDDEN, false, "Forbidden"));
+    }
+
+    /// Serve each of `responses` to a connection in turn.
+    async fn serve(responses: Vec<&'static str>) -> String {
+        serve_recording(responses).await.0
+    }
+
+    /// Like [`serve`], also recording the head of each request received.
+    async fn serve_recording(
+        responses: Vec<&'static str>,
+    ) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::AsyncReadExt as _;
+        use tokio::io::AsyncWriteExt as _;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::Mutex::new(vec![]));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let read = stream.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).into_owned();
+                recorded.lock().unwrap().push(request);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_send_retries() {
+        const BAD_GATEWAY: &str =
+            "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        const RATE_LIMITED: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 5\r\n\
+            Content-Length: 0\r\nConnection: close\r\n\r\n";
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+        let start = "2026-03-01T09:00:00Z".parse().unwrap();
+
+        let clock = Arc::new(crate::clock::FakeClock::new(start).with_jitter(0.0));
+        let client = GithubHttpClient::new("token".to_string())
+            .unwrap()
+            .with_clock(clock.clone());
+        let url = serve(vec![BAD_GATEWAY, RATE_LIMITED, OK]).await;
+        assert_eq!(client.get(&url, GITHUB_JSON).await.unwrap(), "{}");
+        assert_eq!(
+            clock.sleeps(),
+            [Duration::from_millis(500), Duration::from_secs(5)]
+        );
+
+        // Only rate limits are retried for requests that aren't idempotent
+        let url = serve(vec![BAD_GATEWAY]).await;
+        assert!(client.post(&url, "{}").await.is_err());
+        let url = serve(vec![RATE_LIMITED, OK]).await;
+        assert_eq!(client.post(&url, "{}").await.unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_send_follows_redirects() {
+        const MOVED: &str = "HTTP/1.1 301 Moved Permanently\r\nLocation: /repositories/1\r\n\
+            Content-Length: 0\r\nConnection: close\r\n\r\n";
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+        let client = GithubHttpClient::new("token".to_string()).unwrap();
+        let url = serve(vec![OK]).await;
+        client.get(&url, GITHUB_JSON).await.unwrap();
+        assert!(!client.redirected());
+
+        let url = serve(vec![MOVED, OK]).await;
+        assert_eq!(client.get(&url, GITHUB_JSON).await.unwrap(), "{}");
+        assert!(client.redirected());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_refuses_changes() {
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+        let client = GithubHttpClient::new("token".to_string())
+            .unwrap()
+            .with_dry_run();
+        let url = serve(vec![OK, OK]).await;
+        assert!(client.post(&url, "{}").await.is_err());
+        assert!(client.patch(&url, "{}").await.is_err());
+        assert!(client.put(&url, "{}").await.is_err());
+        assert!(client.delete(&url).await.is_err());
+        assert_eq!(client.get(&url, GITHUB_JSON).await.unwrap(), "{}");
+        assert_eq!(client.query(&url, "{}").await.unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_offline_refuses_everything() {
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+        let client = GithubHttpClient::new("token".to_string())
+            .unwrap()
+            .with_offline();
+        let (url, requests) = serve_recording(vec![OK]).await;
+        assert!(client.get(&url, GITHUB_JSON).await.is_err());
+        assert!(client.query(&url, "{}").await.is_err());
+        assert!(client.post(&url, "{}").await.is_err());
+        assert!(client.post_webhook(&url, "{}").await.is_err());
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_token() {
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+        let client = GithubHttpClient::new("read".to_string())
+            .unwrap()
+            .with_write_token("write".to_string());
+        let (url, requests) = serve_recording(vec![OK, OK, OK, OK]).await;
+        client.get(&url, GITHUB_JSON).await.unwrap();
+        client.query(&url, "{}").await.unwrap();
+        client.post(&url, "{}").await.unwrap();
+        client.delete(&url).await.unwrap();
+        let tokens = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| {
+                let request = request.to_lowercase();
+                let auth = request
+                    .lines()
+                    .find(|line| line.starts_with("authorization:"));
+                auth.unwrap()
+                    .trim_start_matches("authorization: bearer ")
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["read", "read", "write", "write"]);
+    }
+
+    #[tokio::test]
+    async fn test_post_webhook() {
+        const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        const NOT_FOUND: &str =
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+        let client = GithubHttpClient::new("token".to_string()).unwrap();
+        let (url, requests) = serve_recording(vec![OK, NOT_FOUND]).await;
+        client
+            .post_webhook(&url, r#"{"text": "hi"}"#)
+            .await
+            .unwrap();
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.starts_with("post / "));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.ends_with(r#"{"text": "hi"}"#));
+        // The GitHub token isn't sent to the webhook
+        assert!(!request.contains("authorization:"));
+
+        let err = client.post_webhook(&url, "{}").await.unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+
+        let client = client.with_dry_run();
+        assert!(client.post_webhook(&url, "{}").await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+}
@@ -1,15 +1,23 @@
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::path;
 
 use anyhow::Context;
 use anyhow::bail;
+use serde::Deserialize;
+use serde::Serialize;
 use tokio::process::Command;
 
 use super::git;
+use crate::i18n::tr;
+use crate::jj_push;
 
 /// Length of the change ID to use in GitHub branch names
 pub const GITHUB_CHANGE_ID_LENGTH: usize = 8;
 
+/// Revset for the trunk commit, unless configured otherwise.
+pub const DEFAULT_TRUNK_REVSET: &str = "trunk()";
+
 // -----------------------------------------------------------------------------
 // Types
 
@@ -19,46 +27,109 @@ pub const GITHUB_CHANGE_ID_LENGTH: usize = 8;
 /// delegated to the Git client.
 pub struct JujutsuClient {
     path: path::PathBuf,
+    /// Revset for the trunk commit, which stacks are built on.
+    trunk: String,
+    /// Remote branches are fetched from.
+    remote: String,
 }
 
 /// A Jujutsu commit.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct JujutsuCommit {
     pub change_id: JujutsuChangeId,
     pub commit_id: git::CommitId,
     pub message: JujutsuCommitMessage,
     pub parent_change_ids: Vec<JujutsuChangeId>,
+    /// Whether other visible commits have the same change ID.
+    #[serde(default)]
+    pub divergent: bool,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct JujutsuChangeId(pub String);
 
 /// A Jujutsu commit message with title and body.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct JujutsuCommitMessage {
     pub title: Option<String>,
     pub body: Option<String>,
 }
 
+/// Translate a `--since` value into a revset.  Dates (`2024-01-31`,
+/// `2024-01-31T12:00`, `3 days ago`, `yesterday`) select commits last
+/// modified after that date; anything else is a revision, and selects its
+/// descendants.
+pub fn since_revset(since: &str) -> String {
+    let bytes = since.as_bytes();
+    let is_date = (bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[7] == b'-')
+        || since.ends_with(" ago")
+        || since == "yesterday"
+        || since == "today";
+    if is_date {
+        format!("committer_date(after:{:?})", since)
+    } else {
+        format!("descendants({since}) ~ ({since})")
+    }
+}
+
 // -----------------------------------------------------------------------------
 // JujutsuClient impl
 
 impl JujutsuClient {
     pub fn new(path: path::PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            trunk: DEFAULT_TRUNK_REVSET.to_string(),
+            remote: "origin".to_string(),
+        }
+    }
+
+    /// Fetch from a remote other than `origin`.
+    pub fn with_remote(self, remote: &str) -> Self {
+        Self {
+            remote: remote.to_string(),
+            ..self
+        }
+    }
+
+    /// Use a revset other than `trunk()` for the trunk commit.
+    pub fn with_trunk(self, revset: &str) -> Self {
+        Self {
+            trunk: format!("({revset})"),
+            ..self
+        }
+    }
+
+    /// The revset for the trunk commit.
+    pub fn trunk(&self) -> &str {
+        &self.trunk
     }
 
     /// Get the head commit(s) of a stack.
     pub async fn get_stack_heads(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
         self.get_commits(&format!(
-            "heads(descendants({revset}) ~ ancestors(trunk()))"
+            "heads(descendants({revset}) ~ ancestors({trunk}))"
         ))
         .await
     }
 
+    /// Get the head commits of every stack in the repository, i.e. of all
+    /// mutable commits off trunk.
+    pub async fn get_all_stack_heads(&self) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!("heads(mutable() ~ ancestors({trunk}))"))
+            .await
+    }
+
     /// Get all ancestors commits in a stack.
     pub async fn get_stack_ancestors(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
-        self.get_commits(&format!("ancestors({revset}) ~ ancestors(trunk())"))
+        let trunk = &self.trunk;
+        self.get_commits(&format!("ancestors({revset}) ~ ancestors({trunk})"))
             .await
     }
 
@@ -66,15 +137,242 @@ impl JujutsuClient {
         &self,
         revset: &str,
     ) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
         self.get_commits(&format!(
-            "ancestors({revset}) ~ ancestors(trunk()) ~ {revset}"
+            "ancestors({revset}) ~ ancestors({trunk}) ~ {revset}"
         ))
         .await
     }
 
+    /// Get the commits in a stack that match a `--since` value; see
+    /// [`since_revset`].
+    pub async fn get_stack_ancestors_since(
+        &self,
+        revset: &str,
+        since: &str,
+    ) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!(
+            "(ancestors({revset}) ~ ancestors({trunk})) & ({})",
+            since_revset(since)
+        ))
+        .await
+    }
+
+    /// Get the commits matching a revset that are in a stack.
+    pub async fn get_stack_commits(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!("({revset}) ~ ancestors({trunk})"))
+            .await
+    }
+
+    /// Get the children of a commit that are in a stack.
+    pub async fn get_stack_children(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!("children({revset}) ~ ancestors({trunk})"))
+            .await
+    }
+
+    /// Get the parents of a commit that are in a stack.
+    pub async fn get_stack_parents(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!("parents({revset}) ~ ancestors({trunk})"))
+            .await
+    }
+
+    /// Get the root commit(s) of a stack.
+    pub async fn get_stack_roots(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
+        let trunk = &self.trunk;
+        self.get_commits(&format!("roots(ancestors({revset}) ~ ancestors({trunk}))"))
+            .await
+    }
+
+    /// Make a commit the working-copy commit.
+    pub async fn edit(&self, revset: &str) -> anyhow::Result<()> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(["edit", revset])
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        if !output.status.success() {
+            bail!(
+                "jj command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Set the description of a commit.
+    pub async fn describe(&self, revset: &str, message: &str) -> anyhow::Result<()> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(["describe", "-r", revset, "-m", message])
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        if !output.status.success() {
+            bail!(
+                "jj command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fetch branches from the remote.
+    pub async fn git_fetch(&self, branches: &[&str]) -> anyhow::Result<()> {
+        let mut args = vec!["git", "fetch", "--remote", &self.remote];
+        for branch in branches {
+            args.extend(["--branch", branch]);
+        }
+        self.run(&args).await
+    }
+
+    /// Create a new change on top of a revision, and make it the working
+    /// copy.
+    pub async fn new_change(&self, revset: &str, message: &str) -> anyhow::Result<()> {
+        self.run(&["new", revset, "-m", message]).await
+    }
+
+    /// Set the working copy's files to their contents in another revision.
+    pub async fn restore_from(&self, revset: &str) -> anyhow::Result<()> {
+        self.run(&["restore", "--from", revset]).await
+    }
+
+    /// Rebase a revision and its descendants onto another.
+    pub async fn rebase(&self, source: &str, destination: &str) -> anyhow::Result<()> {
+        self.run(&["rebase", "-s", source, "-d", destination]).await
+    }
+
+    /// Abandon the commits in a revset.
+    pub async fn abandon(&self, revset: &str) -> anyhow::Result<()> {
+        self.run(&["abandon", revset]).await
+    }
+
+    /// Whether any commits in a revset have conflicts.
+    pub async fn has_conflicts(&self, revset: &str) -> anyhow::Result<bool> {
+        Ok(!self
+            .get_commits(&format!("conflicts() & ({revset})"))
+            .await?
+            .is_empty())
+    }
+
+    /// List jj config values under a name, as `name = value` lines.
+    pub async fn config_list(&self, name: &str) -> anyhow::Result<String> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(["config", "list", name])
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        if !output.status.success() {
+            bail!(
+                "jj command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// The prefix `jj git push --change` names branches with, from jj's
+    /// `git.push-bookmark-prefix` setting.
+    pub async fn push_bookmark_prefix(&self) -> anyhow::Result<String> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(["config", "get", "git.push-bookmark-prefix"])
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        // Unset, or an older jj without the setting
+        if !output.status.success() {
+            return Ok(jj_push::DEFAULT_PREFIX.to_string());
+        }
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Get the ID of the current operation.
+    pub async fn get_operation_id(&self) -> anyhow::Result<String> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(["op", "log", "--no-graph", "-n", "1", "-T", "id"])
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        if !output.status.success() {
+            bail!(
+                "jj command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Run a jj command, discarding its output.
+    async fn run(&self, args: &[&str]) -> anyhow::Result<()> {
+        let output = Command::new("jj")
+            .current_dir(&self.path)
+            .args(args)
+            .output()
+            .await
+            .context("Failed to execute jj command")?;
+
+        if !output.status.success() {
+            bail!(
+                "jj command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(())
+    }
+
     /// Get the trunk commit.
     pub async fn get_trunk(&self) -> anyhow::Result<JujutsuCommit> {
-        self.get_commit("trunk()").await
+        let not_found = || tr!("trunk-not-found", revset = self.trunk.as_str());
+        let mut commits = self.get_commits(&self.trunk).await.context(not_found())?;
+        if commits.is_empty() {
+            bail!(not_found());
+        }
+        if commits.len() > 1 {
+            bail!(tr!(
+                "trunk-ambiguous",
+                revset = self.trunk.as_str(),
+                count = commits.len()
+            ));
+        }
+        Ok(commits.remove(0))
+    }
+
+    /// Get which of some changes are still visible, i.e. haven't been
+    /// abandoned.
+    pub async fn get_visible_changes(
+        &self,
+        change_ids: &[&str],
+    ) -> anyhow::Result<HashSet<String>> {
+        if change_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let revset = change_ids
+            .iter()
+            .map(|change_id| format!("present(change_id({change_id}))"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let commits = self.get_commits(&revset).await?;
+        Ok(commits
+            .into_iter()
+            .map(|commit| commit.change_id.0)
+            .collect())
     }
 
     /// Get the single commit matching a revset.
@@ -98,7 +396,7 @@ impl JujutsuClient {
 
     /// Get all commits matching a revset.
     async fn get_commits(&self, revset: &str) -> anyhow::Result<Vec<JujutsuCommit>> {
-        // Get commit_id, change_id, description, and parent change IDs in a single jj command
+        // Get commit_id, change_id, divergence, description, and parent change IDs in a single jj command
         // Use \x00 as record separator to handle multi-line descriptions
         let output = Command::new("jj").current_dir(&self.path)
             .args([
@@ -107,7 +405,7 @@ impl JujutsuClient {
                 revset,
                 "--no-graph",
                 "-T",
-                r#"commit_id ++ "|" ++ change_id ++ "|" ++ description ++ "|" ++ parents.map(|p| p.change_id()).join(",") ++ "\x00""#,
+                r#"commit_id ++ "|" ++ change_id ++ "|" ++ if(divergent, "divergent") ++ "|" ++ description ++ "|" ++ parents.map(|p| p.change_id()).join(",") ++ "\x00""#,
             ])
             .output()
             .await
@@ -130,19 +428,20 @@ impl JujutsuClient {
                 continue;
             }
 
-            let parts: Vec<&str> = record.splitn(4, '|').collect();
+            let parts: Vec<&str> = record.splitn(5, '|').collect();
 
-            if parts.len() != 4 {
+            if parts.len() != 5 {
                 bail!(
-                    "Unexpected jj output format for revset {revset}: expected 4 parts, got {}: {record}, {parts:?}",
+                    "Unexpected jj output format for revset {revset}: expected 5 parts, got {}: {record}, {parts:?}",
                     parts.len(),
                 );
             }
 
             let commit_id = git::CommitId(parts[0].to_string());
             let change_id = JujutsuChangeId(parts[1].to_string());
-            let description = parts[2].to_string();
-            let parent_ids_str = parts[3];
+            let divergent = parts[2] == "divergent";
+            let description = parts[3].to_string();
+            let parent_ids_str = parts[4];
 
             // Parse parent change IDs (comma-separated, may be empty)
             let parent_change_ids: Vec<_> = if parent_ids_str.is_empty() {
@@ -183,6 +482,7 @@ impl JujutsuClient {
                 commit_id,
                 message: JujutsuCommitMessage { title, body },
                 parent_change_ids,
+                divergent,
             });
         }
 
@@ -0,0 +1,203 @@
+//! Time, for anything that waits, polls or compares against now.
+//!
+//! Watch modes, CI polling, retries and age-based warnings all read the time
+//! and sleep through a [`Clock`], so tests can substitute a [`FakeClock`] and
+//! run them instantly and deterministically.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+
+use crate::i18n::tr;
+
+/// How often to poll GitHub for something to finish, e.g. CI, unless
+/// `jr.pollInterval` says otherwise.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A source of time.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Wait for `duration`.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// A sample in `[0, 1)`, to spread out retries (see [`Backoff`]).
+    fn jitter(&self) -> f64;
+}
+
+/// The system's clock.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn jitter(&self) -> f64 {
+        // Hashers are randomly seeded, which is random enough for jitter
+        let sample = RandomState::new().hash_one(0u8);
+        (sample >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A clock for tests, whose time only moves when told to or slept on.
+/// Sleeping returns immediately, after advancing the time.
+#[derive(Debug)]
+pub struct FakeClock {
+    state: Mutex<FakeState>,
+}
+
+#[derive(Debug)]
+struct FakeState {
+    now: DateTime<Utc>,
+    sleeps: Vec<Duration>,
+    jitter: f64,
+}
+
+impl FakeClock {
+    /// A clock starting at `now`, with a jitter of 0.5.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            state: Mutex::new(FakeState {
+                now,
+                sleeps: vec![],
+                jitter: 0.5,
+            }),
+        }
+    }
+
+    /// Use `jitter` as every jitter sample.
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        self.state.lock().expect("Shouldn't fail").jitter = jitter;
+        self
+    }
+
+    /// Move the time forward.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().expect("Shouldn't fail");
+        state.now += duration;
+    }
+
+    /// The durations slept for so far, in order.
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.state.lock().expect("Shouldn't fail").sleeps.clone()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state.lock().expect("Shouldn't fail").now
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let mut state = self.state.lock().expect("Shouldn't fail");
+        state.now += duration;
+        state.sleeps.push(duration);
+        Box::pin(std::future::ready(()))
+    }
+
+    fn jitter(&self) -> f64 {
+        self.state.lock().expect("Shouldn't fail").jitter
+    }
+}
+
+/// Exponential backoff for retries.  Each delay doubles, up to `max`, and is
+/// jittered between half and all of that, so clients that failed together
+/// don't retry together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// The delay before retry number `attempt`, counting from 0, given a
+    /// jitter sample from [`Clock::jitter`].
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let ceiling = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max);
+        ceiling / 2 + (ceiling / 2).mul_f64(jitter)
+    }
+}
+
+/// Parse an age such as `30d`: a number of hours (`h`), days (`d`) or weeks
+/// (`w`).
+pub fn parse_age(age: &str) -> Result<TimeDelta, String> {
+    let invalid = || tr!("age-invalid", age = age);
+    let unit = age.chars().last().ok_or_else(invalid)?;
+    let count = age[..age.len() - unit.len_utf8()]
+        .parse::<u32>()
+        .map_err(|_| invalid())?;
+    let count = i64::from(count);
+    match unit {
+        'h' => TimeDelta::try_hours(count),
+        'd' => TimeDelta::try_days(count),
+        'w' => TimeDelta::try_weeks(count),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fake_clock() {
+        let start = "2026-03-01T09:00:00Z".parse().unwrap();
+        let clock = FakeClock::new(start);
+        clock.sleep(Duration::from_secs(30)).await;
+        clock.advance(Duration::from_secs(60));
+        clock.sleep(Duration::from_secs(30)).await;
+        assert_eq!(clock.now() - start, chrono::TimeDelta::minutes(2));
+        assert_eq!(
+            clock.sleeps(),
+            [Duration::from_secs(30), Duration::from_secs(30)]
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff::default();
+        let delays = (0..8)
+            .map(|attempt| backoff.delay(attempt, 0.0).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [0, 1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(backoff.delay(3, 0.5), Duration::from_secs(6));
+        assert!(backoff.delay(20, 0.999) < backoff.max);
+        assert!((0.0..1.0).contains(&SystemClock.jitter()));
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d"), Ok(TimeDelta::days(30)));
+        assert_eq!(parse_age("12h"), Ok(TimeDelta::hours(12)));
+        assert_eq!(parse_age("2w"), Ok(TimeDelta::weeks(2)));
+        for age in ["", "d", "30", "30m", "-1d", "1.5d"] {
+            assert!(parse_age(age).is_err(), "{age}");
+        }
+    }
+}
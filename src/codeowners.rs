@@ -0,0 +1,159 @@
+//! Parsing and matching of GitHub CODEOWNERS files.
+//!
+//! See https://docs.github.com/en/repositories/managing-your-repositorys-settings-and-features/customizing-your-repository/about-code-owners
+//! for the file format.  As on GitHub, the last matching rule wins.
+
+use regex::Regex;
+
+/// Locations GitHub searches for a CODEOWNERS file, in priority order.
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// A parsed CODEOWNERS file.
+#[derive(Debug)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Parse the contents of a CODEOWNERS file.  Invalid patterns are skipped.
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut tokens = line.split_whitespace();
+                let regex = pattern_to_regex(tokens.next()?)?;
+                let owners = tokens
+                    .take_while(|token| !token.starts_with('#'))
+                    .map(str::to_string)
+                    .collect();
+                Some(Rule { regex, owners })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Get the owners of a file, or an empty list if it is unowned.
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.regex.is_match(path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Get the combined owners of a set of files, in order of first
+    /// appearance.
+    pub fn owners_of_all<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut owners: Vec<String> = vec![];
+        for path in paths {
+            for owner in self.owners_of(path) {
+                if !owners.contains(owner) {
+                    owners.push(owner.clone());
+                }
+            }
+        }
+        owners
+    }
+}
+
+/// Translate a gitignore-style CODEOWNERS pattern into a regex matching
+/// repository-relative file paths.
+fn pattern_to_regex(pattern: &str) -> Option<Regex> {
+    // Patterns containing a non-trailing slash are relative to the root;
+    // others match at any depth
+    let anchored = pattern.starts_with('/') || pattern.trim_end_matches('/').contains('/');
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+
+    let mut re = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    // A pattern naming a directory owns everything beneath it.  A wildcard in
+    // the final component (e.g. `docs/*`) only matches direct children.
+    let last_component = pattern.rsplit('/').next().unwrap_or(pattern);
+    if dir_only {
+        re.push_str("/.*$");
+    } else if last_component.contains(['*', '?']) {
+        re.push('$');
+    } else {
+        re.push_str("(?:/.*)?$");
+    }
+
+    Regex::new(&re).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+# Default owners
+*       @global-owner
+
+*.js    @js-owner  # inline comment
+**/logs @monalisa
+/build/logs/ @doctocat
+docs/*  docs@example.com
+apps/   @octocat
+/scripts/ @doctocat @octocat
+/unowned
+";
+
+    #[test]
+    fn test_owners_of() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(owners.owners_of("README.md"), ["@global-owner"]);
+        assert_eq!(owners.owners_of("src/index.js"), ["@js-owner"]);
+        assert_eq!(owners.owners_of("build/logs/out.txt"), ["@doctocat"]);
+        assert_eq!(
+            owners.owners_of("docs/getting-started.md"),
+            ["docs@example.com"]
+        );
+        assert_eq!(
+            owners.owners_of("docs/build-app/troubleshooting.md"),
+            ["@global-owner"]
+        );
+        assert_eq!(owners.owners_of("apps/web/main.rs"), ["@octocat"]);
+        assert_eq!(owners.owners_of("nested/apps/main.rs"), ["@octocat"]);
+        assert_eq!(
+            owners.owners_of("scripts/deploy.sh"),
+            ["@doctocat", "@octocat"]
+        );
+        assert_eq!(owners.owners_of("deep/down/logs/x.txt"), ["@monalisa"]);
+        assert!(owners.owners_of("unowned/file.rs").is_empty());
+    }
+
+    #[test]
+    fn test_owners_of_all() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(
+            owners.owners_of_all(["scripts/a.sh", "src/b.js", "apps/c.rs"]),
+            ["@doctocat", "@octocat", "@js-owner"]
+        );
+    }
+}
@@ -1,7 +1,39 @@
 //! Command implementations for jr CLI operations.
 
+pub mod adopt;
+pub mod bisect_stack;
+pub mod blame;
+pub mod checkout_pr;
+pub mod cleanup;
 pub mod create;
+pub mod dashboard;
+pub mod describe;
+pub mod diff;
+pub mod doctor;
+pub mod draft;
+pub mod edit_base;
+pub mod files;
+pub mod fork;
+pub mod gc;
+pub mod graph;
 pub mod init;
+pub mod label;
+pub mod mcp;
+pub mod merge;
+pub mod navigate;
+pub mod open;
+pub mod owners;
+pub mod ping_reviewers;
+pub mod push;
+pub mod ready;
+pub mod report;
 pub mod restack;
+pub mod review_stack;
+pub mod serve;
 pub mod status;
+pub mod submit;
+pub mod sync;
+pub mod token;
+pub mod tutorial;
 pub mod update;
+pub mod why;
@@ -0,0 +1,92 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::clients::jujutsu::JujutsuChangeId;
+use crate::commands::create::CreateOptions;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::jj_push;
+
+impl App {
+    /// Converge on one branch per change in the stack, where `jj git push
+    /// --change` also pushed one (see [`jj_push`]).  From the bottom up, each
+    /// such change gets a jr PR if it has none yet; then any open PR from
+    /// jj's branch is closed with a comment pointing at jr's, and jj's branch
+    /// is deleted.
+    pub async fn cmd_adopt(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-adopt", revision = revision));
+        }
+        self.ensure_repository_writable().await?;
+
+        let (prefix, branches) = self.jj_push_branches().await?;
+        let mut adopted = 0;
+        for commit in self.get_stack(revision).await?.into_iter().rev() {
+            let duplicates = jj_push::duplicates(&commit.change_id.0, &prefix, &branches);
+            if duplicates.is_empty() {
+                continue;
+            }
+
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            if self.gh.get_pr(&branch).await?.is_none() {
+                self.cmd_create(&commit.commit_id.0, &CreateOptions::default(), stdout)
+                    .await?;
+            }
+            let url = self
+                .gh
+                .pr_url(&branch)
+                .await?
+                .context("PR not found for branch")?;
+            for duplicate in duplicates {
+                if let Some(pr) = self.gh.get_pr(duplicate).await?
+                    && pr.is_open()
+                {
+                    let comment = tr!("adopt-superseded", url = url.as_str());
+                    self.gh.pr_comment(pr.number, &comment).await?;
+                    self.gh.pr_close(pr.number).await?;
+                    writeln!(
+                        stdout,
+                        "{}",
+                        tr!(
+                            "adopt-closed",
+                            duplicate = pr.url.as_str(),
+                            url = url.as_str()
+                        )
+                    )?;
+                }
+                self.git.delete_branch(duplicate).await?;
+                writeln!(stdout, "{}", tr!("adopt-deleted", branch = duplicate))?;
+            }
+            adopted += 1;
+        }
+        if adopted == 0 {
+            writeln!(stdout, "{}", tr!("adopt-nothing"))?;
+        }
+        Ok(())
+    }
+
+    /// The prefix of the branches `jj git push --change` pushes, and those
+    /// on the push remote.
+    async fn jj_push_branches(&self) -> Result<(String, Vec<String>)> {
+        let prefix = self.jj.push_bookmark_prefix().await?;
+        if prefix.is_empty() {
+            return Ok((prefix, vec![]));
+        }
+        let branches = self.git.find_branches_with_prefix(&prefix).await?;
+        Ok((prefix, branches))
+    }
+
+    /// How many of the changes in a stack have a branch pushed by `jj git
+    /// push --change` as well as jr's.
+    pub(crate) async fn count_jj_push_duplicates<'a>(
+        &self,
+        change_ids: impl Iterator<Item = &'a JujutsuChangeId>,
+    ) -> Result<usize> {
+        let (prefix, branches) = self.jj_push_branches().await?;
+        Ok(change_ids
+            .filter(|change_id| !jj_push::duplicates(&change_id.0, &prefix, &branches).is_empty())
+            .count())
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use chrono::TimeDelta;
+use colored::Colorize;
+use log::debug;
+use tokio::process::Command;
+
+use crate::App;
+use crate::bisect::Bisection;
+use crate::clients::jujutsu::GITHUB_CHANGE_ID_LENGTH;
+use crate::clients::jujutsu::JujutsuCommit;
+use crate::i18n::tr;
+use crate::model::CiState;
+
+/// Options for `jr bisect-stack`.
+#[derive(Debug)]
+pub struct BisectOptions {
+    /// Run this command in a checkout of each commit, instead of using CI.
+    pub command: Option<String>,
+    /// Give up waiting for CI on a commit after this many minutes.
+    pub timeout: u64,
+}
+
+impl App {
+    /// Find the first commit in a stack that fails CI or a local command,
+    /// with a binary search over the stack's prefixes (see [`Bisection`]).
+    ///
+    /// With a command, each commit is checked out into a temporary worktree
+    /// and the command run there through `sh`; it passes if the command
+    /// succeeds.  Otherwise each commit is pushed to a temporary branch and
+    /// tested by CI.  Worktrees and branches are removed after each test.
+    pub async fn cmd_bisect_stack(
+        &self,
+        revision: &str,
+        options: &BisectOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("bisect-on-trunk", revision = revision));
+        }
+
+        // Bottom first, so positions count up the stack
+        let mut commits = self.jj.get_stack_ancestors(revision).await?;
+        commits.reverse();
+
+        if self
+            .test_stack_prefix(&commits, commits.len() - 1, options, stdout)
+            .await?
+        {
+            writeln!(stdout, "{}", tr!("bisect-top-passes"))?;
+            return Ok(());
+        }
+
+        let mut bisection = Bisection::new(commits.len());
+        while let Some(position) = bisection.next() {
+            let passed = self
+                .test_stack_prefix(&commits, position, options, stdout)
+                .await?;
+            bisection.record(position, passed);
+        }
+
+        let position = bisection.first_failing();
+        let commit = &commits[position];
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "bisect-first-failing",
+                position = position + 1,
+                total = commits.len(),
+                change = short_change_id(commit).cyan().to_string(),
+                title = commit.message.title.as_deref().unwrap_or_default()
+            )
+            .bold()
+        )?;
+        Ok(())
+    }
+
+    /// Test the stack up to and including the commit at `position`.
+    async fn test_stack_prefix(
+        &self,
+        commits: &[JujutsuCommit],
+        position: usize,
+        options: &BisectOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<bool> {
+        let commit = &commits[position];
+        write!(
+            stdout,
+            "{}",
+            tr!(
+                "bisect-testing",
+                position = position + 1,
+                total = commits.len(),
+                change = short_change_id(commit).cyan().to_string(),
+                title = commit.message.title.as_deref().unwrap_or_default()
+            )
+        )?;
+        stdout.flush()?;
+
+        let passed = match &options.command {
+            Some(command) => self.test_with_command(commit, command).await?,
+            None => self.test_with_ci(commit, options.timeout).await?,
+        };
+        let result = if passed {
+            tr!("bisect-passed").green()
+        } else {
+            tr!("bisect-failed").red()
+        };
+        writeln!(stdout, " {result}")?;
+        Ok(passed)
+    }
+
+    async fn test_with_command(&self, commit: &JujutsuCommit, command: &str) -> Result<bool> {
+        let worktree = self.git.get_git_dir().await?.join("jr-bisect");
+        if worktree.exists() {
+            // Left over from an interrupted run
+            self.git.remove_worktree(&worktree).await?;
+        }
+        self.git.add_worktree(&worktree, &commit.commit_id).await?;
+        let passed = run_test_command(command, &worktree).await;
+        self.git.remove_worktree(&worktree).await?;
+        passed
+    }
+
+    async fn test_with_ci(&self, commit: &JujutsuCommit, timeout: u64) -> Result<bool> {
+        let branch = format!(
+            "{}bisect-{}",
+            self.config.github_branch_prefix,
+            short_change_id(commit)
+        );
+        self.git
+            .push_commit_to_branch(&commit.commit_id, &branch)
+            .await?;
+        let passed = self.wait_for_ci(&commit.commit_id.0, timeout).await;
+        self.git.delete_branch(&branch).await?;
+        passed
+    }
+
+    /// Wait for CI on a commit to finish, returning whether it passed.
+    async fn wait_for_ci(&self, sha: &str, timeout: u64) -> Result<bool> {
+        let deadline = self.clock.now() + TimeDelta::minutes(timeout as i64);
+        loop {
+            match self.gh.ci_state(sha).await? {
+                CiState::Success => return Ok(true),
+                CiState::Failure => return Ok(false),
+                // Checks can take a while to appear after a push
+                CiState::None | CiState::Pending => {}
+            }
+            if self.clock.now() >= deadline {
+                bail!(tr!("bisect-ci-timeout", commit = sha, minutes = timeout));
+            }
+            self.clock.sleep(self.poll_interval()).await;
+        }
+    }
+}
+
+/// Run a test command through `sh` in a directory, returning whether it
+/// succeeded.
+async fn run_test_command(command: &str, path: &Path) -> Result<bool> {
+    let output = Command::new("sh")
+        .current_dir(path)
+        .args(["-c", command])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to execute test command")?;
+    debug!(
+        "test command output:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(output.status.success())
+}
+
+fn short_change_id(commit: &JujutsuCommit) -> &str {
+    let change_id = &commit.change_id.0;
+    &change_id[..GITHUB_CHANGE_ID_LENGTH.min(change_id.len())]
+}
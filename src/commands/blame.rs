@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::jobs::try_join_limited;
+
+impl App {
+    /// Show blame for a file, annotating lines introduced by commits in the
+    /// current stack with the owning change, its sync status and PR number.
+    ///
+    /// Lines last modified by commits outside the stack (i.e. on trunk) are
+    /// shown without annotation.
+    pub async fn cmd_blame(
+        &self,
+        revision: &str,
+        file: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let commit = self.jj.get_commit(revision).await?;
+        let lines = self.git.blame(&commit.commit_id, file).await?;
+
+        // Only elaborate the stack commits that actually own a line
+        let stack = self.jj.get_stack_ancestors(&commit.commit_id.0).await?;
+        let owners = stack
+            .into_iter()
+            .filter(|commit| lines.iter().any(|line| line.commit_id == commit.commit_id))
+            .map(|commit| CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git));
+        let owners = try_join_limited(owners, self.config.jobs()).await?;
+
+        let mut annotations = HashMap::new();
+        for commit_info in &owners {
+            let pr = commit_info
+                .pr
+                .as_ref()
+                .map(|pr| format!("#{}", pr.number))
+                .unwrap_or_default();
+            let annotation = format!(
+                "{} {} {:>6}",
+                commit_info.status(),
+                commit_info.short_id().cyan(),
+                pr
+            );
+            annotations.insert(commit_info.commit.commit_id.clone(), annotation);
+        }
+
+        for line in &lines {
+            match annotations.get(&line.commit_id) {
+                Some(annotation) => writeln!(stdout, "{} │ {}", annotation, line.content.yellow())?,
+                None => writeln!(stdout, "{:13} │ {}", "", line.content)?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::App;
+use crate::i18n::tr;
+use crate::review;
+
+impl App {
+    /// Check out a PR for local testing, as a new change on top of its base
+    /// branch containing just that PR's changes.
+    ///
+    /// Works for any PR in a stack: the base branch is the parent PR's branch,
+    /// so its changes are there too.  Only the GitHub API and `jj` are used,
+    /// so this works without the author's Jujutsu repo.
+    pub async fn cmd_checkout_pr(
+        &self,
+        reference: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let (repo, number) = review::parse_pr_reference(reference, &self.gh.host().name)?;
+        if let Some(repo) = repo
+            && !repo.eq_ignore_ascii_case(&self.gh.full_name())
+        {
+            bail!(tr!(
+                "review-stack-other-repo",
+                repo = repo,
+                this = self.gh.full_name()
+            ));
+        }
+
+        let pr = self.gh.get_pr_by_number(number).await?;
+        let (base, head) = (&pr.base.name, &pr.head.name);
+        self.jj.git_fetch(&[base, head]).await?;
+
+        let message = tr!(
+            "checkout-pr-message",
+            number = number,
+            title = pr.title.as_str()
+        );
+        // Quote branch names, which may contain revset operators like '/'
+        let remote = self.git.remote();
+        self.jj
+            .new_change(&format!("\"{base}\"@{remote}"), &message)
+            .await?;
+        self.jj
+            .restore_from(&format!("\"{head}\"@{remote}"))
+            .await?;
+
+        let commit = self.jj.get_commit("@").await?;
+        let change_id = &commit.change_id.0[..8.min(commit.change_id.0.len())];
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "checkout-pr-done",
+                change = change_id.cyan().to_string(),
+                number = number,
+                base = base.as_str()
+            )
+        )?;
+        Ok(())
+    }
+}
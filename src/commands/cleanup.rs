@@ -0,0 +1,105 @@
+use anyhow::Result;
+use chrono::TimeDelta;
+
+use crate::App;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+
+/// Options for `jr cleanup`.
+#[derive(Debug, Default)]
+pub struct CleanupOptions {
+    /// Only list the branches that would be deleted.
+    pub dry_run: bool,
+    /// Only delete branches whose tips were committed longer ago than this,
+    /// including those without a PR.
+    pub older_than: Option<TimeDelta>,
+}
+
+impl App {
+    /// Delete the remote PR branches under our prefix whose PRs have been
+    /// merged or closed.  Branches with an open PR, or protected by
+    /// `jr.protectBranch`, are kept.
+    ///
+    /// With `older_than`, only branches whose tips are older than that are
+    /// deleted, but those without a PR are too, so that a scheduled cleanup
+    /// can prune abandoned branches.
+    pub async fn cmd_cleanup(
+        &self,
+        options: &CleanupOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let dry_run = options.dry_run;
+        if !dry_run {
+            self.ensure_repository_writable().await?;
+        }
+        let prefix = &self.config.github_branch_prefix;
+        let branches = self.git.find_branches_with_prefix(prefix).await?;
+        let prs = try_join_limited(
+            branches.iter().map(|branch| self.gh.get_pr(branch)),
+            self.config.jobs(),
+        )
+        .await?;
+        let dates = match options.older_than {
+            Some(_) => self.git.get_branch_tip_dates(prefix).await?,
+            None => Default::default(),
+        };
+        let cutoff = options.older_than.map(|age| self.clock.now() - age);
+
+        let mut deleted = 0;
+        for (branch, pr) in branches.iter().zip(prs) {
+            if pr.as_ref().is_some_and(|pr| pr.is_open()) {
+                continue;
+            }
+            let date = dates.get(branch);
+            let deletable = match cutoff {
+                Some(cutoff) => date.is_some_and(|date| *date < cutoff),
+                None => pr.is_some(),
+            };
+            if !deletable {
+                continue;
+            }
+            if self.config.is_protected(branch) {
+                writeln!(
+                    stdout,
+                    "{}",
+                    tr!("cleanup-protected", branch = branch.as_str())
+                )?;
+                continue;
+            }
+
+            let date = date.map_or_else(String::new, |date| date.format("%Y-%m-%d").to_string());
+            let message = match (&pr, dry_run) {
+                (Some(pr), true) => tr!(
+                    "cleanup-would-delete",
+                    branch = branch.as_str(),
+                    url = pr.url.as_str()
+                ),
+                (Some(pr), false) => tr!(
+                    "cleanup-deleted",
+                    branch = branch.as_str(),
+                    url = pr.url.as_str()
+                ),
+                (None, true) => tr!(
+                    "cleanup-would-delete-stale",
+                    branch = branch.as_str(),
+                    date = date
+                ),
+                (None, false) => tr!(
+                    "cleanup-deleted-stale",
+                    branch = branch.as_str(),
+                    date = date
+                ),
+            };
+            if !dry_run {
+                self.git.delete_branch(branch).await?;
+            }
+            writeln!(stdout, "{message}")?;
+            deleted += 1;
+        }
+        if deleted == 0 {
+            writeln!(stdout, "{}", tr!("cleanup-nothing"))?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,97 @@
+use anyhow::Context;
 use anyhow::bail;
+use log::warn;
 
 use crate::App;
+use crate::audit::AuditEvent;
+use crate::clients::git::CommitId;
 use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::limits;
+use crate::notify::StackEvent;
+use crate::plan;
+use crate::template;
+use crate::title;
+
+/// Options for `jr create`.
+#[derive(Debug, Default)]
+pub struct CreateOptions {
+    /// Request reviews from the code owners of the changed files.
+    pub request_owners: bool,
+    /// Milestone to assign the PR to, overriding `jr.defaultMilestone`.
+    pub milestone: Option<String>,
+    /// Project number to add the PR to, overriding `jr.defaultProject`.
+    pub project: Option<u64>,
+    /// Users or teams (`org/team`) to request reviews from, overriding
+    /// `jr.defaultReviewers`.
+    pub reviewers: Vec<String>,
+    /// Labels to add, besides those configured with `jr.label`.
+    pub labels: Vec<String>,
+    /// Users to assign the PR to, overriding `jr.defaultAssignees`.
+    pub assignees: Vec<String>,
+    /// Succeed without changes if the PR already exists and is up to date.
+    pub idempotent: bool,
+    /// Only prepare the PR branch locally, for `jr push` to push later.
+    pub no_push: bool,
+    /// Open the PR ready for review rather than as a draft, whatever
+    /// `jr.createDraft` says.
+    pub ready: bool,
+}
+
+/// A PR's title and body, derived from its commit's description and any PR
+/// template (see [`template`]).
+pub(crate) struct PrText {
+    pub title: String,
+    pub body: String,
+    /// The rest of a body too long for GitHub, to post as comments.
+    pub overflow: Vec<String>,
+}
+
+/// A PR template, with where the PR's commit is in its stack.
+pub(crate) struct PrTemplate {
+    pub text: String,
+    /// The commit's position in its stack, from 1 at the bottom.
+    pub stack_position: usize,
+    pub stack_size: usize,
+}
+
+impl PrText {
+    /// Split a commit's description into a PR's title and body, rendering
+    /// the body with `template` if given.
+    pub(crate) fn new(commit: &CommitInfo, template: Option<&PrTemplate>) -> anyhow::Result<Self> {
+        let commit_message = commit.message();
+        let title = commit_message.title.as_deref().map(title::sanitize);
+        let Some(title) = title.filter(|title| !title.is_empty()) else {
+            bail!(tr!("pr-empty-description"));
+        };
+        let body = commit_message.body.as_deref().unwrap_or("");
+        let body = match template {
+            Some(template) => template::render(
+                &template.text,
+                &[
+                    ("title", &title),
+                    ("body", body),
+                    ("change_id", &commit.commit.change_id.0),
+                    ("stack_position", &template.stack_position.to_string()),
+                    ("stack_size", &template.stack_size.to_string()),
+                ],
+            ),
+            None => body.to_string(),
+        };
+        let body = body.as_str();
+        limits::check_title(&title)?;
+        let (body, overflow) = match limits::split_body(body, &tr!("pr-body-truncated")) {
+            Some((truncated, parts)) => (truncated, parts),
+            None => (body.to_string(), vec![]),
+        };
+        Ok(Self {
+            title,
+            body,
+            overflow,
+        })
+    }
+}
 
 impl App {
     /// Create a new pull request.
@@ -13,45 +103,395 @@ impl App {
     ///    - Use the base branch as the parent.
     /// 2. Push to a remote PR branch named after this revision's change ID.
     /// 3. Create a pull request to merge the PR branch into the base branch.
+    /// 4. Assign the PR to a milestone and project, request reviews, and add
+    ///    labels and assignees, as given or configured.
+    /// 5. Optionally request reviews from the expected reviewers according
+    ///    to CODEOWNERS, which are reported before step 1.
+    /// 6. Notify the configured webhook, if any.
+    ///
+    /// Once the PR exists, failures of steps 4 and 5 are only warnings, so
+    /// that running the command again doesn't fail on the existing branch.
+    ///
+    /// With `no_push`, stop after step 1, pointing a local branch named like
+    /// the PR branch at the commit without contacting GitHub.  `jr push` does
+    /// the rest.
+    ///
+    /// In a dry run (see [`App::is_dry_run`]), only say what would be done.
     pub async fn cmd_create(
         &self,
         revision: &str,
+        options: &CreateOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> anyhow::Result<()> {
+        self.create_pr(revision, options, stdout).await?;
+        Ok(())
+    }
+
+    /// Create PRs for every commit in a stack without one, from the bottom
+    /// up, each against its parent's PR branch, then list the PRs created.
+    pub async fn cmd_create_stack(
+        &self,
+        revision: &str,
+        options: &CreateOptions,
         stdout: &mut impl std::io::Write,
     ) -> anyhow::Result<()> {
-        self.check_parent_prs_up_to_date(revision).await?;
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("create-on-trunk", revision = revision));
+        }
+
+        let mut created = vec![];
+        let mut prepared = 0;
+        for commit in self.get_stack(revision).await?.into_iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            if self.git.get_pr_branch_tip(&branch).await.is_ok() {
+                continue;
+            }
+            match self.create_pr(&commit.commit_id.0, options, stdout).await? {
+                Some(url) => created.push(url),
+                None => prepared += 1,
+            }
+        }
+
+        if created.is_empty() && prepared == 0 {
+            writeln!(stdout, "{}", tr!("create-stack-none"))?;
+        } else if !created.is_empty() {
+            writeln!(
+                stdout,
+                "{}",
+                tr!("create-stack-created", count = created.len())
+            )?;
+            for url in created {
+                writeln!(stdout, "  {url}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a PR as [`App::cmd_create`] describes, returning its URL, or
+    /// `None` if it already existed or was only prepared.
+    async fn create_pr(
+        &self,
+        revision: &str,
+        options: &CreateOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> anyhow::Result<Option<String>> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("create-on-trunk", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
         let commit = self.jj.get_commit(revision).await?;
         let commit = CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+        if !options.no_push {
+            self.ensure_repository_writable().await?;
+            // A dry run of `--stack` doesn't create the PRs below this one
+            if !self.is_dry_run() || commit.base_tip.is_some() {
+                self.check_parent_prs_up_to_date(revision).await?;
+            }
+        }
         if commit.pr_tip.is_some() {
-            bail!("PR branch already exists: {}", commit.pr_branch);
+            // For scripts, an existing PR that matches isn't an error
+            if options.idempotent
+                && let Some(pr) = &commit.pr
+                && pr.is_open()
+                && commit.status() == SyncStatus::Synced
+            {
+                writeln!(
+                    stdout,
+                    "{}",
+                    tr!("pr-exists-up-to-date", url = pr.url.as_str())
+                )?;
+                return Ok(None);
+            }
+            bail!(tr!("pr-branch-exists", branch = commit.pr_branch.as_str()));
         }
 
-        let commit_message = commit.message();
-        let Some(pr_title) = &commit_message.title else {
-            bail!("Cannot create PR with empty description");
-        };
-        let pr_body = commit_message.body.as_deref().unwrap_or("");
+        // GitHub PRs can only target branches in the upstream repository, so
+        // PRs from a fork can't be stacked on each other
+        if self.config.push_remote.is_some() && commit.base_branch != self.config.default_branch {
+            bail!(tr!("create-fork-stacked"));
+        }
 
+        let text = self.pr_text(&commit).await?;
+        let owners = self
+            .expected_reviewers(&commit.commit.commit_id, stdout)
+            .await?;
+        if self.is_dry_run() {
+            let branch = commit.pr_branch.as_str();
+            let message = if options.no_push {
+                tr!("dry-run-prepare", branch = branch)
+            } else {
+                self.plan_push(self.create_requests(&text, &owners, options));
+                tr!(
+                    "dry-run-create",
+                    branch = branch,
+                    base = commit.base_branch.as_str(),
+                    title = text.title.as_str()
+                )
+            };
+            writeln!(stdout, "{message}")?;
+            return Ok(None);
+        }
+
+        // A base whose PR branch was only prepared is on a local branch
+        let base_tip = if options.no_push && commit.base_tip.is_none() {
+            self.git.get_local_branch_tip(&commit.base_branch).await?
+        } else {
+            commit.base_tip.clone().expect("must exist")
+        };
         let tree = self.git.get_tree(&commit.commit.commit_id).await?;
 
         let new_commit = self
             .git
-            .commit_tree(
-                &tree,
-                vec![&commit.base_tip.clone().expect("must exist")],
-                &commit.full_message(),
-            )
+            .commit_tree(&tree, vec![&base_tip], &commit.full_message())
             .await?;
 
-        self.git
-            .push_commit_to_branch(&new_commit, &commit.pr_branch)
+        if options.no_push {
+            self.git
+                .set_local_branch(&commit.pr_branch, &new_commit)
+                .await?;
+            writeln!(
+                stdout,
+                "{}",
+                tr!("pr-prepared", branch = commit.pr_branch.as_str())
+            )?;
+            return Ok(None);
+        }
+        let pr_url = self
+            .open_pr(&commit, &new_commit, &text, &owners, options, stdout)
+            .await?;
+        Ok(Some(pr_url))
+    }
+
+    /// The GitHub requests to open a PR and set it up, for a dry run's plan.
+    fn create_requests(&self, text: &PrText, owners: &[String], options: &CreateOptions) -> u64 {
+        let config = &self.config;
+        let steps = [
+            options.milestone.is_some() || config.default_milestone.is_some(),
+            options.project.is_some() || config.default_project.is_some(),
+            !options.reviewers.is_empty() || !config.default_reviewers.is_empty(),
+            !options.labels.is_empty()
+                || !config.labels.is_empty()
+                || !options.assignees.is_empty()
+                || !config.default_assignees.is_empty(),
+            options.request_owners && !owners.is_empty(),
+        ];
+        // Finding the PR, then a comment per part
+        let overflow = match text.overflow.len() {
+            0 => 0,
+            parts => 1 + parts,
+        };
+        let steps = steps.into_iter().filter(|&step| step).count() + overflow;
+        plan::CREATE_REQUESTS + steps as u64
+    }
+
+    /// Print the reviewers CODEOWNERS expects for a commit's changed files,
+    /// before its PR is created, and return them.
+    pub(crate) async fn expected_reviewers(
+        &self,
+        commit_id: &CommitId,
+        stdout: &mut impl std::io::Write,
+    ) -> anyhow::Result<Vec<String>> {
+        let Some(code_owners) = self.get_code_owners(commit_id).await? else {
+            return Ok(vec![]);
+        };
+        let files = self.git.get_changed_files(commit_id).await?;
+        let owners = code_owners.owners_of_all(files.iter().map(String::as_str));
+        if !owners.is_empty() {
+            writeln!(
+                stdout,
+                "{}",
+                tr!("owners-expected-reviewers", owners = owners.join(", "))
+            )?;
+        }
+        Ok(owners)
+    }
+
+    /// Push a PR branch's first commit and open its PR, along with the rest
+    /// of [`App::cmd_create`]'s steps, returning the PR's URL.  `owners` are
+    /// the reviewers CODEOWNERS expects (see [`App::expected_reviewers`]).
+    pub(crate) async fn open_pr(
+        &self,
+        commit: &CommitInfo,
+        new_commit: &CommitId,
+        text: &PrText,
+        owners: &[String],
+        options: &CreateOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> anyhow::Result<String> {
+        let (pr_title, overflow) = (text.title.as_str(), &text.overflow);
+        self.push_pr_branch(new_commit, &commit.commit, &commit.pr_branch)
             .await?;
 
+        let draft = !options.ready && !self.config.create_ready;
         let pr_url = self
             .gh
-            .pr_create(&commit.pr_branch, &commit.base_branch, pr_title, pr_body)
+            .pr_create(
+                &commit.pr_branch,
+                &commit.base_branch,
+                pr_title,
+                &text.body,
+                draft,
+            )
             .await?;
-        writeln!(stdout, "Created PR: {}", pr_url)?;
+        writeln!(stdout, "{}", tr!("pr-created", url = pr_url.as_str()))?;
+        if draft && !self.gh.capabilities().await?.drafts {
+            writeln!(stdout, "{}", tr!("pr-created-ready"))?;
+        }
+        self.warn_if_unmergeable(&commit.pr_branch, stdout).await?;
+        self.warn_if_oversized(&commit.commit, stdout).await?;
+
+        // Post the full text of a truncated body
+        if !overflow.is_empty() {
+            let pr = self
+                .gh
+                .get_pr(&commit.pr_branch)
+                .await?
+                .context("PR not found for branch")?;
+            for (i, part) in overflow.iter().enumerate() {
+                let heading = tr!("pr-body-part", part = i + 1, count = overflow.len());
+                self.gh
+                    .pr_comment(pr.number, &format!("{heading}\n\n{part}"))
+                    .await?;
+            }
+            writeln!(
+                stdout,
+                "{}",
+                tr!("pr-body-overflow", count = overflow.len())
+            )?;
+        }
+
+        let milestone = options
+            .milestone
+            .as_ref()
+            .or(self.config.default_milestone.as_ref());
+        // The PR exists now, so failing would leave it half set up, with a
+        // branch that stops the command being run again
+        if let Some(milestone) = milestone
+            && let Err(err) = self.gh.pr_set_milestone(&commit.pr_branch, milestone).await
+        {
+            let error = format!("{err:#}");
+            warn!(
+                "{}",
+                tr!(
+                    "pr-milestone-failed",
+                    milestone = milestone.as_str(),
+                    error = error
+                )
+            );
+        }
+        if let Some(project) = options.project.or(self.config.default_project)
+            && let Err(err) = self.gh.pr_add_to_project(&commit.pr_branch, project).await
+        {
+            let error = format!("{err:#}");
+            warn!(
+                "{}",
+                tr!("pr-project-failed", project = project, error = error)
+            );
+        }
+        if let Err(err) = self.add_pr_participants(&commit.pr_branch, options).await {
+            warn!(
+                "{}",
+                tr!("pr-participants-failed", error = format!("{err:#}"))
+            );
+        }
+        if options.request_owners
+            && !owners.is_empty()
+            && let Err(err) = self
+                .gh
+                .pr_request_reviewers(&commit.pr_branch, owners)
+                .await
+        {
+            warn!(
+                "{}",
+                tr!("pr-owners-request-failed", error = format!("{err:#}"))
+            );
+        }
+
+        self.record_audit(AuditEvent::Created, &commit.pr_branch, &pr_url)
+            .await;
+        self.update_stack_comments(&commit.commit.commit_id.0).await;
+        self.notify(StackEvent::Created, pr_title, &pr_url, &commit.pr_branch)
+            .await;
+
+        Ok(pr_url)
+    }
+    /// A new PR's title and body, from its commit's description and the PR
+    /// template: `jr.prTemplate`, or else the one committed as of the commit.
+    pub(crate) async fn pr_text(&self, commit: &CommitInfo) -> anyhow::Result<PrText> {
+        let commit_id = &commit.commit.commit_id;
+        let template = match &self.config.pr_template {
+            Some(template) => Some(template.clone()),
+            None => {
+                self.git
+                    .get_file(commit_id, template::TEMPLATE_PATH)
+                    .await?
+            }
+        };
+        let Some(text) = template else {
+            return PrText::new(commit, None);
+        };
+
+        let stack_position = self.jj.get_stack_ancestors(&commit_id.0).await?.len();
+        let heads = self.jj.get_stack_heads(&commit_id.0).await?;
+        // Where the stack branches above the commit, count up to the commit
+        let stack_size = match heads.as_slice() {
+            [head] => self.jj.get_stack_ancestors(&head.commit_id.0).await?.len(),
+            _ => stack_position,
+        };
+        let template = PrTemplate {
+            text,
+            stack_position,
+            stack_size,
+        };
+        PrText::new(commit, Some(&template))
+    }
+
+    /// Request reviews of a new PR, and add labels and assignees, from the
+    /// options or else the config.
+    async fn add_pr_participants(
+        &self,
+        branch: &str,
+        options: &CreateOptions,
+    ) -> anyhow::Result<()> {
+        let given_or = |given: &[String], default: &[String]| {
+            let names = if given.is_empty() { default } else { given };
+            // Handles may be given with or without their @
+            names
+                .iter()
+                .map(|name| name.trim_start_matches('@').to_string())
+                .collect::<Vec<_>>()
+        };
+        let reviewers = given_or(&options.reviewers, &self.config.default_reviewers);
+        let assignees = given_or(&options.assignees, &self.config.default_assignees);
+        let mut labels = self.config.labels.clone();
+        for label in &options.labels {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
 
+        if !reviewers.is_empty() {
+            // In the form CODEOWNERS gives them
+            let reviewers = reviewers
+                .iter()
+                .map(|reviewer| format!("@{reviewer}"))
+                .collect::<Vec<_>>();
+            self.gh.pr_request_reviewers(branch, &reviewers).await?;
+        }
+        if labels.is_empty() && assignees.is_empty() {
+            return Ok(());
+        }
+        let pr_number = self
+            .gh
+            .pr_number(branch)
+            .await?
+            .context("PR not found for branch")?;
+        if !labels.is_empty() {
+            self.gh.pr_add_labels(pr_number, &labels).await?;
+        }
+        if !assignees.is_empty() {
+            self.gh.pr_add_assignees(pr_number, &assignees).await?;
+        }
         Ok(())
     }
 }
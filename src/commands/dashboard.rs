@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+use colored::ColoredString;
+use colored::Colorize;
+use serde::Serialize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+use crate::model::CiState;
+use crate::model::ReviewDecision;
+use crate::stack::Stack;
+use crate::title;
+
+/// Options for `jr dashboard`.
+#[derive(Debug, Default)]
+pub struct DashboardOptions {
+    /// Redraw the dashboard every this many seconds.
+    pub watch: Option<u64>,
+    /// Print JSON instead of a table.
+    pub json: bool,
+}
+
+/// All of the user's stacks, with each PR's review and CI state.
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub stacks: Vec<DashboardStack>,
+}
+
+/// A stack's commits, from parent to child.
+#[derive(Debug, Serialize)]
+pub struct DashboardStack {
+    pub commits: Vec<DashboardCommit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DashboardCommit {
+    pub change_id: String,
+    pub title: String,
+    pub status: SyncStatus,
+    pub pr: Option<DashboardPr>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DashboardPr {
+    pub number: u64,
+    pub url: String,
+    pub draft: bool,
+    pub review_decision: Option<ReviewDecision>,
+    pub ci: CiState,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl App {
+    /// Show all of the user's stacks, with each PR's sync status, review
+    /// decision, CI state and age, e.g. for a team wallboard.
+    ///
+    /// With `watch`, the screen is cleared and the dashboard redrawn at that
+    /// interval (or, with `json`, a line of JSON is printed each time).
+    pub async fn cmd_dashboard(
+        &self,
+        options: &DashboardOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        loop {
+            let dashboard = self.load_dashboard().await?;
+            match (options.json, options.watch) {
+                (true, Some(_)) => serde_json::to_writer(&mut *stdout, &dashboard)?,
+                (true, None) => serde_json::to_writer_pretty(&mut *stdout, &dashboard)?,
+                (false, Some(_)) => {
+                    write!(stdout, "\x1b[2J\x1b[H")?;
+                    write_dashboard(&dashboard, self.clock.now(), stdout)?;
+                }
+                (false, None) => write_dashboard(&dashboard, self.clock.now(), stdout)?,
+            }
+            if options.json {
+                writeln!(stdout)?;
+            }
+
+            let Some(interval) = options.watch else {
+                return Ok(());
+            };
+            stdout.flush()?;
+            self.clock.sleep(Duration::from_secs(interval)).await;
+            self.gh.clear_cache();
+        }
+    }
+
+    async fn load_dashboard(&self) -> Result<Dashboard> {
+        // Include pairing co-authors' stacks, which we share
+        let mine = std::iter::once("mine()".to_string())
+            .chain(
+                self.config
+                    .pairs
+                    .iter()
+                    .map(|pair| format!("author({:?})", pair.email)),
+            )
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let mut stacks = vec![];
+        for head in self.jj.get_stack_heads(&mine).await? {
+            let commits = self.jj.get_stack_ancestors(&head.commit_id.0).await?;
+            let stack = Stack::load(commits, &self.config, &self.jj, &self.gh, &self.git).await?;
+            let commits = stack
+                .commits
+                .iter()
+                .zip(stack.statuses())
+                .map(|(commit, status)| self.load_dashboard_commit(commit, status));
+            stacks.push(DashboardStack {
+                commits: try_join_limited(commits, self.config.jobs()).await?,
+            });
+        }
+        Ok(Dashboard { stacks })
+    }
+
+    async fn load_dashboard_commit(
+        &self,
+        commit: &CommitInfo,
+        status: SyncStatus,
+    ) -> Result<DashboardCommit> {
+        let pr = match commit.pr.as_ref().filter(|pr| pr.is_open()) {
+            Some(pr) => Some(DashboardPr {
+                number: pr.number,
+                url: pr.url.clone(),
+                draft: pr.draft,
+                review_decision: self.gh.pr_review_decision(&pr.node_id).await?,
+                ci: self.gh.ci_state(&pr.head.sha).await?,
+                created_at: pr.created_at,
+            }),
+            None => None,
+        };
+        Ok(DashboardCommit {
+            change_id: commit.short_id(),
+            title: title::display(commit.commit.message.title.as_deref().unwrap_or_default()),
+            status,
+            pr,
+        })
+    }
+}
+
+/// Write a dashboard as a table per stack, each from the top down, with PR
+/// ages relative to `now`.
+pub fn write_dashboard(
+    dashboard: &Dashboard,
+    now: DateTime<Utc>,
+    stdout: &mut impl std::io::Write,
+) -> Result<()> {
+    if dashboard.stacks.is_empty() {
+        writeln!(stdout, "{}", tr!("dashboard-no-stacks"))?;
+        return Ok(());
+    }
+
+    for (i, stack) in dashboard.stacks.iter().enumerate() {
+        if i > 0 {
+            writeln!(stdout)?;
+        }
+        let head = stack.commits.last().expect("stacks aren't empty");
+        let header = tr!(
+            "dashboard-stack",
+            head = head.change_id.as_str(),
+            count = stack.commits.len()
+        );
+        writeln!(stdout, "{}", header.bold())?;
+
+        for commit in stack.commits.iter().rev() {
+            let (number, review, ci, age) = match &commit.pr {
+                Some(pr) => (
+                    format!("#{}", pr.number),
+                    review_text(pr),
+                    ci_text(pr.ci),
+                    pr.created_at
+                        .map(|created_at| format_age(now - created_at))
+                        .unwrap_or_default(),
+                ),
+                None => (String::new(), "".normal(), "".normal(), String::new()),
+            };
+            // Pad before coloring, since escape codes would count as width
+            let line = format!(
+                "{} {} {:>6} {} {} {:>4}  {}",
+                commit.status,
+                commit.change_id.cyan(),
+                number,
+                pad(review, 17),
+                pad(ci, 10),
+                age,
+                commit.title
+            );
+            writeln!(stdout, "{}", line.trim_end())?;
+        }
+    }
+    Ok(())
+}
+
+fn review_text(pr: &DashboardPr) -> ColoredString {
+    if pr.draft {
+        return tr!("review-state-draft").dimmed();
+    }
+    match pr.review_decision {
+        Some(ReviewDecision::Approved) => tr!("review-state-approved").green(),
+        Some(ReviewDecision::ChangesRequested) => tr!("review-state-changes-requested").red(),
+        Some(ReviewDecision::ReviewRequired) => tr!("review-state-review-required").yellow(),
+        None => "".normal(),
+    }
+}
+
+fn ci_text(ci: CiState) -> ColoredString {
+    match ci {
+        CiState::None => "".normal(),
+        CiState::Pending => tr!("dashboard-ci-pending").yellow(),
+        CiState::Success => tr!("dashboard-ci-passed").green(),
+        CiState::Failure => tr!("dashboard-ci-failed").red(),
+    }
+}
+
+/// Pad a colored string to a width, ignoring its escape codes.
+fn pad(text: ColoredString, width: usize) -> String {
+    let padding = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Format an age as a number of days, hours or minutes.
+fn format_age(age: TimeDelta) -> String {
+    if age.num_days() > 0 {
+        format!("{}d", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h", age.num_hours())
+    } else {
+        format!("{}m", age.num_minutes().max(0))
+    }
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+
+use crate::App;
+
+impl App {
+    /// Edit a revision's description in the user's editor, prefilled with the
+    /// describe command's suggestion for its diff, or else its current
+    /// description.
+    pub async fn cmd_describe(&self, revision: &str) -> Result<()> {
+        let commit = self.jj.get_commit(revision).await?;
+        let diff = self.git.get_commit_diff(&commit.commit_id).await?;
+        let message = self.compose_message(&diff, &commit.full_message()).await?;
+        self.jj.describe(&commit.commit_id.0, &message).await
+    }
+}
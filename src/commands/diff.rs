@@ -0,0 +1,88 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::App;
+use crate::diff_utils;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+use crate::title;
+
+impl App {
+    /// Show a revision's diff or, with `stack`, the combined diff of its
+    /// whole stack against trunk: what trunk will look like once the stack
+    /// is merged.  Each file in the combined diff is marked with the commits
+    /// in the stack that change it, for a final self-review.
+    pub async fn cmd_diff(
+        &self,
+        revision: &str,
+        stack: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if !stack {
+            let commit = self.jj.get_commit(revision).await?;
+            let parent = self
+                .jj
+                .get_commit(&format!("{}-", commit.commit_id))
+                .await?;
+            let diff = self
+                .git
+                .get_diff(&parent.commit_id, &commit.commit_id)
+                .await?;
+            write!(stdout, "{diff}")?;
+            return Ok(());
+        }
+
+        // From the bottom up
+        let commits = self.get_stack(revision).await?.into_iter().rev();
+        let commits = commits.map(async |commit| {
+            let files = self.git.get_changed_files(&commit.commit_id).await?;
+            anyhow::Ok((commit, files))
+        });
+        let commits = try_join_limited(commits, self.config.jobs()).await?;
+        let Some((top, _)) = commits.last() else {
+            writeln!(stdout, "{}", tr!("diff-empty-stack"))?;
+            return Ok(());
+        };
+        // Where the stack branches off trunk
+        let base = self
+            .jj
+            .get_commit(&format!(
+                "heads(::{} & ::({}))",
+                top.commit_id,
+                self.jj.trunk()
+            ))
+            .await?;
+        let diff = self.git.get_diff(&base.commit_id, &top.commit_id).await?;
+
+        let describe = |commit: &crate::clients::jujutsu::JujutsuCommit| {
+            let change_id = &commit.change_id.0[..8.min(commit.change_id.0.len())];
+            match &commit.message.title {
+                Some(title) => format!("{} {}", change_id, title::display(title)),
+                None => change_id.to_string(),
+            }
+        };
+        writeln!(
+            stdout,
+            "{}",
+            tr!("diff-stack-heading", count = commits.len()).bold()
+        )?;
+        for (commit, _) in &commits {
+            writeln!(stdout, "  {}", describe(commit))?;
+        }
+        for (path, part) in diff_utils::split_files(&diff) {
+            let changed_by = commits
+                .iter()
+                .filter(|(_, files)| files.iter().any(|file| file == path))
+                .map(|(commit, _)| describe(commit))
+                .collect::<Vec<_>>();
+            writeln!(stdout)?;
+            writeln!(
+                stdout,
+                "{}",
+                format!("### {path}: {}", changed_by.join(", ")).yellow()
+            )?;
+            write!(stdout, "{part}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::App;
+use crate::i18n::tr;
+
+impl App {
+    /// Check jr's configuration and environment, reporting every problem
+    /// found rather than stopping at the first.
+    pub async fn cmd_doctor(&self, stdout: &mut impl std::io::Write) -> Result<()> {
+        let mut failures = 0;
+
+        let problems = self.config_problems().await;
+        failures += write_check(stdout, &tr!("doctor-config"), &problems)?;
+
+        let problems = match self.jj.get_trunk().await {
+            Ok(_) => vec![],
+            Err(err) => vec![err.to_string()],
+        };
+        failures += write_check(stdout, &tr!("doctor-jj"), &problems)?;
+
+        failures += match self.gh.current_user().await {
+            Ok(login) => write_check(stdout, &tr!("doctor-token", login = login), &[])?,
+            Err(err) => write_check(stdout, &tr!("doctor-token-failed"), &[err.to_string()])?,
+        };
+
+        let problems = match self.moved_remote_hint().await {
+            Ok(None) => vec![],
+            Ok(Some((moved_to, remote, url))) => vec![tr!(
+                "repo-moved-hint",
+                repo = self.gh.full_name(),
+                moved_to = moved_to,
+                remote = remote,
+                url = url
+            )],
+            Err(err) => vec![err.to_string()],
+        };
+        let check = tr!("doctor-repository", repo = self.gh.full_name());
+        failures += write_check(stdout, &check, &problems)?;
+
+        if failures > 0 {
+            bail!(tr!("doctor-failed", count = failures));
+        }
+        Ok(())
+    }
+}
+
+/// Write a check's result, with any problems indented below it, returning the
+/// number of problems.
+fn write_check(
+    stdout: &mut impl std::io::Write,
+    check: &str,
+    problems: &[String],
+) -> Result<usize> {
+    if problems.is_empty() {
+        writeln!(stdout, "{} {}", "✓".green(), check)?;
+    } else {
+        writeln!(stdout, "{} {}", "✗".red(), check)?;
+        for problem in problems {
+            writeln!(stdout, "    {}", problem.trim())?;
+        }
+    }
+    Ok(problems.len())
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+
+impl App {
+    /// Convert a PR back to a draft, e.g. when CI reveals problems.
+    ///
+    /// With `stack`, every open PR in the stack containing the revision is
+    /// converted, in a single request, so reviewers don't see the stack half
+    /// withdrawn.
+    pub async fn cmd_draft(
+        &self,
+        revision: &str,
+        stack: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-draft", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        self.ensure_repository_writable().await?;
+
+        let commits = if stack {
+            self.get_stack(revision).await?
+        } else {
+            vec![self.jj.get_commit(revision).await?]
+        };
+        let mut prs = vec![];
+        for commit in commits.iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            match self.gh.get_pr(&branch).await? {
+                Some(pr) if pr.is_open() && !pr.draft => prs.push(pr),
+                Some(pr) if !stack => {
+                    writeln!(stdout, "{}", tr!("draft-already", url = pr.url.as_str()))?;
+                    return Ok(());
+                }
+                None if !stack => bail!(tr!("pr-branch-missing", branch = branch.as_str())),
+                _ => {}
+            }
+        }
+        if prs.is_empty() {
+            writeln!(stdout, "{}", tr!("draft-nothing"))?;
+            return Ok(());
+        }
+
+        self.gh.prs_convert_to_draft(&prs).await?;
+        for pr in &prs {
+            writeln!(stdout, "{}", tr!("draft-done", url = pr.url.as_str()))?;
+        }
+
+        Ok(())
+    }
+}
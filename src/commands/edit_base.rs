@@ -0,0 +1,71 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::Config;
+use crate::commit::CommitInfo;
+use crate::config;
+use crate::i18n::tr;
+
+impl App {
+    /// Point a PR at a base of the user's choosing, e.g. a hotfix branch,
+    /// rather than its parent's PR branch or the default branch.  The
+    /// override is kept in `jr.baseOverride`, so status, updates and restacks
+    /// go on using it.  With no `base`, the override is dropped and the PR
+    /// pointed back at its computed base.
+    pub async fn cmd_edit_base(
+        &self,
+        revision: &str,
+        base: Option<&str>,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-edit-base", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        self.ensure_repository_writable().await?;
+
+        let commit = self.jj.get_commit(revision).await?;
+        let change_id = commit.change_id.clone();
+        let pr_branch = CommitInfo::find_branch(&change_id, &self.config, &self.git).await;
+        if !self.gh.pr_is_open(&pr_branch).await? {
+            bail!(tr!("pr-not-open", branch = pr_branch.as_str()));
+        }
+
+        let new_base = match base {
+            Some(base) => {
+                if self.git.get_branch_tip(base).await.is_err() {
+                    bail!(tr!(
+                        "edit-base-missing",
+                        branch = base,
+                        remote = self.git.remote()
+                    ));
+                }
+                base.to_string()
+            }
+            None => {
+                // Work the base out as if it had never been overridden
+                let config = Config {
+                    base_overrides: vec![],
+                    ..(*self.config).clone()
+                };
+                CommitInfo::new(commit, &config, &self.jj, &self.gh, &self.git)
+                    .await?
+                    .base_branch
+            }
+        };
+
+        let pr_url = self.gh.pr_edit(&pr_branch, &new_base).await?;
+        config::save_base_override(&change_id.0, base.map(|_| new_base.as_str()))?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "edit-base-done",
+                url = pr_url.as_str(),
+                base = new_base.as_str()
+            )
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::App;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+use crate::model::PullRequest;
+
+impl App {
+    /// List the files changed by a revision, flagging those also changed by
+    /// other commits in its stack (review hotspots) or by other people's open
+    /// PRs (conflict risk).
+    pub async fn cmd_files(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        let commit = self.jj.get_commit(revision).await?;
+        let files = self.git.get_changed_files(&commit.commit_id).await?;
+
+        // Other commits in the stack, and the files they change
+        let heads = self.jj.get_stack_heads(revision).await?;
+        let stack = match heads.as_slice() {
+            [head] => self.jj.get_stack_ancestors(&head.commit_id.0).await?,
+            _ => self.jj.get_stack_ancestors(revision).await?,
+        };
+        let stack = stack
+            .into_iter()
+            .filter(|other| other.change_id != commit.change_id)
+            .map(async |other| {
+                let files = self.git.get_changed_files(&other.commit_id).await?;
+                anyhow::Ok((other, files))
+            });
+        let stack = try_join_limited(stack, self.config.jobs()).await?;
+
+        // Other people's open PRs, and the files they change
+        let prs = self
+            .gh
+            .open_prs()
+            .await?
+            .into_iter()
+            .filter(|pr| {
+                !self
+                    .config
+                    .branch_prefixes()
+                    .any(|prefix| pr.head.name.starts_with(prefix))
+            })
+            .map(async |pr: PullRequest| {
+                let files = self.gh.pr_files(pr.number).await?;
+                anyhow::Ok((pr, files))
+            });
+        let prs = try_join_limited(prs, self.config.jobs()).await?;
+
+        let width = files.iter().map(|file| file.len()).max().unwrap_or(0);
+        for file in &files {
+            let commits = stack
+                .iter()
+                .filter(|(_, changed)| changed.contains(file))
+                .map(|(other, _)| other.change_id.0[..4.min(other.change_id.0.len())].to_string())
+                .collect::<Vec<_>>();
+            let prs = prs
+                .iter()
+                .filter(|(_, changed)| changed.contains(file))
+                .map(|(pr, _)| format!("#{}", pr.number))
+                .collect::<Vec<_>>();
+
+            let mut notes = vec![];
+            if !commits.is_empty() {
+                let note = tr!("files-in-stack", commits = commits.join(" "));
+                notes.push(note.yellow());
+            }
+            if !prs.is_empty() {
+                let note = tr!("files-in-prs", count = prs.len(), prs = prs.join(" "));
+                notes.push(note.red());
+            }
+            let notes = notes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("  ");
+            let line = format!("{:width$}  {}", file, notes);
+            writeln!(stdout, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use crate::App;
+use crate::config;
+use crate::i18n::tr;
+
+impl App {
+    /// Set up pushing PR branches to the user's fork, for repositories they
+    /// can't push to.
+    ///
+    /// The fork is found or created with the GitHub API, added as a git
+    /// remote (using the same protocol as the repository's remote) if it isn't one already, and
+    /// saved as `jr.pushRemote`.
+    pub async fn cmd_fork(&self, remote: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        let login = self.gh.current_user().await?;
+        let fork = self.gh.find_or_create_fork(&login).await?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!("fork-found", repo = fork.full_name.as_str())
+        )?;
+
+        if self.git.get_url_of(remote).await.is_err() {
+            let origin = self.git.get_url_of(self.git.remote()).await?;
+            let url = if origin.starts_with("git@") {
+                &fork.ssh_url
+            } else {
+                &fork.clone_url
+            };
+            self.git.add_remote(remote, url).await?;
+            writeln!(
+                stdout,
+                "{}",
+                tr!("fork-remote-added", remote = remote, url = url.as_str())
+            )?;
+        }
+
+        config::save_push_remote(remote)?;
+        writeln!(stdout, "{}", tr!("fork-done", remote = remote))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::TimeDelta;
+
+use crate::App;
+use crate::audit;
+use crate::i18n::tr;
+use crate::provenance::NOTES_REF;
+use crate::run;
+use crate::run::Run;
+use crate::state;
+
+impl App {
+    /// Prune jr's own state in the git directory, reporting how much each
+    /// part shrank:
+    ///
+    /// - PR metadata (see [`state`]) for PRs last seen merged or closed, and
+    ///   for changes that have since been abandoned.
+    /// - Audit log entries from more than `keep_days` days ago.
+    /// - A run manifest (see [`run`]) that can't be resumed any more, because
+    ///   a change it has yet to submit or restack is gone.
+    /// - Provenance notes for commits that no longer exist.
+    ///
+    /// If `dry_run`, nothing is changed.
+    pub async fn cmd_gc(
+        &self,
+        keep_days: u32,
+        dry_run: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let git_dir = self.git.get_git_dir().await?;
+        let mut state = self.load_state().await;
+        let run_path = git_dir.join(run::FILE_NAME);
+        let run_json = read_optional(&run_path).await?;
+
+        let run = run_json.as_deref().and_then(Run::parse);
+        let pending = run
+            .iter()
+            .flat_map(|run| &run.steps)
+            .filter(|step| !step.done)
+            .map(|step| step.change_id.as_str())
+            .collect::<Vec<_>>();
+        let mut change_ids = state.prs.keys().map(String::as_str).collect::<Vec<_>>();
+        change_ids.extend(pending.iter().copied());
+        let visible = self.jj.get_visible_changes(&change_ids).await?;
+
+        let state_path = git_dir.join(state::FILE_NAME);
+        let before = file_size(&state_path).await;
+        let total = state.prs.len();
+        let pruned = state.prune(|change_id| visible.contains(change_id));
+        let after = if pruned > 0 {
+            state.to_json().len() as u64
+        } else {
+            before
+        };
+        if pruned > 0 && !dry_run {
+            self.save_state(&state).await;
+        }
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "gc-state",
+                pruned = pruned,
+                total = total,
+                before = format_size(before),
+                after = format_size(after)
+            )
+        )?;
+
+        let audit_path = git_dir.join(audit::FILE_NAME);
+        let log = read_optional(&audit_path).await?.unwrap_or_default();
+        let since = self.clock.now() - TimeDelta::days(keep_days.into());
+        let (kept, pruned) = audit::prune(&log, since);
+        if pruned > 0 && !dry_run {
+            tokio::fs::write(&audit_path, &kept).await?;
+        }
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "gc-audit",
+                pruned = pruned,
+                total = audit::parse_log(&log).len(),
+                days = keep_days,
+                before = format_size(log.len() as u64),
+                after = format_size(kept.len() as u64)
+            )
+        )?;
+
+        if let Some(run_json) = &run_json {
+            let stale = run.is_none()
+                || pending
+                    .iter()
+                    .any(|change_id| !visible.contains(*change_id));
+            if stale {
+                if !dry_run {
+                    tokio::fs::remove_file(&run_path).await?;
+                }
+                let size = format_size(run_json.len() as u64);
+                writeln!(stdout, "{}", tr!("gc-run", size = size))?;
+            }
+        }
+
+        let pruned = self.git.prune_notes(NOTES_REF, dry_run).await?;
+        writeln!(stdout, "{}", tr!("gc-notes", pruned = pruned))?;
+
+        if dry_run {
+            writeln!(stdout, "{}", tr!("gc-dry-run"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a file, or `None` if there isn't one.
+async fn read_optional(path: &Path) -> Result<Option<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn file_size(path: &Path) -> u64 {
+    tokio::fs::metadata(path)
+        .await
+        .map_or(0, |metadata| metadata.len())
+}
+
+/// Format a size in bytes, e.g. `812 B` or `48.2 KiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::graph;
+use crate::graph::GraphFormat;
+use crate::i18n::tr;
+use crate::stack::Stack;
+
+impl App {
+    /// Write a diagram of the stack, bottom first, with each commit's PR and
+    /// sync status and an edge to its PR's base (see [`graph`]).
+    ///
+    /// With `embed`, the diagram is also put in the top PR's description, in
+    /// Mermaid, replacing any diagram embedded before.
+    pub async fn cmd_graph(
+        &self,
+        revision: &str,
+        format: GraphFormat,
+        embed: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-graph", revision = revision));
+        }
+        let commits = self.get_stack(revision).await?;
+        let stack = Stack::load(commits, &self.config, &self.jj, &self.gh, &self.git).await?;
+        write!(stdout, "{}", graph::render(&stack, format))?;
+
+        if embed {
+            let Some(pr) = stack
+                .commits
+                .iter()
+                .rev()
+                .find_map(|commit| commit.pr.as_ref())
+            else {
+                bail!(tr!("graph-no-prs"));
+            };
+            let diagram = graph::render(&stack, GraphFormat::Mermaid);
+            let body = graph::embed(pr.body.as_deref().unwrap_or(""), &diagram);
+            self.gh.pr_set_body(pr.number, &body).await?;
+            writeln!(stdout, "{}", tr!("graph-embedded", url = pr.url.as_str()))?;
+        }
+        Ok(())
+    }
+}
@@ -2,16 +2,21 @@ use std::io;
 use std::io::Write;
 
 use anyhow::Result;
+use anyhow::bail;
 
 use crate::App;
+use crate::clients::github::GithubClient;
 use crate::config::Config;
+use crate::config::TokenSource;
+use crate::i18n::tr;
 
 impl App {
     #[rustfmt::skip]
-    pub async fn cmd_init(&self, stdout: &mut impl std::io::Write) -> Result<()> {
+    pub async fn cmd_init(&self, files: bool, stdout: &mut impl std::io::Write) -> Result<()> {
         // Query the default branch from git
         let detected_default_branch = self.git.get_default_branch().await
             .unwrap_or_else(|_| "main".to_string());
+        let host = &self.gh.host().name;
 
         let current_config = Config::load()
             .unwrap_or_else(|_| Config::new(
@@ -30,14 +35,14 @@ impl App {
         writeln!(stdout, "Either:")?;
         writeln!(stdout)?;
         writeln!(stdout, " - Create a fine-grained Personal Access Token for this repository at:")?;
-        writeln!(stdout, "   https://github.com/settings/personal-access-tokens/new")?;
+        writeln!(stdout, "   https://{host}/settings/personal-access-tokens/new")?;
         writeln!(stdout)?;
         writeln!(stdout, "   Required permissions:")?;
         writeln!(stdout, "    - Contents: Read and write")?;
         writeln!(stdout, "    - Pull requests: Read and write")?;
         writeln!(stdout)?;
         writeln!(stdout, " - Or, create a classic Personal Access Token at:")?;
-        writeln!(stdout, "   https://github.com/settings/tokens/new")?;
+        writeln!(stdout, "   https://{host}/settings/tokens/new")?;
         writeln!(stdout)?;
         writeln!(stdout, "   Required scopes:")?;
         writeln!(stdout, "    - Repo")?;
@@ -45,13 +50,102 @@ impl App {
 
         let github_token =
             prompt_with_default("GitHub Personal Access Token", current_config.github_token)?;
+        let token_source = prompt_token_source(current_config.token_source)?;
 
-        Config::new(github_branch_prefix, github_token, default_branch).save()?;
+        let config = Config {
+            token_source,
+            ..Config::new(github_branch_prefix, github_token, default_branch)
+        };
+        save(config, files, stdout)
+    }
+
+    /// Initialize configuration from the `gh` CLI's login and the GitHub
+    /// API, only prompting to confirm each detected value.
+    pub async fn cmd_init_from_gh(
+        &self,
+        files: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let Some(gh_token) = GithubClient::detect_gh_token(self.gh.host()).await else {
+            bail!(tr!("init-no-gh-token"));
+        };
+        let gh = GithubClient::new(
+            gh_token.clone(),
+            std::env::current_dir()?,
+            self.gh.host().clone(),
+            self.git.remote(),
+        )
+        .await?;
+        let login = gh.current_user().await?;
+        let repository = gh.repository().await?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "init-detected",
+                visibility = repository.visibility,
+                repo = gh.full_name()
+            )
+        )?;
 
-        writeln!(stdout, "Configuration saved to .git/config")?;
+        let github_branch_prefix =
+            prompt_with_default("GitHub branch prefix", format!("{login}/"))?;
+        let default_branch = prompt_with_default("Default branch", repository.default_branch)?;
+        let use_gh_token = prompt_with_default("Use the token from 'gh auth token'?", "Y".into())?;
+        let github_token = if use_gh_token.eq_ignore_ascii_case("y") {
+            gh_token
+        } else {
+            prompt_with_default("GitHub Personal Access Token", String::new())?
+        };
+        let token_source = prompt_token_source(TokenSource::GitConfig)?;
 
-        Ok(())
+        let config = Config {
+            token_source,
+            ..Config::new(github_branch_prefix, github_token, default_branch)
+        };
+        save(config, files, stdout)
+    }
+}
+
+/// Save the config from `jr init`, to the config files or git config.
+fn save(config: Config, files: bool, stdout: &mut impl std::io::Write) -> Result<()> {
+    if files {
+        config.save_to_files()?;
+        writeln!(stdout, "{}", tr!("init-saved-files"))?;
+    } else {
+        config.save()?;
+        writeln!(stdout, "{}", tr!("init-saved"))?;
     }
+    if files || config.token_source == TokenSource::Keychain {
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "init-token-saved",
+                destination = config.token_source.to_string()
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// Ask whether to keep the token in the system keychain rather than git
+/// config, defaulting to where it's kept now.
+fn prompt_token_source(current: TokenSource) -> Result<TokenSource> {
+    let default = if current == TokenSource::Keychain {
+        "Y"
+    } else {
+        "N"
+    };
+    let answer = prompt_with_default(
+        "Save the token in the system keychain instead of .git/config?",
+        default.into(),
+    )?;
+    Ok(if answer.eq_ignore_ascii_case("y") {
+        TokenSource::Keychain
+    } else {
+        TokenSource::GitConfig
+    })
 }
 
 fn prompt_with_default(prompt: &str, default: String) -> Result<String> {
@@ -0,0 +1,76 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::labels;
+
+impl App {
+    /// Make sure every open PR in the stack containing the revision carries
+    /// the labels configured with `jr.label`.  With `prune`, labels jr applied
+    /// before that are no longer configured are removed.
+    pub async fn cmd_label_sync(
+        &self,
+        revision: &str,
+        prune: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-label", revision = revision));
+        }
+        self.ensure_repository_writable().await?;
+
+        let mut state = self.load_state().await;
+        let wanted = &self.config.labels;
+        let stale = if prune {
+            state
+                .labels
+                .iter()
+                .filter(|label| !wanted.contains(label))
+                .cloned()
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        if wanted.is_empty() && stale.is_empty() {
+            writeln!(stdout, "{}", tr!("label-none-configured"))?;
+            return Ok(());
+        }
+
+        let mut changed = false;
+        for commit in self.get_stack(revision).await?.iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            let Some(pr) = self.gh.get_pr(&branch).await? else {
+                continue;
+            };
+            if !pr.is_open() {
+                continue;
+            }
+            let current = pr
+                .labels
+                .iter()
+                .map(|label| label.name.clone())
+                .collect::<Vec<_>>();
+            if let Some(labels) = labels::sync(&current, wanted, &stale) {
+                self.gh.pr_set_labels(pr.number, &labels).await?;
+                writeln!(stdout, "{}", tr!("label-synced", url = pr.url.as_str()))?;
+                changed = true;
+            }
+        }
+        if !changed {
+            writeln!(stdout, "{}", tr!("label-nothing"))?;
+        }
+
+        // Remember what jr manages, so it can be pruned later
+        if prune {
+            state.labels = wanted.iter().cloned().collect();
+        } else {
+            state.labels.extend(wanted.iter().cloned());
+        }
+        self.save_state(&state).await;
+        Ok(())
+    }
+}
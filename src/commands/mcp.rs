@@ -0,0 +1,97 @@
+use anyhow::Result;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::App;
+use crate::mcp;
+use crate::rpc;
+use crate::rpc::Method;
+use crate::rpc::Response;
+
+impl App {
+    /// Serve jr's operations to a coding assistant over the Model Context
+    /// Protocol on stdin and stdout (see [`mcp`]), until stdin closes.
+    ///
+    /// Reads are always available.  Changes to PRs need `allow_writes`, and
+    /// are only made once the assistant has confirmed them with the user.
+    pub async fn cmd_mcp(
+        &self,
+        allow_writes: bool,
+        stdin: &mut impl std::io::BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        // Output is returned to the assistant, not shown in a terminal
+        colored::control::set_override(false);
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stdin.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(err) => {
+                    let response = Response::error(Value::Null, rpc::PARSE_ERROR, err.to_string());
+                    writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+                    stdout.flush()?;
+                    continue;
+                }
+            };
+            // Notifications have no ID, and get no response
+            let Some(id) = message.get("id").cloned() else {
+                continue;
+            };
+
+            let method = message["method"].as_str().unwrap_or_default();
+            let response = match method {
+                "initialize" => Response::success(id, mcp::initialize_result()),
+                "ping" => Response::success(id, json!({})),
+                "tools/list" => Response::success(id, mcp::tool_list(allow_writes)),
+                "tools/call" => match mcp::parse_tool_call(&message["params"], allow_writes) {
+                    Ok(call) => Response::success(id, self.call_tool(call).await),
+                    Err(err) => Response::error(id, rpc::INVALID_PARAMS, err),
+                },
+                _ => Response::error(
+                    id,
+                    rpc::METHOD_NOT_FOUND,
+                    format!("Unknown method: {method}"),
+                ),
+            };
+            writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+            stdout.flush()?;
+        }
+    }
+
+    /// Call a tool, returning its result or error as the tool's output.
+    async fn call_tool(&self, call: mcp::ToolCall) -> Value {
+        let result = if call.method.is_mutating()
+            && let Err(err) = self.ensure_push_confirmed().await
+        {
+            Err(err)
+        } else if call.method.is_mutating() && !call.confirmed {
+            // Name a submitted range by its top commit
+            let revision = match &call.method {
+                Method::Submit(params) => format!("heads({})", params.revision),
+                method => method.revision().to_string(),
+            };
+            self.jj.get_commit(&revision).await.map(|commit| {
+                let title = commit.message.title.unwrap_or_default();
+                Value::String(mcp::preview(&call.method, &title))
+            })
+        } else {
+            self.call(call.method).await
+        };
+        match result {
+            Ok(Value::String(text)) => mcp::tool_result(&text, false),
+            Ok(value) => {
+                let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+                mcp::tool_result(&text, false)
+            }
+            Err(err) => mcp::tool_result(&format!("{err:#}"), true),
+        }
+    }
+}
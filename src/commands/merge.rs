@@ -0,0 +1,213 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::audit::AuditEvent;
+use crate::auto_ready::AutoReady;
+use crate::clients::jujutsu::JujutsuCommit;
+use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::merge;
+use crate::notify::StackEvent;
+use crate::run::Action;
+use crate::run::Run;
+use crate::run::RunCommand;
+
+impl App {
+    /// Merge the PR at the bottom of a stack, then bring the rest of the
+    /// stack up to date with it.
+    ///
+    /// 1. Merge the bottom PR on GitHub, with the configured merge method if
+    ///    the repository allows it, and only if its head hasn't moved.
+    /// 2. Point the PRs based on it at the default branch.
+    /// 3. Fetch the default branch, rebase the rest of the stack onto trunk
+    ///    and abandon the merged commit.
+    /// 4. Restack the remaining PRs from the bottom up, stopping at the first
+    ///    that has conflicts or local changes, for the user to resolve.
+    ///
+    /// Each step is recorded as it finishes, so that after a failure or a
+    /// stop, `resume` carries on from the next.
+    pub async fn cmd_merge(
+        &self,
+        revision: &str,
+        resume: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let mut run = if resume {
+            self.load_run(RunCommand::Merge).await?
+        } else {
+            self.plan_merge(revision).await?
+        };
+        self.save_run(&run).await;
+
+        for (index, step) in run.pending() {
+            let finished = match step.action {
+                Action::Merge => {
+                    self.merge_bottom(&step.change_id, stdout).await?;
+                    true
+                }
+                Action::Rebase => {
+                    let rest = run.steps.len() > index + 1;
+                    self.rebase_off_merged(&step.change_id, rest).await?;
+                    true
+                }
+                Action::Restack => self.restack_merged_child(&step.change_id, stdout).await?,
+                Action::Submit => unreachable!("merge has no submit steps"),
+            };
+            // Leave the rest for the user to resolve, then resume
+            if !finished {
+                return Ok(());
+            }
+            run.complete(index);
+            self.save_run(&run).await;
+        }
+        self.clear_run().await;
+        Ok(())
+    }
+
+    /// Plan merging the bottom of the stack containing a revision: merge it,
+    /// rebase off it, then restack the rest of the stack from the bottom up.
+    async fn plan_merge(&self, revision: &str) -> Result<Run> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-merge", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+
+        let roots = self.jj.get_stack_roots(revision).await?;
+        let [bottom] = roots.as_slice() else {
+            bail!(tr!("merge-multiple-roots"));
+        };
+        let rest = self
+            .get_stack(revision)
+            .await?
+            .into_iter()
+            .rev()
+            .filter(|commit| commit.change_id != bottom.change_id)
+            .map(|commit| (Action::Restack, commit.change_id.0));
+        let bottom = &bottom.change_id.0;
+        let steps = [
+            (Action::Merge, bottom.clone()),
+            (Action::Rebase, bottom.clone()),
+        ];
+        Ok(Run::new(RunCommand::Merge, steps.into_iter().chain(rest)))
+    }
+
+    async fn get_change(&self, change_id: &str) -> Result<JujutsuCommit> {
+        self.jj.get_commit(&format!("change_id({change_id})")).await
+    }
+
+    /// Merge a change's PR, and point the PRs based on it at the default
+    /// branch.
+    async fn merge_bottom(&self, change_id: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        self.ensure_repository_writable().await?;
+        let bottom = self.get_change(change_id).await?;
+        let bottom = CommitInfo::new(bottom, &self.config, &self.jj, &self.gh, &self.git).await?;
+        let (Some(pr), Some(pr_tip)) = (&bottom.pr, &bottom.pr_tip) else {
+            bail!(tr!("pr-branch-missing", branch = bottom.pr_branch.as_str()));
+        };
+        if !self.gh.pr_is_open(&bottom.pr_branch).await? {
+            bail!(tr!("pr-not-open", branch = bottom.pr_branch.as_str()));
+        }
+        if pr.draft {
+            bail!(tr!("merge-draft", url = pr.url.as_str()));
+        }
+        if self.gh.capabilities().await?.merge_queue {
+            bail!(tr!(
+                "merge-queue",
+                branch = self.config.default_branch.as_str(),
+                url = pr.url.as_str()
+            ));
+        }
+        if bottom.status() != SyncStatus::Synced {
+            bail!(tr!("merge-not-synced", url = pr.url.as_str()));
+        }
+
+        let repository = self.gh.repository().await?;
+        let method = merge::choose(
+            self.config.merge_method,
+            &repository.allowed_merge_methods(),
+        )?;
+        self.gh.pr_merge(pr.number, method, &pr_tip.0).await?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!("merge-done", url = pr.url.as_str(), method = method.name())
+        )?;
+        self.record_audit(AuditEvent::Merged, &bottom.pr_branch, &pr.url)
+            .await;
+        self.notify(StackEvent::Merged, &pr.title, &pr.url, &bottom.pr_branch)
+            .await;
+
+        // Unless GitHub deletes merged branches, PRs based on this one still
+        // point at its branch.  Once retargeted, `jr sync` can't tell their
+        // parent was merged, so mark them ready here if jr.autoReady says to.
+        let default_branch = &self.config.default_branch;
+        let auto_ready = self.config.auto_ready.unwrap_or(AutoReady::Off);
+        for child in self.gh.open_prs().await? {
+            if child.base.name == bottom.pr_branch {
+                self.gh.pr_edit(&child.head.name, default_branch).await?;
+                if child.draft && auto_ready != AutoReady::Off {
+                    self.gh.pr_set_draft(&child.head.name, false).await?;
+                    writeln!(stdout, "{}", tr!("ready-done", url = child.url.as_str()))?;
+                    self.notify(
+                        StackEvent::Ready,
+                        &child.title,
+                        &child.url,
+                        &child.head.name,
+                    )
+                    .await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch the default branch, rebase the `rest` of the stack, if any,
+    /// onto trunk, and abandon the merged change.
+    async fn rebase_off_merged(&self, change_id: &str, rest: bool) -> Result<()> {
+        self.jj.git_fetch(&[&self.config.default_branch]).await?;
+        let bottom_id = &self.get_change(change_id).await?.commit_id.0;
+        if rest {
+            self.jj
+                .rebase(&format!("children({bottom_id})"), self.jj.trunk())
+                .await?;
+        }
+        self.jj.abandon(bottom_id).await?;
+        self.gh.clear_cache();
+        Ok(())
+    }
+
+    /// Restack a change's PR after its parent's was merged, returning whether
+    /// to go on: not if it has conflicts or changes not in its PR yet.
+    async fn restack_merged_child(
+        &self,
+        change_id: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<bool> {
+        let commit = self.get_change(change_id).await?;
+        let commit_id = commit.commit_id.0.clone();
+        if self.jj.has_conflicts(&commit_id).await? {
+            writeln!(
+                stdout,
+                "{}",
+                tr!("merge-cascade-conflict", commit = commit_id.as_str())
+            )?;
+            return Ok(false);
+        }
+        let commit = CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+        match commit.status() {
+            SyncStatus::Synced => {}
+            SyncStatus::Restack => self.cmd_restack(&commit_id, stdout).await?,
+            SyncStatus::Changed | SyncStatus::Unknown => {
+                writeln!(
+                    stdout,
+                    "{}",
+                    tr!("merge-cascade-stopped", commit = commit_id.as_str())
+                )?;
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
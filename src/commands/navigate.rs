@@ -0,0 +1,83 @@
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::App;
+use crate::clients::jujutsu::JujutsuCommit;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::title;
+
+/// Where to move the working copy within the stack.
+#[derive(Clone, Copy, Debug)]
+pub enum Direction {
+    /// The head of the stack.
+    Top,
+    /// The oldest commit in the stack.
+    Bottom,
+    /// The child of the working-copy commit.
+    Next,
+    /// The parent of the working-copy commit.
+    Prev,
+}
+
+impl App {
+    /// Move the working copy to another commit in the stack with `jj edit`,
+    /// then print the new position and its PR status.
+    pub async fn cmd_navigate(
+        &self,
+        direction: Direction,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let candidates = match direction {
+            Direction::Top => self.jj.get_stack_heads("@").await?,
+            Direction::Bottom => self.jj.get_stack_roots("@").await?,
+            Direction::Next => self.jj.get_stack_children("@").await?,
+            Direction::Prev => self.jj.get_stack_parents("@").await?,
+        };
+
+        let target = match candidates.as_slice() {
+            [] => match direction {
+                Direction::Top | Direction::Next => bail!(tr!("navigate-at-top")),
+                Direction::Bottom | Direction::Prev => bail!(tr!("navigate-at-bottom")),
+            },
+            [target] => target.clone(),
+            _ => bail!(tr!(
+                "navigate-ambiguous",
+                candidates = candidates
+                    .iter()
+                    .map(short_description)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        };
+
+        self.jj.edit(&target.commit_id.0).await?;
+
+        let commit = CommitInfo::new(target, &self.config, &self.jj, &self.gh, &self.git).await?;
+        let title = title::display(commit.commit.message.title.as_deref().unwrap_or(""));
+        let out = tr!(
+            "navigate-now-at",
+            commit = format!(
+                "{} {} {}",
+                commit.status(),
+                commit.short_id().cyan(),
+                title.white().bold()
+            )
+        );
+        writeln!(stdout, "{}", out.trim_end())?;
+        if let Some(pr) = &commit.pr {
+            writeln!(stdout, "{}", format!("  {}", pr.url).dimmed())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn short_description(commit: &JujutsuCommit) -> String {
+    let change_id = &commit.change_id.0[..8.min(commit.change_id.0.len())];
+    match &commit.message.title {
+        Some(title) => format!("{} ({})", change_id, title::display(title)),
+        None => change_id.to_string(),
+    }
+}
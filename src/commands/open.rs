@@ -0,0 +1,53 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+
+impl App {
+    /// Open a revision's PR in the browser or, with `stack`, every PR in its
+    /// stack, from the bottom up, as tabs for a review session.  With
+    /// `print`, the URLs are printed in that order instead.
+    pub async fn cmd_open(
+        &self,
+        revision: &str,
+        stack: bool,
+        print: bool,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-open", revision = revision));
+        }
+
+        let commits = if stack {
+            self.get_stack(revision).await?
+        } else {
+            vec![self.jj.get_commit(revision).await?]
+        };
+        let mut urls = vec![];
+        for commit in commits.iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            match self.gh.get_pr(&branch).await? {
+                Some(pr) => urls.push(pr.url),
+                None if !stack => bail!(tr!("pr-branch-missing", branch = branch.as_str())),
+                None => {}
+            }
+        }
+        if urls.is_empty() {
+            writeln!(stdout, "{}", tr!("open-nothing"))?;
+            return Ok(());
+        }
+
+        for url in &urls {
+            if print {
+                writeln!(stdout, "{url}")?;
+            } else {
+                writeln!(stdout, "{}", tr!("open-opening", url = url.as_str()))?;
+                open::that(url).with_context(|| tr!("open-failed", url = url.as_str()))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use crate::App;
+use crate::clients::git::CommitId;
+use crate::codeowners::CODEOWNERS_PATHS;
+use crate::codeowners::CodeOwners;
+use crate::i18n::tr;
+
+impl App {
+    /// Preview which CODEOWNERS entries match the files changed by a revision,
+    /// i.e. who will be asked to review its PR.
+    pub async fn cmd_owners(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        let commit = self.jj.get_commit(revision).await?;
+        let Some(code_owners) = self.get_code_owners(&commit.commit_id).await? else {
+            writeln!(stdout, "{}", tr!("owners-no-codeowners"))?;
+            return Ok(());
+        };
+
+        let files = self.git.get_changed_files(&commit.commit_id).await?;
+        let width = files.iter().map(|file| file.len()).max().unwrap_or(0);
+        for file in &files {
+            let owners = code_owners.owners_of(file);
+            let owners = if owners.is_empty() {
+                tr!("owners-none")
+            } else {
+                owners.join(" ")
+            };
+            writeln!(stdout, "{:width$}  {}", file, owners)?;
+        }
+
+        let owners = code_owners.owners_of_all(files.iter().map(String::as_str));
+        if !owners.is_empty() {
+            writeln!(stdout)?;
+            writeln!(
+                stdout,
+                "{}",
+                tr!("owners-expected-reviewers", owners = owners.join(", "))
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the CODEOWNERS file as of a commit, if there is one.
+    pub(crate) async fn get_code_owners(&self, commit_id: &CommitId) -> Result<Option<CodeOwners>> {
+        for path in CODEOWNERS_PATHS {
+            if let Some(contents) = self.git.get_file(commit_id, path).await? {
+                return Ok(Some(CodeOwners::parse(&contents)));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use chrono::TimeDelta;
+use colored::Colorize;
+
+use crate::App;
+use crate::i18n::tr;
+use crate::nudge;
+use crate::title;
+
+/// Options for `jr ping-reviewers`.
+#[derive(Debug)]
+pub struct PingOptions {
+    /// Post a nudge comment mentioning the reviewers.
+    pub comment: bool,
+    /// Re-request reviews from the reviewers.
+    pub re_request: bool,
+    /// Nudge at most this many PRs.
+    pub max: usize,
+    /// Only list the PRs that would be nudged.
+    pub dry_run: bool,
+}
+
+impl App {
+    /// Nudge the reviewers of your open, ready-for-review PRs that have been
+    /// waiting longer than `jr.nudgeAfterDays`.
+    ///
+    /// A PR isn't nudged again until it has waited that long since the last
+    /// nudge (see [`nudge`]), and at most `max` PRs are nudged per run, so
+    /// this is safe to run from a scheduled job.
+    pub async fn cmd_ping_reviewers(
+        &self,
+        options: &PingOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let after_days = self
+            .config
+            .nudge_after_days
+            .unwrap_or(nudge::DEFAULT_AFTER_DAYS);
+        let threshold = TimeDelta::days(after_days as i64);
+        let template = self
+            .config
+            .nudge_template
+            .as_deref()
+            .unwrap_or(nudge::DEFAULT_TEMPLATE);
+        let me = self.gh.current_user().await?;
+        let now = self.clock.now();
+
+        let mut nudged = 0;
+        for pr in self.gh.open_prs().await? {
+            if pr.draft || pr.user.as_ref().is_none_or(|user| user.login != me) {
+                continue;
+            }
+            let reviews = self.gh.pr_reviews(pr.number).await?;
+            let reviewers = nudge::waiting_reviewers(&pr.requested_reviewers, &reviews, &me);
+            if reviewers.is_empty() {
+                continue;
+            }
+            let comments = self.gh.pr_comments(pr.number).await?;
+            let Some(since) = nudge::waiting_since(pr.created_at, &reviews, &comments) else {
+                continue;
+            };
+            if now - since < threshold {
+                continue;
+            }
+
+            if nudged == options.max {
+                writeln!(stdout, "{}", tr!("ping-limit", max = options.max))?;
+                break;
+            }
+            nudged += 1;
+
+            let days = (now - since).num_days();
+            let reviewers_list = reviewers.join(", ");
+            let message = if options.dry_run {
+                tr!("ping-would-nudge", reviewers = reviewers_list, days = days)
+            } else {
+                if options.comment {
+                    let body = nudge::render_comment(template, &reviewers, &pr.title, &pr.url);
+                    self.gh.pr_comment(pr.number, &body).await?;
+                }
+                if options.re_request {
+                    let handles = reviewers
+                        .iter()
+                        .map(|login| format!("@{login}"))
+                        .collect::<Vec<_>>();
+                    self.gh
+                        .pr_request_reviewers(&pr.head.name, &handles)
+                        .await?;
+                }
+                tr!("ping-nudged", reviewers = reviewers_list, days = days)
+            };
+            writeln!(
+                stdout,
+                "{} {}",
+                format!("#{}", pr.number).cyan(),
+                title::display(&pr.title)
+            )?;
+            writeln!(stdout, "{}", format!("  {} ({})", pr.url, message).dimmed())?;
+        }
+
+        if nudged == 0 {
+            writeln!(stdout, "{}", tr!("ping-none", days = after_days))?;
+        }
+        Ok(())
+    }
+}
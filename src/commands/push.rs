@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use crate::App;
+use crate::commands::create::CreateOptions;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+
+impl App {
+    /// Push the PR branches in a stack that `jr create --no-push` and
+    /// `jr update --no-push` prepared locally, bottom first, then open or
+    /// update their PRs as those commands would have.  Each local branch is
+    /// deleted once pushed.
+    pub async fn cmd_push(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        self.ensure_repository_writable().await?;
+        let stack = self.get_stack(revision).await?;
+
+        let mut pushed = 0;
+        for commit in stack.into_iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            let Ok(prepared) = self.git.get_local_branch_tip(&branch).await else {
+                continue;
+            };
+            let revision = commit.commit_id.0.clone();
+            let commit =
+                CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+            match &commit.pr_tip {
+                // Already pushed, e.g. by a run that failed partway
+                Some(pr_tip) if *pr_tip == prepared => {}
+                Some(_) => {
+                    self.push_pr_update(&revision, &commit, &prepared, stdout)
+                        .await?;
+                    pushed += 1;
+                }
+                None => {
+                    let text = self.pr_text(&commit).await?;
+                    let owners = self
+                        .expected_reviewers(&commit.commit.commit_id, stdout)
+                        .await?;
+                    let options = CreateOptions::default();
+                    self.open_pr(&commit, &prepared, &text, &owners, &options, stdout)
+                        .await?;
+                    pushed += 1;
+                }
+            }
+            self.git.delete_local_branch(&branch).await?;
+        }
+
+        if pushed == 0 {
+            writeln!(stdout, "{}", tr!("push-nothing"))?;
+        }
+        Ok(())
+    }
+}
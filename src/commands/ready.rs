@@ -0,0 +1,52 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::notify::StackEvent;
+use crate::policy::Policy;
+
+impl App {
+    /// Mark a draft PR as ready for review.
+    ///
+    /// Reviewers should receive a stack bottom-up, so this is subject to the
+    /// `parent-ready` policy: the parent commit's PR must not be a draft.
+    pub async fn cmd_ready(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-ready", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        self.ensure_repository_writable().await?;
+
+        let commit = self.jj.get_commit(revision).await?;
+        let commit = CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+        let Some(pr) = &commit.pr else {
+            bail!(tr!("pr-branch-missing", branch = commit.pr_branch.as_str()));
+        };
+        if !pr.draft {
+            writeln!(stdout, "{}", tr!("ready-already", url = pr.url.as_str()))?;
+            return Ok(());
+        }
+
+        let policies = &self.config.policies;
+        if policies.is_enabled(Policy::ParentReady) {
+            let check = match self.gh.get_pr(&commit.base_branch).await? {
+                Some(parent) if parent.draft => Err(anyhow::anyhow!(tr!(
+                    "ready-parent-draft",
+                    url = parent.url.as_str()
+                ))),
+                _ => Ok(()),
+            };
+            policies.enforce(Policy::ParentReady, check)?;
+        }
+
+        self.gh.pr_set_draft(&commit.pr_branch, false).await?;
+        writeln!(stdout, "{}", tr!("ready-done", url = pr.url.as_str()))?;
+        self.notify(StackEvent::Ready, &pr.title, &pr.url, &commit.pr_branch)
+            .await;
+        self.warn_if_oversized(&commit.commit, stdout).await?;
+
+        Ok(())
+    }
+}
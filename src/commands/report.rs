@@ -0,0 +1,26 @@
+use anyhow::Result;
+use chrono::TimeDelta;
+
+use crate::App;
+use crate::audit;
+use crate::audit::Report;
+
+impl App {
+    /// Summarize jr's activity over the last `days` days from the audit log,
+    /// as markdown.  This only reads the local log; nothing is sent anywhere.
+    pub async fn cmd_report(&self, days: u32, stdout: &mut impl std::io::Write) -> Result<()> {
+        let path = self.git.get_git_dir().await?.join(audit::FILE_NAME);
+        let log = match tokio::fs::read_to_string(&path).await {
+            Ok(log) => log,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let until = self.clock.now();
+        let since = until - TimeDelta::days(days.into());
+        let report = Report::new(&audit::parse_log(&log), since, until);
+        write!(stdout, "{}", report.to_markdown())?;
+
+        Ok(())
+    }
+}
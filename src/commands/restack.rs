@@ -2,7 +2,11 @@ use anyhow::Result;
 use anyhow::bail;
 
 use crate::App;
+use crate::audit::AuditEvent;
 use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::plan;
 
 impl App {
     /// Update a pull request in the case where (i) there are no local changes,
@@ -16,6 +20,8 @@ impl App {
     /// 2. Push to the remote PR branch named after this revision's change ID.
     /// 3. Update the pull request's base branch.
     ///
+    /// In a dry run (see [`App::is_dry_run`]), only say what would be done.
+    ///
     /// Note: The merge commit uses the Jujutsu revision's tree directly, which
     /// reflects any conflict resolutions already made in Jujutsu, rather than
     /// computing a new merge via Git's merge machinery.
@@ -24,34 +30,35 @@ impl App {
         revision: &str,
         stdout: &mut impl std::io::Write,
     ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-restack", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        self.ensure_repository_writable().await?;
         self.check_parent_prs_up_to_date(revision).await?;
 
         let commit = self.jj.get_commit(revision).await?;
         let commit = CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
 
-        let Some(pr_tip) = commit.pr_tip else {
-            bail!(
-                "PR branch {} does not exist. Use 'jr create' to create a new PR.",
-                commit.pr_branch
-            );
+        let Some(pr_tip) = commit.pr_tip.clone() else {
+            bail!(tr!("pr-branch-missing", branch = commit.pr_branch.as_str()));
         };
 
         if !self.gh.pr_is_open(&commit.pr_branch).await? {
-            bail!(
-                "No open PR found for branch {}. The PR may have been closed or merged.",
-                commit.pr_branch
-            );
+            bail!(tr!("pr-not-open", branch = commit.pr_branch.as_str()));
         }
+        self.check_pr_tip_is_mine(&pr_tip, &commit.pr_branch)
+            .await?;
 
-        if commit.commit_diff_norm != commit.pr_diff_norm.expect("pr branch exists") {
-            bail!(concat!(
-                "Cannot restack: commit has local changes.\n",
-                "Use 'jr update -m \"<message>\"' to update with your changes."
-            ));
+        if commit.commit_diff_norm != *commit.pr_diff_norm.as_ref().expect("pr branch exists") {
+            bail!(tr!("restack-local-changes"));
         }
 
         if commit.pr_contains_base {
-            bail!("Base hasn't changed; no need to restack");
+            bail!(tr!("restack-base-unchanged"));
+        }
+        if self.is_dry_run() {
+            return self.report_dry_run_push(&commit, stdout);
         }
 
         let tree = self.git.get_tree(&commit.commit.commit_id).await?;
@@ -65,15 +72,69 @@ impl App {
             )
             .await?;
 
-        self.git
-            .push_commit_to_branch(&new_commit, &commit.pr_branch)
+        self.push_pr_branch(&new_commit, &commit.commit, &commit.pr_branch)
             .await?;
 
         let pr_url = self
             .gh
             .pr_edit(&commit.pr_branch, &commit.base_branch)
             .await?;
-        writeln!(stdout, "Updated PR: {}", pr_url)?;
+        writeln!(stdout, "{}", tr!("pr-updated", url = pr_url.as_str()))?;
+        self.warn_if_unmergeable(&commit.pr_branch, stdout).await?;
+        self.record_audit(AuditEvent::Restacked, &commit.pr_branch, &pr_url)
+            .await;
+        self.update_stack_comments(revision).await;
+
+        Ok(())
+    }
+
+    /// Restack every PR in the stack containing a revision that needs it,
+    /// from the bottom up, so each is restacked on its already-restacked
+    /// parent.  Stops at the first commit with changes not yet in its PR.
+    pub async fn cmd_restack_all(
+        &self,
+        revision: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-restack", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+
+        let mut restacked = 0;
+        for commit in self.get_stack(revision).await?.into_iter().rev() {
+            let commit_id = commit.commit_id.0.clone();
+            let commit =
+                CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+            match commit.status() {
+                // A dry run doesn't restack the PRs below, so this one isn't
+                // behind them yet
+                SyncStatus::Synced if self.is_dry_run() && restacked > 0 => {
+                    let message = tr!(
+                        "dry-run-push-merge",
+                        branch = commit.pr_branch.as_str(),
+                        base = commit.base_branch.as_str()
+                    );
+                    writeln!(stdout, "{message}")?;
+                    self.plan_push(plan::UPDATE_REQUESTS);
+                    restacked += 1;
+                }
+                SyncStatus::Synced => {}
+                SyncStatus::Restack => {
+                    self.cmd_restack(&commit_id, stdout).await?;
+                    restacked += 1;
+                }
+                SyncStatus::Changed => {
+                    bail!(tr!("restack-all-changed", commit = commit_id.as_str()))
+                }
+                SyncStatus::Unknown => {
+                    bail!(tr!("restack-all-no-pr", commit = commit_id.as_str()))
+                }
+            }
+        }
+        if restacked == 0 {
+            writeln!(stdout, "{}", tr!("restack-all-none"))?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::App;
+use crate::i18n::tr;
+use crate::model::PullRequest;
+use crate::model::ReviewDecision;
+use crate::review;
+use crate::title;
+
+impl App {
+    /// Show someone else's stack, from its top PR down, with each PR's review
+    /// status and a suggested review order.
+    ///
+    /// This only uses the GitHub API, so it works without the author's
+    /// Jujutsu repo.
+    pub async fn cmd_review_stack(
+        &self,
+        reference: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let (repo, number) = review::parse_pr_reference(reference, &self.gh.host().name)?;
+        if let Some(repo) = repo
+            && !repo.eq_ignore_ascii_case(&self.gh.full_name())
+        {
+            bail!(tr!(
+                "review-stack-other-repo",
+                repo = repo,
+                this = self.gh.full_name()
+            ));
+        }
+
+        // Follow base branches down to the bottom of the stack
+        let default_branch = self.gh.repository().await?.default_branch;
+        let mut prs = vec![self.gh.get_pr_by_number(number).await?];
+        let mut seen = HashSet::from([number]);
+        loop {
+            let base = &prs.last().expect("not empty").base.name;
+            if *base == default_branch {
+                break;
+            }
+            match self.gh.get_pr(base).await? {
+                Some(parent) if seen.insert(parent.number) => {
+                    prs.push(self.gh.get_pr_by_number(parent.number).await?);
+                }
+                _ => break,
+            }
+        }
+        prs.reverse();
+
+        writeln!(
+            stdout,
+            "{}",
+            tr!("review-stack-header", count = prs.len(), number = number)
+        )?;
+        for pr in prs.iter().rev() {
+            let behind = if pr.is_open() {
+                self.gh.commits_behind(&pr.base.name, &pr.head.sha).await?
+            } else {
+                0
+            };
+            let number = format!("#{}", pr.number);
+            writeln!(
+                stdout,
+                "{} {}",
+                number.cyan(),
+                title::display(&pr.title).white()
+            )?;
+            let notes = review_notes(pr, behind).join(", ");
+            writeln!(stdout, "{}", format!("  {} ({})", pr.url, notes).dimmed())?;
+        }
+
+        let order = review::review_order(&prs);
+        if order.is_empty() {
+            writeln!(stdout, "{}", tr!("review-order-none"))?;
+        } else {
+            let order = order
+                .iter()
+                .map(|number| format!("#{number}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(stdout, "{}", tr!("review-order", prs = order))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describe a PR's review state, and how far behind its base branch it is.
+fn review_notes(pr: &PullRequest, behind: u64) -> Vec<String> {
+    let mut notes = vec![];
+    if !pr.is_open() {
+        notes.push(tr!("review-state-closed"));
+    } else if pr.draft {
+        notes.push(tr!("review-state-draft"));
+    }
+    notes.push(match pr.review_decision {
+        Some(ReviewDecision::Approved) => tr!("review-state-approved"),
+        Some(ReviewDecision::ChangesRequested) => tr!("review-state-changes-requested"),
+        Some(ReviewDecision::ReviewRequired) | None => tr!("review-state-review-required"),
+    });
+    if behind > 0 {
+        notes.push(tr!("review-state-behind", count = behind));
+    }
+    notes
+}
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::debug;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncWriteExt as _;
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::Mutex;
+use tokio::sync::Notify;
+
+use crate::App;
+use crate::commands::create::CreateOptions;
+use crate::commands::submit::SubmitOptions;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::rpc;
+use crate::rpc::DiffResult;
+use crate::rpc::Method;
+use crate::rpc::OperationResult;
+use crate::rpc::Response;
+use crate::rpc::StackStatus;
+use crate::stack::Stack;
+
+/// State shared by a server's connections.
+#[derive(Default)]
+struct Server {
+    /// Held while performing an operation, so that they don't interleave.
+    lock: Mutex<()>,
+    /// Notified when an operation changes PRs, so that event streams send
+    /// the new status straight away.
+    changed: Notify,
+    /// Clients must send this as a bearer token (see [`rpc::authorize`]).
+    token: String,
+}
+
+impl App {
+    /// Serve jr's operations over JSON-RPC on a local HTTP port (see
+    /// [`rpc`]), so that editor plugins can drive jr without starting a
+    /// process per call.  Clients authenticate with a token printed at
+    /// startup.
+    ///
+    /// Cached PRs are kept between requests, and dropped after an operation
+    /// changes PRs.  Event streams send the status of the working copy's
+    /// stack whenever it changes, checking every `interval` seconds.
+    pub async fn cmd_serve(
+        self: Arc<Self>,
+        port: u16,
+        interval: u64,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        // Output is returned to clients, not shown in a terminal
+        colored::control::set_override(false);
+
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let token = rpc::generate_token()?;
+        writeln!(stdout, "{}", tr!("serve-listening", url = url))?;
+        writeln!(stdout, "{}", tr!("serve-token", token = token.as_str()))?;
+        stdout.flush()?;
+
+        let server = Arc::new(Server {
+            token,
+            ..Server::default()
+        });
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let app = self.clone();
+            let server = server.clone();
+            tokio::spawn(async move {
+                if let Err(err) = app.serve_connection(stream, &server, interval).await {
+                    debug!("Connection failed: {err:#}");
+                }
+            });
+        }
+    }
+
+    async fn serve_connection(
+        &self,
+        stream: TcpStream,
+        server: &Server,
+        interval: u64,
+    ) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let Some(request) = rpc::read_http_request(&mut BufReader::new(reader)).await? else {
+            return Ok(());
+        };
+
+        if let Err(status) = rpc::authorize(&request, &server.token) {
+            let response = rpc::http_response(status, "text/plain", &format!("{status}\n"));
+            writer.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+
+        let response = match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/") => {
+                let response = match rpc::parse_request(&request.body) {
+                    Ok((id, method)) => {
+                        let is_mutating = method.is_mutating();
+                        let result = {
+                            let _guard = server.lock.lock().await;
+                            self.call(method).await
+                        };
+                        if is_mutating {
+                            server.changed.notify_waiters();
+                        }
+                        match result {
+                            Ok(result) => Response::success(id, result),
+                            Err(err) => Response::error(id, rpc::SERVER_ERROR, format!("{err:#}")),
+                        }
+                    }
+                    Err(response) => response,
+                };
+                let body = serde_json::to_string(&response)?;
+                rpc::http_response("200 OK", "application/json", &body)
+            }
+            ("GET", "/events") => return self.stream_events(writer, server, interval).await,
+            _ => rpc::http_response("404 Not Found", "text/plain", "Not found\n"),
+        };
+        writer.write_all(response.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Perform an operation, returning its result as JSON.
+    pub async fn call(&self, method: Method) -> Result<Value> {
+        let mut output = vec![];
+        match method {
+            Method::Status(params) => {
+                if params.refresh {
+                    self.gh.clear_cache();
+                }
+                return Ok(serde_json::to_value(
+                    self.stack_status(&params.revision).await?,
+                )?);
+            }
+            Method::Pr(params) => {
+                let commit = self.get_commit_info(&params.revision).await?;
+                return Ok(serde_json::to_value(commit.pr)?);
+            }
+            Method::Diff(params) => {
+                let commit = self.get_commit_info(&params.revision).await?;
+                return Ok(serde_json::to_value(DiffResult {
+                    commit_diff: commit.commit_diff,
+                    pr_diff: commit.pr_diff,
+                })?);
+            }
+            Method::Create(params) => {
+                let options = CreateOptions::default();
+                let result = self
+                    .cmd_create(&params.revision, &options, &mut output)
+                    .await;
+                self.gh.clear_cache();
+                result?;
+            }
+            Method::Update(params) => {
+                let result = self
+                    .cmd_update(&params.revision, Some(&params.message), false, &mut output)
+                    .await;
+                self.gh.clear_cache();
+                result?;
+            }
+            Method::Restack(params) => {
+                let result = self.cmd_restack(&params.revision, &mut output).await;
+                self.gh.clear_cache();
+                result?;
+            }
+            Method::Submit(params) => {
+                let options = SubmitOptions {
+                    message: Some(params.message.clone()),
+                    ..Default::default()
+                };
+                let result = self
+                    .cmd_submit(&params.revision, &options, &mut output)
+                    .await;
+                self.gh.clear_cache();
+                result?;
+            }
+        }
+        let output = String::from_utf8_lossy(&output).into_owned();
+        Ok(serde_json::to_value(OperationResult { output })?)
+    }
+
+    /// Get the status of the stack containing a revision.
+    pub async fn stack_status(&self, revision: &str) -> Result<StackStatus> {
+        if self.is_on_trunk(revision).await? {
+            return Ok(StackStatus { commits: vec![] });
+        }
+        let commits = self.get_stack(revision).await?;
+        let stack = Stack::load(commits, &self.config, &self.jj, &self.gh, &self.git).await?;
+        Ok(StackStatus::new(&stack))
+    }
+
+    async fn get_commit_info(&self, revision: &str) -> Result<CommitInfo> {
+        let commit = self.jj.get_commit(revision).await?;
+        CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await
+    }
+
+    /// Send the working copy's stack status as a server-sent event whenever
+    /// it changes, until the client disconnects.
+    async fn stream_events(
+        &self,
+        mut writer: OwnedWriteHalf,
+        server: &Server,
+        interval: u64,
+    ) -> Result<()> {
+        let headers =
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n";
+        writer.write_all(headers.as_bytes()).await?;
+
+        let mut last = None;
+        loop {
+            let status = {
+                let _guard = server.lock.lock().await;
+                self.gh.clear_cache();
+                self.stack_status("@").await
+            };
+            let event = match status {
+                Ok(status) => serde_json::to_string(&status)?,
+                Err(err) => json!({ "error": format!("{err:#}") }).to_string(),
+            };
+            if last.as_ref() != Some(&event) {
+                writer
+                    .write_all(format!("data: {event}\n\n").as_bytes())
+                    .await?;
+                last = Some(event);
+            }
+
+            tokio::select! {
+                _ = self.clock.sleep(Duration::from_secs(interval)) => {}
+                _ = server.changed.notified() => {}
+            }
+        }
+    }
+}
@@ -1,86 +1,515 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::time::Duration;
+
 use anyhow::Result;
 use colored::Colorize;
-use futures_util::future::try_join_all;
-use log::warn;
 
 use crate::App;
+use crate::clients::jujutsu::JujutsuChangeId;
+use crate::clients::jujutsu::JujutsuCommit;
 use crate::commit::CommitInfo;
 use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+use crate::model::PullRequest;
+use crate::model::TimelineEvent;
+use crate::stack;
+use crate::stack::Stack;
+use crate::stack::group_branches;
+use crate::title;
+
+/// Shown in place of the sync status of a divergent change.
+const DIVERGENT_SYMBOL: &str = "⑂";
+
+/// Marks the lines of a watched status that changed since it was last drawn.
+const CHANGED_SYMBOL: &str = "▌";
+
+/// Options for `jr status`.
+#[derive(Debug, Default)]
+pub struct StatusOptions {
+    /// Only check and show this many commits from the top of the stack.
+    pub depth: Option<usize>,
+    /// Only check and show commits from the top of the stack that were
+    /// modified after this date or descend from this revision.
+    pub since: Option<String>,
+    /// Check each PR's timeline for changes made by someone else, e.g. a base
+    /// branch retargeted on the web.
+    pub verbose: bool,
+    /// Look up every PR again, rather than reusing those last seen while
+    /// their branches are unchanged.
+    pub refresh: bool,
+    /// Show every stack in the repository, not just the current one.
+    pub all: bool,
+    /// Redraw the status every this many seconds.
+    pub watch: Option<u64>,
+}
+
+/// What's known of the commits of a branch below those shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Hidden {
+    /// How many commits aren't shown.
+    pub count: usize,
+    /// The effective status of the top one, if it affects the commits shown
+    /// (see [`stack::top_status`]).
+    pub top: Option<SyncStatus>,
+}
 
 impl App {
-    pub async fn cmd_status(&self, stdout: &mut impl std::io::Write) -> Result<()> {
-        // Get stack commits
-        let heads = self.jj.get_stack_heads("@").await?;
-        let commits = if heads.is_empty() {
-            // Current commit is on trunk
-            vec![]
-        } else if heads.len() == 1 {
-            let head_commit_id = &heads[0].commit_id.0;
-            self.jj.get_stack_ancestors(head_commit_id).await?
+    /// Show the sync status of each commit in the stack, from the top down.
+    /// If the stack branches above the working copy, each branch is shown,
+    /// with the commits they share shown once.
+    ///
+    /// With a depth limit or `--since` filter, only the top commits of each
+    /// branch are shown, and commits further down are summarized in a footer.
+    /// They're checked from the top down only until the statuses of the
+    /// commits shown are settled, i.e. down to the first commit that isn't
+    /// in sync, or to trunk if they all are.
+    ///
+    /// With `--all`, every stack of mutable commits is shown this way, each
+    /// under a header, whether or not the working copy is in it.
+    ///
+    /// With `watch`, the screen is cleared and the status redrawn at that
+    /// interval, with every PR looked up again and the lines that changed
+    /// marked.  Failures are shown in place of the status, rather than
+    /// ending the watch.
+    pub async fn cmd_status(
+        &self,
+        options: &StatusOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let Some(interval) = options.watch else {
+            return self.write_current_status(options, stdout).await;
+        };
+        let mut previous: Option<String> = None;
+        loop {
+            let mut out = vec![];
+            if let Err(err) = self.write_current_status(options, &mut out).await {
+                writeln!(out, "{}", format!("{err:#}").red())?;
+            }
+            let out = String::from_utf8_lossy(&out).into_owned();
+            let time = self.clock.now().with_timezone(&chrono::Local);
+            let header = tr!(
+                "status-watch-header",
+                interval = interval,
+                time = time.format("%H:%M:%S").to_string()
+            );
+            write!(stdout, "\x1b[2J\x1b[H")?;
+            writeln!(stdout, "{}", header.dimmed())?;
+            writeln!(stdout)?;
+            write_highlighted(previous.as_deref(), &out, stdout)?;
+            stdout.flush()?;
+            previous = Some(out);
+
+            self.clock.sleep(Duration::from_secs(interval)).await;
+            self.gh.clear_cache();
+        }
+    }
+
+    /// Write the status once, for [`Self::cmd_status`].
+    async fn write_current_status(
+        &self,
+        options: &StatusOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let heads = if options.all {
+            self.jj.get_all_stack_heads().await?
+        } else if self.is_on_trunk("@").await? {
+            writeln!(stdout, "{}", tr!("status-on-trunk"))?;
+            return Ok(());
         } else {
-            warn!("Warning: Multiple stack heads detected. Showing stack from rev to trunk.");
-            self.jj.get_stack_ancestors("@").await?
+            self.jj.get_stack_heads("@").await?
         };
+        if heads.is_empty() {
+            writeln!(stdout, "{}", tr!("status-no-stacks"))?;
+            return Ok(());
+        }
 
-        // Build CommitInfo for each commit
-        let commit_futures = commits
-            .into_iter()
-            .map(|commit| CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git));
-        let commit_infos = try_join_all(commit_futures).await?;
-
-        // Calculate sync statuses with propagation from parent to child
-        // Iterate from parent to child (oldest to youngest)
-        let commits_rev = commit_infos.iter().rev().collect::<Vec<_>>();
-        let mut statuses: Vec<SyncStatus> = vec![];
-        let mut restack = false;
-
-        for commit_info in commits_rev.iter() {
-            let status = commit_info.status();
-
-            // If any ancestor needs restacking, all descendants need restacking
-            match status {
-                SyncStatus::Unknown | SyncStatus::Changed | SyncStatus::Restack => {
-                    restack = true;
-                    statuses.push(status);
-                }
-                SyncStatus::Synced => {
-                    if restack {
-                        statuses.push(SyncStatus::Restack);
-                    } else {
-                        statuses.push(SyncStatus::Synced);
-                    }
+        // Get the commits of each branch, from its head to trunk, and which
+        // changes it has before truncating, to tell which branches are one
+        // stack
+        let branches = heads.iter().map(async |head| {
+            let mut commits = self.jj.get_stack_ancestors(&head.commit_id.0).await?;
+            let changes = commits
+                .iter()
+                .map(|commit| commit.change_id.clone())
+                .collect::<HashSet<_>>();
+            let below = self.truncate_stack(&mut commits, options).await?;
+            anyhow::Ok((commits, below, changes))
+        });
+        let branches = try_join_limited(branches, self.config.jobs()).await?;
+        let stacks = if options.all {
+            let changes = branches
+                .iter()
+                .map(|(_, _, changes)| changes.clone())
+                .collect::<Vec<_>>();
+            group_branches(&changes)
+        } else {
+            vec![(0..branches.len()).collect()]
+        };
+
+        // Load each commit once, however many branches share it
+        let mut unique: Vec<JujutsuCommit> = vec![];
+        for commit in branches.iter().flat_map(|(commits, _, _)| commits) {
+            if !unique
+                .iter()
+                .any(|other| other.change_id == commit.change_id)
+            {
+                unique.push(commit.clone());
+            }
+        }
+        // Reuse the PRs last seen, while their branches haven't moved
+        let mut state = self.load_state().await;
+        if !options.refresh && options.watch.is_none() {
+            let tips = unique.iter().map(async |commit| {
+                let branch =
+                    CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+                anyhow::Ok(self.git.get_pr_branch_tip(&branch).await.ok())
+            });
+            let tips = try_join_limited(tips, self.config.jobs()).await?;
+            for (commit, tip) in unique.iter().zip(tips) {
+                if let Some(cached) = tip.and_then(|tip| state.fresh(&commit.change_id, &tip)) {
+                    self.gh.seed_pr(cached.pr.clone(), cached.diff.clone());
                 }
             }
         }
+        let (loaded, current_commit, repository) = tokio::try_join!(
+            Stack::load(unique, &self.config, &self.jj, &self.gh, &self.git),
+            self.jj.get_commit("@"),
+            self.gh.repository(),
+        )?;
+        let branches = branches.into_iter().map(async |(commits, below, _)| {
+            let commits = commits
+                .iter()
+                .rev()
+                .map(|commit| {
+                    loaded
+                        .commits
+                        .iter()
+                        .find(|info| info.commit.change_id == commit.change_id)
+                        .expect("every commit is loaded")
+                        .clone()
+                })
+                .collect::<Vec<_>>();
+            let bottom = commits.first().expect("stack isn't empty");
+            let hidden = self.check_hidden(bottom, below).await?;
+            anyhow::Ok((Stack::new(commits), hidden))
+        });
+        let branches = try_join_limited(branches, self.config.jobs()).await?;
+
+        state.record(&loaded);
+        self.save_state(&state).await;
+
+        if repository.is_read_only() {
+            let note = tr!("status-read-only", repo = self.gh.full_name());
+            writeln!(stdout, "{}", note.yellow())?;
+        }
+        // Not worth failing the status over
+        let change_ids = loaded.commits.iter().map(|commit| &commit.commit.change_id);
+        let duplicates = self.count_jj_push_duplicates(change_ids).await.unwrap_or(0);
+        if duplicates > 0 {
+            let note = tr!("status-jj-push-duplicates", count = duplicates);
+            writeln!(stdout, "{}", note.yellow())?;
+        }
+
+        let external_changes = if options.verbose {
+            self.external_changes(&loaded).await?
+        } else {
+            HashMap::new()
+        };
 
-        // Reverse statuses to match original commit order (child to parent)
-        statuses.reverse();
-
-        let current_commit = self.jj.get_commit("@").await?;
-
-        for (commit_info, status) in commit_infos.iter().zip(statuses.iter()) {
-            let branch = &commit_info.pr_branch;
-            let pr_url_result = self.gh.pr_url(branch).await;
-
-            // Display status symbol + abbreviated change ID (cyan) + title (white) on first line
-            let abbreviated_change_id = commit_info.short_id();
-            let change_id_colored = abbreviated_change_id.cyan();
-            let commit_title = commit_info.commit.message.title.as_deref().unwrap_or("");
-            let is_current = commit_info.commit.change_id == current_commit.change_id;
-            let commit_title = if is_current {
-                commit_title.white().bold()
-            } else {
-                commit_title.white()
-            };
-            let out = format!("{} {} {}", status, change_id_colored, commit_title);
-            writeln!(stdout, "{}", out.trim_end())?;
-
-            // Display URL on second line if PR exists (dimmed to be less prominent)
-            if let Ok(Some(pr_url)) = pr_url_result {
-                let url_line = format!("  {}", pr_url);
-                writeln!(stdout, "{}", url_line.dimmed())?;
+        if !options.all {
+            return write_forest(
+                &branches,
+                &current_commit.change_id,
+                &external_changes,
+                stdout,
+            );
+        }
+        for (i, stack) in stacks.iter().enumerate() {
+            let branches = stack
+                .iter()
+                .map(|&branch| branches[branch].clone())
+                .collect::<Vec<_>>();
+            let bottom = branches[0].0.commits.first().expect("stack isn't empty");
+            let header = tr!(
+                "status-stack-header",
+                number = i + 1,
+                count = stacks.len(),
+                title = title::display(bottom.commit.message.title.as_deref().unwrap_or(""))
+            );
+            if i > 0 {
+                writeln!(stdout)?;
             }
+            writeln!(stdout, "{}", header.bold())?;
+            write_forest(
+                &branches,
+                &current_commit.change_id,
+                &external_changes,
+                stdout,
+            )?;
         }
         Ok(())
     }
+
+    /// Only keep the top of a stack's commits (ordered from child to parent)
+    /// if requested, returning those dropped.
+    async fn truncate_stack(
+        &self,
+        commits: &mut Vec<JujutsuCommit>,
+        options: &StatusOptions,
+    ) -> Result<Vec<JujutsuCommit>> {
+        let total = commits.clone();
+        if let Some(since) = &options.since {
+            let head = &commits.first().expect("stack isn't empty").commit_id.0;
+            let recent = self.jj.get_stack_ancestors_since(head, since).await?;
+            let recent = commits
+                .iter()
+                .take_while(|commit| recent.iter().any(|r| r.change_id == commit.change_id))
+                .count();
+            commits.truncate(recent);
+        }
+        if let Some(depth) = options.depth {
+            commits.truncate(depth);
+        }
+        Ok(total[commits.len()..].to_vec())
+    }
+
+    /// Check the commits below those shown (ordered from child to parent),
+    /// from the top down, only as far as the effective status of the bottom
+    /// commit shown depends on them.
+    async fn check_hidden(&self, bottom: &CommitInfo, below: Vec<JujutsuCommit>) -> Result<Hidden> {
+        let count = below.len();
+        if count == 0 || bottom.status().is_conclusive() {
+            return Ok(Hidden { count, top: None });
+        }
+        let mut own = vec![];
+        for batch in below.chunks(self.config.jobs()) {
+            let loaded =
+                Stack::load(batch.to_vec(), &self.config, &self.jj, &self.gh, &self.git).await?;
+            own.extend(loaded.commits.iter().rev().map(CommitInfo::status));
+            if let Some(top) = stack::top_status(&own, false) {
+                return Ok(Hidden {
+                    count,
+                    top: Some(top),
+                });
+            }
+        }
+        Ok(Hidden {
+            count,
+            top: stack::top_status(&own, true),
+        })
+    }
+
+    /// Find the changes someone else made to each PR in a stack, keyed by PR
+    /// number.
+    async fn external_changes(&self, stack: &Stack) -> Result<HashMap<u64, Vec<TimelineEvent>>> {
+        let me = self.gh.current_user().await?;
+        let prs = stack
+            .commits
+            .iter()
+            .filter_map(|commit| commit.pr.as_ref())
+            .collect::<Vec<_>>();
+        let timelines = prs.iter().map(|pr| self.gh.pr_timeline(pr.number));
+        let timelines = try_join_limited(timelines, self.config.jobs()).await?;
+        Ok(prs
+            .iter()
+            .zip(timelines)
+            .map(|(pr, events)| {
+                let events = events
+                    .into_iter()
+                    .filter(|event| event.is_external_change(&me))
+                    .collect();
+                (pr.number, events)
+            })
+            .collect())
+    }
+}
+
+/// Write the status of each branch of a branching stack, given with what's
+/// known of the commits hidden below each.  The first branch is written in full
+/// as by [`write_status`], and the others above it, each down to where it
+/// branches off a commit already written.
+pub fn write_forest(
+    branches: &[(Stack, Hidden)],
+    current_change_id: &JujutsuChangeId,
+    external_changes: &HashMap<u64, Vec<TimelineEvent>>,
+    stdout: &mut impl std::io::Write,
+) -> Result<()> {
+    let Some(((main, hidden), others)) = branches.split_first() else {
+        return Ok(());
+    };
+    let mut written = main
+        .commits
+        .iter()
+        .map(|commit| &commit.commit.change_id)
+        .collect::<HashSet<_>>();
+    for (stack, hidden) in others {
+        let statuses = stack.statuses_after(hidden.top.unwrap_or(SyncStatus::Synced));
+        let own = stack
+            .commits
+            .iter()
+            .position(|commit| !written.contains(&commit.commit.change_id))
+            .unwrap_or(stack.commits.len());
+        for i in (own..stack.commits.len()).rev() {
+            let parent_pr = i.checked_sub(1).and_then(|i| stack.commits[i].pr.as_ref());
+            write_commit(
+                &stack.commits[i],
+                statuses[i],
+                parent_pr,
+                current_change_id,
+                external_changes,
+                stdout,
+            )?;
+            written.insert(&stack.commits[i].commit.change_id);
+        }
+        if let Some(fork) = own.checked_sub(1).map(|i| &stack.commits[i]) {
+            let note = tr!("status-branches-from", commit = fork.short_id());
+            writeln!(stdout, "  {}", note.dimmed())?;
+        }
+    }
+    write_status(main, current_change_id, *hidden, external_changes, stdout)
+}
+
+/// Write a status redrawn by `jr status --watch`, marking each line that
+/// isn't in the previous drawing, if there was one.
+pub fn write_highlighted(
+    previous: Option<&str>,
+    current: &str,
+    stdout: &mut impl std::io::Write,
+) -> Result<()> {
+    for line in current.lines() {
+        let changed = previous.is_some_and(|previous| !previous.lines().any(|old| old == line));
+        if changed {
+            writeln!(stdout, "{} {line}", CHANGED_SYMBOL.yellow())?;
+        } else {
+            writeln!(stdout, "  {line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the status of each commit in a stack, from the top down, followed
+/// by a footer summarizing the `hidden` commits below it, if any.  Changes
+/// someone else made to a PR are listed under it.
+pub fn write_status(
+    stack: &Stack,
+    current_change_id: &JujutsuChangeId,
+    hidden: Hidden,
+    external_changes: &HashMap<u64, Vec<TimelineEvent>>,
+    stdout: &mut impl std::io::Write,
+) -> Result<()> {
+    let statuses = stack.statuses_after(hidden.top.unwrap_or(SyncStatus::Synced));
+    let parent_prs = std::iter::once(None)
+        .chain(stack.commits.iter().map(|commit| commit.pr.as_ref()))
+        .collect::<Vec<_>>();
+    // Show the stack from the top down
+    for ((commit_info, status), parent_pr) in
+        stack.commits.iter().zip(statuses).zip(parent_prs).rev()
+    {
+        write_commit(
+            commit_info,
+            status,
+            parent_pr,
+            current_change_id,
+            external_changes,
+            stdout,
+        )?;
+    }
+
+    if hidden.count > 0 {
+        let footer = match hidden.top {
+            None => tr!("status-more-below", count = hidden.count),
+            Some(SyncStatus::Synced) => tr!("status-more-below-synced", count = hidden.count),
+            Some(_) => tr!("status-more-below-out-of-sync", count = hidden.count),
+        };
+        writeln!(stdout, "{}", footer.dimmed())?;
+    }
+    Ok(())
+}
+
+/// Write the status of a commit, given its effective status and its parent's
+/// PR.
+fn write_commit(
+    commit_info: &CommitInfo,
+    status: SyncStatus,
+    parent_pr: Option<&PullRequest>,
+    current_change_id: &JujutsuChangeId,
+    external_changes: &HashMap<u64, Vec<TimelineEvent>>,
+    stdout: &mut impl std::io::Write,
+) -> Result<()> {
+    // Display status symbol + abbreviated change ID (cyan) + title (white) on first line
+    let abbreviated_change_id = commit_info.short_id();
+    let change_id_colored = abbreviated_change_id.cyan();
+    let commit_title = title::display(commit_info.commit.message.title.as_deref().unwrap_or(""));
+    let is_current = &commit_info.commit.change_id == current_change_id;
+    let commit_title = if is_current {
+        commit_title.white().bold()
+    } else {
+        commit_title.white()
+    };
+    // Divergent changes get their own symbol, as their status is moot
+    let status = if commit_info.commit.divergent {
+        DIVERGENT_SYMBOL.red().to_string()
+    } else {
+        status.to_string()
+    };
+    let out = format!("{} {} {}", status, change_id_colored, commit_title);
+    writeln!(stdout, "{}", out.trim_end())?;
+    if commit_info.commit.divergent {
+        writeln!(stdout, "  {}", tr!("status-divergent").red())?;
+    }
+
+    // Display URL on second line if PR exists (dimmed to be less
+    // prominent), along with the PR's milestone if it has one, and a
+    // warning if it's ready for review before its parent
+    if let Some(pr) = &commit_info.pr {
+        let mut notes = vec![];
+        if let Some(milestone) = &pr.milestone {
+            notes.push(tr!("status-milestone", milestone = milestone.title.as_str()).dimmed());
+        }
+        if !pr.draft && parent_pr.is_some_and(|parent| parent.draft) {
+            notes.push(tr!("status-ready-before-parent").yellow());
+        }
+        if pr.is_open() && pr.mergeable == Some(false) {
+            notes.push(tr!("status-conflicts").yellow());
+        }
+        let notes = notes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        write!(stdout, "{}", format!("  {}", pr.url).dimmed())?;
+        if !notes.is_empty() {
+            write!(stdout, " {}", format!("({})", notes.join(", ")).dimmed())?;
+        }
+        writeln!(stdout)?;
+
+        for event in external_changes.get(&pr.number).into_iter().flatten() {
+            let warning = format!("! {}", describe_external_change(event, pr));
+            writeln!(stdout, "    {}", warning.yellow())?;
+        }
+    }
+    Ok(())
+}
+
+/// Describe a change someone else made to a PR.
+fn describe_external_change(event: &TimelineEvent, pr: &PullRequest) -> String {
+    let actor = event
+        .actor
+        .as_ref()
+        .map(|actor| actor.login.as_str())
+        .unwrap_or_default();
+    let date = event
+        .created_at
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    match event.event.as_str() {
+        "base_ref_changed" => tr!(
+            "status-external-base-changed",
+            actor = actor,
+            base = pr.base.name.as_str(),
+            date = date
+        ),
+        "convert_to_draft" => tr!("status-external-draft", actor = actor, date = date),
+        "ready_for_review" => tr!("status-external-ready", actor = actor, date = date),
+        _ => tr!("status-external-force-pushed", actor = actor, date = date),
+    }
 }
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::clients::jujutsu::since_revset;
+use crate::commands::create::CreateOptions;
+use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::run::Action;
+use crate::run::Run;
+use crate::run::RunCommand;
+use crate::stack;
+
+/// Options for `jr submit`.
+#[derive(Debug, Default)]
+pub struct SubmitOptions {
+    /// Commit message for the PRs updated, rather than composing one for each.
+    pub message: Option<String>,
+    /// Only submit the commits of the range that match this, a date or a
+    /// revision (see [`since_revset`]).
+    pub since: Option<String>,
+    /// Carry on with an interrupted submit (see [`crate::run`]), rather than
+    /// submitting the revset.
+    pub resume: bool,
+}
+
+impl App {
+    /// Create, update or restack the PRs of a contiguous range of a stack as
+    /// needed, from the bottom up, e.g. to get early review on the bottom of a
+    /// big stack.  Commits above the range are left alone, so show as needing
+    /// a restack afterwards.
+    ///
+    /// Changed commits are updated with the given message, or else one
+    /// composed in the user's editor for each.  Afterwards, the stack's PRs
+    /// are given any labels configured with `jr.label`.
+    ///
+    /// Each commit submitted is recorded, so that if one fails, `--resume`
+    /// picks up from there.
+    pub async fn cmd_submit(
+        &self,
+        revset: &str,
+        options: &SubmitOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let mut run = if options.resume {
+            self.load_run(RunCommand::Submit).await?
+        } else {
+            let commits = match &options.since {
+                Some(since) => {
+                    let matching = format!("({revset}) & ({})", since_revset(since));
+                    self.jj.get_stack_commits(&matching).await?
+                }
+                None => self.jj.get_stack_commits(revset).await?,
+            };
+            if commits.is_empty() {
+                if let Some(since) = &options.since {
+                    bail!(tr!(
+                        "submit-since-none",
+                        revset = revset,
+                        since = since.as_str()
+                    ));
+                }
+                bail!(tr!("pr-on-trunk-submit", revision = revset));
+            }
+            let Some(range) = stack::contiguous_range(commits) else {
+                bail!(tr!("submit-not-contiguous", revset = revset));
+            };
+            let steps = range
+                .into_iter()
+                .map(|commit| (Action::Submit, commit.change_id.0));
+            Run::new(RunCommand::Submit, steps)
+        };
+        self.save_run(&run).await;
+
+        let message = options.message.as_deref();
+        for (index, step) in run.pending() {
+            let commit = self
+                .jj
+                .get_commit(&format!("change_id({})", step.change_id))
+                .await?;
+            let commit_id = commit.commit_id.0.clone();
+            let commit =
+                CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
+            match commit.status() {
+                SyncStatus::Synced => {}
+                SyncStatus::Unknown => {
+                    self.cmd_create(&commit_id, &CreateOptions::default(), stdout)
+                        .await?
+                }
+                SyncStatus::Changed => self.cmd_update(&commit_id, message, false, stdout).await?,
+                SyncStatus::Restack => self.cmd_restack(&commit_id, stdout).await?,
+            }
+            run.complete(index);
+            self.save_run(&run).await;
+        }
+        self.clear_run().await;
+
+        if !self.config.labels.is_empty()
+            && let Some(top) = run.steps.last()
+        {
+            self.cmd_label_sync(&top.change_id, false, stdout).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::auto_ready;
+use crate::auto_ready::AutoReady;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::notify::StackEvent;
+
+/// Options for `jr sync`.
+#[derive(Debug, Default)]
+pub struct SyncOptions {
+    /// When to mark draft PRs ready, instead of `jr.autoReady`.
+    pub auto_ready: Option<AutoReady>,
+    /// Sync again every this many seconds.
+    pub watch: Option<u64>,
+}
+
+impl App {
+    /// Bring a stack up to date with GitHub:
+    ///
+    /// 1. Fetch the default branch.
+    /// 2. Abandon the commits at the bottom of the stack whose PRs have been
+    ///    merged into it, pointing the PRs based on them at the default
+    ///    branch.
+    /// 3. Rebase the rest of the stack onto trunk.
+    /// 4. With `jr.autoReady`, mark each draft PR ready for review once its
+    ///    parent PR is approved or merged.
+    ///
+    /// With `watch`, this runs until interrupted, e.g. as a daemon.
+    pub async fn cmd_sync(
+        &self,
+        revision: &str,
+        options: &SyncOptions,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let auto_ready = options
+            .auto_ready
+            .or(self.config.auto_ready)
+            .unwrap_or(AutoReady::Off);
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-sync", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        self.ensure_repository_writable().await?;
+
+        loop {
+            let mut changed = self.sync_with_trunk(revision, stdout).await?;
+            if auto_ready != AutoReady::Off && !self.is_on_trunk(revision).await? {
+                changed |= self.mark_ready(revision, auto_ready, stdout).await? > 0;
+            }
+            if !changed && options.watch.is_none() {
+                writeln!(stdout, "{}", tr!("sync-nothing"))?;
+            }
+
+            let Some(interval) = options.watch else {
+                return Ok(());
+            };
+            stdout.flush()?;
+            self.clock.sleep(Duration::from_secs(interval)).await;
+            self.gh.clear_cache();
+        }
+    }
+
+    /// Fetch the default branch, abandon the stack's merged commits and
+    /// rebase the rest onto trunk, returning whether anything changed.
+    async fn sync_with_trunk(
+        &self,
+        revision: &str,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<bool> {
+        let default_branch = &self.config.default_branch;
+        self.jj.git_fetch(&[default_branch]).await?;
+        // Commits merged without rewriting are now in trunk, so out of the
+        // stack, but squashed or rebased ones are still here
+        let stack = self.get_stack(revision).await?;
+
+        // Only PRs merged into the default branch are in trunk, and a PR
+        // can't be merged there before its parent
+        let mut merged = vec![];
+        for commit in stack.iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            match self.gh.get_pr(&branch).await? {
+                Some(pr) if pr.merged_at.is_some() && pr.base.name == *default_branch => {
+                    merged.push((commit, pr));
+                }
+                _ => break,
+            }
+        }
+
+        let mut rebased = false;
+        let trunk = self.jj.get_trunk().await?;
+        if let Some((top_merged, _)) = merged.last() {
+            if merged.len() < stack.len() {
+                let children = format!("children({})", top_merged.commit_id);
+                self.jj.rebase(&children, self.jj.trunk()).await?;
+                rebased = true;
+            }
+            for child in self.gh.open_prs().await? {
+                if merged.iter().any(|(_, pr)| child.base.name == pr.head.name) {
+                    self.gh.pr_edit(&child.head.name, default_branch).await?;
+                }
+            }
+            for (commit, pr) in &merged {
+                self.jj.abandon(&commit.commit_id.0).await?;
+                writeln!(
+                    stdout,
+                    "{}",
+                    tr!(
+                        "sync-abandoned",
+                        commit = commit.commit_id.to_string(),
+                        url = pr.url.as_str()
+                    )
+                )?;
+            }
+        } else {
+            for root in self.jj.get_stack_roots(revision).await? {
+                if !root.parent_change_ids.contains(&trunk.change_id) {
+                    self.jj.rebase(&root.commit_id.0, self.jj.trunk()).await?;
+                    rebased = true;
+                }
+            }
+        }
+
+        if rebased {
+            writeln!(
+                stdout,
+                "{}",
+                tr!("sync-rebased", commit = trunk.commit_id.to_string())
+            )?;
+            let rest = stack[..stack.len() - merged.len()]
+                .iter()
+                .map(|commit| format!("change_id({})", commit.change_id.0))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            if self.jj.has_conflicts(&rest).await? {
+                writeln!(stdout, "{}", tr!("sync-conflicts"))?;
+            }
+        }
+        Ok(rebased || !merged.is_empty())
+    }
+
+    /// Mark the stack's draft PRs ready whose parents allow it, from the
+    /// bottom up, returning how many were.
+    async fn mark_ready(
+        &self,
+        revision: &str,
+        auto_ready: AutoReady,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<usize> {
+        let mut marked = 0;
+        for commit in self.get_stack(revision).await?.iter().rev() {
+            let branch = CommitInfo::find_branch(&commit.change_id, &self.config, &self.git).await;
+            let Some(pr) = self.gh.get_pr(&branch).await? else {
+                continue;
+            };
+            if !pr.is_open() || !pr.draft || pr.base.name == self.config.default_branch {
+                continue;
+            }
+            let Some(parent) = self.gh.get_pr(&pr.base.name).await? else {
+                continue;
+            };
+            let parent = self.gh.get_pr_by_number(parent.number).await?;
+            if auto_ready::should_mark_ready(auto_ready, &pr, &parent) {
+                self.gh.pr_set_draft(&branch, false).await?;
+                writeln!(stdout, "{}", tr!("ready-done", url = pr.url.as_str()))?;
+                self.notify(StackEvent::Ready, &pr.title, &pr.url, &branch)
+                    .await;
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+}
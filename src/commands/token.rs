@@ -0,0 +1,115 @@
+use std::io::BufRead;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::App;
+use crate::clients::github::GithubClient;
+use crate::config;
+use crate::config::Config;
+use crate::config::TokenSource;
+use crate::i18n::tr;
+use crate::keychain;
+
+impl App {
+    /// Replace the GitHub token.
+    ///
+    /// The new token is checked against the repository before it's saved, to
+    /// the git config scope the current token was read from (so the old token
+    /// can't shadow it), and then read back and used to make a request.
+    pub async fn cmd_token_rotate(
+        &self,
+        scope: Option<&str>,
+        stdin: &mut impl BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        write!(stdout, "{}: ", tr!("token-prompt"))?;
+        stdout.flush()?;
+        let mut token = String::new();
+        stdin.read_line(&mut token)?;
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            bail!(tr!("token-empty"));
+        }
+
+        // Check the new token before touching the config
+        let gh = GithubClient::new(
+            token.clone(),
+            std::env::current_dir()?,
+            self.config.github_host(),
+            self.config.remote(),
+        )
+        .await?;
+        let login = gh
+            .current_user()
+            .await
+            .map_err(|err| anyhow::anyhow!(tr!("token-rejected", error = err.to_string())))?;
+        let permissions = gh.repository().await?.permissions;
+        // With a fork, PRs are opened from branches we push there instead
+        if !permissions.push && self.config.push_remote.is_none() {
+            bail!(tr!("token-no-push", repo = gh.full_name()));
+        }
+
+        let scope = match scope {
+            Some(scope) => scope.to_string(),
+            None if self.config.token_source == TokenSource::Keychain => "keychain".to_string(),
+            None => config::token_scope()?.unwrap_or_else(|| "local".to_string()),
+        };
+        let destination = if scope == "keychain" {
+            keychain::set_token(&token)?;
+            TokenSource::Keychain.to_string()
+        } else {
+            config::save_token(&token, &scope)?;
+            tr!("token-git-scope", scope = scope.as_str())
+        };
+
+        // Make sure jr now reads the new token, and that it works
+        let loaded = Config::load()?;
+        let saved = loaded.github_token.clone();
+        if saved != token {
+            if loaded.token_source == TokenSource::GitConfig {
+                let shadowed_by = config::token_scope()?.unwrap_or_default();
+                bail!(tr!(
+                    "token-shadowed",
+                    destination = destination.as_str(),
+                    shadowed_by = shadowed_by
+                ));
+            }
+            bail!(tr!(
+                "token-shadowed-by-source",
+                destination = destination.as_str(),
+                source = loaded.token_source.to_string()
+            ));
+        }
+        GithubClient::new(
+            saved,
+            std::env::current_dir()?,
+            loaded.github_host(),
+            loaded.remote(),
+        )
+        .await?
+        .current_user()
+        .await?;
+
+        writeln!(
+            stdout,
+            "{}",
+            tr!(
+                "token-rotated",
+                login = login,
+                destination = destination.as_str()
+            )
+        )?;
+        Ok(())
+    }
+
+    /// Delete the token from the system keychain.
+    pub fn cmd_token_delete(&self, stdout: &mut impl std::io::Write) -> Result<()> {
+        if keychain::delete_token()? {
+            writeln!(stdout, "{}", tr!("token-deleted"))?;
+        } else {
+            writeln!(stdout, "{}", tr!("token-delete-none"))?;
+        }
+        Ok(())
+    }
+}
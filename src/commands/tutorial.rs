@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+use tempfile::TempDir;
+use tokio::process::Command;
+
+use crate::App;
+use crate::clients::git::GitClient;
+use crate::clients::github::GithubClient;
+use crate::commands::create::CreateOptions;
+use crate::commands::status::StatusOptions;
+use crate::commit::CommitInfo;
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::model::BranchRef;
+use crate::model::PullRequest;
+use crate::model::PullRequestState;
+
+/// The sandbox's stack, from the bottom up: `parser`, with `cli` on top of
+/// it.
+const SANDBOX_STACK: [&str; 2] = ["parser", "cli"];
+
+/// Steps in the tutorial.
+const STEPS: usize = 6;
+
+impl App {
+    /// Walk through the create → status → update → restack workflow in a
+    /// sandbox: a temporary repository with a stack of two commits, whose
+    /// GitHub is an offline client (see [`GithubClient::offline`]).
+    ///
+    /// Each step runs the real command in a dry run, then does what it would
+    /// have done, playing GitHub's part, so nothing outside the sandbox is
+    /// changed.  This doesn't need jr to be configured.
+    pub async fn cmd_tutorial(
+        stdin: &mut impl BufRead,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        let dir = tempfile::Builder::new()
+            .prefix("jr-tutorial-")
+            .tempdir()
+            .context(tr!("tutorial-sandbox-failed"))?;
+        writeln!(
+            stdout,
+            "{}",
+            tr!("tutorial-building", path = dir.path().display().to_string()).dimmed()
+        )?;
+        let sandbox = Sandbox::build(dir)
+            .await
+            .context(tr!("tutorial-sandbox-failed"))?;
+
+        heading(1, &tr!("tutorial-intro-title"), stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-intro"))?;
+        sandbox.status(stdout).await?;
+        if !pause(stdin, stdout)? {
+            return Ok(());
+        }
+
+        heading(2, &tr!("tutorial-create-title"), stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-create"))?;
+        for name in SANDBOX_STACK {
+            echo(&format!("jr create -r {name}"), stdout)?;
+            let revision = sandbox.revision(name);
+            sandbox
+                .app
+                .cmd_create(revision, &CreateOptions::default(), stdout)
+                .await?;
+            sandbox.publish(name, None).await?;
+        }
+        writeln!(stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-created"))?;
+        if !pause(stdin, stdout)? {
+            return Ok(());
+        }
+
+        heading(3, &tr!("tutorial-status-title"), stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-status"))?;
+        sandbox.status(stdout).await?;
+        if !pause(stdin, stdout)? {
+            return Ok(());
+        }
+
+        heading(4, &tr!("tutorial-update-title"), stdout)?;
+        sandbox.edit("parser", "cli").await?;
+        writeln!(stdout, "{}", tr!("tutorial-update"))?;
+        sandbox.status(stdout).await?;
+        let message = "Address review comments";
+        echo(&format!("jr update -r parser -m {message:?}"), stdout)?;
+        sandbox
+            .app
+            .cmd_update(sandbox.revision("parser"), Some(message), false, stdout)
+            .await?;
+        sandbox.publish("parser", Some(message)).await?;
+        if !pause(stdin, stdout)? {
+            return Ok(());
+        }
+
+        heading(5, &tr!("tutorial-restack-title"), stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-restack"))?;
+        sandbox.status(stdout).await?;
+        echo("jr restack -r cli", stdout)?;
+        sandbox
+            .app
+            .cmd_restack(sandbox.revision("cli"), stdout)
+            .await?;
+        sandbox.publish("cli", Some("Merge")).await?;
+        writeln!(stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-restacked"))?;
+        sandbox.status(stdout).await?;
+        if !pause(stdin, stdout)? {
+            return Ok(());
+        }
+
+        heading(6, &tr!("tutorial-navigate-title"), stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-navigate"))?;
+        writeln!(stdout)?;
+        writeln!(stdout, "{}", tr!("tutorial-done"))?;
+        Ok(())
+    }
+}
+
+/// A temporary repository for the tutorial, removed when dropped, with an
+/// app whose GitHub is offline.
+struct Sandbox {
+    _dir: TempDir,
+    app: App,
+    /// Writes to the sandbox's git repository and its `origin`, for what the
+    /// app's dry runs would have done.
+    git: GitClient,
+    repo: PathBuf,
+    /// Each commit's change ID, by name.
+    change_ids: HashMap<String, String>,
+}
+
+impl Sandbox {
+    /// Build the sandbox's repository in `dir`: `dir/repo`, colocated with
+    /// jj, tracking `main` from a bare `dir/origin.git`, with the stack on
+    /// top and the working copy a new empty commit above it.
+    async fn build(dir: TempDir) -> Result<Self> {
+        let origin = dir.path().join("origin.git");
+        let repo = dir.path().join("repo");
+        tokio::fs::create_dir_all(&repo).await?;
+
+        run(
+            dir.path(),
+            "git",
+            &["init", "--bare", "-b", "main", "origin.git"],
+        )
+        .await?;
+        run(&repo, "git", &["init", "-b", "main"]).await?;
+        run(&repo, "git", &["config", "user.name", "Tutorial User"]).await?;
+        run(
+            &repo,
+            "git",
+            &["config", "user.email", "tutorial@example.com"],
+        )
+        .await?;
+        let origin_url = origin.to_str().context("Non-UTF-8 sandbox path")?;
+        run(&repo, "git", &["remote", "add", "origin", origin_url]).await?;
+        tokio::fs::write(repo.join("README.md"), "Tutorial\n").await?;
+        run(&repo, "git", &["add", "README.md"]).await?;
+        run(&repo, "git", &["commit", "-m", "Initial commit"]).await?;
+        run(&repo, "git", &["push", "-u", "origin", "main"]).await?;
+        run(&repo, "jj", &["git", "init", "--colocate"]).await?;
+        run(&repo, "jj", &["bookmark", "track", "main@origin"]).await?;
+
+        let mut change_ids = HashMap::new();
+        let mut parent = "main".to_string();
+        for name in SANDBOX_STACK {
+            run(&repo, "jj", &["new", &parent, "-m", name]).await?;
+            tokio::fs::write(repo.join(format!("{name}.txt")), format!("{name}\n")).await?;
+            let change_id = output(
+                &repo,
+                "jj",
+                &["log", "--no-graph", "-r", "@", "-T", "change_id"],
+            )
+            .await?;
+            parent = change_id.trim().to_string();
+            change_ids.insert(name.to_string(), parent.clone());
+        }
+        run(&repo, "jj", &["new", &parent]).await?;
+
+        let config = Config {
+            github_branch_prefix: "tutorial/".to_string(),
+            default_branch: "main".to_string(),
+            ..Default::default()
+        };
+        let gh = GithubClient::offline("tutorial", "repo", &config.default_branch)?;
+        let app = App::new(config, gh, repo.clone());
+        let git = GitClient::new(repo.clone()).with_remote(app.config.remote());
+        Ok(Self {
+            _dir: dir,
+            app,
+            git,
+            repo,
+            change_ids,
+        })
+    }
+
+    fn revision(&self, name: &str) -> &str {
+        &self.change_ids[name]
+    }
+
+    async fn status(&self, stdout: &mut impl std::io::Write) -> Result<()> {
+        echo("jr status", stdout)?;
+        self.app.cmd_status(&StatusOptions::default(), stdout).await
+    }
+
+    /// Change a commit's file, as if addressing review, then move `@` back
+    /// on top of `top`.
+    async fn edit(&self, name: &str, top: &str) -> Result<()> {
+        run(&self.repo, "jj", &["edit", self.revision(name)]).await?;
+        let path = self.repo.join(format!("{name}.txt"));
+        tokio::fs::write(&path, format!("{name}, reviewed\n")).await?;
+        run(&self.repo, "jj", &["new", self.revision(top)]).await?;
+        Ok(())
+    }
+
+    /// Do what creating, updating or restacking a commit's PR would have
+    /// done: push a commit with its tree to its PR branch, on top of the
+    /// branch's tip and its base if that's moved, and have GitHub's PR match.
+    async fn publish(&self, name: &str, message: Option<&str>) -> Result<()> {
+        let app = &self.app;
+        let commit = app.jj.get_commit(self.revision(name)).await?;
+        let commit = CommitInfo::new(commit, &app.config, &app.jj, &app.gh, &app.git).await?;
+        let base_tip = commit.base_tip.clone().context("Base isn't pushed")?;
+        let mut parents = commit.pr_tip.iter().collect::<Vec<_>>();
+        if commit.pr_tip.is_none() || !commit.pr_contains_base {
+            parents.push(&base_tip);
+        }
+        let message = message.map_or_else(|| commit.full_message(), str::to_string);
+        let tree = self.git.get_tree(&commit.commit.commit_id).await?;
+        let tip = self.git.commit_tree(&tree, parents, &message).await?;
+        self.git
+            .push_commit_to_branch(&tip, &commit.pr_branch)
+            .await?;
+
+        // Number PRs in the order they're created, i.e. from the bottom up
+        let number = match commit.pr {
+            Some(pr) => pr.number,
+            None => SANDBOX_STACK
+                .iter()
+                .position(|other| *other == name)
+                .map_or(0, |i| i as u64 + 1),
+        };
+        let diff = self.git.get_diff(&base_tip, &tip).await?;
+        let pr = PullRequest {
+            number,
+            node_id: format!("PR_{number}"),
+            url: format!("https://github.com/{}/pull/{number}", app.gh.full_name()),
+            state: PullRequestState::Open,
+            draft: true,
+            title: commit.commit.message.title.clone().unwrap_or_default(),
+            body: None,
+            base: BranchRef {
+                name: commit.base_branch.clone(),
+                sha: base_tip.0.clone(),
+            },
+            head: BranchRef {
+                name: commit.pr_branch.clone(),
+                sha: tip.0.clone(),
+            },
+            mergeable: None,
+            review_decision: None,
+            milestone: None,
+            user: None,
+            requested_reviewers: vec![],
+            created_at: None,
+            merged_at: None,
+            labels: vec![],
+        };
+        app.gh.seed_pr(pr, Some(diff));
+        Ok(())
+    }
+}
+
+/// Run a command in the sandbox, failing if it does.
+async fn run(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
+    output(dir, program, args).await?;
+    Ok(())
+}
+
+/// Run a command in the sandbox, returning its stdout.  jj commits as the
+/// tutorial user too.
+async fn output(dir: &Path, program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .current_dir(dir)
+        .args(args)
+        .env("JJ_USER", "Tutorial User")
+        .env("JJ_EMAIL", "tutorial@example.com")
+        .output()
+        .await
+        .with_context(|| format!("Failed to execute {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn heading(step: usize, title: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+    let heading = format!("[{step}/{STEPS}] {title}");
+    writeln!(stdout, "{}", heading.bold())?;
+    Ok(())
+}
+
+/// Show a command as if it had been typed.
+fn echo(command: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+    writeln!(stdout)?;
+    writeln!(stdout, "{}", format!("$ {command}").cyan())?;
+    Ok(())
+}
+
+/// Wait for Enter, returning whether to go on, i.e. stdin hasn't ended.
+fn pause(stdin: &mut impl BufRead, stdout: &mut impl std::io::Write) -> Result<bool> {
+    writeln!(stdout)?;
+    write!(stdout, "{}", tr!("tutorial-continue").dimmed())?;
+    stdout.flush()?;
+    let mut input = String::new();
+    let more = stdin.read_line(&mut input)? > 0;
+    writeln!(stdout)?;
+    Ok(more)
+}
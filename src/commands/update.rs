@@ -2,7 +2,11 @@ use anyhow::Result;
 use anyhow::bail;
 
 use crate::App;
+use crate::audit::AuditEvent;
+use crate::clients::git::CommitId;
 use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::plan;
 
 impl App {
     /// Update a pull request in the case where (i) there are local changes, and
@@ -18,62 +22,162 @@ impl App {
     /// 2. Push to the remote PR branch named after this revision's change ID.
     /// 3. Update the pull request's base branch.
     ///
+    /// Without a message, one is composed in the user's editor from the changes
+    /// since the PR was last pushed (see [`App::compose_message`]).
+    ///
+    /// With `no_push`, stop after step 1, pointing a local branch named like
+    /// the PR branch at the commit without contacting GitHub.  `jr push` does
+    /// the rest.
+    ///
+    /// In a dry run (see [`App::is_dry_run`]), only say what would be done.
+    ///
     /// Note: When creating a merge commit we use the Jujutsu revision's tree
     /// directly, which reflects any conflict resolutions already made in
     /// Jujutsu, rather than computing a new merge via Git's merge machinery.
     pub async fn cmd_update(
         &self,
         revision: &str,
-        message: &str,
+        message: Option<&str>,
+        no_push: bool,
         stdout: &mut impl std::io::Write,
     ) -> Result<()> {
-        self.check_parent_prs_up_to_date(revision).await?;
+        if self.is_on_trunk(revision).await? {
+            bail!(tr!("pr-on-trunk-update", revision = revision));
+        }
+        self.ensure_not_divergent(revision).await?;
+        if !no_push {
+            self.ensure_repository_writable().await?;
+            self.check_parent_prs_up_to_date(revision).await?;
+        }
 
         let commit = self.jj.get_commit(revision).await?;
         let commit = CommitInfo::new(commit, &self.config, &self.jj, &self.gh, &self.git).await?;
 
-        let Some(pr_tip) = commit.pr_tip else {
-            bail!(
-                "PR branch {} does not exist. Use 'jr create' to create a new PR.",
-                commit.pr_branch
-            );
+        let Some(pr_tip) = commit.pr_tip.clone() else {
+            bail!(tr!("pr-branch-missing", branch = commit.pr_branch.as_str()));
         };
 
-        if !self.gh.pr_is_open(&commit.pr_branch).await? {
-            bail!(
-                "No open PR found for branch {}. The PR may have been closed or merged.",
-                commit.pr_branch
-            );
+        if !no_push && !self.gh.pr_is_open(&commit.pr_branch).await? {
+            bail!(tr!("pr-not-open", branch = commit.pr_branch.as_str()));
         }
+        self.check_pr_tip_is_mine(&pr_tip, &commit.pr_branch)
+            .await?;
 
-        if commit.commit_diff_norm == commit.pr_diff_norm.expect("should be set") {
+        // Without GitHub there may be no PR diff to compare with
+        if commit
+            .pr_diff_norm
+            .as_ref()
+            .is_some_and(|pr_diff| *pr_diff == commit.commit_diff_norm)
+        {
             if commit.pr_contains_base {
-                bail!("No changes detected");
+                bail!(tr!("update-no-changes"));
             } else {
-                bail!("Commit unchanged; use 'jr restack' instead");
+                bail!(tr!("update-commit-unchanged"));
+            }
+        }
+
+        if self.is_dry_run() {
+            if no_push {
+                let branch = commit.pr_branch.as_str();
+                writeln!(stdout, "{}", tr!("dry-run-prepare", branch = branch))?;
+                return Ok(());
             }
+            return self.report_dry_run_push(&commit, stdout);
         }
 
         let parents = if !commit.pr_contains_base {
-            vec![pr_tip, commit.base_tip.expect("should be set")]
+            // A base whose PR branch was only prepared is on a local branch
+            let base_tip = if no_push && commit.base_tip.is_none() {
+                self.git.get_local_branch_tip(&commit.base_branch).await?
+            } else {
+                commit.base_tip.clone().expect("should be set")
+            };
+            vec![pr_tip.clone(), base_tip]
         } else {
-            vec![pr_tip]
+            vec![pr_tip.clone()]
+        };
+        let message = match message {
+            Some(message) => message.to_string(),
+            None => {
+                let diff = self.git.get_diff(&pr_tip, &commit.commit.commit_id).await?;
+                self.compose_message(&diff, "").await?
+            }
         };
         let tree = self.git.get_tree(&commit.commit.commit_id).await?;
         let new_commit = self
             .git
-            .commit_tree(&tree, parents.iter().collect::<Vec<_>>(), message)
+            .commit_tree(&tree, parents.iter().collect::<Vec<_>>(), &message)
             .await?;
 
-        self.git
-            .push_commit_to_branch(&new_commit, &commit.pr_branch)
+        if no_push {
+            self.git
+                .set_local_branch(&commit.pr_branch, &new_commit)
+                .await?;
+            writeln!(
+                stdout,
+                "{}",
+                tr!("pr-prepared", branch = commit.pr_branch.as_str())
+            )?;
+            return Ok(());
+        }
+        self.push_pr_update(revision, &commit, &new_commit, stdout)
+            .await
+    }
+
+    /// Say what pushing a new commit to an existing PR branch would do, for
+    /// a dry run: the commit, which merges in the base if it's moved, and
+    /// whether the PR's base changes.
+    pub(crate) fn report_dry_run_push(
+        &self,
+        commit: &CommitInfo,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        self.plan_push(plan::UPDATE_REQUESTS);
+        let (branch, base) = (commit.pr_branch.as_str(), commit.base_branch.as_str());
+        let message = if commit.pr_contains_base {
+            tr!("dry-run-push", branch = branch)
+        } else {
+            tr!("dry-run-push-merge", branch = branch, base = base)
+        };
+        writeln!(stdout, "{message}")?;
+        if let Some(pr) = &commit.pr
+            && pr.base.name != commit.base_branch
+        {
+            writeln!(
+                stdout,
+                "{}",
+                tr!(
+                    "dry-run-retarget",
+                    url = pr.url.as_str(),
+                    old = pr.base.name.as_str(),
+                    base = base
+                )
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Push a commit to an existing PR branch and retarget its PR, along with
+    /// the rest of [`App::cmd_update`]'s steps.
+    pub(crate) async fn push_pr_update(
+        &self,
+        revision: &str,
+        commit: &CommitInfo,
+        new_commit: &CommitId,
+        stdout: &mut impl std::io::Write,
+    ) -> Result<()> {
+        self.push_pr_branch(new_commit, &commit.commit, &commit.pr_branch)
             .await?;
 
         let pr_url = self
             .gh
             .pr_edit(&commit.pr_branch, &commit.base_branch)
             .await?;
-        writeln!(stdout, "Updated PR: {}", pr_url)?;
+        writeln!(stdout, "{}", tr!("pr-updated", url = pr_url.as_str()))?;
+        self.warn_if_unmergeable(&commit.pr_branch, stdout).await?;
+        self.record_audit(AuditEvent::Updated, &commit.pr_branch, &pr_url)
+            .await;
+        self.update_stack_comments(revision).await;
 
         Ok(())
     }
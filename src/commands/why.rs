@@ -0,0 +1,96 @@
+use anyhow::Result;
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::App;
+use crate::clients::jujutsu::JujutsuChangeId;
+use crate::commit::CommitInfo;
+use crate::i18n::tr;
+use crate::model::PullRequest;
+use crate::provenance::NOTES_REF;
+use crate::provenance::Provenance;
+
+impl App {
+    /// Show which jj commit a PR branch commit was synthesized from, as
+    /// recorded in its provenance note (see [`crate::provenance`]).  If
+    /// GitHub can't merge its PR, the files that conflict with the PR's base
+    /// are listed too.
+    ///
+    /// The commit can be given as any git revision, or as the name of a PR
+    /// branch for its tip.
+    pub async fn cmd_why(&self, revision: &str, stdout: &mut impl std::io::Write) -> Result<()> {
+        let branch_tip = self.git.get_pr_branch_tip(revision).await.ok();
+        let note = match self.git.get_note(NOTES_REF, revision).await? {
+            Some(note) => Some(note),
+            None => match &branch_tip {
+                Some(tip) => self.git.get_note(NOTES_REF, &tip.0).await?,
+                None => None,
+            },
+        };
+        let provenance = note.as_deref().and_then(Provenance::parse);
+        let branch = match (&branch_tip, &provenance) {
+            (Some(_), _) => Some(revision.to_string()),
+            (None, Some(provenance)) => {
+                let change_id = JujutsuChangeId(provenance.change_id.clone());
+                Some(CommitInfo::find_branch(&change_id, &self.config, &self.git).await)
+            }
+            (None, None) => None,
+        };
+        let conflicts = match &branch {
+            Some(branch) => self.pr_conflicts(branch).await?,
+            None => None,
+        };
+        if provenance.is_none() && conflicts.is_none() {
+            bail!(tr!(
+                "why-no-provenance",
+                revision = revision,
+                remote = self.git.push_remote()
+            ));
+        }
+
+        if let Some(provenance) = provenance {
+            writeln!(
+                stdout,
+                "{}",
+                tr!(
+                    "why-provenance",
+                    change = provenance.change_id.cyan().to_string(),
+                    commit = provenance.commit_id.as_str(),
+                    operation = provenance.operation.as_str()
+                )
+            )?;
+        }
+        if let Some((pr, files)) = conflicts {
+            let (url, base) = (pr.url.as_str(), pr.base.name.as_str());
+            if files.is_empty() {
+                let note = tr!("why-conflicts-not-local", url = url, base = base);
+                writeln!(stdout, "{}", note.yellow())?;
+            } else {
+                let heading = tr!("why-conflicts", url = url, base = base);
+                writeln!(stdout, "{}", heading.yellow())?;
+                for file in files {
+                    writeln!(stdout, "  {file}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// If GitHub can't merge a branch's open PR, the PR and the files that
+    /// conflict with its base, as of the last fetch.
+    async fn pr_conflicts(&self, branch: &str) -> Result<Option<(PullRequest, Vec<String>)>> {
+        let Some(pr) = self.gh.get_pr(branch).await? else {
+            return Ok(None);
+        };
+        if !pr.is_open() || self.gh.pr_mergeable(pr.number).await? != Some(false) {
+            return Ok(None);
+        }
+        let base_tip = match self.git.get_branch_tip(&pr.base.name).await {
+            Ok(tip) => tip,
+            Err(_) => self.git.get_pr_branch_tip(&pr.base.name).await?,
+        };
+        let head_tip = self.git.get_pr_branch_tip(branch).await?;
+        let files = self.git.merge_conflicts(&base_tip, &head_tip).await?;
+        Ok(Some((pr, files)))
+    }
+}
@@ -2,6 +2,8 @@ use std::fmt::Display;
 
 use anyhow::bail;
 use log::debug;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::Config;
 use crate::clients::git::CommitId;
@@ -12,11 +14,14 @@ use crate::clients::jujutsu::JujutsuClient;
 use crate::clients::jujutsu::JujutsuCommit;
 use crate::clients::jujutsu::JujutsuCommitMessage;
 use crate::diff_utils::normalize_diff;
+use crate::i18n::tr;
+use crate::model::PullRequest;
 
 /// Length of the change ID to use in GitHub branch names
 pub const GITHUB_CHANGE_ID_LENGTH: usize = 8;
 
 /// An elaborated Jujutsu commit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CommitInfo {
     pub commit: JujutsuCommit,
     /// The diff of this commit.
@@ -27,6 +32,8 @@ pub struct CommitInfo {
     pub pr_branch: String,
     /// The tip of the remote PR branch, if it exists.
     pub pr_tip: Option<CommitId>,
+    /// The PR for the PR branch, if it exists.
+    pub pr: Option<PullRequest>,
     /// The current PR diff, if it exists.
     pub pr_diff: Option<String>,
     /// The normalized PR diff, if it exists.
@@ -39,6 +46,9 @@ pub struct CommitInfo {
     pub pr_contains_base: bool,
 }
 
+/// Whether a commit is in sync with its PR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SyncStatus {
     /// Commit has no associated PR
     Unknown,
@@ -70,49 +80,65 @@ impl CommitInfo {
         gh: &GithubClient,
         git: &GitClient,
     ) -> anyhow::Result<Self> {
-        let commit_diff = git.get_commit_diff(&commit.commit_id).await?;
+        // Each lookup is a git subprocess or GitHub request, so run those
+        // that don't depend on each other at once
+        let parent_revset = format!(
+            "parents({}) & change_id({})",
+            commit.commit_id, commit.parent_change_ids[0]
+        );
+        let (commit_diff, trunk_commit, pr_branch, parent_commit) = tokio::try_join!(
+            git.get_commit_diff(&commit.commit_id),
+            jj.get_trunk(),
+            async { anyhow::Ok(Self::find_branch(&commit.change_id, config, git).await) },
+            // Select the parent by commit too, in case its change is divergent
+            jj.get_commit(&parent_revset),
+        )?;
         let commit_diff_norm = normalize_diff(&commit_diff);
-        let trunk_commit = jj.get_trunk().await?;
-        if git
-            .is_ancestor(&commit.commit_id, &trunk_commit.commit_id)
-            .await?
-        {
-            bail!(
-                "Commit {} is an ancestor of trunk; this commit is already merged.",
-                commit.commit_id
-            );
+        let parent_commit_id = parent_commit.commit_id;
+
+        let (merged, pr_tip, pr, pr_diff, (base_branch, base_tip)) = tokio::try_join!(
+            git.is_ancestor(&commit.commit_id, &trunk_commit.commit_id),
+            async { anyhow::Ok(git.get_pr_branch_tip(&pr_branch).await.ok()) },
+            async { anyhow::Ok(gh.get_pr(&pr_branch).await.ok().flatten()) },
+            async { anyhow::Ok(gh.pr_diff(&pr_branch).await.ok()) },
+            async {
+                if let Some(branch) = config.base_override(&commit.change_id) {
+                    // Set by hand with `jr pr edit-base`
+                    let base_tip = git.get_branch_tip(branch).await.ok();
+                    anyhow::Ok((branch.to_string(), base_tip))
+                } else if git
+                    .is_ancestor(&parent_commit_id, &trunk_commit.commit_id)
+                    .await?
+                {
+                    // Parent is either trunk or an ancestor of trunk; in both
+                    // cases return the default branch name from config.
+                    //
+                    // Use whatever commit we're currently branched off, not
+                    // trunk().  This is because the base branch has advanced
+                    // independently of us, so merging in trunk() *when we
+                    // haven't locally done so* risks silently dropping
+                    // conflicting changes in the base branch.
+                    anyhow::Ok((
+                        config.default_branch.clone(),
+                        Some(parent_commit_id.clone()),
+                    ))
+                } else {
+                    // Parent is in our stack
+                    let base_branch =
+                        Self::find_branch(&commit.parent_change_ids[0], config, git).await;
+                    let base_tip = git.get_pr_branch_tip(&base_branch).await.ok();
+                    anyhow::Ok((base_branch, base_tip))
+                }
+            },
+        )?;
+        if merged {
+            bail!(tr!(
+                "commit-already-merged",
+                commit = commit.commit_id.to_string()
+            ));
         }
-
-        let pr_branch = Self::branch_name(&commit.change_id, &config.github_branch_prefix);
-        let pr_tip = git.get_branch_tip(&pr_branch).await.ok();
-        let pr_diff = gh.pr_diff(&pr_branch).await.ok();
         let pr_diff_norm = pr_diff.as_ref().map(|diff| normalize_diff(diff));
 
-        let parent_change_id = &commit.parent_change_ids[0];
-        let parent_commit_id = jj.get_commit(&parent_change_id.0).await?.commit_id;
-        let (base_branch, base_tip) = if git
-            .is_ancestor(&parent_commit_id, &trunk_commit.commit_id)
-            .await?
-        {
-            // Parent is either trunk or an ancestor of trunk; in both cases
-            // return the default branch name from config.
-            let base_branch = config.default_branch.clone();
-
-            // Use whatever commit we're currently branched off, not trunk().
-            // This is because the base branch has advanced independently of us,
-            // so merging in trunk() *when we haven't locally done so* risks
-            // silently dropping conflicting changes in the base branch.
-            let base_tip = Some(jj.get_commit(&parent_commit_id.0).await?.commit_id);
-
-            (base_branch, base_tip)
-        } else {
-            // Parent is in our stack
-            let base_branch =
-                Self::branch_name(&commit.parent_change_ids[0], &config.github_branch_prefix);
-            let base_tip = git.get_branch_tip(&base_branch).await.ok();
-            (base_branch, base_tip)
-        };
-
         let mut pr_contains_base = false;
         if let Some(base_tip) = &base_tip
             && let Some(pr_tip) = &pr_tip
@@ -126,6 +152,7 @@ impl CommitInfo {
             commit_diff_norm,
             pr_branch,
             pr_tip,
+            pr,
             pr_diff,
             pr_diff_norm,
             base_branch,
@@ -162,6 +189,24 @@ impl CommitInfo {
         SyncStatus::Synced
     }
 
+    /// Find a change's PR branch.  In pairing mode this may be under a
+    /// co-author's prefix; if no branch exists yet, it's under ours.
+    pub(crate) async fn find_branch(
+        change_id: &JujutsuChangeId,
+        config: &Config,
+        git: &GitClient,
+    ) -> String {
+        if !config.pairs.is_empty() {
+            for prefix in config.branch_prefixes() {
+                let branch = Self::branch_name(change_id, prefix);
+                if git.get_pr_branch_tip(&branch).await.is_ok() {
+                    return branch;
+                }
+            }
+        }
+        Self::branch_name(change_id, &config.github_branch_prefix)
+    }
+
     fn branch_name(change_id: &JujutsuChangeId, github_branch_prefix: &str) -> String {
         format!(
             "{github_branch_prefix}{}",
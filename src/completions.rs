@@ -0,0 +1,145 @@
+//! Shell completions (see `jr completions`).
+//!
+//! Static completions are generated by clap.  With `--dynamic`, the script
+//! also completes revision arguments, by calling back into `jr
+//! revset-candidates` for the revset aliases configured for jr and jj, and
+//! jj's common revset functions.
+
+use anyhow::Result;
+use anyhow::bail;
+use clap_complete::Shell;
+
+/// The hidden command dynamic completions call for revision candidates.
+pub const CANDIDATES_COMMAND: &str = "revset-candidates";
+
+/// Options that take a revset.
+const REVISION_OPTIONS: [&str; 3] = ["-r", "--revision", "--since"];
+
+/// Common jj revset functions and symbols.  Functions that take arguments
+/// are completed up to their opening parenthesis.
+const REVSET_FUNCTIONS: [&str; 20] = [
+    "@",
+    "trunk()",
+    "mine()",
+    "mutable()",
+    "immutable()",
+    "conflicts()",
+    "empty()",
+    "all()",
+    "visible_heads()",
+    "ancestors(",
+    "descendants(",
+    "children(",
+    "parents(",
+    "heads(",
+    "roots(",
+    "bookmarks(",
+    "description(",
+    "author(",
+    "change_id(",
+    "files(",
+];
+
+/// Revision candidates, given jr's revset alias names and the output of `jj
+/// config list revset-aliases`.
+pub fn revset_candidates(jr_aliases: &[String], jj_config: &str) -> Vec<String> {
+    let mut candidates = jr_aliases.to_vec();
+    candidates.extend(jj_config.lines().filter_map(parse_jj_alias));
+    candidates.extend(REVSET_FUNCTIONS.iter().map(|function| function.to_string()));
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.clone()));
+    candidates
+}
+
+/// Parse the alias name from a line of `jj config list revset-aliases`, e.g.
+/// `revset-aliases.'stack(x)' = "..."`, completing aliases that take
+/// arguments up to their opening parenthesis.
+fn parse_jj_alias(line: &str) -> Option<String> {
+    let (key, _) = line.split_once('=')?;
+    let name = key.trim().strip_prefix("revset-aliases.")?;
+    let name = name.trim_matches(|c| c == '\'' || c == '"');
+    let name = match name.split_once('(') {
+        Some((function, "")) | Some((function, ")")) => format!("{function}()"),
+        Some((function, _)) => format!("{function}("),
+        None => name.to_string(),
+    };
+    Some(name)
+}
+
+/// Script to load after clap's completions for a shell, completing revision
+/// options with `jr revset-candidates`.
+pub fn dynamic_script(shell: Shell, bin: &str) -> Result<String> {
+    let script = match shell {
+        Shell::Bash => {
+            let options = REVISION_OPTIONS.join("|");
+            format!(
+                r#"
+_{bin}_dynamic() {{
+    case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+        {options})
+            local IFS=$'\n'
+            COMPREPLY=($(compgen -W "$({bin} {CANDIDATES_COMMAND} 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+            compopt -o nospace
+            return 0
+            ;;
+    esac
+    _{bin} "$@"
+}}
+complete -F _{bin}_dynamic -o nosort -o bashdefault -o default {bin}
+"#
+            )
+        }
+        Shell::Fish => {
+            let candidates = format!("({bin} {CANDIDATES_COMMAND} 2>/dev/null)");
+            format!(
+                "\ncomplete -c {bin} -s r -l revision -x -a '{candidates}'\n\
+                 complete -c {bin} -l since -x -a '{candidates}'\n"
+            )
+        }
+        _ => bail!("Dynamic completions are only available for bash and fish"),
+    };
+    Ok(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jj_alias() {
+        assert_eq!(
+            parse_jj_alias("revset-aliases.wip = \"mine() & empty()\""),
+            Some("wip".to_string())
+        );
+        assert_eq!(
+            parse_jj_alias("revset-aliases.'stack(x)' = \"x::\""),
+            Some("stack(".to_string())
+        );
+        assert_eq!(
+            parse_jj_alias("revset-aliases.'trunk()' = \"main@origin\""),
+            Some("trunk()".to_string())
+        );
+        assert_eq!(parse_jj_alias("ui.editor = \"vim\""), None);
+    }
+
+    #[test]
+    fn test_revset_candidates() {
+        let jj_config = "revset-aliases.'trunk()' = \"main@origin\"\nrevset-aliases.wip = \"x\"";
+        let candidates = revset_candidates(&["myStack".to_string()], jj_config);
+        assert_eq!(candidates[..3], ["myStack", "trunk()", "wip"]);
+        // Duplicates of the built-in functions are dropped
+        assert_eq!(candidates.len(), 2 + REVSET_FUNCTIONS.len());
+    }
+
+    #[test]
+    fn test_dynamic_script() {
+        let script = dynamic_script(Shell::Fish, "jr").unwrap();
+        assert!(script.contains("complete -c jr -s r -l revision -x -a '(jr revset-candidates"));
+        assert!(
+            dynamic_script(Shell::Bash, "jr")
+                .unwrap()
+                .contains("_jr \"$@\"")
+        );
+        assert!(dynamic_script(Shell::PowerShell, "jr").is_err());
+    }
+}
@@ -1,49 +1,271 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use anyhow::Context;
 use anyhow::Result;
+use clap::ValueEnum as _;
+use regex::Captures;
+use regex::Regex;
+
+use crate::auto_ready::AutoReady;
+use crate::clients::github::GithubHost;
+use crate::clients::jujutsu::JujutsuChangeId;
+use crate::config_file;
+use crate::config_file::ConfigFile;
+use crate::i18n::tr;
+use crate::jobs;
+use crate::keychain;
+use crate::merge::MergeMethod;
+use crate::policy::Policies;
+use crate::policy::Policy;
+use crate::policy::Severity;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     pub github_branch_prefix: String,
     pub github_token: String,
+    /// Where the GitHub token was found.
+    pub token_source: TokenSource,
+    /// Token for requests that change something on GitHub, if they shouldn't
+    /// use `github_token`, which is then only used to read.
+    pub github_write_token: Option<String>,
     pub default_branch: String,
+    /// Webhook URL to notify on stack events, if any.
+    pub webhook_url: Option<String>,
+    /// Payload template for webhook notifications.
+    pub webhook_template: Option<String>,
+    /// Command to validate PR branch pushes, run like a git pre-push hook.
+    pub pre_push_command: Option<String>,
+    /// Name for this repository in the branch prefix's `{repo}`, if not its
+    /// GitHub name.
+    pub repo_slug: Option<String>,
+    /// Template for new PRs' descriptions (see [`crate::template`]).
+    pub pr_template: Option<String>,
+    /// Most lines a PR may change before jr suggests splitting it.
+    pub size_budget: Option<usize>,
+    /// Days a PR waits for review before `jr ping-reviewers` nudges.
+    pub nudge_after_days: Option<u64>,
+    /// Comment template for `jr ping-reviewers`.
+    pub nudge_template: Option<String>,
+    /// Command that suggests a commit message, given a diff on stdin.
+    pub describe_command: Option<String>,
+    /// Milestone to assign to new PRs by default.
+    pub default_milestone: Option<String>,
+    /// Number of the GitHub project to add new PRs to by default.
+    pub default_project: Option<u64>,
+    /// Severity of each policy, plus any overrides for this invocation.
+    pub policies: Policies,
+    /// Remote the repository is fetched from, if not origin.
+    pub remote: Option<String>,
+    /// Remote to push PR branches to, e.g. a fork, if not `remote`.
+    pub push_remote: Option<String>,
+    /// Push provenance notes (see `jr why`) along with PR branches.
+    pub push_notes: bool,
+    /// Keep a comment on each PR in a stack listing the stack's PRs.  Off
+    /// unless enabled, since it posts to every PR in the stack.
+    pub stack_comments: bool,
+    /// Create PRs ready for review rather than as drafts, when
+    /// `jr.createDraft` is false.
+    pub create_ready: bool,
+    /// Commits or PRs elaborated at once, if not the default (see [`jobs`]).
+    pub jobs: Option<usize>,
+    /// GitHub requests in flight at once, if not the default.
+    pub github_jobs: Option<usize>,
+    /// Most commits a stack may have for jr to load it, if limited.
+    pub stack_limit: Option<usize>,
+    /// Seconds before a GitHub API request times out, if not the default.
+    pub http_timeout: Option<u64>,
+    /// Seconds between polls of GitHub for something to finish, e.g. CI.
+    pub poll_interval: Option<u64>,
+    /// PEM file of CA certificates to trust, besides the system's.
+    pub ca_bundle: Option<String>,
+    /// Hostname of a GitHub Enterprise Server, if not github.com.
+    pub github_host: Option<String>,
+    /// Base URL of the GitHub REST API, if not where the host puts it.
+    pub api_url: Option<String>,
+    /// Revset for the trunk commit, if not jj's `trunk()`.
+    pub trunk_revset: Option<String>,
+    /// When draft PRs are marked ready by `jr sync` and `jr merge`, if ever.
+    pub auto_ready: Option<AutoReady>,
+    /// How `jr merge` merges PRs, if not the first the repository allows.
+    pub merge_method: Option<MergeMethod>,
+    /// Co-authors whose stacks are shared with ours.
+    pub pairs: Vec<Pair>,
+    /// Labels every PR in a stack should carry.
+    pub labels: Vec<String>,
+    /// Users or teams to request reviews of new PRs from.
+    pub default_reviewers: Vec<String>,
+    /// Users to assign new PRs to.
+    pub default_assignees: Vec<String>,
+    /// Branches `jr cleanup` never deletes: names, or regexes matching whole
+    /// names.
+    pub protected_branches: Vec<String>,
+    /// PR bases set by `jr pr edit-base`, as (change ID, branch) pairs.
+    pub base_overrides: Vec<(String, String)>,
+    /// Revset aliases, as (name, revset) pairs.
+    pub revset_aliases: Vec<(String, String)>,
+    /// Locale for user-facing messages, from `JR_LOCALE` or `jr.locale`.
+    pub locale: Option<String>,
+    /// Directory containing `<locale>.ftl` message catalogs.
+    pub locale_dir: Option<String>,
 }
 
 impl Config {
-    /// Load config from .git/config
+    /// Load config from the config files (see [`config_file`]) and git
+    /// config
     pub fn load() -> Result<Self> {
-        let prefix_output = std::process::Command::new("git")
-            .args(["config", "--get", "jr.githubBranchPrefix"])
-            .output()?;
-
-        if !prefix_output.status.success() {
-            anyhow::bail!("Config not found in .git/config. Run 'jr init' to create one.");
-        }
-
-        let token_output = std::process::Command::new("git")
-            .args(["config", "--get", "jr.githubToken"])
-            .output()?;
-
-        if !token_output.status.success() {
-            anyhow::bail!("GitHub token not found in .git/config. Run 'jr init' to configure.");
-        }
-
-        let default_branch_output = std::process::Command::new("git")
-            .args(["config", "--get", "jr.defaultBranch"])
-            .output()?;
+        Self::load_inner(true)
+    }
 
-        if !default_branch_output.status.success() {
-            anyhow::bail!("Default branch not found in .git/config. Run 'jr init' to configure.");
-        }
+    /// Load config like [`Config::load`], but with an empty token if none is
+    /// found, for `jr token` to set one.
+    pub fn load_without_token() -> Result<Self> {
+        Self::load_inner(false)
+    }
 
-        let github_branch_prefix = String::from_utf8(prefix_output.stdout)?.trim().to_string();
-        let github_token = String::from_utf8(token_output.stdout)?.trim().to_string();
-        let default_branch = String::from_utf8(default_branch_output.stdout)?
-            .trim()
-            .to_string();
+    fn load_inner(require_token: bool) -> Result<Self> {
+        let sources = Sources::load()?;
+        // These may be set but empty, which `problems` explains
+        let Some(github_branch_prefix) = sources.get("jr.githubBranchPrefix")? else {
+            anyhow::bail!(tr!("config-not-found"));
+        };
+        let github_host = sources.get_optional("jr.githubHost")?;
+        let token = find_token(
+            |var| std::env::var(var).ok(),
+            || get("jr.githubToken"),
+            keychain::get_token,
+            || gh_auth_token(github_host.as_deref().unwrap_or("github.com")),
+        )?;
+        let (github_token, token_source) = match token {
+            Some(token) => token,
+            None if require_token => anyhow::bail!(tr!("config-token-not-found")),
+            None => Default::default(),
+        };
+        let github_write_token = match std::env::var(WRITE_TOKEN_ENV_VAR) {
+            Ok(token) if !token.is_empty() => Some(token),
+            _ => get("jr.githubWriteToken")?,
+        };
+        let Some(default_branch) = sources.get("jr.defaultBranch")? else {
+            anyhow::bail!(tr!("config-default-branch-not-found"));
+        };
 
         Ok(Self {
             github_branch_prefix,
             github_token,
+            token_source,
+            github_write_token,
             default_branch,
+            webhook_url: sources.get_optional("jr.webhookUrl")?,
+            webhook_template: sources.get_optional("jr.webhookTemplate")?,
+            pre_push_command: sources.get_optional("jr.prePushCommand")?,
+            pr_template: sources.get_optional("jr.prTemplate")?,
+            repo_slug: sources.get_optional("jr.repoSlug")?,
+            describe_command: sources.get_optional("jr.describeCommand")?,
+            size_budget: sources
+                .get_optional("jr.sizeBudget")?
+                .map(|budget| budget.parse())
+                .transpose()
+                .context(tr!("config-not-lines", key = "jr.sizeBudget"))?,
+            nudge_after_days: sources
+                .get_optional("jr.nudgeAfterDays")?
+                .map(|days| days.parse())
+                .transpose()
+                .context(tr!("config-not-days", key = "jr.nudgeAfterDays"))?,
+            nudge_template: sources.get_optional("jr.nudgeTemplate")?,
+            default_milestone: sources.get_optional("jr.defaultMilestone")?,
+            default_project: sources
+                .get_optional("jr.defaultProject")?
+                .map(|project| project.parse())
+                .transpose()
+                .context(tr!("config-not-project", key = "jr.defaultProject"))?,
+            policies: load_policies(&sources)?,
+            remote: sources.get_optional("jr.remote")?,
+            push_remote: sources.get_optional("jr.pushRemote")?,
+            push_notes: sources
+                .get_optional("jr.pushNotes")?
+                .map(|push| parse_bool(&push))
+                .transpose()
+                .context(tr!("config-not-bool", key = "jr.pushNotes"))?
+                .unwrap_or(false),
+            stack_comments: sources
+                .get_optional("jr.stackComments")?
+                .map(|comments| parse_bool(&comments))
+                .transpose()
+                .context(tr!("config-not-bool", key = "jr.stackComments"))?
+                .unwrap_or(false),
+            create_ready: !sources
+                .get_optional("jr.createDraft")?
+                .map(|draft| parse_bool(&draft))
+                .transpose()
+                .context(tr!("config-not-bool", key = "jr.createDraft"))?
+                .unwrap_or(true),
+            jobs: sources
+                .get_optional("jr.jobs")?
+                .map(|jobs| jobs.parse())
+                .transpose()
+                .context(tr!("config-not-number", key = "jr.jobs"))?,
+            github_jobs: sources
+                .get_optional("jr.githubJobs")?
+                .map(|jobs| jobs.parse())
+                .transpose()
+                .context(tr!("config-not-number", key = "jr.githubJobs"))?,
+            stack_limit: sources
+                .get_optional("jr.stackLimit")?
+                .map(|limit| limit.parse())
+                .transpose()
+                .context(tr!("config-not-number", key = "jr.stackLimit"))?,
+            http_timeout: sources
+                .get_optional("jr.httpTimeout")?
+                .map(|timeout| timeout.parse())
+                .transpose()
+                .context(tr!("config-not-seconds", key = "jr.httpTimeout"))?,
+            poll_interval: sources
+                .get_optional("jr.pollInterval")?
+                .map(|interval| interval.parse())
+                .transpose()
+                .context(tr!("config-not-seconds", key = "jr.pollInterval"))?,
+            ca_bundle: sources.get_optional("jr.caBundle")?,
+            github_host,
+            api_url: sources.get_optional("jr.apiUrl")?,
+            trunk_revset: sources.get_optional("jr.trunkRevset")?,
+            merge_method: sources
+                .get_optional("jr.mergeMethod")?
+                .map(|method| {
+                    MergeMethod::from_str(&method, true)
+                        .map_err(|_| anyhow::anyhow!(tr!("config-invalid-merge-method")))
+                })
+                .transpose()?,
+            auto_ready: sources
+                .get_optional("jr.autoReady")?
+                .map(|auto_ready| {
+                    AutoReady::from_str(&auto_ready, true)
+                        .map_err(|_| anyhow::anyhow!(tr!("config-invalid-auto-ready")))
+                })
+                .transpose()?,
+            pairs: sources
+                .get_all("jr.pair")?
+                .iter()
+                .map(|value| Pair::parse(value))
+                .collect::<Result<_>>()?,
+            labels: sources.get_all("jr.label")?,
+            default_reviewers: sources.get_all("jr.defaultReviewers")?,
+            default_assignees: sources.get_all("jr.defaultAssignees")?,
+            protected_branches: sources.get_all("jr.protectBranch")?,
+            base_overrides: sources
+                .get_all("jr.baseOverride")?
+                .iter()
+                .filter_map(|value| {
+                    let (change_id, branch) = value.split_once(char::is_whitespace)?;
+                    Some((change_id.to_string(), branch.trim().to_string()))
+                })
+                .collect(),
+            revset_aliases: sources.revset_aliases()?,
+            locale: match std::env::var("JR_LOCALE") {
+                Ok(locale) if !locale.is_empty() => Some(locale),
+                _ => sources.get_optional("jr.locale")?,
+            },
+            locale_dir: sources.get_optional("jr.localeDir")?,
         })
     }
 
@@ -58,34 +280,59 @@ impl Config {
             .output()?;
 
         if !prefix_output.status.success() {
-            anyhow::bail!("Failed to save github_branch_prefix to .git/config");
+            anyhow::bail!(tr!("config-save-failed", key = "github_branch_prefix"));
         }
 
-        let token_output = std::process::Command::new("git")
-            .args(["config", "jr.githubToken", &self.github_token])
-            .output()?;
-
-        if !token_output.status.success() {
-            anyhow::bail!("Failed to save github_token to .git/config");
-        }
+        self.save_token()?;
 
         let default_branch_output = std::process::Command::new("git")
             .args(["config", "jr.defaultBranch", &self.default_branch])
             .output()?;
 
         if !default_branch_output.status.success() {
-            anyhow::bail!("Failed to save default_branch to .git/config");
+            anyhow::bail!(tr!("config-save-failed", key = "default_branch"));
         }
 
         Ok(())
     }
 
+    /// Save config to the config files (see [`config_file`]): the default
+    /// branch to the repository's, to share with teammates, and the branch
+    /// prefix to the user's.  The token is saved to git config, or the
+    /// keychain.
+    pub fn save_to_files(&self) -> Result<()> {
+        let root = repository_root()?.context("Not in a git repository")?;
+        config_file::save_value(
+            &root.join(config_file::REPO_FILE),
+            "defaultBranch",
+            &self.default_branch,
+        )?;
+        let user_path = config_file::user_path().context("Can't find your home directory")?;
+        config_file::save_value(&user_path, "githubBranchPrefix", &self.github_branch_prefix)?;
+        self.save_token()
+    }
+
+    /// Save the token to the keychain if that's its source, removing it from
+    /// git config, where it would take precedence; otherwise to git config.
+    fn save_token(&self) -> Result<()> {
+        if self.token_source != TokenSource::Keychain {
+            return save_token(&self.github_token, "local");
+        }
+        keychain::set_token(&self.github_token)?;
+        // Fails if it isn't set, which is fine
+        std::process::Command::new("git")
+            .args(["config", "--local", "--unset", "jr.githubToken"])
+            .output()?;
+        Ok(())
+    }
+
     /// Create a new config with explicit values (useful for tests)
     pub fn new(github_branch_prefix: String, github_token: String, default_branch: String) -> Self {
         Self {
             github_branch_prefix,
             github_token,
             default_branch,
+            ..Default::default()
         }
     }
 
@@ -95,19 +342,550 @@ impl Config {
             github_branch_prefix: "test/".to_string(),
             github_token: "test_token".to_string(),
             default_branch: "main".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Check the config for problems that would otherwise fail far from their
+    /// cause, returning a description of each along with how to fix it.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        let prefix = &self.github_branch_prefix;
+        if let Some(reason) = invalid_ref_reason(prefix) {
+            problems.push(tr!(
+                "config-invalid-prefix",
+                prefix = prefix.as_str(),
+                reason = reason
+            ));
+        } else if !prefix.is_empty() && !prefix.ends_with('/') {
+            problems.push(tr!(
+                "config-prefix-no-slash",
+                prefix = prefix.as_str(),
+                fixed = format!("{prefix}/")
+            ));
+        }
+
+        let branch = &self.default_branch;
+        if branch.is_empty() {
+            problems.push(tr!("config-default-branch-empty"));
+        } else if let Some(reason) = invalid_ref_reason(branch) {
+            problems.push(tr!(
+                "config-invalid-default-branch",
+                branch = branch.as_str(),
+                reason = reason
+            ));
+        }
+
+        let token = &self.github_token;
+        if token.is_empty() {
+            problems.push(tr!("config-token-empty"));
+        } else if token.chars().any(char::is_whitespace) {
+            problems.push(tr!("config-token-whitespace"));
+        }
+        if let Some(token) = &self.github_write_token
+            && (token.is_empty() || token.chars().any(char::is_whitespace))
+        {
+            problems.push(tr!("config-write-token-invalid"));
+        }
+
+        for pattern in &self.protected_branches {
+            if let Err(err) = protected_branch_regex(pattern) {
+                problems.push(tr!(
+                    "config-invalid-protect",
+                    pattern = pattern.as_str(),
+                    error = err.to_string()
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Fail with every problem found by [`Config::problems`], if any.
+    pub fn validate(&self) -> Result<()> {
+        ensure_no_problems(&self.problems())
+    }
+
+    /// Whether `jr cleanup` must keep a branch (see `jr.protectBranch`).
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches.iter().any(|pattern| {
+            pattern == branch
+                || protected_branch_regex(pattern).is_ok_and(|regex| regex.is_match(branch))
+        })
+    }
+
+    /// Commits or PRs to elaborate at once.
+    pub fn jobs(&self) -> usize {
+        self.jobs.unwrap_or(jobs::DEFAULT_JOBS)
+    }
+
+    /// GitHub requests to have in flight at once.
+    pub fn github_jobs(&self) -> usize {
+        self.github_jobs.unwrap_or(jobs::DEFAULT_GITHUB_JOBS)
+    }
+
+    /// Fill in the placeholders of the branch prefix, and pairing
+    /// co-authors', for the GitHub repository `owner/repo` (see
+    /// [`expand_branch_prefix`]).
+    pub fn expand_branch_prefix(&mut self, owner: &str, repo: &str) {
+        let repo = self.repo_slug.as_deref().unwrap_or(repo);
+        self.github_branch_prefix = expand_branch_prefix(&self.github_branch_prefix, owner, repo);
+        for pair in &mut self.pairs {
+            pair.prefix = expand_branch_prefix(&pair.prefix, owner, repo);
         }
     }
 
+    /// Our PR branch prefix, followed by those of any pairing co-authors.
+    pub fn branch_prefixes(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.github_branch_prefix.as_str())
+            .chain(self.pairs.iter().map(|pair| pair.prefix.as_str()))
+    }
+
+    /// The remote the repository is fetched from.
+    pub fn remote(&self) -> &str {
+        self.remote.as_deref().unwrap_or("origin")
+    }
+
+    /// The GitHub instance the repository is on.
+    pub fn github_host(&self) -> GithubHost {
+        GithubHost::new(
+            self.github_host.as_deref().unwrap_or("github.com"),
+            self.api_url.as_deref(),
+        )
+    }
+
+    /// The base set for a change's PR by `jr pr edit-base`, if any.
+    pub fn base_override(&self, change_id: &JujutsuChangeId) -> Option<&str> {
+        self.base_overrides
+            .iter()
+            .find(|(id, _)| *id == change_id.0)
+            .map(|(_, branch)| branch.as_str())
+    }
+
+    /// Expand any revset aliases in a revset, so it can be handed to jj.
+    ///
+    /// Aliases are matched as whole symbols outside of string literals, and
+    /// are not expanded recursively.
+    pub fn resolve_revset(&self, revset: &str) -> String {
+        if self.revset_aliases.is_empty() {
+            return revset.to_string();
+        }
+        REVSET_SYMBOL
+            .replace_all(revset, |caps: &Captures| {
+                let token = &caps[0];
+                self.revset_aliases
+                    .iter()
+                    .find(|(name, _)| name == token)
+                    .map(|(_, expansion)| format!("({expansion})"))
+                    .unwrap_or_else(|| token.to_string())
+            })
+            .into_owned()
+    }
+
     /// Default GitHub branch prefix based on current user
     pub fn default_github_branch_prefix() -> String {
         std::env::var("USER").unwrap_or_else(|_| "dev".to_string()) + "/"
     }
 }
 
+/// A string literal, or a symbol (with a `(` if it's a function call) in a
+/// revset.  As in jj, hyphens may join the parts of a symbol, so `my-feature`
+/// is one symbol but `feature-` is `feature`'s parents.
+static REVSET_SYMBOL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""[^"]*"|'[^']*'|[A-Za-z_][A-Za-z0-9_]*(?:-[A-Za-z0-9_]+)*(?:\()?"#)
+        .expect("valid regex")
+});
+
+/// A `jr.protectBranch` pattern as a regex matching whole branch names.
+fn protected_branch_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{pattern})$"))
+}
+
+/// Load the configured severity of each policy from `jr.policy.<name>`.
+fn load_policies(sources: &Sources) -> Result<Policies> {
+    let mut policies = Policies::default();
+    for policy in Policy::value_variants() {
+        let key = format!("jr.policy.{}", policy.name());
+        if let Some(value) = sources.get_optional(&key)? {
+            let severity = Severity::from_str(&value, true)
+                .map_err(|_| anyhow::anyhow!(tr!("config-invalid-policy", key = key.as_str())))?;
+            policies.set_severity(*policy, severity);
+        }
+    }
+    Ok(policies)
+}
+
+/// Fail with a list of config problems, if there are any.
+pub(crate) fn ensure_no_problems(problems: &[String]) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{}\n{}",
+        tr!("config-invalid"),
+        problems
+            .iter()
+            .map(|problem| format!("  - {problem}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+/// Check (part of) a branch name against git's rules for ref names, returning
+/// why it's invalid, if it is.
+fn invalid_ref_reason(name: &str) -> Option<&'static str> {
+    if name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        Some("contains whitespace")
+    } else if name.contains(['~', '^', ':', '?', '*', '[', '\\']) {
+        Some("contains one of ~ ^ : ? * [ \\")
+    } else if name.contains("..") || name.contains("@{") || name.contains("//") {
+        Some("contains .., @{ or //")
+    } else if name.starts_with(['/', '-']) {
+        Some("starts with / or -")
+    } else if name
+        .split('/')
+        .any(|part| part.starts_with('.') || part.ends_with(".lock"))
+    {
+        Some("has a component starting with . or ending with .lock")
+    } else {
+        None
+    }
+}
+
+/// A co-author in pairing mode.  Their PR branches (under their prefix) are
+/// treated as ours, and their commits on our PR branches as our own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair {
+    pub prefix: String,
+    pub email: String,
+}
+
+impl Pair {
+    /// Parse a `jr.pair` value, of the form `<branch prefix> <email>`.
+    fn parse(value: &str) -> Result<Self> {
+        let Some((prefix, email)) = value.split_once(char::is_whitespace) else {
+            anyhow::bail!(tr!("config-invalid-pair", value = value));
+        };
+        Ok(Self {
+            prefix: prefix.to_string(),
+            email: email.trim().to_string(),
+        })
+    }
+}
+
+/// Load revset aliases from `jr.revsetAlias`, a multi-valued key whose values
+/// have the form `name = revset`, as (name, revset) pairs.
+pub fn load_revset_aliases() -> Result<Vec<(String, String)>> {
+    Sources::load()?.revset_aliases()
+}
+
+/// Load the GitHub host from `jr.githubHost` and `jr.apiUrl`, for commands
+/// that run before jr is configured.
+pub fn load_github_host() -> Result<GithubHost> {
+    let sources = Sources::load()?;
+    Ok(GithubHost::new(
+        sources
+            .get_optional("jr.githubHost")?
+            .as_deref()
+            .unwrap_or("github.com"),
+        sources.get_optional("jr.apiUrl")?.as_deref(),
+    ))
+}
+
+/// Load the remote from `jr.remote`, for commands that run before jr is
+/// configured.
+pub fn load_remote() -> Result<String> {
+    Ok(Sources::load()?
+        .get_optional("jr.remote")?
+        .unwrap_or_else(|| "origin".to_string()))
+}
+
+/// Where config values are read from: the repository's config file, then the
+/// user's, then git config (see [`config_file`]).
+struct Sources {
+    files: Vec<ConfigFile>,
+}
+
+impl Sources {
+    fn load() -> Result<Self> {
+        let mut paths = vec![];
+        if let Some(root) = repository_root()? {
+            paths.push(root.join(config_file::REPO_FILE));
+        }
+        paths.extend(config_file::user_path());
+        let mut files = vec![];
+        for path in paths {
+            files.extend(ConfigFile::load(&path)?);
+        }
+        Ok(Self { files })
+    }
+
+    /// Read a value, which may be empty, returning None if unset.
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        match self.files.iter().find_map(|file| file.get(key)) {
+            Some(values) => Ok(values.last().cloned()),
+            None => get(key),
+        }
+    }
+
+    /// Read a value, returning None if unset or empty.
+    fn get_optional(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.get(key)?.filter(|value| !value.is_empty()))
+    }
+
+    /// Read all values of a multi-valued key.
+    fn get_all(&self, key: &str) -> Result<Vec<String>> {
+        match self.files.iter().find_map(|file| file.get(key)) {
+            Some(values) => Ok(values.to_vec()),
+            None => get_all(key),
+        }
+    }
+
+    fn revset_aliases(&self) -> Result<Vec<(String, String)>> {
+        self.get_all("jr.revsetAlias")?
+            .iter()
+            .map(|value| {
+                let (name, revset) = value
+                    .split_once('=')
+                    .with_context(|| tr!("config-invalid-revset-alias", value = value.as_str()))?;
+                let revset = revset.trim().trim_matches('"');
+                Ok((name.trim().to_string(), revset.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Where the GitHub token was found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenSource {
+    /// An environment variable, one of [`TOKEN_ENV_VARS`].
+    Env(&'static str),
+    /// `jr.githubToken` in git config.
+    #[default]
+    GitConfig,
+    /// The system keychain (see [`keychain`]).
+    Keychain,
+    /// The `gh` CLI's login, from `gh auth token`.
+    GhCli,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(var) => write!(f, "${var}"),
+            Self::GitConfig => write!(f, "git config jr.githubToken"),
+            Self::Keychain => write!(f, "the system keychain"),
+            Self::GhCli => write!(f, "gh auth token"),
+        }
+    }
+}
+
+/// Expand a branch prefix template, configured once for every repository,
+/// into a repository's own prefix, e.g. `jnb/{repo}/` into `jnb/jr/`.  It
+/// may reference `{owner}` and `{repo}`, and end with `{change_id}`, which
+/// is dropped, as branch names always end with the change ID.
+pub fn expand_branch_prefix(template: &str, owner: &str, repo: &str) -> String {
+    template
+        .strip_suffix("{change_id}")
+        .unwrap_or(template)
+        .replace("{owner}", owner)
+        .replace("{repo}", repo)
+}
+
+/// Environment variables the GitHub token is read from, in order of
+/// precedence.
+pub const TOKEN_ENV_VARS: [&str; 2] = ["JR_GITHUB_TOKEN", "GITHUB_TOKEN"];
+
+/// Environment variable the write token is read from, before
+/// `jr.githubWriteToken` in git config.
+pub const WRITE_TOKEN_ENV_VAR: &str = "JR_GITHUB_WRITE_TOKEN";
+
+/// Find the GitHub token: in the environment, then git config (it's never
+/// read from config files, which may be committed), then the keychain, then
+/// from the `gh` CLI.
+/// An empty variable is skipped, but an empty `jr.githubToken` is returned,
+/// for [`Config::problems`] to explain.
+fn find_token(
+    env: impl Fn(&str) -> Option<String>,
+    git_config: impl FnOnce() -> Result<Option<String>>,
+    keychain: impl FnOnce() -> Option<String>,
+    gh: impl FnOnce() -> Option<String>,
+) -> Result<Option<(String, TokenSource)>> {
+    for var in TOKEN_ENV_VARS {
+        if let Some(token) = env(var).filter(|token| !token.is_empty()) {
+            return Ok(Some((token, TokenSource::Env(var))));
+        }
+    }
+    if let Some(token) = git_config()? {
+        return Ok(Some((token, TokenSource::GitConfig)));
+    }
+    if let Some(token) = keychain() {
+        return Ok(Some((token, TokenSource::Keychain)));
+    }
+    Ok(gh().map(|token| (token, TokenSource::GhCli)))
+}
+
+/// Get the token the `gh` CLI is logged in with, if it's installed and
+/// logged in.
+fn gh_auth_token(host: &str) -> Option<String> {
+    let output = std::process::Command::new("gh")
+        .args(["auth", "token", "--hostname", host])
+        .output()
+        .ok()?;
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !token.is_empty()).then_some(token)
+}
+
+/// The root of the repository's working tree, if in one.
+fn repository_root() -> Result<Option<PathBuf>> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(PathBuf::from(
+        String::from_utf8(output.stdout)?.trim(),
+    )))
+}
+
+/// Find the git config scope (e.g. `local` or `global`) that the GitHub token
+/// is read from, if it's set.
+pub fn token_scope() -> Result<Option<String>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--show-scope", "--get", "jr.githubToken"])
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let output = String::from_utf8(output.stdout)?;
+    Ok(output.split('\t').next().map(str::to_string))
+}
+
+/// Save the GitHub token to a git config scope, e.g. `local` or `global`.
+pub fn save_token(token: &str, scope: &str) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["config", &format!("--{scope}"), "jr.githubToken", token])
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(tr!("config-save-failed", key = "github_token"));
+    }
+    Ok(())
+}
+
+/// Save the remote to push PR branches to.
+pub fn save_push_remote(remote: &str) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["config", "jr.pushRemote", remote])
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(tr!("config-save-failed", key = "push_remote"));
+    }
+    Ok(())
+}
+
+/// Record a PR base set by `jr pr edit-base` for a change, replacing any
+/// already recorded, or with `None` forget it.
+pub fn save_base_override(change_id: &str, branch: Option<&str>) -> Result<()> {
+    // Fails if there's nothing to unset, which is fine
+    std::process::Command::new("git")
+        .args([
+            "config",
+            "--local",
+            "--unset-all",
+            "jr.baseOverride",
+            &format!("^{change_id} "),
+        ])
+        .output()?;
+    if let Some(branch) = branch {
+        let output = std::process::Command::new("git")
+            .args([
+                "config",
+                "--local",
+                "--add",
+                "jr.baseOverride",
+                &format!("{change_id} {branch}"),
+            ])
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!(tr!("config-save-failed", key = "base_override"));
+        }
+    }
+    Ok(())
+}
+
+/// Read all values of a multi-valued key from git config.
+fn get_all(key: &str) -> Result<Vec<String>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get-all", key])
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(vec![]);
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Parse a boolean config value, as git does.
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => anyhow::bail!("Not a boolean: {value}"),
+    }
+}
+
+/// Read a value from git config, which may be empty, returning None if unset.
+fn get(key: &str) -> Result<Option<String>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8(output.stdout)?.trim().to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_bool() {
+        assert!(parse_bool("true").unwrap());
+        assert!(parse_bool("Yes").unwrap());
+        assert!(!parse_bool("off").unwrap());
+        assert!(!parse_bool("0").unwrap());
+        assert!(parse_bool("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_pair_parse() {
+        assert_eq!(
+            Pair::parse("alice/  alice@example.com").unwrap(),
+            Pair {
+                prefix: "alice/".to_string(),
+                email: "alice@example.com".to_string(),
+            }
+        );
+        assert!(Pair::parse("alice/").is_err());
+    }
+
     #[test]
     fn test_default_for_tests() {
         let config = Config::default_for_tests();
@@ -126,10 +904,174 @@ mod tests {
         assert_eq!(config.default_branch, "main");
     }
 
+    #[test]
+    fn test_expand_branch_prefix() {
+        assert_eq!(
+            expand_branch_prefix("jnb/{repo}/{change_id}", "jnb", "jr"),
+            "jnb/jr/"
+        );
+        assert_eq!(expand_branch_prefix("{owner}-{repo}/", "o", "r"), "o-r/");
+        assert_eq!(expand_branch_prefix("jnb/", "o", "r"), "jnb/");
+
+        let mut config = Config {
+            github_branch_prefix: "jnb/{repo}/".to_string(),
+            repo_slug: Some("web".to_string()),
+            ..Config::default_for_tests()
+        };
+        config.expand_branch_prefix("acme", "website");
+        assert_eq!(config.github_branch_prefix, "jnb/web/");
+    }
+
     #[test]
     fn test_default_github_branch_prefix() {
         let prefix = Config::default_github_branch_prefix();
         // Should be $USER/ or "dev/" if USER not set
         assert!(prefix.ends_with('/'));
     }
+
+    #[test]
+    fn test_base_override() {
+        let config = Config {
+            base_overrides: vec![("klmnopqr".to_string(), "hotfix".to_string())],
+            ..Config::default_for_tests()
+        };
+        let change_id = |id: &str| JujutsuChangeId(id.to_string());
+        assert_eq!(config.base_override(&change_id("klmnopqr")), Some("hotfix"));
+        assert_eq!(config.base_override(&change_id("klmnopqrs")), None);
+    }
+
+    #[test]
+    fn test_resolve_revset() {
+        let config = Config {
+            revset_aliases: vec![
+                (
+                    "myStack".to_string(),
+                    "mine() ~ ancestors(trunk())".to_string(),
+                ),
+                ("wip".to_string(), "description(glob:\"wip*\")".to_string()),
+            ],
+            ..Config::default_for_tests()
+        };
+        assert_eq!(config.resolve_revset("@"), "@");
+        assert_eq!(
+            config.resolve_revset("myStack"),
+            "(mine() ~ ancestors(trunk()))"
+        );
+        assert_eq!(
+            config.resolve_revset("heads(myStack) ~ wip"),
+            "heads((mine() ~ ancestors(trunk()))) ~ (description(glob:\"wip*\"))"
+        );
+        // Only whole symbols outside string literals are aliases
+        assert_eq!(
+            config.resolve_revset("myStack2 | description(\"wip\")"),
+            "myStack2 | description(\"wip\")"
+        );
+        // Including hyphenated symbols, though an alias's parents expand
+        assert_eq!(
+            config.resolve_revset("my-wip | wip-"),
+            "my-wip | (description(glob:\"wip*\"))-"
+        );
+    }
+
+    #[test]
+    fn test_is_protected() {
+        let config = Config {
+            protected_branches: vec!["me/release".to_string(), "me/keep-.*".to_string()],
+            ..Config::default_for_tests()
+        };
+        assert!(config.is_protected("me/release"));
+        assert!(config.is_protected("me/keep-this"));
+        assert!(!config.is_protected("me/release-2"));
+        assert!(!config.is_protected("you/me/keep-this"));
+        assert!(config.problems().is_empty());
+
+        let config = Config {
+            protected_branches: vec!["me/(".to_string()],
+            ..Config::default_for_tests()
+        };
+        assert!(config.is_protected("me/("));
+        assert_eq!(config.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_problems() {
+        assert!(Config::default_for_tests().problems().is_empty());
+
+        let config = Config::new(
+            "my prefix".to_string(),
+            "token\n".to_string(),
+            "".to_string(),
+        );
+        assert_eq!(
+            config.problems(),
+            [
+                "Branch prefix 'my prefix' contains whitespace. Fix it with: git config jr.githubBranchPrefix <prefix>/",
+                "Default branch is empty. Fix it with: git config jr.defaultBranch main",
+                "GitHub token contains whitespace; it was probably pasted with extra characters. Run 'jr init' to configure it again.",
+            ]
+        );
+
+        let config = Config::new(
+            "jnb".to_string(),
+            "token".to_string(),
+            "main..x".to_string(),
+        );
+        assert_eq!(config.problems().len(), 2);
+        assert!(config.problems()[0].contains("git config jr.githubBranchPrefix 'jnb/'"));
+        assert!(
+            config
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid jr configuration:\n  - ")
+        );
+    }
+
+    #[test]
+    fn test_find_token() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let git = || Ok(Some("git".to_string()));
+        let gh = || Some("gh".to_string());
+
+        let found = find_token(
+            env(&[("GITHUB_TOKEN", "b"), ("JR_GITHUB_TOKEN", "a")]),
+            git,
+            || None,
+            gh,
+        );
+        assert_eq!(
+            found.unwrap(),
+            Some(("a".to_string(), TokenSource::Env("JR_GITHUB_TOKEN")))
+        );
+        let found = find_token(
+            env(&[("JR_GITHUB_TOKEN", ""), ("GITHUB_TOKEN", "b")]),
+            git,
+            || None,
+            gh,
+        );
+        assert_eq!(
+            found.unwrap(),
+            Some(("b".to_string(), TokenSource::Env("GITHUB_TOKEN")))
+        );
+        let found = find_token(env(&[]), git, || None, gh);
+        assert_eq!(
+            found.unwrap(),
+            Some(("git".to_string(), TokenSource::GitConfig))
+        );
+        let found = find_token(env(&[]), || Ok(None), || Some("keychain".to_string()), gh);
+        assert_eq!(
+            found.unwrap(),
+            Some(("keychain".to_string(), TokenSource::Keychain))
+        );
+        let found = find_token(env(&[]), || Ok(None), || None, gh);
+        assert_eq!(found.unwrap(), Some(("gh".to_string(), TokenSource::GhCli)));
+        let found = find_token(env(&[]), || Ok(None), || None, || None);
+        assert_eq!(found.unwrap(), None);
+    }
 }
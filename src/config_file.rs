@@ -0,0 +1,213 @@
+//! Config files, read alongside git config.
+//!
+//! Settings can be committed to `.jr.toml` at the root of the repository, to
+//! share them with teammates, and personal defaults (e.g. the branch prefix)
+//! kept in `~/.config/jr/config.toml`.  Keys are git config keys without the
+//! `jr.` prefix, with subsections as tables and multi-valued keys as arrays:
+//!
+//! ```toml
+//! defaultBranch = "main"
+//! revsetAlias = ["myStack = mine() ~ ancestors(trunk())"]
+//!
+//! [policy]
+//! foreign-push = "warn"
+//! ```
+//!
+//! The GitHub token is only read from git config, so it can't be committed by
+//! mistake.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use toml_edit::Document;
+use toml_edit::Item;
+use toml_edit::Value;
+
+use crate::i18n::tr;
+
+/// Name of the repository's config file, at its root.
+pub const REPO_FILE: &str = ".jr.toml";
+
+/// Keys that may not be set in config files.
+const SECRET_KEYS: [&str; 1] = ["jr.githubToken"];
+
+/// The values in a config file, by git config key, e.g. `jr.defaultBranch`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigFile {
+    values: HashMap<String, Vec<String>>,
+}
+
+impl ConfigFile {
+    /// Parse a config file's contents.
+    pub fn parse(text: &str) -> Result<Self> {
+        let document = Document::parse(text)?;
+        let mut values = HashMap::new();
+        flatten("jr", document.as_item(), &mut values)?;
+        if let Some(key) = SECRET_KEYS.iter().find(|key| values.contains_key(**key)) {
+            bail!(tr!("config-file-secret", key = *key));
+        }
+        Ok(Self { values })
+    }
+
+    /// Read a config file, if it exists.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        Self::parse(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+            .map(Some)
+    }
+
+    /// The values of a key, if it's set.
+    pub fn get(&self, key: &str) -> Option<&[String]> {
+        self.values.get(key).map(Vec::as_slice)
+    }
+}
+
+/// Collect the values under a table or value, keyed by their dotted path.
+fn flatten(key: &str, item: &Item, values: &mut HashMap<String, Vec<String>>) -> Result<()> {
+    if let Some(table) = item.as_table_like() {
+        for (name, item) in table.iter() {
+            flatten(&format!("{key}.{name}"), item, values)?;
+        }
+        return Ok(());
+    }
+    let list = match item.as_value() {
+        Some(Value::Array(array)) => array.iter().map(scalar).collect::<Option<_>>(),
+        Some(value) => scalar(value).map(|value| vec![value]),
+        None => None,
+    };
+    let Some(list) = list else {
+        bail!("{key} must be a string, number, boolean or an array of them");
+    };
+    values.insert(key.to_string(), list);
+    Ok(())
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.value().clone()),
+        Value::Integer(integer) => Some(integer.value().to_string()),
+        Value::Boolean(boolean) => Some(boolean.value().to_string()),
+        _ => None,
+    }
+}
+
+/// The user's config file, in `$XDG_CONFIG_HOME` or `~/.config`.
+pub fn user_path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("jr").join("config.toml"))
+}
+
+/// Set a top-level string key in a config file's contents, keeping the rest
+/// of the file as it is.
+pub fn set_value(text: &str, name: &str, value: &str) -> String {
+    let line = format!(
+        "{name} = {}",
+        serde_json::to_string(value).expect("strings serialize")
+    );
+    let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+    // Top-level keys come before the first table
+    let top = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..top].iter().position(|line| {
+        line.trim_start()
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(i) => lines[i] = line,
+        None => lines.insert(top, line),
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Set a top-level string key in a config file, creating it if needed.
+pub fn save_value(path: &Path, name: &str, value: &str) -> Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, set_value(&text, name, value))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let file = ConfigFile::parse(
+            r#"
+            defaultBranch = "main"
+            stackLimit = 20
+            pushNotes = true
+            revsetAlias = ["a = b", "c = d"]
+
+            [policy]
+            foreign-push = "warn"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            file.get("jr.defaultBranch"),
+            Some(&["main".to_string()][..])
+        );
+        assert_eq!(file.get("jr.stackLimit"), Some(&["20".to_string()][..]));
+        assert_eq!(file.get("jr.pushNotes"), Some(&["true".to_string()][..]));
+        assert_eq!(file.get("jr.revsetAlias").map(<[_]>::len), Some(2));
+        assert_eq!(
+            file.get("jr.policy.foreign-push"),
+            Some(&["warn".to_string()][..])
+        );
+        assert_eq!(file.get("jr.locale"), None);
+
+        assert!(ConfigFile::parse("githubToken = \"ghp_secret\"").is_err());
+        assert!(ConfigFile::parse("jobs = 1.5").is_err());
+        assert!(ConfigFile::parse("jobs = ").is_err());
+    }
+
+    #[test]
+    fn test_set_value() {
+        assert_eq!(
+            set_value("", "defaultBranch", "main"),
+            "defaultBranch = \"main\"\n"
+        );
+        let text =
+            "# Shared jr settings\ndefaultBranch = \"master\"\n\n[policy]\npre-push = \"warn\"\n";
+        assert_eq!(
+            set_value(text, "defaultBranch", "main"),
+            "# Shared jr settings\ndefaultBranch = \"main\"\n\n[policy]\npre-push = \"warn\"\n"
+        );
+        assert_eq!(
+            set_value("[policy]\npre-push = \"warn\"\n", "mergeMethod", "squash"),
+            "mergeMethod = \"squash\"\n[policy]\npre-push = \"warn\"\n"
+        );
+        // Keys that only share a prefix are left alone
+        assert_eq!(
+            set_value("defaultBranchX = \"a\"", "defaultBranch", "b\"c"),
+            "defaultBranchX = \"a\"\ndefaultBranch = \"b\\\"c\"\n"
+        );
+    }
+}
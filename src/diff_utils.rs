@@ -11,6 +11,26 @@ pub fn normalize_diff(diff: &str) -> String {
         .join("\n")
 }
 
+/// Split a diff into each file's part, with the file's path (its new path,
+/// if renamed).
+pub fn split_files(diff: &str) -> Vec<(&str, &str)> {
+    let mut starts = diff
+        .match_indices("diff --git ")
+        .map(|(i, _)| i)
+        .filter(|&i| i == 0 || diff.as_bytes()[i - 1] == b'\n')
+        .collect::<Vec<_>>();
+    starts.push(diff.len());
+    starts
+        .windows(2)
+        .map(|window| {
+            let part = &diff[window[0]..window[1]];
+            let header = part.lines().next().unwrap_or_default();
+            let path = header.rsplit_once(" b/").map_or(header, |(_, path)| path);
+            (path, part)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +92,27 @@ index abc123..def456 100644\n\
         assert!(normalized.contains("let index = 0;"));
         assert!(normalized.contains("let index = 1;"));
     }
+
+    #[test]
+    fn test_split_files() {
+        let diff = "diff --git a/foo b/foo\n\
+--- a/foo\n\
++++ b/foo\n\
+@@ -1 +1 @@\n\
+-diff --git a/x b/x\n\
++new\n\
+diff --git a/old b/new\n\
+similarity index 100%\n";
+        let files = split_files(diff);
+        assert_eq!(
+            files.iter().map(|(path, _)| *path).collect::<Vec<_>>(),
+            ["foo", "new"]
+        );
+        assert!(files[0].1.ends_with("+new\n"));
+        assert_eq!(
+            files[1].1,
+            "diff --git a/old b/new\nsimilarity index 100%\n"
+        );
+        assert!(split_files("").is_empty());
+    }
 }
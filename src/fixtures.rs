@@ -0,0 +1,248 @@
+//! Synthetic colocated jj+git repositories, for tests, benches and bug
+//! reproductions (built with the `fixtures` feature; see `jr-xtask`).
+//!
+//! A fixture is a repository with a `main` branch tracked from a bare
+//! `origin` repository next to it, and stacks of commits on top shaped by a
+//! [`Shape`].  Each commit adds a file named after it, and is described with
+//! its name.  Nothing is pushed to GitHub, so fixtures work offline; jr
+//! commands that only need jj and git can run in them directly.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use tokio::process::Command;
+
+use crate::App;
+use crate::clients::github::GithubClient;
+use crate::config::Config;
+
+/// The shape of the stacks in a fixture: paths of commit names from trunk
+/// up, separated by commas, each a chain of names separated by dashes.  A
+/// path starting with a name from an earlier path branches off that commit;
+/// otherwise it starts a new stack on trunk.  E.g. `a-b-c,b-d` is a stack
+/// of three commits with a fourth branching off its second, and `a,b` is two
+/// single-commit stacks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shape {
+    /// Each commit's name and parent, if not trunk, with parents first.
+    pub commits: Vec<(String, Option<String>)>,
+}
+
+impl std::str::FromStr for Shape {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut commits: Vec<(String, Option<String>)> = vec![];
+        for path in spec.split(',') {
+            let names = path.split('-').map(str::trim).collect::<Vec<_>>();
+            let known = |name: &str, commits: &[(String, Option<String>)]| {
+                commits.iter().any(|(known, _)| known == name)
+            };
+            // A known first name is where the path branches off
+            let (mut parent, rest) = match names.split_first() {
+                Some((first, rest)) if known(first, &commits) => (Some(first.to_string()), rest),
+                _ => (None, names.as_slice()),
+            };
+            for name in rest {
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    bail!("Invalid commit name {name:?} in shape {spec:?}");
+                }
+                if known(name, &commits) {
+                    bail!("Commit {name:?} appears twice in shape {spec:?}");
+                }
+                commits.push((name.to_string(), parent.clone()));
+                parent = Some(name.to_string());
+            }
+        }
+        Ok(Self { commits })
+    }
+}
+
+impl Shape {
+    /// A single stack of `size` commits, named `c0` at the bottom, `c1` and
+    /// so on.
+    pub fn linear(size: usize) -> Self {
+        let commits = (0..size)
+            .map(|i| {
+                (
+                    format!("c{i}"),
+                    i.checked_sub(1).map(|parent| format!("c{parent}")),
+                )
+            })
+            .collect();
+        Self { commits }
+    }
+}
+
+/// A fixture built by [`build`].
+#[derive(Debug)]
+pub struct Fixture {
+    /// The colocated jj+git repository.
+    pub repo: PathBuf,
+    /// The bare repository it tracks `main` from, as `origin`.
+    pub origin: PathBuf,
+    /// Each commit's change ID, by name.
+    pub change_ids: HashMap<String, String>,
+}
+
+impl Fixture {
+    /// An app for the fixture's repository, naming PR branches with
+    /// `branch_prefix`, whose GitHub is offline (see
+    /// [`GithubClient::offline`]), so it only does dry runs.
+    pub fn offline_app(&self, branch_prefix: &str) -> Result<App> {
+        let config = Config {
+            github_branch_prefix: branch_prefix.to_string(),
+            default_branch: "main".to_string(),
+            ..Default::default()
+        };
+        let gh = GithubClient::offline("fixture", "repo", &config.default_branch)?;
+        Ok(App::new(config, gh, self.repo.clone()))
+    }
+}
+
+/// Build a fixture in `dir`, which should be empty, as `dir/repo` and
+/// `dir/origin.git`.  The working copy is left as a new empty commit on top
+/// of the shape's last commit, or of trunk if it has none.
+pub async fn build(dir: &Path, shape: &Shape) -> Result<Fixture> {
+    let origin = dir.join("origin.git");
+    let repo = dir.join("repo");
+    tokio::fs::create_dir_all(&repo).await?;
+
+    run(dir, "git", &["init", "--bare", "-b", "main", "origin.git"]).await?;
+    run(&repo, "git", &["init", "-b", "main"]).await?;
+    run(&repo, "git", &["config", "user.name", "Fixture User"]).await?;
+    run(
+        &repo,
+        "git",
+        &["config", "user.email", "fixture@example.com"],
+    )
+    .await?;
+    let origin_url = origin.to_str().context("Non-UTF-8 fixture path")?;
+    run(&repo, "git", &["remote", "add", "origin", origin_url]).await?;
+    tokio::fs::write(repo.join("README.md"), "Fixture\n").await?;
+    run(&repo, "git", &["add", "README.md"]).await?;
+    run(&repo, "git", &["commit", "-m", "Initial commit"]).await?;
+    run(&repo, "git", &["push", "-u", "origin", "main"]).await?;
+
+    run(&repo, "jj", &["git", "init", "--colocate"]).await?;
+    run(&repo, "jj", &["bookmark", "track", "main@origin"]).await?;
+
+    let mut change_ids: HashMap<String, String> = HashMap::new();
+    for (name, parent) in &shape.commits {
+        let parent = match parent {
+            Some(parent) => change_ids[parent].as_str(),
+            None => "main",
+        };
+        run(&repo, "jj", &["new", parent, "-m", name]).await?;
+        tokio::fs::write(repo.join(format!("{name}.txt")), format!("{name}\n")).await?;
+        let change_id = output(
+            &repo,
+            "jj",
+            &["log", "--no-graph", "-r", "@", "-T", "change_id"],
+        )
+        .await?;
+        change_ids.insert(name.clone(), change_id.trim().to_string());
+    }
+    let top = match shape.commits.last() {
+        Some((name, _)) => change_ids[name].as_str(),
+        None => "main",
+    };
+    run(&repo, "jj", &["new", top]).await?;
+
+    Ok(Fixture {
+        repo,
+        origin,
+        change_ids,
+    })
+}
+
+/// Diffs for `size` synthetic commits, each touching a few files of a few
+/// hunks: the commit's diff and its PR's, which only differ in the
+/// abbreviation of the `index` line hashes, so match once normalized.
+pub fn synthetic_diffs(size: usize) -> Vec<(String, String)> {
+    (0..size)
+        .map(|i| {
+            let diff = |abbrev: usize| {
+                (0..4)
+                    .map(|file| {
+                        let hash = format!("{:040x}", i * 31 + file);
+                        let hunks = (0..8)
+                            .map(|hunk| {
+                                format!(
+                                    "@@ -{0},3 +{0},3 @@\n context\n-old line {hunk}\n+new line {hunk}\n",
+                                    hunk * 10 + 1
+                                )
+                            })
+                            .collect::<String>();
+                        format!(
+                            "diff --git a/src/file{file}.rs b/src/file{file}.rs\n\
+                             index {0}..{0} 100644\n\
+                             --- a/src/file{file}.rs\n\
+                             +++ b/src/file{file}.rs\n\
+                             {hunks}",
+                            &hash[..abbrev]
+                        )
+                    })
+                    .collect::<String>()
+            };
+            (diff(10), diff(11))
+        })
+        .collect()
+}
+
+/// Run a command, e.g. jj in a fixture's repository, failing if it does.
+pub async fn run(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
+    output(dir, program, args).await?;
+    Ok(())
+}
+
+/// Run a command, returning its stdout.  jj commits as the fixture user too.
+async fn output(dir: &Path, program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .current_dir(dir)
+        .args(args)
+        .env("JJ_USER", "Fixture User")
+        .env("JJ_EMAIL", "fixture@example.com")
+        .output()
+        .await
+        .with_context(|| format!("Failed to execute {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(name: &str, parent: Option<&str>) -> (String, Option<String>) {
+        (name.to_string(), parent.map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_shape() {
+        let shape: Shape = "a-b-c,b-d,e".parse().unwrap();
+        assert_eq!(
+            shape.commits,
+            [
+                commit("a", None),
+                commit("b", Some("a")),
+                commit("c", Some("b")),
+                commit("d", Some("b")),
+                commit("e", None),
+            ]
+        );
+        assert!("a-b,c-b".parse::<Shape>().is_err());
+        assert!("a--b".parse::<Shape>().is_err());
+        assert_eq!("c0-c1-c2".parse::<Shape>().unwrap(), Shape::linear(3));
+    }
+}
@@ -0,0 +1,255 @@
+//! Diagrams of a stack, for docs and PR descriptions (see `jr graph`).
+//!
+//! Each commit is a node labelled with its change ID, title, PR number and
+//! sync status, with an edge to its PR's base: the PR branch of the commit
+//! below it, or a branch outside the stack, e.g. the default branch.  The
+//! diagram reads bottom-up, like the stack.
+
+use clap::ValueEnum;
+
+use crate::commit::CommitInfo;
+use crate::commit::GITHUB_CHANGE_ID_LENGTH;
+use crate::commit::SyncStatus;
+use crate::stack::Stack;
+use crate::title;
+
+/// Hidden markers around a diagram embedded in a PR's description, so later
+/// runs replace it rather than adding another.
+pub const MARKER_START: &str = "<!-- jr:graph -->";
+pub const MARKER_END: &str = "<!-- /jr:graph -->";
+
+/// A diagram language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    /// Mermaid flowchart, which GitHub renders in Markdown.
+    Mermaid,
+}
+
+/// A node of the diagram: a commit, or a base branch outside the stack.
+struct Node {
+    id: String,
+    label: Vec<String>,
+    is_branch: bool,
+}
+
+/// Render a stack as a diagram.
+pub fn render(stack: &Stack, format: GraphFormat) -> String {
+    let (nodes, edges) = layout(stack);
+    match format {
+        GraphFormat::Dot => render_dot(&nodes, &edges),
+        GraphFormat::Mermaid => render_mermaid(&nodes, &edges),
+    }
+}
+
+/// Replace the diagram embedded in a PR description, or append one.
+pub fn embed(body: &str, diagram: &str) -> String {
+    let block = format!("{MARKER_START}\n```mermaid\n{diagram}```\n{MARKER_END}");
+    if let Some(start) = body.find(MARKER_START)
+        && let Some(end) = body[start..].find(MARKER_END)
+    {
+        let end = start + end + MARKER_END.len();
+        return format!("{}{block}{}", &body[..start], &body[end..]);
+    }
+    if body.trim().is_empty() {
+        block
+    } else {
+        format!("{}\n\n{block}", body.trim_end())
+    }
+}
+
+/// The stack's nodes, bottom first, and the edges from each commit to its
+/// base.
+fn layout(stack: &Stack) -> (Vec<Node>, Vec<(String, String)>) {
+    let mut nodes: Vec<Node> = vec![];
+    let mut edges = vec![];
+    for (commit, status) in stack.commits.iter().zip(stack.statuses()) {
+        let id = node_id(commit);
+        let base = stack
+            .commits
+            .iter()
+            .find(|other| other.pr_branch == commit.base_branch)
+            .map(node_id);
+        let base = match base {
+            Some(base) => base,
+            None => {
+                let base = branch_id(&commit.base_branch);
+                if !nodes.iter().any(|node| node.id == base) {
+                    nodes.push(Node {
+                        id: base.clone(),
+                        label: vec![commit.base_branch.clone()],
+                        is_branch: true,
+                    });
+                }
+                base
+            }
+        };
+        nodes.push(Node {
+            id: id.clone(),
+            label: commit_label(commit, status),
+            is_branch: false,
+        });
+        edges.push((id, base));
+    }
+    (nodes, edges)
+}
+
+fn node_id(commit: &CommitInfo) -> String {
+    let change_id = &commit.commit.change_id.0;
+    change_id[..GITHUB_CHANGE_ID_LENGTH.min(change_id.len())].to_string()
+}
+
+/// An ID for a branch's node that can't clash with a change ID, which only
+/// has the letters k-z.
+fn branch_id(branch: &str) -> String {
+    let id = branch
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("branch_{id}")
+}
+
+fn commit_label(commit: &CommitInfo, status: SyncStatus) -> Vec<String> {
+    let message = commit.message();
+    let title = message.title.as_deref().map(title::sanitize);
+    let mut heading = node_id(commit);
+    if let Some(title) = title.filter(|title| !title.is_empty()) {
+        heading = format!("{heading} {title}");
+    }
+    let pr = match &commit.pr {
+        Some(pr) => format!("#{} {status}", pr.number),
+        None => status.to_string(),
+    };
+    vec![heading, pr]
+}
+
+fn render_dot(nodes: &[Node], edges: &[(String, String)]) -> String {
+    let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = "digraph stack {\n    rankdir=BT;\n    node [shape=box];\n".to_string();
+    for node in nodes {
+        let label = node
+            .label
+            .iter()
+            .map(|line| quote(line))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        let shape = if node.is_branch {
+            ", shape=ellipse"
+        } else {
+            ""
+        };
+        out.push_str(&format!("    {} [label=\"{label}\"{shape}];\n", node.id));
+    }
+    for (from, to) in edges {
+        out.push_str(&format!("    {from} -> {to};\n"));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(nodes: &[Node], edges: &[(String, String)]) -> String {
+    let quote = |text: &str| {
+        text.replace('&', "#amp;")
+            .replace('"', "#quot;")
+            .replace('<', "#lt;")
+            .replace('>', "#gt;")
+    };
+    let mut out = "flowchart BT\n".to_string();
+    for node in nodes {
+        let label = node
+            .label
+            .iter()
+            .map(|line| quote(line))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        if node.is_branch {
+            out.push_str(&format!("    {}([\"{label}\"])\n", node.id));
+        } else {
+            out.push_str(&format!("    {}[\"{label}\"]\n", node.id));
+        }
+    }
+    for (from, to) in edges {
+        out.push_str(&format!("    {from} --> {to}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::git::CommitId;
+    use crate::clients::jujutsu::JujutsuChangeId;
+    use crate::clients::jujutsu::JujutsuCommit;
+    use crate::clients::jujutsu::JujutsuCommitMessage;
+
+    fn commit(change_id: &str, title: &str, base_branch: &str) -> CommitInfo {
+        CommitInfo {
+            commit: JujutsuCommit {
+                change_id: JujutsuChangeId(change_id.repeat(8)),
+                commit_id: CommitId("0".repeat(40)),
+                message: JujutsuCommitMessage {
+                    title: Some(title.to_string()),
+                    body: None,
+                },
+                parent_change_ids: vec![],
+                divergent: false,
+            },
+            commit_diff: String::new(),
+            commit_diff_norm: String::new(),
+            pr_branch: format!("test/{}", change_id.repeat(8)),
+            pr_tip: None,
+            pr: None,
+            pr_diff: None,
+            pr_diff_norm: None,
+            base_branch: base_branch.to_string(),
+            base_tip: None,
+            pr_contains_base: false,
+        }
+    }
+
+    fn stack() -> Stack {
+        Stack::new(vec![
+            commit("k", "Add \"quotes\"", "main"),
+            commit("l", "Use <them>", "test/kkkkkkkk"),
+        ])
+    }
+
+    #[test]
+    fn test_render_dot() {
+        assert_eq!(
+            render(&stack(), GraphFormat::Dot),
+            "digraph stack {\n\
+             \x20   rankdir=BT;\n\
+             \x20   node [shape=box];\n\
+             \x20   branch_main [label=\"main\", shape=ellipse];\n\
+             \x20   kkkkkkkk [label=\"kkkkkkkk Add \\\"quotes\\\"\\n?\"];\n\
+             \x20   llllllll [label=\"llllllll Use <them>\\n?\"];\n\
+             \x20   kkkkkkkk -> branch_main;\n\
+             \x20   llllllll -> kkkkkkkk;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        assert_eq!(
+            render(&stack(), GraphFormat::Mermaid),
+            "flowchart BT\n\
+             \x20   branch_main([\"main\"])\n\
+             \x20   kkkkkkkk[\"kkkkkkkk Add #quot;quotes#quot;<br/>?\"]\n\
+             \x20   llllllll[\"llllllll Use #lt;them#gt;<br/>?\"]\n\
+             \x20   kkkkkkkk --> branch_main\n\
+             \x20   llllllll --> kkkkkkkk\n"
+        );
+    }
+
+    #[test]
+    fn test_embed() {
+        let block = "<!-- jr:graph -->\n```mermaid\nA\n```\n<!-- /jr:graph -->";
+        assert_eq!(embed("", "A\n"), block);
+        assert_eq!(embed("Body\n", "A\n"), format!("Body\n\n{block}"));
+        let embedded = format!("Body\n\n{block}\n\nMore");
+        assert_eq!(embed(&embedded, "B\n"), embedded.replace("\nA\n", "\nB\n"));
+    }
+}
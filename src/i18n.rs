@@ -0,0 +1,194 @@
+//! Localization of user-facing messages.
+//!
+//! Messages are looked up by a stable ID in a [Fluent](https://projectfluent.org)
+//! catalog.  The English catalog is built in; other locales are loaded from
+//! `<locale>.ftl` in the directory named by `jr.localeDir`, and fall back to
+//! English for any message they don't define.  The locale is selected by
+//! `JR_LOCALE`, or else `jr.locale`.
+//!
+//! The special locale `ids` renders each message as its ID followed by its
+//! arguments, so wrappers and tests can match on IDs rather than text.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use fluent_bundle::FluentArgs;
+use fluent_bundle::FluentResource;
+use fluent_bundle::FluentValue;
+use fluent_bundle::concurrent::FluentBundle;
+use unic_langid::LanguageIdentifier;
+
+/// The built-in English catalog.
+const EN_US: &str = include_str!("../locales/en-US.ftl");
+
+/// Locale that renders message IDs instead of text.
+pub const IDS_LOCALE: &str = "ids";
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// A set of message catalogs, in order of preference.
+pub struct Catalog {
+    bundles: Vec<FluentBundle<FluentResource>>,
+    ids_only: bool,
+}
+
+impl Catalog {
+    /// The built-in English catalog.
+    pub fn english() -> Self {
+        Self {
+            bundles: vec![bundle("en-US", EN_US.to_string()).expect("built-in catalog is valid")],
+            ids_only: false,
+        }
+    }
+
+    /// Load the catalog for a locale, falling back to English.
+    pub fn load(locale: &str, locale_dir: Option<&Path>) -> Result<Self> {
+        let mut catalog = Self::english();
+        if locale == IDS_LOCALE {
+            catalog.ids_only = true;
+        } else if locale != "en-US" {
+            let dir = locale_dir
+                .with_context(|| format!("No catalog for locale {locale}; set jr.localeDir"))?;
+            let path = dir.join(format!("{locale}.ftl"));
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let translated =
+                bundle(locale, source).with_context(|| format!("Invalid {}", path.display()))?;
+            catalog.bundles.insert(0, translated);
+        }
+        Ok(catalog)
+    }
+
+    /// Format a message.  Unknown message IDs are rendered as-is.
+    pub fn format(&self, id: &str, args: &FluentArgs) -> String {
+        if self.ids_only {
+            return args.iter().fold(id.to_string(), |out, (name, value)| {
+                format!("{out} {name}={}", display_value(value))
+            });
+        }
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = vec![];
+                return bundle
+                    .format_pattern(pattern, Some(args), &mut errors)
+                    .into_owned();
+            }
+        }
+        id.to_string()
+    }
+}
+
+/// Set the catalog used for the rest of this invocation.  Messages formatted
+/// before this is called use English.
+pub fn init(catalog: Catalog) {
+    let _ = CATALOG.set(catalog);
+}
+
+/// Format a message from the current catalog.  Prefer the [`tr!`] macro.
+pub fn format(id: &str, args: &FluentArgs) -> String {
+    CATALOG.get_or_init(Catalog::english).format(id, args)
+}
+
+/// Format a user-facing message by ID, e.g.
+/// `tr!("pr-created", url = pr_url.as_str())`.
+macro_rules! tr {
+    ($id:literal $(, $name:ident = $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)*
+        $crate::i18n::format($id, &args)
+    }};
+}
+pub(crate) use tr;
+
+fn bundle(locale: &str, source: String) -> Result<FluentBundle<FluentResource>> {
+    let langid: LanguageIdentifier = locale
+        .parse()
+        .map_err(|_| anyhow!("Invalid locale: {locale}"))?;
+    let resource = FluentResource::try_new(source)
+        .map_err(|(_, errors)| anyhow!("Invalid message catalog: {errors:?}"))?;
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Isolation marks would end up in terminal output and error messages
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|errors| anyhow!("Duplicate messages in catalog: {errors:?}"))?;
+    Ok(bundle)
+}
+
+fn display_value(value: &FluentValue) -> String {
+    match value {
+        FluentValue::String(s) => s.to_string(),
+        FluentValue::Number(n) => n.as_string().into_owned(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english() {
+        let catalog = Catalog::english();
+        let mut args = FluentArgs::new();
+        args.set("url", "https://github.com/o/r/pull/1");
+        assert_eq!(
+            catalog.format("pr-created", &args),
+            "Created PR: https://github.com/o/r/pull/1"
+        );
+        assert_eq!(
+            catalog.format("restack-local-changes", &FluentArgs::new()),
+            "Cannot restack: commit has local changes.\nUse 'jr update -m \"<message>\"' to update with your changes."
+        );
+    }
+
+    #[test]
+    fn test_plurals() {
+        let catalog = Catalog::english();
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(
+            catalog.format("status-more-below", &args),
+            "… 1 more commit below (not checked)"
+        );
+        args.set("count", 3);
+        assert_eq!(
+            catalog.format("status-more-below", &args),
+            "… 3 more commits below (not checked)"
+        );
+    }
+
+    #[test]
+    fn test_ids_locale() {
+        let catalog = Catalog::load(IDS_LOCALE, None).unwrap();
+        let mut args = FluentArgs::new();
+        args.set("url", "u");
+        args.set("milestone", "v1");
+        assert_eq!(
+            catalog.format("status-milestone", &args),
+            "status-milestone milestone=v1 url=u"
+        );
+    }
+
+    #[test]
+    fn test_translation_falls_back_to_english() {
+        let dir = std::env::temp_dir().join(format!("jr-i18n-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("fr.ftl"), "pr-created = PR créée : { $url }\n").unwrap();
+        let catalog = Catalog::load("fr", Some(&dir)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut args = FluentArgs::new();
+        args.set("url", "u");
+        assert_eq!(catalog.format("pr-created", &args), "PR créée : u");
+        assert_eq!(
+            catalog.format("update-no-changes", &args),
+            "No changes detected"
+        );
+        assert!(Catalog::load("de", None).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! Branches pushed by `jj git push --change`, which duplicate jr's.
+//!
+//! jj names the branch it pushes for a change after the change ID, under
+//! its `git.push-bookmark-prefix` setting, e.g. `push-kxqpmnnvzzrt`.  A
+//! change pushed that way as well as with jr ends up with two branches, and
+//! often two PRs.  `jr status` points these out, and `jr adopt
+//! --from-jj-push` keeps jr's.
+
+use crate::clients::jujutsu::GITHUB_CHANGE_ID_LENGTH;
+
+/// jj's default `git.push-bookmark-prefix`.
+pub const DEFAULT_PREFIX: &str = "push-";
+
+/// The branches among `branches` that jj pushed for a change, given its full
+/// ID and the prefix jj names them with.  Branches named after fewer of the
+/// ID's characters than jr's would be too likely to match by chance.
+pub fn duplicates<'a>(change_id: &str, prefix: &str, branches: &'a [String]) -> Vec<&'a str> {
+    branches
+        .iter()
+        .filter(|branch| {
+            branch.strip_prefix(prefix).is_some_and(|short_id| {
+                short_id.len() >= GITHUB_CHANGE_ID_LENGTH && change_id.starts_with(short_id)
+            })
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates() {
+        let branches = [
+            "push-kxqpmnnvzzrt",
+            "push-kxqp",
+            "push-zzzzzzzzzzzz",
+            "me/kxqpmnnv",
+        ]
+        .map(String::from);
+        assert_eq!(
+            duplicates("kxqpmnnvzzrtoyuwlusqwsonmqxspytp", "push-", &branches),
+            ["push-kxqpmnnvzzrt"]
+        );
+        assert!(duplicates("kxqpmnnvzzrtoyuwlusqwsonmqxspytp", "jj/", &branches).is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+//! Limits on how much work jr does at once.
+//!
+//! Commands that elaborate several commits or PRs do so concurrently, and
+//! each runs git subprocesses and makes GitHub requests.  Unbounded, a large
+//! stack can overwhelm a weak machine or trip GitHub's secondary rate limits,
+//! so `--jobs` (`jr.jobs`) bounds how many are elaborated at once, and
+//! `--github-jobs` (`jr.githubJobs`) how many GitHub requests are in flight.
+
+use std::future::Future;
+
+use anyhow::Result;
+use futures_util::StreamExt as _;
+use futures_util::TryStreamExt as _;
+use futures_util::stream;
+
+/// Commits or PRs elaborated at once, by default.
+pub const DEFAULT_JOBS: usize = 8;
+/// GitHub requests in flight at once, by default.
+pub const DEFAULT_GITHUB_JOBS: usize = 8;
+
+/// Run futures with at most `jobs` at once, collecting their results in
+/// order, or the first error.
+pub async fn try_join_limited<T>(
+    futures: impl IntoIterator<Item = impl Future<Output = Result<T>>>,
+    jobs: usize,
+) -> Result<Vec<T>> {
+    stream::iter(futures)
+        .buffered(jobs.max(1))
+        .try_collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_try_join_limited() {
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        let futures = (0..10).map(|i| {
+            let (running, most) = (&running, &most);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                anyhow::Ok(i)
+            }
+        });
+        let results = try_join_limited(futures, 3).await.unwrap();
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+        assert_eq!(most.load(Ordering::SeqCst), 3);
+
+        let futures = [Ok(1), Err(anyhow::anyhow!("failed"))].map(|result| async { result });
+        assert!(try_join_limited(futures, 0).await.is_err());
+    }
+}
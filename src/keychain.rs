@@ -0,0 +1,51 @@
+//! The GitHub token in the system keychain: the macOS Keychain, Windows
+//! Credential Manager or the Secret Service on Linux.  It keeps the token out
+//! of plaintext git config.
+
+use anyhow::Context;
+use anyhow::Result;
+use keyring::Entry;
+use log::debug;
+
+/// Service and user names of the keychain entry holding the token.
+const SERVICE: &str = "jr";
+const USER: &str = "github-token";
+
+/// Run a keychain operation on its own thread.  The Secret Service client
+/// blocks on an async runtime of its own, which can't be nested in ours.
+fn run<T: Send>(f: impl FnOnce(&Entry) -> keyring::Result<T> + Send) -> keyring::Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| f(&Entry::new(SERVICE, USER)?))
+            .join()
+            .expect("Keychain thread shouldn't panic")
+    })
+}
+
+/// Get the token from the keychain, if it's there.  A missing or locked
+/// keychain, e.g. on a headless machine, is treated as having no token.
+pub fn get_token() -> Option<String> {
+    match run(|entry| entry.get_password()) {
+        Ok(token) => Some(token),
+        Err(keyring::Error::NoEntry) => None,
+        Err(err) => {
+            debug!("Can't read the system keychain: {err}");
+            None
+        }
+    }
+}
+
+/// Save the token to the keychain, replacing any there.
+pub fn set_token(token: &str) -> Result<()> {
+    run(|entry| entry.set_password(token))
+        .context("Failed to save the token to the system keychain")
+}
+
+/// Delete the token from the keychain, returning whether there was one.
+pub fn delete_token() -> Result<bool> {
+    match run(|entry| entry.delete_credential()) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(err).context("Failed to delete the token from the system keychain"),
+    }
+}
@@ -0,0 +1,56 @@
+//! Keeping a configured set of labels (`jr.label`) on every PR in a stack.
+//!
+//! jr remembers which labels it has applied (see [`crate::state`]), so that
+//! `jr label sync --prune` can remove those no longer configured without
+//! touching labels added by people.
+
+use std::collections::BTreeSet;
+
+/// The labels a PR should have, given its `current` labels, the `wanted`
+/// ones and, if pruning, the `stale` ones jr applied before but that are no
+/// longer configured.  `None` if its labels are already right.
+pub fn sync(
+    current: &[String],
+    wanted: &[String],
+    stale: &BTreeSet<String>,
+) -> Option<Vec<String>> {
+    let mut labels = current
+        .iter()
+        .filter(|label| !stale.contains(*label) || wanted.contains(label))
+        .cloned()
+        .collect::<Vec<_>>();
+    for label in wanted {
+        if !labels.contains(label) {
+            labels.push(label.clone());
+        }
+    }
+    (labels != current).then_some(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_sync() {
+        let wanted = strings(&["stacked", "team:infra"]);
+        let stale = BTreeSet::from(["team:old".to_string(), "stacked".to_string()]);
+        assert_eq!(
+            sync(&strings(&["bug"]), &wanted, &BTreeSet::new()),
+            Some(strings(&["bug", "stacked", "team:infra"]))
+        );
+        assert_eq!(
+            sync(&strings(&["team:old", "stacked", "bug"]), &wanted, &stale),
+            Some(strings(&["stacked", "bug", "team:infra"]))
+        );
+        assert_eq!(
+            sync(&strings(&["team:infra", "stacked"]), &wanted, &stale),
+            None
+        );
+        assert_eq!(sync(&strings(&["team:old"]), &[], &BTreeSet::new()), None);
+    }
+}
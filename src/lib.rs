@@ -1,10 +1,44 @@
 pub mod clients;
 
 mod app;
+pub mod audit;
+pub mod auto_ready;
+mod bisect;
+pub mod clock;
+pub mod codeowners;
 pub mod commands;
-mod commit;
+pub mod commit;
+pub mod completions;
 pub mod config;
+pub mod config_file;
 pub mod diff_utils;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod graph;
+pub mod i18n;
+pub mod jj_push;
+pub mod jobs;
+pub mod keychain;
+pub mod labels;
+pub mod limits;
+mod mcp;
+pub mod merge;
+mod message;
+pub mod model;
+mod notify;
+mod nudge;
+pub mod plan;
+pub mod policy;
+pub mod pr_size;
+pub mod provenance;
+pub mod review;
+pub mod rpc;
+pub mod run;
+pub mod stack;
+pub mod stack_comment;
+pub mod state;
+pub mod template;
+pub mod title;
 
 // Re-export App and Config from modules
 pub use app::App;
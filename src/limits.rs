@@ -0,0 +1,94 @@
+//! GitHub's limits on the length of PR fields.
+//!
+//! GitHub rejects a PR with a title or body that's too long, with a
+//! validation error that doesn't say which.  Titles are checked before
+//! anything is pushed.  Bodies are truncated with a marker instead, and the
+//! full text posted in comments, which have the same limit as bodies.
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::i18n::tr;
+
+/// Longest PR title GitHub accepts, in characters.
+pub const MAX_TITLE_LENGTH: usize = 256;
+/// Longest PR body or comment GitHub accepts, in characters.
+pub const MAX_BODY_LENGTH: usize = 65536;
+/// Room left in each part of a split body for a marker or heading.
+const RESERVED_LENGTH: usize = 1024;
+
+/// Fail if a title is too long for a PR.
+pub fn check_title(title: &str) -> Result<()> {
+    let length = title.chars().count();
+    if length > MAX_TITLE_LENGTH {
+        bail!(tr!(
+            "pr-title-too-long",
+            length = length,
+            max = MAX_TITLE_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Split a body that's too long for a PR into a truncated body, ending with
+/// `marker`, and the full text in parts that leave room for a heading in a
+/// comment.  Returns `None` if the body fits.
+pub fn split_body(body: &str, marker: &str) -> Option<(String, Vec<String>)> {
+    if body.chars().count() <= MAX_BODY_LENGTH {
+        return None;
+    }
+    let parts = split_text(body, MAX_BODY_LENGTH - RESERVED_LENGTH);
+    let truncated = format!("{}\n\n{marker}", parts[0].trim_end());
+    Some((truncated, parts))
+}
+
+/// Split text into parts of at most `size` characters, preferring to split
+/// after a line.
+fn split_text(text: &str, size: usize) -> Vec<String> {
+    let mut parts = vec![];
+    let mut rest = text;
+    while rest.chars().count() > size {
+        let end = rest
+            .char_indices()
+            .nth(size)
+            .map_or(rest.len(), |(index, _)| index);
+        let cut = rest[..end].rfind('\n').map_or(end, |index| index + 1);
+        parts.push(rest[..cut].to_string());
+        rest = &rest[cut..];
+    }
+    parts.push(rest.to_string());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_title() {
+        assert!(check_title(&"é".repeat(MAX_TITLE_LENGTH)).is_ok());
+        assert!(check_title(&"é".repeat(MAX_TITLE_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_split_text() {
+        assert_eq!(split_text("one\ntwo\nthree", 9), ["one\ntwo\n", "three"]);
+        assert_eq!(split_text("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(split_text("ééé\né", 2), ["éé", "é\n", "é"]);
+        assert_eq!(split_text("", 3), [""]);
+    }
+
+    #[test]
+    fn test_split_body() {
+        assert_eq!(split_body("short", "[truncated]"), None);
+
+        let line = format!("{}\n", "x".repeat(99));
+        let body = line.repeat(MAX_BODY_LENGTH / 100 + 1);
+        let (truncated, parts) = split_body(&body, "[truncated]").unwrap();
+        assert!(truncated.chars().count() <= MAX_BODY_LENGTH);
+        assert!(truncated.ends_with("x\n\n[truncated]"));
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts.concat(), body);
+        assert!(parts.iter().all(|part| part.ends_with('\n')));
+    }
+}
@@ -1,11 +1,37 @@
 use std::env;
+use std::io::IsTerminal as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
+use chrono::TimeDelta;
+use clap::CommandFactory;
 use clap::Parser;
 use clap::Subcommand;
+use clap_complete::Shell;
 use jr::App;
 use jr::Config;
+use jr::auto_ready::AutoReady;
 use jr::clients::github::GithubClient;
+use jr::clients::jujutsu::JujutsuClient;
+use jr::clock;
+use jr::commands::bisect_stack::BisectOptions;
+use jr::commands::cleanup::CleanupOptions;
+use jr::commands::create::CreateOptions;
+use jr::commands::dashboard::DashboardOptions;
+use jr::commands::navigate::Direction;
+use jr::commands::ping_reviewers::PingOptions;
+use jr::commands::status::StatusOptions;
+use jr::commands::submit::SubmitOptions;
+use jr::commands::sync::SyncOptions;
+use jr::completions;
+use jr::config;
+use jr::graph::GraphFormat;
+use jr::i18n;
+use jr::i18n::Catalog;
+use jr::policy::Policy;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::Layer as _;
 use tracing_subscriber::layer::SubscriberExt as _;
@@ -17,35 +43,473 @@ use tracing_subscriber::util::SubscriberInitExt as _;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Downgrade a blocking policy to a warning for this invocation
+    #[arg(long = "override", global = true, value_name = "POLICY")]
+    pub overrides: Vec<Policy>,
+    /// Commits or PRs to elaborate at once (defaults to jr.jobs, or 8)
+    #[arg(long, global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// GitHub requests to have in flight at once (defaults to jr.githubJobs,
+    /// or 8)
+    #[arg(long, global = true, value_name = "N")]
+    pub github_jobs: Option<usize>,
+    /// Most commits a stack may have (defaults to jr.stackLimit, or no limit)
+    #[arg(long, global = true, value_name = "N")]
+    pub limit: Option<usize>,
+    /// Don't ask before the first push from this clone
+    #[arg(long, short, global = true)]
+    pub yes: bool,
+    /// Revision stacks are built on (defaults to jr.trunkRevset, or trunk())
+    #[arg(long, global = true, value_name = "REVSET")]
+    pub trunk: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize configuration file in the current repository
-    Init,
+    Init {
+        /// Detect settings from the GitHub API and the gh CLI's login
+        #[arg(long)]
+        from_gh: bool,
+        /// Save the default branch to .jr.toml, to commit and share, and the
+        /// branch prefix to ~/.config/jr/config.toml (the token still goes to
+        /// .git/config)
+        #[arg(long)]
+        files: bool,
+    },
     /// Create a new PR (uses jj commit message)
     Create {
         /// Revision to use (defaults to @)
         #[arg(short, long, default_value = "@")]
         revision: String,
+        /// Request reviews from the code owners of the changed files
+        #[arg(long)]
+        request_owners: bool,
+        /// Milestone to assign the PR to (defaults to jr.defaultMilestone)
+        #[arg(long)]
+        milestone: Option<String>,
+        /// Number of the project to add the PR to (defaults to jr.defaultProject)
+        #[arg(long)]
+        project: Option<u64>,
+        /// User or team (org/team) to request a review from; may be repeated
+        /// (defaults to jr.defaultReviewers)
+        #[arg(long = "reviewer", value_name = "REVIEWER")]
+        reviewers: Vec<String>,
+        /// Label to add, besides those configured with jr.label; may be
+        /// repeated
+        #[arg(long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+        /// User to assign the PR to; may be repeated (defaults to
+        /// jr.defaultAssignees)
+        #[arg(long = "assignee", value_name = "USER")]
+        assignees: Vec<String>,
+        /// Succeed without changes if the PR already exists and is up to date
+        #[arg(long)]
+        idempotent: bool,
+        /// Only prepare the PR branch locally, without pushing it or
+        /// contacting GitHub (see `jr push`)
+        #[arg(long)]
+        no_push: bool,
+        /// Create a PR for every commit in the stack without one, from the
+        /// bottom up
+        #[arg(long, visible_alias = "all")]
+        stack: bool,
+        /// Open the PR ready for review rather than as a draft (defaults to
+        /// the opposite of jr.createDraft)
+        #[arg(long)]
+        ready: bool,
+        /// Show what would be pushed and created, without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Update an existing PR with local changes
     Update {
         /// Revision to use (defaults to @)
         #[arg(short, long, default_value = "@")]
         revision: String,
-        /// Commit message describing the changes
+        /// Commit message describing the changes (defaults to composing one
+        /// in your editor)
         #[arg(short, long)]
-        message: String,
+        message: Option<String>,
+        /// Only prepare the PR branch locally, without pushing it or
+        /// contacting GitHub (see `jr push`)
+        #[arg(long)]
+        no_push: bool,
+        /// Show what would be pushed, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Push the PR branches prepared with --no-push, and open or update their
+    /// PRs
+    Push {
+        /// Revision in the stack (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+    /// Create, update or restack the PRs of a contiguous range of the stack as
+    /// needed, leaving the commits above it alone
+    Submit {
+        /// Range of commits to submit, e.g. a::c (defaults to the stack up to
+        /// @)
+        #[arg(short, long, default_value = "::@")]
+        revision: String,
+        /// Commit message for the PRs updated (defaults to composing one in
+        /// your editor for each)
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Only submit the commits of the range modified after a date (e.g.
+        /// 2024-01-31, "3 days ago") or descending from a revision
+        #[arg(long, value_name = "REV|DATE")]
+        since: Option<String>,
+        /// Carry on from where an interrupted submit stopped
+        #[arg(long, conflicts_with_all = ["revision", "since"])]
+        resume: bool,
     },
     /// Restack an existing PR on updated parent (only works if no local changes)
     Restack {
         /// Revision to use (defaults to @)
         #[arg(short, long, default_value = "@")]
         revision: String,
+        /// Restack every PR in the stack that needs it, from the bottom up
+        #[arg(long)]
+        all: bool,
+        /// Show what would be pushed, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge the bottom PR of a stack, and restack the PRs above it
+    #[command(visible_alias = "land")]
+    Merge {
+        /// Revision in the stack (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Carry on from where an interrupted or stopped merge left off
+        #[arg(long, conflicts_with = "revision")]
+        resume: bool,
+    },
+    /// Mark a draft PR as ready for review
+    Ready {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+    /// Convert a PR back to a draft, e.g. when CI reveals problems
+    Draft {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Convert every open PR in the stack, all at once
+        #[arg(long)]
+        stack: bool,
+    },
+    /// Open a PR in the browser
+    Open {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Open every PR in the stack, from the bottom up
+        #[arg(long)]
+        stack: bool,
+        /// Print the URLs instead of opening them
+        #[arg(long)]
+        print: bool,
+    },
+    /// Keep only jr's PR branch for each change in the stack that another
+    /// tool also pushed a branch for
+    Adopt {
+        /// Revision in the stack (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Adopt the branches pushed by `jj git push --change`: close their
+        /// PRs in favor of jr's, creating jr's if needed, and delete them
+        #[arg(long, required = true)]
+        from_jj_push: bool,
+    },
+    /// Print a diagram of the stack and its PRs
+    Graph {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Diagram language
+        #[arg(long, value_enum, default_value_t = GraphFormat::Mermaid)]
+        format: GraphFormat,
+        /// Also embed the diagram, in Mermaid, in the top PR's description
+        #[arg(long)]
+        embed: bool,
     },
     /// Show status of stacked PRs
-    Status,
+    Status {
+        /// Only check and show this many commits from the top of the stack
+        #[arg(long)]
+        depth: Option<usize>,
+        /// Only check and show the commits at the top of the stack modified
+        /// after a date (e.g. 2024-01-31, "3 days ago") or descending from a
+        /// revision
+        #[arg(long, value_name = "REV|DATE")]
+        since: Option<String>,
+        /// Also warn about PRs someone else changed, e.g. retargeted or marked
+        /// ready for review on the web
+        #[arg(short, long)]
+        verbose: bool,
+        /// Look up every PR on GitHub again, rather than reusing those seen
+        /// by an earlier command whose branches haven't moved since
+        #[arg(long)]
+        refresh: bool,
+        /// Show every stack in the repository, not just the current one
+        #[arg(long)]
+        all: bool,
+        /// Redraw the status every this many seconds, marking what changed
+        #[arg(long, value_name = "SECONDS")]
+        watch: Option<u64>,
+    },
+    /// Move to the top of the stack
+    Top,
+    /// Move to the bottom of the stack
+    Bottom,
+    /// Move to the next (child) commit in the stack
+    Next,
+    /// Move to the previous (parent) commit in the stack
+    Prev,
+    /// Show the code owners of the files changed by a revision
+    Owners {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+    /// List the files changed by a revision, flagging overlaps with other
+    /// commits in the stack and with other open PRs
+    Files {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+    /// Show a revision's diff, or the combined diff of its whole stack
+    /// against trunk
+    Diff {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Show the combined diff of the stack, marking each file with the
+        /// commits that change it
+        #[arg(long)]
+        stack: bool,
+    },
+    /// Show blame for a file, highlighting lines owned by PRs in the stack
+    Blame {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// File to blame
+        file: String,
+    },
+    /// Find the first commit in a stack that fails CI, or a local command
+    BisectStack {
+        /// Top of the stack to bisect (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Run this command in a checkout of each commit instead of using CI
+        #[arg(long)]
+        command: Option<String>,
+        /// Give up waiting for CI on a commit after this many minutes
+        #[arg(long, value_name = "MINUTES", default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Edit a revision's description, prefilled with a suggestion from
+    /// jr.describeCommand if it's configured
+    Describe {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+    /// Show someone else's stack, with review statuses and a suggested review
+    /// order, using only the GitHub API
+    ReviewStack {
+        /// Number or URL of the top PR in the stack
+        pr: String,
+    },
+    /// Check out a PR for local testing, as a new change on top of its base
+    /// branch with just that PR's changes
+    CheckoutPr {
+        /// Number or URL of the PR
+        pr: String,
+    },
+    /// Nudge the reviewers of your PRs that have been waiting too long for
+    /// review (see jr.nudgeAfterDays)
+    PingReviewers {
+        /// Re-request reviews from the reviewers
+        #[arg(long)]
+        re_request: bool,
+        /// Don't post a nudge comment
+        #[arg(long)]
+        no_comment: bool,
+        /// Nudge at most this many PRs
+        #[arg(long, default_value_t = 10)]
+        max: usize,
+        /// Only list the PRs that would be nudged
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show all of your stacks, with each PR's status, review decision, CI
+    /// state and age
+    Dashboard {
+        /// Redraw the dashboard every this many seconds
+        #[arg(long, value_name = "SECONDS")]
+        watch: Option<u64>,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Fetch the default branch, abandon commits whose PRs were merged and
+    /// rebase the rest of the stack onto trunk, then mark draft PRs ready as
+    /// set by jr.autoReady
+    Sync {
+        /// Revision in the stack (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// When to mark draft PRs ready, instead of jr.autoReady
+        #[arg(long, value_name = "WHEN")]
+        auto_ready: Option<AutoReady>,
+        /// Sync again every this many seconds, until interrupted
+        #[arg(long, value_name = "SECONDS")]
+        watch: Option<u64>,
+    },
+    /// Delete the remote branches of your merged and closed PRs
+    Cleanup {
+        /// Only list the branches that would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Only delete branches whose tips are older than this, e.g. 30d (or
+        /// 12h, 2w), including branches without a PR
+        #[arg(long, value_name = "AGE", value_parser = clock::parse_age)]
+        older_than: Option<TimeDelta>,
+        /// Never delete this branch, or branches matching this regex; may be
+        /// repeated (adds to jr.protectBranch)
+        #[arg(long, value_name = "PATTERN")]
+        protect: Vec<String>,
+    },
+    /// Prune jr's own state: PR metadata for merged, closed or abandoned
+    /// changes, old audit log entries, a run that can't be resumed and notes
+    /// on commits that are gone
+    Gc {
+        /// Keep audit log entries from this many days
+        #[arg(long, value_name = "DAYS", default_value_t = 90)]
+        keep_days: u32,
+        /// Only report what would be pruned
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Push PR branches to your fork of the repository, creating it if needed
+    Fork {
+        /// Name of the git remote to add for the fork
+        #[arg(long, default_value = "fork")]
+        remote: String,
+    },
+    /// Serve jr's operations over JSON-RPC on a local port, for editor
+    /// plugins
+    Serve {
+        /// Port to listen on (0 picks a free port)
+        #[arg(long, default_value_t = 0)]
+        port: u16,
+        /// Check for status changes to send to clients every this many
+        /// seconds
+        #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+        interval: u64,
+    },
+    /// Serve jr's operations to coding assistants over the Model Context
+    /// Protocol on stdin and stdout
+    Mcp {
+        /// Allow creating and updating PRs, after confirmation
+        #[arg(long)]
+        allow_writes: bool,
+    },
+    /// Summarize your jr activity from the local audit log, as markdown
+    Report {
+        /// Number of days to cover
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Show which change a PR branch commit was pushed from
+    Why {
+        /// Git revision, or PR branch name
+        revision: String,
+    },
+    /// Walk through the jr workflow in a sandbox repository
+    Tutorial,
+    /// Check jr's configuration and environment for problems
+    Doctor,
+    /// Print a shell completion script
+    Completions {
+        /// Shell to complete for
+        shell: Shell,
+        /// Also complete revision arguments with revset aliases from jr and
+        /// jj config, and common revset functions (bash and fish only)
+        #[arg(long)]
+        dynamic: bool,
+    },
+    /// Print revision candidates, for dynamic completions
+    #[command(hide = true)]
+    RevsetCandidates,
+    /// Manage a single PR
+    Pr {
+        #[command(subcommand)]
+        command: PrCommands,
+    },
+    /// Manage the labels on the stack's PRs
+    Label {
+        #[command(subcommand)]
+        command: LabelCommands,
+    },
+    /// Manage the GitHub token
+    #[command(alias = "auth")]
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PrCommands {
+    /// Point a PR at another base branch, e.g. a hotfix branch, until reset
+    EditBase {
+        /// Revision to use (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Branch to use as the PR's base
+        #[arg(long, required_unless_present = "reset", conflicts_with = "reset")]
+        base: Option<String>,
+        /// Point the PR back at its usual base: its parent's PR branch or
+        /// the default branch
+        #[arg(long)]
+        reset: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LabelCommands {
+    /// Add the labels configured with jr.label to every open PR in the stack
+    Sync {
+        /// Revision in the stack (defaults to @)
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+        /// Also remove labels jr added before that are no longer configured
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TokenCommands {
+    /// Replace the GitHub token with a new one, read from stdin, checking it
+    /// works before and after saving it
+    #[command(alias = "set")]
+    Rotate {
+        /// Git config scope, or the system keychain, to save the token to
+        /// (defaults to where the current token is read from)
+        #[arg(long, value_parser = ["local", "global", "worktree", "keychain"])]
+        scope: Option<String>,
+    },
+    /// Delete the token from the system keychain
+    Delete,
 }
 
 #[tokio::main]
@@ -55,46 +519,554 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Handle Init command specially - it creates the config
-    if matches!(cli.command, Some(Commands::Init)) {
+    if let Some(Commands::Init { from_gh, files }) = cli.command {
         // For init, we don't need to load config first
         let temp_config = Config::default_for_tests(); // Placeholder, not used
-        let temp_github =
-            GithubClient::new(temp_config.github_token.clone(), env::current_dir()?).await?;
+        let temp_github = GithubClient::new(
+            temp_config.github_token.clone(),
+            env::current_dir()?,
+            config::load_github_host()?,
+            &config::load_remote()?,
+        )
+        .await?;
         let app = App::new(temp_config, temp_github, env::current_dir()?);
-        app.cmd_init(&mut std::io::stdout()).await?;
+        if from_gh {
+            app.cmd_init_from_gh(files, &mut std::io::stdout()).await?;
+        } else {
+            app.cmd_init(files, &mut std::io::stdout()).await?;
+        }
         return Ok(());
     }
 
+    // Completions shouldn't need jr to be configured
+    match &cli.command {
+        Some(Commands::Completions { shell, dynamic }) => {
+            let mut command = Cli::command();
+            clap_complete::generate(*shell, &mut command, "jr", &mut std::io::stdout());
+            if *dynamic {
+                write!(
+                    std::io::stdout(),
+                    "{}",
+                    completions::dynamic_script(*shell, "jr")?
+                )?;
+            }
+            return Ok(());
+        }
+        // The tutorial runs in a sandbox, with a config of its own
+        Some(Commands::Tutorial) => {
+            return App::cmd_tutorial(&mut std::io::stdin().lock(), &mut std::io::stdout()).await;
+        }
+        Some(Commands::RevsetCandidates) => {
+            let jr_aliases = config::load_revset_aliases()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+            let jj_config = JujutsuClient::new(env::current_dir()?)
+                .config_list("revset-aliases")
+                .await
+                .unwrap_or_default();
+            for candidate in completions::revset_candidates(&jr_aliases, &jj_config) {
+                writeln!(std::io::stdout(), "{candidate}")?;
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // For all other commands, load config first
-    let config = Config::load()?;
-    let github = GithubClient::new(config.github_token.clone(), env::current_dir()?).await?;
+    // `jr token` sets the token, so mustn't need one already
+    let mut config = if matches!(cli.command, Some(Commands::Token { .. })) {
+        Config::load_without_token()?
+    } else {
+        Config::load()?
+    };
+    for policy in cli.overrides {
+        config.policies.add_override(policy);
+    }
+    config.jobs = cli.jobs.or(config.jobs);
+    config.github_jobs = cli.github_jobs.or(config.github_jobs);
+    config.stack_limit = cli.limit.or(config.stack_limit);
+    config.trunk_revset = cli.trunk.or(config.trunk_revset);
+    if let Some(Commands::Cleanup { protect, .. }) = &cli.command {
+        config.protected_branches.extend(protect.iter().cloned());
+    }
+    if let Some(locale) = &config.locale {
+        let locale_dir = config.locale_dir.as_deref().map(Path::new);
+        i18n::init(Catalog::load(locale, locale_dir)?);
+    }
+    let mut command = cli.command;
+    if let Some(command) = &mut command {
+        resolve_revset_aliases(command, &config);
+    }
+    let mut github = GithubClient::new(
+        config.github_token.clone(),
+        env::current_dir()?,
+        config.github_host(),
+        config.remote(),
+    )
+    .await?;
+    if let Some(remote) = &config.push_remote {
+        github = github
+            .with_push_remote(&env::current_dir()?, remote)
+            .await?;
+    }
+    github = github.with_max_requests(config.github_jobs());
+    if let Some(timeout) = config.http_timeout {
+        github = github.with_timeout(Duration::from_secs(timeout))?;
+    }
+    if let Some(ca_bundle) = &config.ca_bundle {
+        github = github.with_ca_bundle(Path::new(ca_bundle))?;
+    }
+    if let Some(token) = &config.github_write_token {
+        github = github.with_write_token(token.clone());
+    }
+    // Refuse any change to git or GitHub, as a backstop for the commands'
+    // own dry runs
+    if matches!(
+        command,
+        Some(
+            Commands::Create { dry_run: true, .. }
+                | Commands::Update { dry_run: true, .. }
+                | Commands::Restack { dry_run: true, .. }
+        )
+    ) {
+        github = github.with_dry_run();
+    }
+    config.expand_branch_prefix(github.owner(), github.repo());
     let app = App::new(config, github, env::current_dir()?);
 
-    match cli.command {
-        Some(Commands::Init) => unreachable!(), // Already handled above
-        Some(Commands::Create { revision }) => {
-            app.cmd_create(&revision, &mut std::io::stdout()).await?
+    // Catch config mistakes up front, rather than partway through a command.
+    // `jr doctor` reports the same problems without stopping.
+    if !matches!(command, Some(Commands::Doctor | Commands::Token { .. })) {
+        if std::io::stdin().is_terminal() && !app.is_dry_run() {
+            app.offer_to_push_default_branch(&mut std::io::stdin().lock(), &mut std::io::stdout())
+                .await?;
+        }
+        app.validate_config().await?;
+    }
+
+    // Make sure the user knows where PR branches go before the first push
+    if matches!(
+        command,
+        Some(
+            Commands::Create {
+                no_push: false,
+                dry_run: false,
+                ..
+            } | Commands::Update {
+                no_push: false,
+                dry_run: false,
+                ..
+            } | Commands::Push { .. }
+                | Commands::Restack { dry_run: false, .. }
+                | Commands::Adopt { .. }
+                | Commands::Submit { .. }
+                | Commands::Merge { .. }
+                | Commands::BisectStack { .. }
+                | Commands::Cleanup { dry_run: false, .. }
+                | Commands::Serve { .. }
+        )
+    ) {
+        app.confirm_first_push(
+            cli.yes,
+            &mut std::io::stdin().lock(),
+            &mut std::io::stdout(),
+        )
+        .await?;
+    }
+
+    // `jr mcp` can't ask, since stdin and stdout carry the protocol, so its
+    // writes are refused until a push is confirmed, here with --yes or else
+    // by another command
+    if cli.yes && matches!(command, Some(Commands::Mcp { allow_writes: true })) {
+        app.confirm_first_push(true, &mut std::io::empty(), &mut std::io::sink())
+            .await?;
+    }
+
+    // Overlap fetching our PRs with jj's work, for commands that need them.
+    // `jr status` looks up its stack's PRs with GraphQL instead, which also
+    // tells it whether they can be merged.
+    if matches!(
+        command,
+        Some(
+            Commands::Create { no_push: false, .. }
+                | Commands::Update { no_push: false, .. }
+                | Commands::Push { .. }
+                | Commands::Restack { .. }
+                | Commands::Submit { .. }
+                | Commands::Merge { .. }
+                | Commands::Ready { .. }
+                | Commands::Draft { .. }
+                | Commands::Open { .. }
+                | Commands::Graph { .. }
+                | Commands::Adopt { .. }
+                | Commands::Label { .. }
+                | Commands::Pr { .. }
+                | Commands::Top
+                | Commands::Bottom
+                | Commands::Next
+                | Commands::Prev
+                | Commands::Dashboard { .. }
+                | Commands::Sync { .. }
+        )
+    ) {
+        app.start_prefetch().await;
+    }
+
+    match command {
+        Some(Commands::Init { .. }) => unreachable!(), // Already handled above
+        Some(Commands::Completions { .. } | Commands::RevsetCandidates | Commands::Tutorial) => {
+            unreachable!()
+        }
+        Some(Commands::Create {
+            revision,
+            request_owners,
+            milestone,
+            project,
+            reviewers,
+            labels,
+            assignees,
+            idempotent,
+            no_push,
+            stack,
+            ready,
+            dry_run: _,
+        }) => {
+            let options = CreateOptions {
+                request_owners,
+                milestone,
+                project,
+                reviewers,
+                labels,
+                assignees,
+                idempotent,
+                no_push,
+                ready,
+            };
+            if stack {
+                app.cmd_create_stack(&revision, &options, &mut std::io::stdout())
+                    .await?
+            } else {
+                app.cmd_create(&revision, &options, &mut std::io::stdout())
+                    .await?
+            }
+        }
+        Some(Commands::Update {
+            revision,
+            message,
+            no_push,
+            dry_run: _,
+        }) => {
+            app.cmd_update(
+                &revision,
+                message.as_deref(),
+                no_push,
+                &mut std::io::stdout(),
+            )
+            .await?
+        }
+        Some(Commands::Push { revision }) => {
+            app.cmd_push(&revision, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Submit {
+            revision,
+            message,
+            since,
+            resume,
+        }) => {
+            let options = SubmitOptions {
+                message,
+                since,
+                resume,
+            };
+            app.cmd_submit(&revision, &options, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Restack { revision, all, .. }) => {
+            if all {
+                app.cmd_restack_all(&revision, &mut std::io::stdout())
+                    .await?
+            } else {
+                app.cmd_restack(&revision, &mut std::io::stdout()).await?
+            }
+        }
+        Some(Commands::Merge { revision, resume }) => {
+            app.cmd_merge(&revision, resume, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Ready { revision }) => {
+            app.cmd_ready(&revision, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Draft { revision, stack }) => {
+            app.cmd_draft(&revision, stack, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Open {
+            revision,
+            stack,
+            print,
+        }) => {
+            app.cmd_open(&revision, stack, print, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Adopt { revision, .. }) => {
+            app.cmd_adopt(&revision, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Graph {
+            revision,
+            format,
+            embed,
+        }) => {
+            app.cmd_graph(&revision, format, embed, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Status {
+            depth,
+            since,
+            verbose,
+            refresh,
+            all,
+            watch,
+        }) => {
+            let options = StatusOptions {
+                depth,
+                since,
+                verbose,
+                refresh,
+                all,
+                watch,
+            };
+            app.cmd_status(&options, &mut std::io::stdout()).await?
+        }
+        None => {
+            app.cmd_status(&StatusOptions::default(), &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Top) => {
+            app.cmd_navigate(Direction::Top, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Bottom) => {
+            app.cmd_navigate(Direction::Bottom, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Next) => {
+            app.cmd_navigate(Direction::Next, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Prev) => {
+            app.cmd_navigate(Direction::Prev, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Owners { revision }) => {
+            app.cmd_owners(&revision, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Files { revision }) => {
+            app.cmd_files(&revision, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Diff { revision, stack }) => {
+            app.cmd_diff(&revision, stack, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Blame { revision, file }) => {
+            app.cmd_blame(&revision, &file, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::BisectStack {
+            revision,
+            command,
+            timeout,
+        }) => {
+            let options = BisectOptions { command, timeout };
+            app.cmd_bisect_stack(&revision, &options, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Describe { revision }) => app.cmd_describe(&revision).await?,
+        Some(Commands::ReviewStack { pr }) => {
+            app.cmd_review_stack(&pr, &mut std::io::stdout()).await?
+        }
+        Some(Commands::CheckoutPr { pr }) => {
+            app.cmd_checkout_pr(&pr, &mut std::io::stdout()).await?
         }
-        Some(Commands::Update { revision, message }) => {
-            app.cmd_update(&revision, &message, &mut std::io::stdout())
+        Some(Commands::PingReviewers {
+            re_request,
+            no_comment,
+            max,
+            dry_run,
+        }) => {
+            let options = PingOptions {
+                comment: !no_comment,
+                re_request,
+                max,
+                dry_run,
+            };
+            app.cmd_ping_reviewers(&options, &mut std::io::stdout())
                 .await?
         }
-        Some(Commands::Restack { revision }) => {
-            app.cmd_restack(&revision, &mut std::io::stdout()).await?
+        Some(Commands::Dashboard { watch, json }) => {
+            let options = DashboardOptions { watch, json };
+            app.cmd_dashboard(&options, &mut std::io::stdout()).await?
         }
-        Some(Commands::Status) | None => app.cmd_status(&mut std::io::stdout()).await?,
+        Some(Commands::Sync {
+            revision,
+            auto_ready,
+            watch,
+        }) => {
+            let options = SyncOptions { auto_ready, watch };
+            app.cmd_sync(&revision, &options, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Cleanup {
+            dry_run,
+            older_than,
+            ..
+        }) => {
+            let options = CleanupOptions {
+                dry_run,
+                older_than,
+            };
+            app.cmd_cleanup(&options, &mut std::io::stdout()).await?
+        }
+        Some(Commands::Gc { keep_days, dry_run }) => {
+            app.cmd_gc(keep_days, dry_run, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Fork { remote }) => app.cmd_fork(&remote, &mut std::io::stdout()).await?,
+        Some(Commands::Serve { port, interval }) => {
+            return Arc::new(app)
+                .cmd_serve(port, interval, &mut std::io::stdout())
+                .await;
+        }
+        Some(Commands::Mcp { allow_writes }) => {
+            app.cmd_mcp(
+                allow_writes,
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout(),
+            )
+            .await?
+        }
+        Some(Commands::Report { days }) => app.cmd_report(days, &mut std::io::stdout()).await?,
+        Some(Commands::Why { revision }) => app.cmd_why(&revision, &mut std::io::stdout()).await?,
+        Some(Commands::Doctor) => app.cmd_doctor(&mut std::io::stdout()).await?,
+        Some(Commands::Pr {
+            command: PrCommands::EditBase { revision, base, .. },
+        }) => {
+            app.cmd_edit_base(&revision, base.as_deref(), &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Label {
+            command: LabelCommands::Sync { revision, prune },
+        }) => {
+            app.cmd_label_sync(&revision, prune, &mut std::io::stdout())
+                .await?
+        }
+        Some(Commands::Token {
+            command: TokenCommands::Rotate { scope },
+        }) => {
+            app.cmd_token_rotate(
+                scope.as_deref(),
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout(),
+            )
+            .await?
+        }
+        Some(Commands::Token {
+            command: TokenCommands::Delete,
+        }) => app.cmd_token_delete(&mut std::io::stdout())?,
+    }
+
+    // Sum up what a dry run would have done, from how long real runs' pushes
+    // and requests took
+    if app.is_dry_run() {
+        app.report_plan(&mut std::io::stdout()).await?;
+    } else {
+        app.record_timings().await;
     }
 
+    // GitHub redirects requests for a renamed or transferred repository, so
+    // the command may have worked, but not everything follows redirects
+    app.offer_to_update_moved_remote(
+        std::io::stdin().is_terminal() && !app.is_dry_run(),
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+    )
+    .await?;
+
     Ok(())
 }
 
+/// Expand revset aliases from jr config in a command's revision argument.
+fn resolve_revset_aliases(command: &mut Commands, config: &Config) {
+    match command {
+        Commands::Create { revision, .. }
+        | Commands::Update { revision, .. }
+        | Commands::Push { revision }
+        | Commands::Restack { revision, .. }
+        | Commands::Merge { revision, .. }
+        | Commands::Ready { revision }
+        | Commands::Draft { revision, .. }
+        | Commands::Open { revision, .. }
+        | Commands::Graph { revision, .. }
+        | Commands::Adopt { revision, .. }
+        | Commands::Label {
+            command: LabelCommands::Sync { revision, .. },
+        }
+        | Commands::Pr {
+            command: PrCommands::EditBase { revision, .. },
+        }
+        | Commands::Owners { revision }
+        | Commands::Files { revision }
+        | Commands::Diff { revision, .. }
+        | Commands::Blame { revision, .. }
+        | Commands::BisectStack { revision, .. }
+        | Commands::Sync { revision, .. }
+        | Commands::Describe { revision } => *revision = config.resolve_revset(revision),
+        Commands::Submit {
+            revision, since, ..
+        } => {
+            *revision = config.resolve_revset(revision);
+            if let Some(since) = since {
+                *since = config.resolve_revset(since);
+            }
+        }
+        Commands::Status {
+            since: Some(since), ..
+        } => *since = config.resolve_revset(since),
+        Commands::Init { .. }
+        | Commands::Status { .. }
+        | Commands::Top
+        | Commands::Bottom
+        | Commands::Next
+        | Commands::Prev
+        | Commands::Tutorial
+        | Commands::Doctor
+        | Commands::ReviewStack { .. }
+        | Commands::CheckoutPr { .. }
+        | Commands::PingReviewers { .. }
+        | Commands::Dashboard { .. }
+        | Commands::Cleanup { .. }
+        | Commands::Gc { .. }
+        | Commands::Fork { .. }
+        | Commands::Serve { .. }
+        | Commands::Mcp { .. }
+        | Commands::Report { .. }
+        | Commands::Why { .. }
+        | Commands::Completions { .. }
+        | Commands::RevsetCandidates
+        | Commands::Token { .. } => {}
+    }
+}
+
 fn setup_logging() -> anyhow::Result<()> {
     let timer = tracing_subscriber::fmt::time::ChronoLocal::new("%H:%M:%S%.3f".into());
     let format = tracing_subscriber::fmt::format().with_timer(timer);
     let filter = tracing_subscriber::EnvFilter::builder()
         .with_default_directive(LevelFilter::INFO.into())
         .from_env()?;
+    // Keep stdout for output, which `jr mcp` uses as its protocol channel
     let subscriber = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
         .event_format(format)
         .with_filter(filter);
     tracing_subscriber::registry().with(subscriber).init();
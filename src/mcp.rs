@@ -0,0 +1,218 @@
+//! The Model Context Protocol, for coding assistants (see `jr mcp`).
+//!
+//! Messages are JSON-RPC 2.0, one per line on stdin and stdout, and each
+//! [`Method`] is a tool.  Tools that change PRs are only available when the
+//! server allows writes, and then only do anything when called with
+//! `confirm: true`.  Without it they return a preview, so the assistant has
+//! to check with the user first.
+
+use serde_json::Value;
+use serde_json::json;
+
+use crate::rpc::Method;
+
+/// The protocol version we implement.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// The result of the `initialize` request.
+pub fn initialize_result() -> Value {
+    json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "jr", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+/// The result of the `tools/list` request.
+pub fn tool_list(allow_writes: bool) -> Value {
+    let revision = json!({
+        "type": "string",
+        "description": "A jj revision; defaults to the working copy, @",
+    });
+    let confirm = json!({
+        "type": "boolean",
+        "description": "Set only after the user has approved the preview returned without it",
+    });
+    let tools = [
+        (
+            "status",
+            "Get the sync status of each commit in a stack and its PR, from the bottom up",
+            json!({
+                "revision": revision,
+                "refresh": { "type": "boolean", "description": "Fetch PRs from GitHub again" },
+            }),
+            vec![],
+        ),
+        (
+            "pr",
+            "Get the GitHub PR for a revision, or null if it has none",
+            json!({ "revision": revision }),
+            vec![],
+        ),
+        (
+            "diff",
+            "Get a revision's diff, and the diff of its PR if it has one",
+            json!({ "revision": revision }),
+            vec![],
+        ),
+        (
+            "create",
+            "Create a PR for a revision",
+            json!({ "revision": revision, "confirm": confirm }),
+            vec![],
+        ),
+        (
+            "update",
+            "Update a revision's PR with its changes, described by a message",
+            json!({
+                "revision": revision,
+                "message": { "type": "string", "description": "Describes the changes" },
+                "confirm": confirm,
+            }),
+            vec!["message"],
+        ),
+        (
+            "restack",
+            "Bring a revision's PR up to date with its updated base branch",
+            json!({ "revision": revision, "confirm": confirm }),
+            vec![],
+        ),
+        (
+            "submit",
+            "Create, update or restack the PRs of a range of a stack as needed",
+            json!({
+                "revision": {
+                    "type": "string",
+                    "description": "A jj revset of commits to submit; defaults to the stack up to the working copy, ::@",
+                },
+                "message": { "type": "string", "description": "Describes the changes to PRs updated" },
+                "confirm": confirm,
+            }),
+            vec!["message"],
+        ),
+    ];
+    let tools = tools
+        .into_iter()
+        .filter(|(_, _, properties, _)| allow_writes || properties.get("confirm").is_none())
+        .map(|(name, description, properties, required)| {
+            json!({
+                "name": name,
+                "description": description,
+                "inputSchema": {
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "tools": tools })
+}
+
+/// A call of a tool.
+#[derive(Debug, PartialEq)]
+pub struct ToolCall {
+    pub method: Method,
+    /// Whether the user has confirmed a change to PRs.
+    pub confirmed: bool,
+}
+
+/// Parse the parameters of a `tools/call` request.
+pub fn parse_tool_call(params: &Value, allow_writes: bool) -> Result<ToolCall, String> {
+    let name = params["name"].as_str().unwrap_or_default();
+    if !Method::NAMES.contains(&name) {
+        return Err(format!("Unknown tool: {name}"));
+    }
+    let mut arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let confirmed = arguments
+        .as_object_mut()
+        .and_then(|arguments| arguments.remove("confirm"))
+        .and_then(|confirm| confirm.as_bool())
+        .unwrap_or(false);
+    let method = Method::new(name, arguments).map_err(|err| err.to_string())?;
+    if method.is_mutating() && !allow_writes {
+        return Err(format!(
+            "Tool {name} changes PRs, which needs jr mcp --allow-writes"
+        ));
+    }
+    Ok(ToolCall { method, confirmed })
+}
+
+/// What an unconfirmed tool call would do to the PR for a commit with the
+/// given title.
+pub fn preview(method: &Method, title: &str) -> String {
+    let action = match method {
+        Method::Create(_) => "create a PR for".to_string(),
+        Method::Update(params) => format!("update the PR with message {:?} for", params.message),
+        Method::Restack(_) => "restack the PR for".to_string(),
+        Method::Submit(_) => "create, update or restack the PRs for".to_string(),
+        Method::Status(_) | Method::Pr(_) | Method::Diff(_) => "read".to_string(),
+    };
+    format!(
+        "This would {action} revision {} ({title:?}). Nothing has been changed. \
+         Ask the user to confirm, then call this tool again with confirm: true.",
+        method.revision()
+    )
+}
+
+/// The result of a `tools/call` request.
+pub fn tool_result(text: &str, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RevisionParams;
+
+    fn tool_names(allow_writes: bool) -> Vec<String> {
+        tool_list(allow_writes)["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_tool_list() {
+        assert_eq!(tool_names(false), ["status", "pr", "diff"]);
+        assert_eq!(tool_names(true), Method::NAMES);
+    }
+
+    #[test]
+    fn test_parse_tool_call() {
+        let call = json!({ "name": "create", "arguments": { "revision": "x", "confirm": true } });
+        assert_eq!(
+            parse_tool_call(&call, true).unwrap(),
+            ToolCall {
+                method: Method::Create(RevisionParams {
+                    revision: "x".to_string()
+                }),
+                confirmed: true,
+            }
+        );
+        assert!(parse_tool_call(&call, false).is_err());
+
+        let call = json!({ "name": "pr" });
+        assert!(!parse_tool_call(&call, false).unwrap().confirmed);
+        assert!(parse_tool_call(&json!({ "name": "land" }), true).is_err());
+        let call = json!({ "name": "update", "arguments": {} });
+        assert!(parse_tool_call(&call, true).is_err());
+    }
+
+    #[test]
+    fn test_preview() {
+        let call = json!({ "name": "update", "arguments": { "message": "Fix it" } });
+        let call = parse_tool_call(&call, true).unwrap();
+        assert_eq!(
+            preview(&call.method, "Add a thing"),
+            "This would update the PR with message \"Fix it\" for revision @ (\"Add a thing\"). \
+             Nothing has been changed. Ask the user to confirm, then call this tool again with \
+             confirm: true."
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Choosing how `jr merge` merges a PR.
+//!
+//! Repositories can disallow any of GitHub's merge methods.  We pick the
+//! configured method (`jr.mergeMethod`) if it's allowed, failing up front if
+//! not, rather than with GitHub's 405 partway through.  Without one, we
+//! prefer squashing, as PR branches are a series of incremental commits.
+
+use anyhow::Result;
+use anyhow::bail;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::i18n::tr;
+
+/// A way GitHub can merge a PR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMethod {
+    Squash,
+    Merge,
+    Rebase,
+}
+
+impl MergeMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Squash => "squash",
+            Self::Merge => "merge",
+            Self::Rebase => "rebase",
+        }
+    }
+}
+
+/// Choose a merge method from those the repository allows, in order of
+/// preference.
+pub fn choose(preferred: Option<MergeMethod>, allowed: &[MergeMethod]) -> Result<MergeMethod> {
+    let names = || {
+        allowed
+            .iter()
+            .map(MergeMethod::name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match preferred {
+        Some(method) if allowed.contains(&method) => Ok(method),
+        Some(method) => bail!(tr!(
+            "merge-method-not-allowed",
+            method = method.name(),
+            allowed = names()
+        )),
+        None => match allowed.first() {
+            Some(method) => Ok(*method),
+            None => bail!(tr!("merge-no-methods")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        use MergeMethod::*;
+        assert_eq!(choose(None, &[Squash, Merge, Rebase]).unwrap(), Squash);
+        assert_eq!(choose(None, &[Merge, Rebase]).unwrap(), Merge);
+        assert_eq!(choose(Some(Rebase), &[Squash, Rebase]).unwrap(), Rebase);
+        assert!(choose(None, &[]).is_err());
+
+        let err = choose(Some(Merge), &[Squash]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "This repository doesn't allow the merge method set by jr.mergeMethod, merge; it only allows: squash"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Composing commit messages.
+//!
+//! When `jr.describeCommand` is configured, it's run through `sh` with a diff
+//! on stdin, and whatever it prints is used as the suggested message (title on
+//! the first line, body after it).  This is the hook point for message
+//! generators, AI-assisted or otherwise.  The suggestion is then handed to the
+//! user's editor, like `git commit` does, so nothing is used unreviewed.
+
+use std::process::Stdio;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use tokio::io::AsyncWriteExt as _;
+use tokio::process::Command;
+
+/// Lines starting with this are dropped from edited messages.
+const COMMENT_PREFIX: &str = "#";
+
+/// Run the describe command with a diff on stdin, returning its suggested
+/// message.
+pub async fn suggest(command: &str, diff: &str, path: &std::path::Path) -> Result<String> {
+    let mut child = Command::new("sh")
+        .current_dir(path)
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute describe command")?;
+    let mut stdin = child.stdin.take().context("Failed to open stdin")?;
+    let diff = diff.to_string();
+    // Write stdin concurrently, so that a command which doesn't read all of a
+    // large diff can't deadlock us
+    let writer = tokio::spawn(async move { stdin.write_all(diff.as_bytes()).await });
+    let output = child.wait_with_output().await?;
+    // The command may exit without reading all of its input; that's fine
+    let _ = writer.await;
+
+    if !output.status.success() {
+        bail!(
+            "describe command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Let the user edit a message in their editor (`$JJ_EDITOR`, `$VISUAL` or
+/// `$EDITOR`, falling back to `vi`), returning it without comment lines.
+pub async fn edit(message: &str, hint: &str, git_dir: &std::path::Path) -> Result<String> {
+    let editor = ["JJ_EDITOR", "VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "vi".to_string());
+
+    let file = git_dir.join("JR_EDITMSG");
+    std::fs::write(&file, template(message, hint))?;
+    let status = Command::new("sh")
+        .args(["-c", &format!("{editor} \"$@\""), "sh"])
+        .arg(&file)
+        .status()
+        .await
+        .context("Failed to run editor")?;
+    if !status.success() {
+        bail!("editor exited with {status}");
+    }
+
+    Ok(strip_comments(&std::fs::read_to_string(&file)?))
+}
+
+/// The initial contents of the file to edit: the message, followed by a
+/// commented-out hint.
+fn template(message: &str, hint: &str) -> String {
+    let mut out = format!("{message}\n\n");
+    for line in hint.lines() {
+        out.push_str(&format!("{COMMENT_PREFIX} {line}\n").replace(" \n", "\n"));
+    }
+    out
+}
+
+/// Remove comment lines and surrounding whitespace from an edited message.
+fn strip_comments(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.starts_with(COMMENT_PREFIX))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_round_trip() {
+        let message = "Fix the thing\n\nIt was broken.";
+        let text = template(
+            message,
+            "Enter a message.\n\nLines starting with '#' are ignored.",
+        );
+        assert_eq!(
+            text,
+            "Fix the thing\n\nIt was broken.\n\n# Enter a message.\n#\n# Lines starting with '#' are ignored.\n"
+        );
+        assert_eq!(strip_comments(&text), message);
+    }
+
+    #[test]
+    fn test_strip_comments_empty() {
+        assert_eq!(strip_comments("\n# Enter a message.\n"), "");
+    }
+}
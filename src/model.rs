@@ -0,0 +1,241 @@
+//! Typed models of forge objects, shared by client implementations.
+//!
+//! These follow the shape of GitHub's REST API, so they can be deserialized
+//! from its responses directly.  Fields only available from some endpoints
+//! are optional.
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A pull request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    /// The GraphQL node ID.
+    pub node_id: String,
+    #[serde(rename = "html_url")]
+    pub url: String,
+    pub state: PullRequestState,
+    #[serde(default)]
+    pub draft: bool,
+    pub title: String,
+    pub body: Option<String>,
+    pub base: BranchRef,
+    pub head: BranchRef,
+    /// Whether the PR can be merged.  Only returned when fetching a single
+    /// PR, and None while GitHub is still computing it.
+    #[serde(default)]
+    pub mergeable: Option<bool>,
+    /// Only available from the GraphQL API.
+    #[serde(default)]
+    pub review_decision: Option<ReviewDecision>,
+    pub milestone: Option<Milestone>,
+    /// The PR's author.
+    #[serde(default)]
+    pub user: Option<Account>,
+    /// Reviewers whose review is requested and still pending.
+    #[serde(default)]
+    pub requested_reviewers: Vec<Account>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the PR was merged, if it was.
+    #[serde(default)]
+    pub merged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestState {
+    Open,
+    Closed,
+}
+
+/// A PR's base or head branch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BranchRef {
+    #[serde(rename = "ref")]
+    pub name: String,
+    pub sha: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+/// The combined state of a commit's CI checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiState {
+    /// No checks ran.
+    None,
+    Pending,
+    Success,
+    Failure,
+}
+
+impl CiState {
+    /// Combine the states of two checks: any failure fails, and otherwise any
+    /// pending check is pending.
+    pub fn combine(self, other: CiState) -> CiState {
+        use CiState::*;
+        match (self, other) {
+            (Failure, _) | (_, Failure) => Failure,
+            (Pending, _) | (_, Pending) => Pending,
+            (Success, _) | (_, Success) => Success,
+            (None, None) => None,
+        }
+    }
+}
+
+/// A review submitted on a PR.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Review {
+    pub user: Option<Account>,
+    pub state: ReviewState,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    Commented,
+    Dismissed,
+    Pending,
+}
+
+/// A comment on a PR's conversation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IssueComment {
+    #[serde(default)]
+    pub id: u64,
+    pub body: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An event in a PR's timeline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub event: String,
+    #[serde(default)]
+    pub actor: Option<Account>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl TimelineEvent {
+    /// Whether this event changed an assumption jr makes about a PR (its base
+    /// branch, draft state or head), and was made by someone other than `me`,
+    /// e.g. on the web.
+    pub fn is_external_change(&self, me: &str) -> bool {
+        let relevant = matches!(
+            self.event.as_str(),
+            "base_ref_changed" | "convert_to_draft" | "ready_for_review" | "head_ref_force_pushed"
+        );
+        relevant && self.actor.as_ref().is_some_and(|actor| actor.login != me)
+    }
+}
+
+/// A GitHub user.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Account {
+    pub login: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Milestone {
+    pub number: u64,
+    pub title: String,
+}
+
+impl PullRequest {
+    pub fn is_open(&self) -> bool {
+        self.state == PullRequestState::Open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ci_state_combine() {
+        let combined =
+            |states: &[CiState]| states.iter().copied().fold(CiState::None, CiState::combine);
+        assert_eq!(combined(&[]), CiState::None);
+        assert_eq!(
+            combined(&[CiState::Success, CiState::None]),
+            CiState::Success
+        );
+        assert_eq!(
+            combined(&[CiState::Success, CiState::Pending]),
+            CiState::Pending
+        );
+        assert_eq!(
+            combined(&[CiState::Pending, CiState::Failure, CiState::Success]),
+            CiState::Failure
+        );
+    }
+
+    #[test]
+    fn test_timeline_event_is_external_change() {
+        let event = |event: &str, actor: &str| TimelineEvent {
+            event: event.to_string(),
+            actor: Some(Account {
+                login: actor.to_string(),
+            }),
+            created_at: None,
+        };
+        assert!(event("base_ref_changed", "alice").is_external_change("me"));
+        assert!(event("ready_for_review", "alice").is_external_change("me"));
+        assert!(!event("ready_for_review", "me").is_external_change("me"));
+        assert!(!event("commented", "alice").is_external_change("me"));
+    }
+
+    #[test]
+    fn test_deserialize_rest_response() {
+        let pr: PullRequest = serde_json::from_value(serde_json::json!({
+            "number": 42,
+            "node_id": "PR_kwDO",
+            "html_url": "https://github.com/o/r/pull/42",
+            "state": "open",
+            "draft": true,
+            "title": "Add feature",
+            "body": null,
+            "base": { "ref": "main", "sha": "abc", "label": "o:main" },
+            "head": { "ref": "test/klmnopqr", "sha": "def", "label": "o:test/klmnopqr" },
+            "milestone": { "number": 1, "title": "v1.0", "state": "open" },
+            "user": { "login": "octocat" },
+            "requested_reviewers": [{ "login": "hubot" }],
+            "created_at": "2024-01-31T12:00:00Z",
+        }))
+        .unwrap();
+        assert!(pr.is_open());
+        assert!(pr.draft);
+        assert_eq!(pr.url, "https://github.com/o/r/pull/42");
+        assert_eq!(pr.base.name, "main");
+        assert_eq!(pr.head.sha, "def");
+        assert_eq!(pr.mergeable, None);
+        assert_eq!(pr.milestone.unwrap().title, "v1.0");
+        assert_eq!(pr.user.unwrap().login, "octocat");
+        assert_eq!(pr.requested_reviewers[0].login, "hubot");
+        assert_eq!(
+            pr.created_at.unwrap().to_rfc3339(),
+            "2024-01-31T12:00:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Webhook notifications for stack events.
+//!
+//! When `jr.webhookUrl` is configured, we POST a JSON payload to it whenever
+//! jr creates a PR, marks one ready for review or merges one.  The payload
+//! is rendered from `jr.webhookTemplate`, which may reference the
+//! placeholders `{event}`, `{title}`, `{url}` and `{branch}`.  The default
+//! template produces a Slack-compatible `{"text": ...}` message.  It's sent
+//! with the GitHub client's HTTP client (see
+//! [`crate::clients::github::GithubClient::post_webhook`]), so it goes
+//! through the same proxy and TLS settings, and isn't sent in a dry run.
+
+use std::fmt::Display;
+
+/// Default payload template; understood by Slack incoming webhooks.
+pub const DEFAULT_TEMPLATE: &str = r#"{"text": "{event}: {title} {url}"}"#;
+
+/// An event in the lifecycle of a stacked PR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackEvent {
+    Created,
+    Ready,
+    Merged,
+}
+
+impl Display for StackEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Created => f.write_str("PR created"),
+            Self::Ready => f.write_str("PR ready for review"),
+            Self::Merged => f.write_str("PR merged"),
+        }
+    }
+}
+
+/// Render a webhook payload, JSON-escaping each substituted value.  The
+/// template is substituted in one pass, so placeholders in the values, e.g.
+/// a PR titled `{url}`, are kept as they are.
+pub fn render_payload(
+    template: &str,
+    event: StackEvent,
+    title: &str,
+    url: &str,
+    branch: &str,
+) -> String {
+    let event = event.to_string();
+    let values = [
+        ("event", event.as_str()),
+        ("title", title),
+        ("url", url),
+        ("branch", branch),
+    ];
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = values.iter().find_map(|(name, value)| {
+            let rest = after.strip_prefix(name)?.strip_prefix('}')?;
+            Some((value, rest))
+        });
+        match value {
+            Some((value, after_placeholder)) => {
+                rendered.push_str(&json_escape(value));
+                rest = after_placeholder;
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Escape a string for inclusion inside a JSON string literal.
+fn json_escape(s: &str) -> String {
+    let quoted = serde_json::to_string(s).expect("strings always serialize");
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_default_payload() {
+        let payload = render_payload(
+            DEFAULT_TEMPLATE,
+            StackEvent::Created,
+            "Add \"quotes\"",
+            "https://github.com/o/r/pull/1",
+            "test/abcdefgh",
+        );
+        assert_eq!(
+            payload,
+            r#"{"text": "PR created: Add \"quotes\" https://github.com/o/r/pull/1"}"#
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&payload).is_ok());
+    }
+
+    #[test]
+    fn test_render_custom_payload() {
+        let payload = render_payload(
+            r#"{"branch": "{branch}", "event": "{event}"}"#,
+            StackEvent::Created,
+            "title",
+            "url",
+            "test/abcdefgh",
+        );
+        assert_eq!(
+            payload,
+            r#"{"branch": "test/abcdefgh", "event": "PR created"}"#
+        );
+    }
+
+    #[test]
+    fn test_render_placeholders_in_values() {
+        let payload = render_payload(
+            r#"{"title": "{title}", "url": "{url}"}"#,
+            StackEvent::Merged,
+            "Fix {url} and {branch}\", \"x\": \"",
+            "https://github.com/o/r/pull/1",
+            "test/abcdefgh",
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+            serde_json::json!({
+                "title": "Fix {url} and {branch}\", \"x\": \"",
+                "url": "https://github.com/o/r/pull/1",
+            })
+        );
+    }
+}
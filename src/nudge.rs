@@ -0,0 +1,163 @@
+//! Nudging reviewers of PRs that have been waiting too long for review.
+//!
+//! A PR is waiting on the reviewers whose review is requested, plus those
+//! whose latest review didn't approve it.  It has been waiting since the last
+//! of: its creation, its latest review, and jr's latest nudge.  Nudge comments
+//! carry a hidden marker so later runs can find them; this is what rate-limits
+//! nudges when jr runs from a scheduled job.
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::model::Account;
+use crate::model::IssueComment;
+use crate::model::Review;
+use crate::model::ReviewState;
+
+/// Hidden marker identifying jr's nudge comments.
+pub const MARKER: &str = "<!-- jr:nudge -->";
+
+/// Default comment template.  May reference `{reviewers}`, `{title}` and
+/// `{url}`.
+pub const DEFAULT_TEMPLATE: &str =
+    "{reviewers}: friendly reminder that this PR is waiting for your review.  Thanks!";
+
+/// Default number of days a PR waits before its reviewers are nudged.
+pub const DEFAULT_AFTER_DAYS: u64 = 2;
+
+/// The logins of the reviewers a PR is waiting on, sorted.
+pub fn waiting_reviewers(pending: &[Account], reviews: &[Review], author: &str) -> Vec<String> {
+    let mut latest: Vec<(&str, ReviewState)> = vec![];
+    // Reviews are returned oldest first
+    for review in reviews {
+        let Some(user) = &review.user else { continue };
+        if review.state == ReviewState::Pending {
+            continue;
+        }
+        match latest.iter_mut().find(|(login, _)| *login == user.login) {
+            Some(entry) => entry.1 = review.state,
+            None => latest.push((&user.login, review.state)),
+        }
+    }
+
+    let mut reviewers = pending
+        .iter()
+        .map(|account| account.login.as_str())
+        .chain(
+            latest
+                .into_iter()
+                .filter(|(_, state)| *state != ReviewState::Approved)
+                .map(|(login, _)| login),
+        )
+        .filter(|login| *login != author)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    reviewers.sort();
+    reviewers.dedup();
+    reviewers
+}
+
+/// When a PR started waiting for review: the last of its creation, its latest
+/// review, and jr's latest nudge.
+pub fn waiting_since(
+    created_at: Option<DateTime<Utc>>,
+    reviews: &[Review],
+    comments: &[IssueComment],
+) -> Option<DateTime<Utc>> {
+    let reviewed = reviews.iter().filter_map(|review| review.submitted_at);
+    let nudged = comments
+        .iter()
+        .filter(|comment| {
+            comment
+                .body
+                .as_deref()
+                .is_some_and(|body| body.contains(MARKER))
+        })
+        .map(|comment| comment.created_at);
+    created_at.into_iter().chain(reviewed).chain(nudged).max()
+}
+
+/// Render a nudge comment, mentioning each reviewer.
+pub fn render_comment(template: &str, reviewers: &[String], title: &str, url: &str) -> String {
+    let reviewers = reviewers
+        .iter()
+        .map(|login| format!("@{login}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let comment = template
+        .replace("{reviewers}", &reviewers)
+        .replace("{title}", title)
+        .replace("{url}", url);
+    format!("{comment}\n\n{MARKER}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(login: &str) -> Account {
+        Account {
+            login: login.to_string(),
+        }
+    }
+
+    fn review(login: &str, state: ReviewState, day: u32) -> Review {
+        Review {
+            user: Some(account(login)),
+            state,
+            submitted_at: Some(date(day)),
+        }
+    }
+
+    fn date(day: u32) -> DateTime<Utc> {
+        format!("2024-01-{day:02}T12:00:00Z").parse().unwrap()
+    }
+
+    #[test]
+    fn test_waiting_reviewers() {
+        let reviews = [
+            review("alice", ReviewState::ChangesRequested, 2),
+            review("alice", ReviewState::Approved, 3),
+            review("bob", ReviewState::Approved, 2),
+            review("bob", ReviewState::Commented, 3),
+            review("me", ReviewState::Commented, 3),
+        ];
+        let pending = [account("carol"), account("bob")];
+        assert_eq!(
+            waiting_reviewers(&pending, &reviews, "me"),
+            vec!["bob", "carol"]
+        );
+    }
+
+    #[test]
+    fn test_waiting_since() {
+        let reviews = [review("alice", ReviewState::Commented, 3)];
+        let comments = [
+            IssueComment {
+                id: 1,
+                body: Some(format!("Ping\n\n{MARKER}")),
+                created_at: date(5),
+            },
+            IssueComment {
+                id: 2,
+                body: Some("Unrelated".to_string()),
+                created_at: date(9),
+            },
+        ];
+        assert_eq!(waiting_since(Some(date(1)), &[], &[]), Some(date(1)));
+        assert_eq!(waiting_since(Some(date(1)), &reviews, &[]), Some(date(3)));
+        assert_eq!(
+            waiting_since(Some(date(1)), &reviews, &comments),
+            Some(date(5))
+        );
+    }
+
+    #[test]
+    fn test_render_comment() {
+        let reviewers = ["alice".to_string(), "bob".to_string()];
+        assert_eq!(
+            render_comment("{reviewers}: please review {title}", &reviewers, "Fix", "u"),
+            format!("@alice, @bob: please review Fix\n\n{MARKER}")
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! What a dry run would cost: how many git pushes and GitHub requests it
+//! would make, and roughly how long they'd take.
+//!
+//! Real runs time their pushes and GitHub requests as they go (see
+//! [`Stopwatch`]), and keep the averages in `.git/jr/state.json` (see
+//! [`crate::state`]).  A dry run counts what it would do instead, and
+//! estimates the time from those averages, or from rough defaults before any
+//! have been recorded, so that on a slow connection it's clear whether to
+//! submit now or batch up more changes first.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// GitHub requests to create a PR: creating it, then checking whether it can
+/// be merged, which may take a couple of polls.
+pub const CREATE_REQUESTS: u64 = 3;
+
+/// GitHub requests after pushing to an existing PR's branch: retargeting the
+/// PR, then checking whether it can be merged.
+pub const UPDATE_REQUESTS: u64 = 3;
+
+/// GitHub requests to update one PR's stack comment: listing its comments,
+/// then editing one.
+pub const STACK_COMMENT_REQUESTS: u64 = 2;
+
+/// How long a push is guessed to take, before any have been timed.
+const DEFAULT_PUSH: Duration = Duration::from_secs(2);
+
+/// How long a GitHub request is guessed to take, before any have been timed.
+const DEFAULT_REQUEST: Duration = Duration::from_millis(500);
+
+/// Samples an average is kept over.  Older ones count for less and less, so
+/// that the estimate follows a connection that's got faster or slower.
+const MAX_SAMPLES: u64 = 50;
+
+/// The pushes and GitHub requests a dry run would make.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub pushes: u64,
+    pub requests: u64,
+}
+
+impl Plan {
+    /// Count a push to a PR branch, and the GitHub requests that go with it.
+    pub fn push(&mut self, requests: u64) {
+        self.pushes += 1;
+        self.requests += requests;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Roughly how long the plan would take, one push and request at a time,
+    /// and whether that's from recorded timings rather than guesses.
+    pub fn estimate(&self, timings: &Timings) -> (Duration, bool) {
+        let push = timings.pushes.average();
+        let request = timings.requests.average();
+        let duration = push.unwrap_or(DEFAULT_PUSH) * self.pushes as u32
+            + request.unwrap_or(DEFAULT_REQUEST) * self.requests as u32;
+        (duration, push.is_some() || request.is_some())
+    }
+}
+
+/// How long pushes and GitHub requests have taken on real runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timings {
+    #[serde(default)]
+    pub pushes: Timing,
+    #[serde(default)]
+    pub requests: Timing,
+}
+
+/// How long something has taken, on average.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub count: u64,
+    pub total_ms: u64,
+}
+
+impl Timing {
+    /// Add the samples timed by a run.
+    pub fn add(&mut self, other: Timing) {
+        self.count += other.count;
+        self.total_ms += other.total_ms;
+        if self.count > MAX_SAMPLES {
+            self.total_ms = self.total_ms * MAX_SAMPLES / self.count;
+            self.count = MAX_SAMPLES;
+        }
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_millis(self.total_ms / self.count))
+    }
+}
+
+/// Times operations of one kind as they happen, e.g. GitHub requests, from
+/// any task.
+#[derive(Debug, Default)]
+pub struct Stopwatch {
+    count: AtomicU64,
+    total_ms: AtomicU64,
+}
+
+impl Stopwatch {
+    pub fn record(&self, elapsed: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let elapsed = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        self.total_ms.fetch_add(elapsed, Ordering::Relaxed);
+    }
+
+    pub fn timing(&self) -> Timing {
+        Timing {
+            count: self.count.load(Ordering::Relaxed),
+            total_ms: self.total_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Format a rough duration, e.g. `40s` or `3m 10s`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs().max(1);
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => format!("{seconds}s"),
+        (minutes, 0) => format!("{minutes}m"),
+        (minutes, seconds) => format!("{minutes}m {seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let mut plan = Plan::default();
+        assert!(plan.is_empty());
+        plan.push(UPDATE_REQUESTS);
+        plan.push(CREATE_REQUESTS + 1);
+        assert_eq!(
+            plan,
+            Plan {
+                pushes: 2,
+                requests: 7
+            }
+        );
+
+        let (duration, recorded) = plan.estimate(&Timings::default());
+        assert_eq!(duration, Duration::from_millis(7500));
+        assert!(!recorded);
+
+        let stopwatch = Stopwatch::default();
+        stopwatch.record(Duration::from_millis(100));
+        stopwatch.record(Duration::from_millis(300));
+        let mut timings = Timings::default();
+        timings.requests.add(stopwatch.timing());
+        let (duration, recorded) = plan.estimate(&timings);
+        assert_eq!(duration, Duration::from_millis(5400));
+        assert!(recorded);
+        assert_eq!(format_duration(duration), "5s");
+        assert_eq!(format_duration(Duration::from_secs(190)), "3m 10s");
+        assert_eq!(format_duration(Duration::from_secs(120)), "2m");
+    }
+
+    #[test]
+    fn test_timing_decays() {
+        let mut timing = Timing {
+            count: MAX_SAMPLES,
+            total_ms: MAX_SAMPLES * 1000,
+        };
+        timing.add(Timing {
+            count: MAX_SAMPLES,
+            total_ms: MAX_SAMPLES * 3000,
+        });
+        assert_eq!(timing.count, MAX_SAMPLES);
+        assert_eq!(timing.average(), Some(Duration::from_secs(2)));
+    }
+}
@@ -0,0 +1,163 @@
+//! Named guards ("policies") with configurable severity.
+//!
+//! Each guard that can stop a command is a [`Policy`].  Its severity is read
+//! from `jr.policy.<name>` in git config (`block`, `warn` or `off`), and a
+//! blocking policy can be downgraded to a warning for a single invocation
+//! with `--override <name>`.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use clap::ValueEnum;
+use log::warn;
+
+use crate::i18n::tr;
+
+/// A named guard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Policy {
+    /// Parent PRs must be in sync before a child PR is created or updated.
+    ParentUpToDate,
+    /// The configured `jr.prePushCommand` must accept the push.
+    PrePush,
+    /// A PR can't be marked ready for review while its parent is a draft.
+    ParentReady,
+    /// A PR branch can't be updated or restacked if someone else pushed its
+    /// tip, so their commits aren't clobbered.
+    ForeignPush,
+}
+
+/// What to do when a policy is violated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Severity {
+    /// Don't check the policy at all.
+    Off,
+    /// Log a warning and carry on.
+    Warn,
+    /// Fail the command.
+    Block,
+}
+
+/// The effective severity of every policy for this invocation.
+#[derive(Clone, Debug, Default)]
+pub struct Policies {
+    severities: HashMap<Policy, Severity>,
+    overrides: Vec<Policy>,
+}
+
+impl Policy {
+    /// The policy's name, as used in config keys and `--override`.
+    pub fn name(&self) -> String {
+        self.to_possible_value()
+            .expect("no skipped variants")
+            .get_name()
+            .to_string()
+    }
+
+    fn default_severity(&self) -> Severity {
+        match self {
+            Self::ParentUpToDate => Severity::Block,
+            Self::PrePush => Severity::Block,
+            Self::ParentReady => Severity::Block,
+            Self::ForeignPush => Severity::Block,
+        }
+    }
+}
+
+impl Policies {
+    /// Set the configured severity of a policy.
+    pub fn set_severity(&mut self, policy: Policy, severity: Severity) {
+        self.severities.insert(policy, severity);
+    }
+
+    /// Downgrade a blocking policy to a warning for this invocation.
+    pub fn add_override(&mut self, policy: Policy) {
+        self.overrides.push(policy);
+    }
+
+    /// The effective severity of a policy, taking overrides into account.
+    pub fn severity(&self, policy: Policy) -> Severity {
+        let severity = self
+            .severities
+            .get(&policy)
+            .copied()
+            .unwrap_or_else(|| policy.default_severity());
+        if severity == Severity::Block && self.overrides.contains(&policy) {
+            Severity::Warn
+        } else {
+            severity
+        }
+    }
+
+    /// Whether a policy should be checked at all.
+    pub fn is_enabled(&self, policy: Policy) -> bool {
+        self.severity(policy) != Severity::Off
+    }
+
+    /// Apply a policy to the outcome of its check.
+    pub fn enforce(&self, policy: Policy, check: Result<()>) -> Result<()> {
+        let Err(violation) = check else {
+            return Ok(());
+        };
+        match self.severity(policy) {
+            Severity::Off => Ok(()),
+            Severity::Warn => {
+                warn!(
+                    "{}",
+                    tr!(
+                        "policy-warning",
+                        violation = violation.to_string(),
+                        policy = policy.name()
+                    )
+                );
+                Ok(())
+            }
+            Severity::Block => Err(anyhow!(tr!(
+                "policy-blocked",
+                violation = violation.to_string(),
+                policy = policy.name()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_severity() {
+        let policies = Policies::default();
+        assert_eq!(policies.severity(Policy::PrePush), Severity::Block);
+        assert!(policies.enforce(Policy::PrePush, Ok(())).is_ok());
+        let err = policies
+            .enforce(Policy::PrePush, Err(anyhow!("rejected")))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rejected (override with --override pre-push)"
+        );
+    }
+
+    #[test]
+    fn test_override_downgrades_block_to_warn() {
+        let mut policies = Policies::default();
+        policies.add_override(Policy::ParentUpToDate);
+        assert_eq!(policies.severity(Policy::ParentUpToDate), Severity::Warn);
+        assert!(
+            policies
+                .enforce(Policy::ParentUpToDate, Err(anyhow!("stale")))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_configured_severity() {
+        let mut policies = Policies::default();
+        policies.set_severity(Policy::PrePush, Severity::Off);
+        policies.add_override(Policy::PrePush);
+        assert_eq!(policies.severity(Policy::PrePush), Severity::Off);
+        assert!(!policies.is_enabled(Policy::PrePush));
+    }
+}
@@ -0,0 +1,173 @@
+//! PR size budgets.
+//!
+//! Large PRs are hard to review, so with `jr.sizeBudget` set, jr warns when a
+//! PR changes more lines than that and suggests `jj split` commands that
+//! would move the biggest groups of files (by directory) into their own
+//! commits.
+
+use std::collections::BTreeMap;
+
+use crate::diff_utils::split_files;
+
+/// Most split suggestions shown for an oversized PR.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Lines added and removed in one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSize {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl FileSize {
+    pub fn lines(&self) -> usize {
+        self.added + self.removed
+    }
+}
+
+/// Files that could be split into a commit of their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitGroup {
+    /// The files' directory, or the file itself if it's alone.
+    pub name: String,
+    /// jj filesets selecting the files, relative to the workspace root.
+    pub filesets: Vec<String>,
+    pub lines: usize,
+}
+
+/// Count the lines added and removed in each file of a git diff.
+pub fn file_sizes(diff: &str) -> Vec<FileSize> {
+    split_files(diff)
+        .into_iter()
+        .map(|(path, part)| {
+            let (mut added, mut removed) = (0, 0);
+            // Only count lines in hunks, so `---`/`+++` headers are skipped
+            // but removed lines that happen to start with `--` are not.
+            let mut in_hunk = false;
+            for line in part.lines() {
+                if line.starts_with("@@") {
+                    in_hunk = true;
+                } else if in_hunk && line.starts_with('+') {
+                    added += 1;
+                } else if in_hunk && line.starts_with('-') {
+                    removed += 1;
+                }
+            }
+            FileSize {
+                path: path.to_string(),
+                added,
+                removed,
+            }
+        })
+        .collect()
+}
+
+/// The biggest groups of files to suggest splitting out, largest first.
+/// Files are grouped by directory; if they're all in one, each file is its
+/// own group.  Nothing is suggested for a single file.
+pub fn split_candidates(files: &[FileSize]) -> Vec<SplitGroup> {
+    let changed = files.iter().filter(|file| file.lines() > 0);
+    let mut by_dir: BTreeMap<&str, Vec<&FileSize>> = BTreeMap::new();
+    for file in changed.clone() {
+        let dir = file.path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        by_dir.entry(dir).or_default().push(file);
+    }
+    let mut groups: Vec<SplitGroup> = if by_dir.len() > 1 {
+        by_dir
+            .into_iter()
+            .map(|(dir, files)| match files.as_slice() {
+                [file] => file_group(file),
+                _ if dir.is_empty() => SplitGroup {
+                    name: "/".to_string(),
+                    filesets: files
+                        .iter()
+                        .map(|file| format!("root:{}", file.path))
+                        .collect(),
+                    lines: files.iter().map(|file| file.lines()).sum(),
+                },
+                _ => SplitGroup {
+                    name: format!("{dir}/"),
+                    filesets: vec![format!("root:{dir}")],
+                    lines: files.iter().map(|file| file.lines()).sum(),
+                },
+            })
+            .collect()
+    } else {
+        changed.map(file_group).collect()
+    };
+    if groups.len() < 2 {
+        return Vec::new();
+    }
+    groups.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+    groups.truncate(MAX_SUGGESTIONS);
+    groups
+}
+
+fn file_group(file: &FileSize) -> SplitGroup {
+    SplitGroup {
+        name: file.path.clone(),
+        filesets: vec![format!("root:{}", file.path)],
+        lines: file.lines(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(path: &str, added: usize, removed: usize) -> FileSize {
+        FileSize {
+            path: path.to_string(),
+            added,
+            removed,
+        }
+    }
+
+    #[test]
+    fn test_file_sizes() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n\
+--- a/src/a.rs\n\
++++ b/src/a.rs\n\
+@@ -1,3 +1,3 @@\n\
+ context\n\
+--- removed comment\n\
++new\n\
++newer\n\
+diff --git a/old b/new\n\
+similarity index 100%\n";
+        assert_eq!(
+            file_sizes(diff),
+            [size("src/a.rs", 2, 1), size("new", 0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_split_candidates() {
+        let files = [
+            size("src/a.rs", 10, 5),
+            size("src/b.rs", 1, 0),
+            size("docs/guide.md", 40, 0),
+            size("README.md", 2, 2),
+            size("Cargo.toml", 1, 0),
+            size("tests/moved.rs", 0, 0),
+        ];
+        let groups = split_candidates(&files);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (group.name.as_str(), group.lines))
+                .collect::<Vec<_>>(),
+            [("docs/guide.md", 40), ("src/", 16), ("/", 5)]
+        );
+        assert_eq!(groups[1].filesets, ["root:src"]);
+        assert_eq!(groups[2].filesets, ["root:README.md", "root:Cargo.toml"]);
+
+        // All in one directory: suggest files
+        let groups = split_candidates(&files[..2]);
+        assert_eq!(groups[0].filesets, ["root:src/a.rs"]);
+        assert_eq!(groups.len(), 2);
+
+        assert!(split_candidates(&files[..1]).is_empty());
+    }
+}
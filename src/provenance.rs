@@ -0,0 +1,81 @@
+//! Where PR branch commits came from.
+//!
+//! PR branch commits are synthesized from jj commits, so nothing in a PR
+//! branch says which change it was pushed from.  jr records that in a git
+//! note on each commit it synthesizes, under [`NOTES_REF`], as trailers.
+//! These survive losing jj's metadata, and `jr why` reads them back.
+
+/// The notes ref that provenance is recorded under.
+pub const NOTES_REF: &str = "refs/notes/jr";
+
+const CHANGE_ID: &str = "jr-change-id";
+const COMMIT_ID: &str = "jr-commit-id";
+const OPERATION: &str = "jr-operation";
+
+/// The jj commit a PR branch commit was synthesized from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Provenance {
+    pub change_id: String,
+    pub commit_id: String,
+    /// The jj operation the commit was synthesized at.
+    pub operation: String,
+}
+
+impl Provenance {
+    /// Format as the contents of a note.
+    pub fn to_note(&self) -> String {
+        format!(
+            "{CHANGE_ID}: {}\n{COMMIT_ID}: {}\n{OPERATION}: {}\n",
+            self.change_id, self.commit_id, self.operation
+        )
+    }
+
+    /// Parse the contents of a note, ignoring anything else in it.
+    pub fn parse(note: &str) -> Option<Self> {
+        let trailer = |key: &str| {
+            note.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == key).then(|| value.trim().to_string())
+            })
+        };
+        Some(Self {
+            change_id: trailer(CHANGE_ID)?,
+            commit_id: trailer(COMMIT_ID)?,
+            operation: trailer(OPERATION)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let provenance = Provenance {
+            change_id: "klmnopqrstuvwxyz".to_string(),
+            commit_id: "0123456789abcdef".to_string(),
+            operation: "fedcba9876543210".to_string(),
+        };
+        let note = provenance.to_note();
+        assert_eq!(
+            note,
+            "jr-change-id: klmnopqrstuvwxyz\njr-commit-id: 0123456789abcdef\njr-operation: fedcba9876543210\n"
+        );
+        assert_eq!(Provenance::parse(&note), Some(provenance));
+    }
+
+    #[test]
+    fn test_parse_other_notes() {
+        assert_eq!(Provenance::parse("Reviewed in person"), None);
+        let note = "Appended by hand\n\njr-operation: op\njr-commit-id: c\njr-change-id: k\n";
+        assert_eq!(
+            Provenance::parse(note),
+            Some(Provenance {
+                change_id: "k".to_string(),
+                commit_id: "c".to_string(),
+                operation: "op".to_string(),
+            })
+        );
+    }
+}
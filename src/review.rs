@@ -0,0 +1,119 @@
+//! Reviewing someone else's stack, using only the GitHub API.
+//!
+//! A stack is reconstructed from its top PR by following base branches down:
+//! each PR's parent is the PR whose head branch is its base, until we reach a
+//! branch with no PR (normally the default branch).
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::model::PullRequest;
+use crate::model::ReviewDecision;
+
+/// Parse a PR reference: a number (optionally prefixed with `#`), or a PR URL
+/// on `host` like `https://github.com/owner/repo/pull/42`.  Returns the
+/// `owner/repo` from URLs, along with the PR number.
+pub fn parse_pr_reference(reference: &str, host: &str) -> Result<(Option<String>, u64)> {
+    let reference = reference.trim();
+    if let Ok(number) = reference.trim_start_matches('#').parse() {
+        return Ok((None, number));
+    }
+
+    let path = reference
+        .strip_prefix("https://")
+        .or_else(|| reference.strip_prefix("http://"))
+        .and_then(|url| url.split_once('/'))
+        .filter(|(authority, _)| authority.eq_ignore_ascii_case(host))
+        .map(|(_, path)| path);
+    if let Some(path) = path
+        && let [owner, repo, "pull", number, ..] = path.split('/').collect::<Vec<_>>()[..]
+        && let Ok(number) = number.parse()
+    {
+        return Ok((Some(format!("{owner}/{repo}")), number));
+    }
+
+    bail!("Not a PR number or URL: {}", reference);
+}
+
+/// Suggest an order to review a stack's PRs in, given them ordered from the
+/// bottom of the stack to the top.
+///
+/// Reviews go bottom-up, since each PR builds on the ones below it.  Approved
+/// and closed PRs are skipped, and drafts come last, as they aren't ready yet.
+pub fn review_order(prs: &[PullRequest]) -> Vec<u64> {
+    let to_review = prs
+        .iter()
+        .filter(|pr| pr.is_open() && pr.review_decision != Some(ReviewDecision::Approved));
+    let (drafts, ready): (Vec<_>, Vec<_>) = to_review.partition(|pr| pr.draft);
+    ready
+        .into_iter()
+        .chain(drafts)
+        .map(|pr| pr.number)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BranchRef;
+    use crate::model::PullRequestState;
+
+    fn pr(number: u64, draft: bool, review_decision: Option<ReviewDecision>) -> PullRequest {
+        PullRequest {
+            number,
+            node_id: format!("PR_{number}"),
+            url: format!("https://github.com/o/r/pull/{number}"),
+            state: PullRequestState::Open,
+            draft,
+            title: format!("PR {number}"),
+            body: None,
+            base: BranchRef {
+                name: "main".to_string(),
+                sha: "0".repeat(40),
+            },
+            head: BranchRef {
+                name: format!("branch-{number}"),
+                sha: "1".repeat(40),
+            },
+            mergeable: None,
+            review_decision,
+            milestone: None,
+            user: None,
+            requested_reviewers: vec![],
+            created_at: None,
+            merged_at: None,
+            labels: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_pr_reference() {
+        assert_eq!(parse_pr_reference("42", "github.com").unwrap(), (None, 42));
+        assert_eq!(parse_pr_reference("#42", "github.com").unwrap(), (None, 42));
+        assert_eq!(
+            parse_pr_reference("https://github.com/o/r/pull/42/files", "github.com").unwrap(),
+            (Some("o/r".to_string()), 42)
+        );
+        assert!(parse_pr_reference("https://github.com/o/r/issues/42", "github.com").is_err());
+        assert!(parse_pr_reference("main", "github.com").is_err());
+        assert_eq!(
+            parse_pr_reference("https://ghe.example.com/o/r/pull/7", "ghe.example.com").unwrap(),
+            (Some("o/r".to_string()), 7)
+        );
+        assert!(parse_pr_reference("https://ghe.example.com/o/r/pull/7", "github.com").is_err());
+    }
+
+    #[test]
+    fn test_review_order() {
+        let mut closed = pr(5, false, None);
+        closed.state = PullRequestState::Closed;
+        let prs = [
+            pr(1, false, Some(ReviewDecision::Approved)),
+            pr(2, true, None),
+            pr(3, false, Some(ReviewDecision::ChangesRequested)),
+            pr(4, false, Some(ReviewDecision::ReviewRequired)),
+            closed,
+        ];
+        assert_eq!(review_order(&prs), vec![3, 4, 2]);
+    }
+}
@@ -0,0 +1,502 @@
+//! A JSON-RPC 2.0 interface to jr, for editor plugins (see `jr serve`).
+//!
+//! Each request is POSTed over HTTP/1.1 on its own connection.  The
+//! operations, their parameters and results are typed here, so other
+//! transports can share them.  Clients can also `GET /events` for a stream of
+//! server-sent events carrying the stack's status whenever it changes.
+//!
+//! Every request must carry the token the server printed when it started, as
+//! `Authorization: Bearer <token>`.  Requests from browsers (with an `Origin`)
+//! or for another host than localhost are refused, so web pages can't drive
+//! jr, even by DNS rebinding.
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt as _;
+use tokio::io::AsyncReadExt as _;
+
+use crate::commit::SyncStatus;
+use crate::stack::Stack;
+
+/// The request body couldn't be parsed as JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// The method doesn't exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters are wrong for the method.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The operation failed.
+pub const SERVER_ERROR: i64 = -32000;
+
+/// Largest request body we accept.
+const MAX_BODY_LENGTH: usize = 1 << 20;
+
+/// A jr operation, with its parameters.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Method {
+    /// Get the status of a stack, as a [`StackStatus`].
+    Status(StatusParams),
+    /// Get a revision's PR, if it has one.
+    Pr(RevisionParams),
+    /// Get a revision's diff and its PR's, as a [`DiffResult`].
+    Diff(RevisionParams),
+    /// Create a PR.
+    Create(RevisionParams),
+    /// Update a PR.
+    Update(UpdateParams),
+    /// Restack a PR.
+    Restack(RevisionParams),
+    /// Create, update or restack the PRs of a range of a stack.
+    Submit(SubmitParams),
+}
+
+impl Method {
+    /// The names of all methods.
+    pub const NAMES: [&str; 7] = [
+        "status", "pr", "diff", "create", "update", "restack", "submit",
+    ];
+
+    /// Parse a method from its name and parameters, which may be omitted if
+    /// they all have defaults.
+    pub fn new(name: &str, params: Value) -> Result<Self, serde_json::Error> {
+        let params = if params.is_null() { json!({}) } else { params };
+        serde_json::from_value(json!({ "method": name, "params": params }))
+    }
+
+    /// Whether the method changes PRs.
+    pub fn is_mutating(&self) -> bool {
+        !matches!(self, Self::Status(_) | Self::Pr(_) | Self::Diff(_))
+    }
+
+    /// The revision the method applies to.
+    pub fn revision(&self) -> &str {
+        match self {
+            Self::Status(params) => &params.revision,
+            Self::Pr(params)
+            | Self::Diff(params)
+            | Self::Create(params)
+            | Self::Restack(params) => &params.revision,
+            Self::Update(params) => &params.revision,
+            Self::Submit(params) => &params.revision,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusParams {
+    /// A revision in the stack.
+    #[serde(default = "default_revision")]
+    pub revision: String,
+    /// Fetch PRs from GitHub again, rather than using cached ones.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RevisionParams {
+    #[serde(default = "default_revision")]
+    pub revision: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateParams {
+    #[serde(default = "default_revision")]
+    pub revision: String,
+    /// Required, since there's no editor to compose one in.
+    pub message: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmitParams {
+    /// The range of commits to submit.
+    #[serde(default = "default_range")]
+    pub revision: String,
+    /// For the PRs updated; required, since there's no editor to compose
+    /// one in.
+    pub message: String,
+}
+
+fn default_revision() -> String {
+    "@".to_string()
+}
+
+fn default_range() -> String {
+    "::@".to_string()
+}
+
+/// The status of each commit in a stack, from parent to child.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StackStatus {
+    pub commits: Vec<CommitStatus>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CommitStatus {
+    pub change_id: String,
+    pub title: String,
+    pub status: SyncStatus,
+    pub divergent: bool,
+    pub pr: Option<PrStatus>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PrStatus {
+    pub number: u64,
+    pub url: String,
+    pub draft: bool,
+}
+
+impl StackStatus {
+    pub fn new(stack: &Stack) -> Self {
+        let commits = stack
+            .commits
+            .iter()
+            .zip(stack.statuses())
+            .map(|(commit, status)| CommitStatus {
+                change_id: commit.commit.change_id.0.clone(),
+                title: commit.commit.message.title.clone().unwrap_or_default(),
+                status,
+                divergent: commit.commit.divergent,
+                pr: commit.pr.as_ref().map(|pr| PrStatus {
+                    number: pr.number,
+                    url: pr.url.clone(),
+                    draft: pr.draft,
+                }),
+            })
+            .collect();
+        Self { commits }
+    }
+}
+
+/// A revision's diff, and its PR's diff if it has one.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DiffResult {
+    pub commit_diff: String,
+    pub pr_diff: Option<String>,
+}
+
+/// The result of an operation that changes PRs: what the command would
+/// have printed.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OperationResult {
+    pub output: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC response.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(ResponseError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// Parse a JSON-RPC request into its ID and method, or the error response to
+/// send instead.
+pub fn parse_request(body: &str) -> Result<(Value, Method), Response> {
+    let request: Request = serde_json::from_str(body)
+        .map_err(|err| Response::error(Value::Null, PARSE_ERROR, err.to_string()))?;
+    if !Method::NAMES.contains(&request.method.as_str()) {
+        let message = format!("Unknown method: {}", request.method);
+        return Err(Response::error(request.id, METHOD_NOT_FOUND, message));
+    }
+    match Method::new(&request.method, request.params) {
+        Ok(method) => Ok((request.id, method)),
+        Err(err) => Err(Response::error(request.id, INVALID_PARAMS, err.to_string())),
+    }
+}
+
+/// An HTTP request.
+#[derive(Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names, lowercased, and values.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// The value of a header, given its lowercase name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Generate a token for clients to authenticate with.
+pub fn generate_token() -> Result<String> {
+    let mut bytes = [0; 32];
+    getrandom::fill(&mut bytes).context("Can't generate a token")?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Check that a request carries the server's token and doesn't come from a
+/// web page, returning the HTTP status to refuse it with if not.
+pub fn authorize(request: &HttpRequest, token: &str) -> Result<(), &'static str> {
+    if request.header("origin").is_some() {
+        return Err("403 Forbidden");
+    }
+    let host = request.header("host").unwrap_or_default();
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !name.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    if !matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+        return Err("403 Forbidden");
+    }
+    let given = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Compare in constant time, so the token can't be guessed byte by byte
+    let matches = given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0;
+    if !matches {
+        return Err("401 Unauthorized");
+    }
+    Ok(())
+}
+
+/// Read an HTTP/1.1 request, or `None` if the connection closed first.
+pub async fn read_http_request(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Option<HttpRequest>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("Malformed request line: {}", line.trim_end());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = vec![];
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim().to_lowercase(), value.trim().to_string());
+            if name == "content-length" {
+                content_length = value.parse().context("Invalid Content-Length")?;
+            }
+            headers.push((name, value));
+        }
+    }
+    if content_length > MAX_BODY_LENGTH {
+        bail!("Request body too large: {content_length} bytes");
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(HttpRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body)?,
+    }))
+}
+
+/// Format an HTTP/1.1 response that closes the connection.
+pub fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let (id, method) =
+            parse_request(r#"{"jsonrpc": "2.0", "id": 1, "method": "status"}"#).unwrap();
+        assert_eq!(id, json!(1));
+        assert_eq!(
+            method,
+            Method::Status(StatusParams {
+                revision: "@".to_string(),
+                refresh: false
+            })
+        );
+
+        let (_, method) = parse_request(
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "update", "params": {"revision": "x", "message": "Fix"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            method,
+            Method::Update(UpdateParams {
+                revision: "x".to_string(),
+                message: "Fix".to_string()
+            })
+        );
+        assert!(method.is_mutating());
+    }
+
+    #[test]
+    fn test_parse_request_errors() {
+        let code = |body: &str| parse_request(body).unwrap_err().error.unwrap().code;
+        assert_eq!(code("{"), PARSE_ERROR);
+        assert_eq!(code(r#"{"id": 1, "method": "land"}"#), METHOD_NOT_FOUND);
+        assert_eq!(code(r#"{"id": 1, "method": "update"}"#), INVALID_PARAMS);
+        assert_eq!(
+            code(r#"{"id": 1, "method": "create", "params": {"rev": "x"}}"#),
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn test_response_serialization() {
+        let response = Response::error(json!(3), SERVER_ERROR, "failed");
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({"jsonrpc": "2.0", "id": 3, "error": {"code": -32000, "message": "failed"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_http_request() {
+        let mut input: &[u8] =
+            b"POST / HTTP/1.1\r\nHost: localhost\r\ncontent-length: 4\r\n\r\n{}\r\nextra";
+        let request = read_http_request(&mut input).await.unwrap().unwrap();
+        assert_eq!(
+            request,
+            HttpRequest {
+                method: "POST".to_string(),
+                path: "/".to_string(),
+                headers: vec![
+                    ("host".to_string(), "localhost".to_string()),
+                    ("content-length".to_string(), "4".to_string()),
+                ],
+                body: "{}\r\n".to_string(),
+            }
+        );
+
+        let mut input: &[u8] = b"GET /events HTTP/1.1\r\n\r\n";
+        let request = read_http_request(&mut input).await.unwrap().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.body.as_str()),
+            ("GET", "")
+        );
+
+        let mut input: &[u8] = b"";
+        assert!(read_http_request(&mut input).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let request = |headers: &[(&str, &str)]| HttpRequest {
+            method: "POST".to_string(),
+            path: "/".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: String::new(),
+        };
+        let auth = ("authorization", "Bearer secret");
+        assert_eq!(
+            authorize(&request(&[("host", "127.0.0.1:8080"), auth]), "secret"),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(&request(&[("host", "[::1]:1"), auth]), "secret"),
+            Ok(())
+        );
+        assert_eq!(
+            authorize(&request(&[("host", "localhost")]), "secret"),
+            Err("401 Unauthorized")
+        );
+        assert_eq!(
+            authorize(
+                &request(&[("host", "localhost"), ("authorization", "Bearer secre")]),
+                "secret"
+            ),
+            Err("401 Unauthorized")
+        );
+        // A web page, directly or by DNS rebinding
+        assert_eq!(
+            authorize(
+                &request(&[
+                    ("host", "localhost"),
+                    ("origin", "https://evil.example"),
+                    auth
+                ]),
+                "secret"
+            ),
+            Err("403 Forbidden")
+        );
+        assert_eq!(
+            authorize(&request(&[("host", "evil.example:8080"), auth]), "secret"),
+            Err("403 Forbidden")
+        );
+        assert_eq!(authorize(&request(&[auth]), "secret"), Err("403 Forbidden"));
+
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token().unwrap());
+    }
+}
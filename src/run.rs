@@ -0,0 +1,126 @@
+//! Progress of an interrupted `jr submit` or `jr merge`.
+//!
+//! Both work through a stack a commit at a time, so a failure partway, e.g. a
+//! rejected push or a dropped connection, leaves the stack half done.  Each
+//! keeps its plan in `.git/jr/run.json` as a list of steps, marking each done
+//! as it finishes, and removes the file once they all are.  `--resume` then
+//! carries on from the first step not done, rather than planning again.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Path of the run manifest, in the `.git` directory.
+pub const FILE_NAME: &str = "jr/run.json";
+
+/// The command a run is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunCommand {
+    Submit,
+    Merge,
+}
+
+impl RunCommand {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Submit => "submit",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+/// What a step does to its change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Create, update or restack the change's PR, as its status needs.
+    Submit,
+    /// Merge the change's PR, and point the PRs based on it at the default
+    /// branch.
+    Merge,
+    /// Rebase the rest of the stack onto trunk, and abandon the merged change.
+    Rebase,
+    /// Restack the change's PR onto its merged parent.
+    Restack,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub action: Action,
+    pub change_id: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// The steps of a run, in order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Run {
+    pub command: RunCommand,
+    pub steps: Vec<Step>,
+}
+
+impl Run {
+    pub fn new(command: RunCommand, steps: impl IntoIterator<Item = (Action, String)>) -> Self {
+        let steps = steps
+            .into_iter()
+            .map(|(action, change_id)| Step {
+                action,
+                change_id,
+                done: false,
+            })
+            .collect();
+        Self { command, steps }
+    }
+
+    /// Parse a run manifest, or `None` if it can't be, e.g. because it's
+    /// from a different version of jr.
+    pub fn parse(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("run serializes")
+    }
+
+    /// The steps not done yet, with their indices.
+    pub fn pending(&self) -> Vec<(usize, Step)> {
+        self.steps
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, step)| !step.done)
+            .collect()
+    }
+
+    /// Mark a step done.
+    pub fn complete(&mut self, index: usize) {
+        self.steps[index].done = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume() {
+        let mut run = Run::new(
+            RunCommand::Merge,
+            [
+                (Action::Merge, "a".to_string()),
+                (Action::Rebase, "a".to_string()),
+                (Action::Restack, "b".to_string()),
+            ],
+        );
+        run.complete(0);
+        let run = Run::parse(&run.to_json()).unwrap();
+        assert_eq!(run.command, RunCommand::Merge);
+        let pending = run
+            .pending()
+            .into_iter()
+            .map(|(index, step)| (index, step.action))
+            .collect::<Vec<_>>();
+        assert_eq!(pending, [(1, Action::Rebase), (2, Action::Restack)]);
+        assert_eq!(Run::parse("{\"command\": \"land\"}"), None);
+    }
+}
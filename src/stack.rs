@@ -0,0 +1,397 @@
+//! Stacks of commits and the propagation of sync statuses through them.
+//!
+//! A commit's own [`SyncStatus`] only compares it with its PR.  Its effective
+//! status also depends on its ancestors: once any ancestor is out of sync, a
+//! commit that is otherwise in sync will need restacking when the ancestor's
+//! PR is updated.
+//!
+//! [`Stack`] and [`CommitInfo`] are serializable, so other tools can load a
+//! stack from a snapshot rather than querying jj, git and GitHub themselves.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Config;
+use crate::clients::git::GitClient;
+use crate::clients::github::GithubClient;
+use crate::clients::jujutsu::JujutsuClient;
+use crate::clients::jujutsu::JujutsuCommit;
+use crate::commit::CommitInfo;
+use crate::commit::SyncStatus;
+use crate::i18n::tr;
+use crate::jobs::try_join_limited;
+
+/// A stack of commits, ordered from parent to child (oldest to youngest).
+/// Where a stack branches, this is one path through it, from trunk to one of
+/// its heads.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stack {
+    pub commits: Vec<CommitInfo>,
+}
+
+impl Stack {
+    /// Create a stack from a snapshot of its commits, ordered from parent to
+    /// child.
+    pub fn new(commits: Vec<CommitInfo>) -> Self {
+        Self { commits }
+    }
+
+    /// Elaborate a stack of Jujutsu commits, ordered from child to parent as
+    /// returned by [`JujutsuClient`], failing if there are more than the
+    /// configured limit.
+    pub async fn load(
+        commits: Vec<JujutsuCommit>,
+        config: &Config,
+        jj: &JujutsuClient,
+        gh: &GithubClient,
+        git: &GitClient,
+    ) -> Result<Self> {
+        if let Some(limit) = config.stack_limit
+            && commits.len() > limit
+        {
+            bail!(tr!(
+                "stack-over-limit",
+                count = commits.len(),
+                limit = limit
+            ));
+        }
+        // Look up all their PRs at once, rather than one by one
+        let mut branches = vec![];
+        for commit in &commits {
+            branches.push(CommitInfo::find_branch(&commit.change_id, config, git).await);
+        }
+        gh.prefetch_branches(&branches).await;
+
+        let commit_futures = commits
+            .into_iter()
+            .rev()
+            .map(|commit| CommitInfo::new(commit, config, jj, gh, git));
+        Ok(Self::new(
+            try_join_limited(commit_futures, config.jobs()).await?,
+        ))
+    }
+
+    /// The effective status of each commit, in stack order.
+    pub fn statuses(&self) -> Vec<SyncStatus> {
+        self.statuses_after(SyncStatus::Synced)
+    }
+
+    /// The effective status of each commit, in stack order, given the
+    /// effective status of the bottom commit's parent, e.g. when the commits
+    /// below it aren't shown.
+    pub fn statuses_after(&self, parent: SyncStatus) -> Vec<SyncStatus> {
+        propagate_from(parent, self.commits.iter().map(CommitInfo::status))
+    }
+}
+
+impl SyncStatus {
+    /// The effective status of a commit with this status, given the effective
+    /// status of its parent.  The bottom commit's parent is trunk, which is
+    /// always [`SyncStatus::Synced`].
+    ///
+    /// | parent \ own | Unknown | Restack | Changed | Synced  |
+    /// |--------------|---------|---------|---------|---------|
+    /// | Synced       | Unknown | Restack | Changed | Synced  |
+    /// | Unknown      | Unknown | Restack | Changed | Restack |
+    /// | Restack      | Unknown | Restack | Changed | Restack |
+    /// | Changed      | Unknown | Restack | Changed | Restack |
+    pub fn after(self, parent: SyncStatus) -> SyncStatus {
+        match (parent, self) {
+            (SyncStatus::Synced, own) => own,
+            (_, SyncStatus::Synced) => SyncStatus::Restack,
+            (_, own) => own,
+        }
+    }
+
+    /// Whether a commit with this status has it as its effective status
+    /// whatever its parent's, so the commits below needn't be checked.  Only
+    /// [`SyncStatus::Synced`] depends on them.
+    pub fn is_conclusive(self) -> bool {
+        self != SyncStatus::Synced
+    }
+}
+
+/// Compute the effective statuses of a stack from each commit's own status,
+/// ordered from parent to child (oldest to youngest).
+pub fn propagate(statuses: impl IntoIterator<Item = SyncStatus>) -> Vec<SyncStatus> {
+    propagate_from(SyncStatus::Synced, statuses)
+}
+
+/// Like [`propagate`], for commits whose bottom one's parent has the given
+/// effective status, rather than being trunk.
+pub fn propagate_from(
+    parent: SyncStatus,
+    statuses: impl IntoIterator<Item = SyncStatus>,
+) -> Vec<SyncStatus> {
+    statuses
+        .into_iter()
+        .scan(parent, |parent, own| {
+            *parent = own.after(*parent);
+            Some(*parent)
+        })
+        .collect()
+}
+
+/// The effective status of the top of a run of commits, given their own
+/// statuses from the top down, as far as they've been checked, and whether
+/// that reaches trunk.  The first [conclusive](SyncStatus::is_conclusive)
+/// status settles it, so checking can stop there; until then, it's `None`.
+pub fn top_status(own_top_down: &[SyncStatus], reaches_trunk: bool) -> Option<SyncStatus> {
+    match own_top_down
+        .iter()
+        .position(|status| status.is_conclusive())
+    {
+        Some(0) => Some(own_top_down[0]),
+        // Synced commits above one that isn't need restacking
+        Some(_) => Some(SyncStatus::Restack),
+        None if reaches_trunk => Some(SyncStatus::Synced),
+        None => None,
+    }
+}
+
+/// Order commits from parent to child, if they're a contiguous run of a
+/// single stack, e.g. `a::c`: each but the bottom commit's parent is another
+/// of them, and none has two children among them.
+pub fn contiguous_range(mut commits: Vec<JujutsuCommit>) -> Option<Vec<JujutsuCommit>> {
+    let has_parent_in = |commit: &JujutsuCommit, commits: &[JujutsuCommit]| {
+        commits
+            .iter()
+            .any(|other| commit.parent_change_ids.contains(&other.change_id))
+    };
+    let mut bottoms = (0..commits.len()).filter(|&i| !has_parent_in(&commits[i], &commits));
+    let bottom = bottoms.next()?;
+    if bottoms.next().is_some() {
+        return None;
+    }
+
+    let mut range = vec![commits.remove(bottom)];
+    while !commits.is_empty() {
+        let top = &range[range.len() - 1].change_id;
+        let mut children =
+            (0..commits.len()).filter(|&i| commits[i].parent_change_ids.contains(top));
+        let child = children.next()?;
+        if children.next().is_some() {
+            return None;
+        }
+        range.push(commits.remove(child));
+    }
+    Some(range)
+}
+
+/// Group the branches of a repository's stacks, given each branch's changes,
+/// into stacks: branches sharing a change, even indirectly, are one stack.
+/// Returns the indices of each stack's branches, in order of their first.
+pub fn group_branches<T: Eq + Hash>(branches: &[HashSet<T>]) -> Vec<Vec<usize>> {
+    let mut stacks: Vec<Vec<usize>> = vec![];
+    for (i, branch) in branches.iter().enumerate() {
+        let (overlapping, rest): (Vec<_>, Vec<_>) = stacks
+            .into_iter()
+            .partition(|stack| stack.iter().any(|&j| !branches[j].is_disjoint(branch)));
+        stacks = rest;
+        let mut merged = overlapping.into_iter().flatten().collect::<Vec<_>>();
+        merged.push(i);
+        merged.sort_unstable();
+        stacks.push(merged);
+    }
+    stacks.sort_unstable_by_key(|stack| stack[0]);
+    stacks
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const ALL: [SyncStatus; 4] = [
+        SyncStatus::Unknown,
+        SyncStatus::Restack,
+        SyncStatus::Changed,
+        SyncStatus::Synced,
+    ];
+
+    #[test]
+    fn test_after() {
+        use SyncStatus::*;
+        // The table on `SyncStatus::after`, as (parent, own, expected)
+        #[rustfmt::skip]
+        let table = [
+            (Synced,  Unknown, Unknown), (Synced,  Restack, Restack),
+            (Synced,  Changed, Changed), (Synced,  Synced,  Synced),
+            (Unknown, Unknown, Unknown), (Unknown, Restack, Restack),
+            (Unknown, Changed, Changed), (Unknown, Synced,  Restack),
+            (Restack, Unknown, Unknown), (Restack, Restack, Restack),
+            (Restack, Changed, Changed), (Restack, Synced,  Restack),
+            (Changed, Unknown, Unknown), (Changed, Restack, Restack),
+            (Changed, Changed, Changed), (Changed, Synced,  Restack),
+        ];
+        for (parent, own, expected) in table {
+            assert_eq!(own.after(parent), expected, "{parent:?} -> {own:?}");
+        }
+    }
+
+    #[test]
+    fn test_propagate() {
+        use SyncStatus::*;
+        assert_eq!(propagate([]), []);
+        assert_eq!(propagate([Synced, Synced]), [Synced, Synced]);
+        assert_eq!(
+            propagate([Synced, Changed, Synced, Synced]),
+            [Synced, Changed, Restack, Restack]
+        );
+        assert_eq!(
+            propagate([Unknown, Synced, Changed]),
+            [Unknown, Restack, Changed]
+        );
+        assert_eq!(propagate([Restack, Synced]), [Restack, Restack]);
+    }
+
+    #[test]
+    fn test_is_conclusive() {
+        for own in ALL {
+            let unchanged = ALL.iter().all(|&parent| own.after(parent) == own);
+            assert_eq!(own.is_conclusive(), unchanged, "{own:?}");
+        }
+    }
+
+    #[test]
+    fn test_top_status() {
+        use SyncStatus::*;
+
+        assert_eq!(top_status(&[Changed, Synced], false), Some(Changed));
+        assert_eq!(top_status(&[Unknown], false), Some(Unknown));
+        assert_eq!(top_status(&[Synced, Synced, Changed], false), Some(Restack));
+        assert_eq!(top_status(&[Synced, Unknown, Synced], false), Some(Restack));
+        // Everything checked is synced, but there may be more below
+        assert_eq!(top_status(&[Synced, Synced], false), None);
+        assert_eq!(top_status(&[Synced, Synced], true), Some(Synced));
+        assert_eq!(top_status(&[], false), None);
+        assert_eq!(top_status(&[], true), Some(Synced));
+
+        // Agrees with propagating the statuses of the whole stack
+        let stack = [Synced, Restack, Synced, Synced];
+        let full = propagate(stack.iter().rev().copied());
+        assert_eq!(top_status(&stack, true), full.last().copied());
+        assert_eq!(
+            propagate_from(Restack, [Synced, Changed]),
+            [Restack, Changed]
+        );
+    }
+
+    #[test]
+    fn test_group_branches() {
+        let branches = [
+            HashSet::from(["a", "b"]),
+            HashSet::from(["c"]),
+            HashSet::from(["d", "e"]),
+            HashSet::from(["a", "f"]),
+            HashSet::from(["e", "c"]),
+        ];
+        assert_eq!(group_branches(&branches), [vec![0, 3], vec![1, 2, 4]]);
+        assert!(group_branches::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_contiguous_range() {
+        use crate::clients::git::CommitId;
+        use crate::clients::jujutsu::JujutsuChangeId;
+        use crate::clients::jujutsu::JujutsuCommitMessage;
+
+        let commit = |id: &str, parent: &str| JujutsuCommit {
+            change_id: JujutsuChangeId(id.to_string()),
+            commit_id: CommitId(id.to_string()),
+            message: JujutsuCommitMessage {
+                title: None,
+                body: None,
+            },
+            parent_change_ids: vec![JujutsuChangeId(parent.to_string())],
+            divergent: false,
+        };
+        let ids = |range: Option<Vec<JujutsuCommit>>| {
+            range.map(|range| {
+                range
+                    .into_iter()
+                    .map(|commit| commit.change_id.0)
+                    .collect::<Vec<_>>()
+            })
+        };
+        let (a, b, c) = (commit("a", "trunk"), commit("b", "a"), commit("c", "b"));
+
+        // jj lists children first
+        assert_eq!(
+            ids(contiguous_range(vec![c.clone(), b.clone(), a.clone()])),
+            Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        assert_eq!(
+            ids(contiguous_range(vec![c.clone(), b.clone()])),
+            Some(vec!["b".to_string(), "c".to_string()])
+        );
+        assert_eq!(ids(contiguous_range(vec![])), None);
+        // A gap, or a branch
+        assert_eq!(ids(contiguous_range(vec![c.clone(), a.clone()])), None);
+        let d = commit("d", "a");
+        assert_eq!(ids(contiguous_range(vec![d, b, a])), None);
+    }
+
+    #[test]
+    fn test_stack_from_snapshot() {
+        let commit = |change_id: &str, pr_tip: Option<&str>| {
+            serde_json::json!({
+                "commit": {
+                    "change_id": change_id,
+                    "commit_id": "0123456789abcdef",
+                    "message": { "title": "Title", "body": null },
+                    "parent_change_ids": [],
+                    "divergent": false,
+                },
+                "commit_diff": "diff",
+                "commit_diff_norm": "diff",
+                "pr_branch": format!("test/{change_id}"),
+                "pr_tip": pr_tip,
+                "pr": null,
+                "pr_diff": "diff",
+                "pr_diff_norm": "diff",
+                "base_branch": "main",
+                "base_tip": "fedcba9876543210",
+                "pr_contains_base": true,
+            })
+        };
+        let snapshot = serde_json::json!({
+            "commits": [commit("klmnopqr", None), commit("stuvwxyz", Some("abc"))],
+        });
+
+        let stack: Stack = serde_json::from_value(snapshot.clone()).unwrap();
+        assert_eq!(stack.statuses(), [SyncStatus::Unknown, SyncStatus::Restack]);
+        assert_eq!(serde_json::to_value(&stack).unwrap(), snapshot);
+        assert_eq!(
+            serde_json::to_value(SyncStatus::Restack).unwrap(),
+            "restack"
+        );
+    }
+
+    fn status() -> impl Strategy<Value = SyncStatus> {
+        prop::sample::select(ALL.to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_propagate(statuses in prop::collection::vec(status(), 0..32)) {
+            let effective = propagate(statuses.clone());
+            prop_assert_eq!(effective.len(), statuses.len());
+            for (i, (own, effective)) in statuses.iter().zip(&effective).enumerate() {
+                // Only in-sync commits are affected by their ancestors
+                if *own != SyncStatus::Synced {
+                    prop_assert_eq!(effective, own);
+                }
+                // A commit is in sync iff it and all its ancestors are
+                let all_synced = statuses[..=i].iter().all(|s| *s == SyncStatus::Synced);
+                prop_assert_eq!(*effective == SyncStatus::Synced, all_synced);
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Stack navigation comments on PRs.
+//!
+//! Reviewers on GitHub can't see a PR's place in its stack, so each PR in a
+//! stack of two or more gets a comment listing the stack's PRs, top first,
+//! with the PR itself marked.  The comment carries a hidden marker, so that
+//! later runs edit it in place rather than posting another.
+
+use crate::i18n::tr;
+use crate::model::IssueComment;
+
+/// Hidden marker identifying jr's stack comments.
+pub const MARKER: &str = "<!-- jr:stack -->";
+
+/// Render the stack comment for the PR numbered `current`, given the numbers
+/// of the stack's PRs from bottom to top.
+pub fn render(numbers: &[u64], current: u64) -> String {
+    let mut body = format!("{}\n\n", tr!("stack-comment-heading"));
+    for number in numbers.iter().rev() {
+        if *number == current {
+            body.push_str(&format!("* **→ #{number}**\n"));
+        } else {
+            body.push_str(&format!("* #{number}\n"));
+        }
+    }
+    body.push_str(&format!("\n{MARKER}"));
+    body
+}
+
+/// Find jr's stack comment among a PR's comments.
+pub fn find(comments: &[IssueComment]) -> Option<&IssueComment> {
+    comments.iter().find(|comment| {
+        comment
+            .body
+            .as_deref()
+            .is_some_and(|body| body.contains(MARKER))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(&[3, 7, 8], 7),
+            "Stack, from top to bottom:\n\n\
+             * #8\n\
+             * **→ #7**\n\
+             * #3\n\
+             \n\
+             <!-- jr:stack -->"
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let comment = |id, body: &str| IssueComment {
+            id,
+            body: Some(body.to_string()),
+            created_at: "2026-03-01T09:00:00Z".parse().unwrap(),
+        };
+        let comments = [comment(1, "LGTM"), comment(2, &render(&[1, 2], 2))];
+        assert_eq!(find(&comments).map(|comment| comment.id), Some(2));
+        assert!(find(&comments[..1]).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+//! PR metadata kept between commands.
+//!
+//! Looking up each commit's PR and its diff costs a GitHub request apiece,
+//! which dominates `jr status` on a large stack.  The PRs last seen are kept
+//! in `.git/jr/state.json`, keyed by change ID, and reused while the PR
+//! branch's tip is unchanged.  Anything else about a PR, e.g. its reviews or
+//! draft status, can go stale until the branch moves, which `jr status
+//! --refresh` forces.
+//!
+//! The file also keeps how long pushes and GitHub requests have taken, for
+//! estimating how long a dry run's would (see [`crate::plan`]).
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::clients::git::CommitId;
+use crate::clients::jujutsu::JujutsuChangeId;
+use crate::model::PullRequest;
+use crate::plan::Timings;
+use crate::stack::Stack;
+
+/// Path of the state file, in the `.git` directory.
+pub const FILE_NAME: &str = "jr/state.json";
+
+/// What was last seen of each commit's PR.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Keyed by change ID.
+    #[serde(default)]
+    pub prs: BTreeMap<String, CachedPr>,
+    /// Labels jr has applied to PRs (see [`crate::labels`]).
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
+    /// How long pushes and GitHub requests take (see [`crate::plan`]).
+    #[serde(default)]
+    pub timings: Timings,
+}
+
+/// A PR, with its diff.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedPr {
+    pub pr: PullRequest,
+    pub diff: Option<String>,
+}
+
+impl State {
+    /// Parse a state file, treating one that can't be parsed, e.g. from a
+    /// different version of jr, as empty.
+    pub fn parse(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("state serializes")
+    }
+
+    /// The PR last seen for a change, if its branch is still at `tip`.
+    pub fn fresh(&self, change_id: &JujutsuChangeId, tip: &CommitId) -> Option<&CachedPr> {
+        self.prs
+            .get(&change_id.0)
+            .filter(|cached| cached.pr.head.sha == tip.0)
+    }
+
+    /// Remember the PRs of a loaded stack, if they match their branch's tip.
+    pub fn record(&mut self, stack: &Stack) {
+        for commit in &stack.commits {
+            if let Some(pr) = &commit.pr
+                && commit
+                    .pr_tip
+                    .as_ref()
+                    .is_some_and(|tip| tip.0 == pr.head.sha)
+            {
+                let cached = CachedPr {
+                    pr: pr.clone(),
+                    diff: commit.pr_diff.clone(),
+                };
+                self.prs.insert(commit.commit.change_id.0.clone(), cached);
+            }
+        }
+    }
+
+    /// Forget the PRs last seen merged or closed, and those of changes that
+    /// aren't `visible` any more, returning how many were forgotten.
+    pub fn prune(&mut self, visible: impl Fn(&str) -> bool) -> usize {
+        let before = self.prs.len();
+        self.prs
+            .retain(|change_id, cached| cached.pr.is_open() && visible(change_id));
+        before - self.prs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh() {
+        let pr: PullRequest = serde_json::from_value(serde_json::json!({
+            "number": 1,
+            "node_id": "PR_1",
+            "html_url": "https://github.com/o/r/pull/1",
+            "state": "open",
+            "title": "Title",
+            "body": null,
+            "base": { "ref": "main", "sha": "0" },
+            "head": { "ref": "me/abc", "sha": "1" },
+            "milestone": null,
+        }))
+        .unwrap();
+        let mut state = State::default();
+        let cached = CachedPr {
+            pr,
+            diff: Some("diff".to_string()),
+        };
+        state.prs.insert("abc".to_string(), cached);
+        let state = State::parse(&state.to_json());
+
+        let change_id = JujutsuChangeId("abc".to_string());
+        let cached = state.fresh(&change_id, &CommitId("1".to_string())).unwrap();
+        assert_eq!(cached.pr.url, "https://github.com/o/r/pull/1");
+        assert_eq!(cached.diff.as_deref(), Some("diff"));
+        assert!(
+            state
+                .fresh(&change_id, &CommitId("2".to_string()))
+                .is_none()
+        );
+        let other = JujutsuChangeId("def".to_string());
+        assert!(state.fresh(&other, &CommitId("1".to_string())).is_none());
+        assert!(State::parse("not json").prs.is_empty());
+    }
+
+    #[test]
+    fn test_prune() {
+        let cached = |change_id: &str, state: &str| {
+            let pr = serde_json::from_value(serde_json::json!({
+                "number": 1,
+                "node_id": "PR_1",
+                "html_url": "https://github.com/o/r/pull/1",
+                "state": state,
+                "title": "Title",
+                "body": null,
+                "base": { "ref": "main", "sha": "0" },
+                "head": { "ref": format!("me/{change_id}"), "sha": "1" },
+                "milestone": null,
+            }))
+            .unwrap();
+            (change_id.to_string(), CachedPr { pr, diff: None })
+        };
+        let mut state = State {
+            prs: [
+                cached("open", "open"),
+                cached("closed", "closed"),
+                cached("abandoned", "open"),
+            ]
+            .into(),
+            ..State::default()
+        };
+        assert_eq!(state.prune(|change_id| change_id != "abandoned"), 2);
+        assert_eq!(state.prs.keys().collect::<Vec<_>>(), ["open"]);
+    }
+}
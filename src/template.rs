@@ -0,0 +1,61 @@
+//! Templates for PR descriptions.
+//!
+//! A template set with `jr.prTemplate`, or else committed as
+//! [`TEMPLATE_PATH`], wraps the commit's description in the PR's.  It may
+//! reference the placeholders `{{title}}`, `{{body}}` (the commit
+//! description's title and the rest of it), `{{change_id}}`,
+//! `{{stack_position}}` and `{{stack_size}}` (the commit's position in its
+//! stack, counting from 1 at the bottom, and how many commits the stack has).
+//! Other text, including unknown placeholders, is kept as is.
+
+/// Where a repository's PR template is committed.
+pub const TEMPLATE_PATH: &str = ".github/jr_pr_template.md";
+
+/// Render a template, replacing each `{{name}}` placeholder, optionally
+/// padded with spaces, with the value of the same name.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = after[..end].trim();
+            let (_, value) = values.iter().find(|(key, _)| *key == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let values = [
+            ("title", "Add {{body}}"),
+            ("body", "Details"),
+            ("stack_position", "2"),
+        ];
+        assert_eq!(
+            render(
+                "## {{ title }}\n\n{{body}}\n\nPart {{stack_position}} {{unknown}} {{",
+                &values
+            ),
+            "## Add {{body}}\n\nDetails\n\nPart 2 {{unknown}} {{"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Cleaning up commit titles for PRs and the terminal.
+//!
+//! Titles come straight from commit descriptions, so may contain tabs,
+//! terminal escape sequences or bidirectional overrides, which misalign
+//! `jr status` output and can be rejected by GitHub.  Widths are measured in
+//! terminal columns, as emoji and CJK characters take two.
+
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+/// Widest title shown in the terminal, in columns.
+pub const DISPLAY_WIDTH: usize = 72;
+
+/// Marks where a title was truncated.
+const ELLIPSIS: char = '…';
+
+/// Strip escape sequences, control and bidirectional formatting characters
+/// from a title, and collapse and trim its whitespace.
+pub fn sanitize(title: &str) -> String {
+    let mut sanitized = String::with_capacity(title.len());
+    let mut chars = title.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence such as a color, up to its final byte
+            if chars.clone().next() == Some('[') {
+                chars.find(|c| ('@'..='~').contains(c) && *c != '[');
+            }
+        } else if c.is_whitespace() || c.is_control() {
+            if !sanitized.is_empty() && !sanitized.ends_with(' ') {
+                sanitized.push(' ');
+            }
+        } else if !is_bidi_control(c) {
+            sanitized.push(c);
+        }
+    }
+    sanitized.truncate(sanitized.trim_end().len());
+    sanitized
+}
+
+/// Truncate a title to at most `width` columns, ending it with an ellipsis
+/// if anything was cut.
+pub fn truncate(title: &str, width: usize) -> String {
+    if title.width() <= width {
+        return title.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = ELLIPSIS.width().unwrap_or(1);
+    for c in title.chars() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// A title as shown in the terminal: sanitized and truncated to
+/// [`DISPLAY_WIDTH`].
+pub fn display(title: &str) -> String {
+    truncate(&sanitize(title), DISPLAY_WIDTH)
+}
+
+/// Whether a character reorders the text around it, so could disguise a
+/// title.
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("  Fix\tthe  bug\r\n"), "Fix the bug");
+        assert_eq!(sanitize("\x1b[31mRed\x1b[0m title"), "Red title");
+        assert_eq!(sanitize("Admin \u{202e}nimda"), "Admin nimda");
+        assert_eq!(sanitize("Ship it 🚀"), "Ship it 🚀");
+        // Zero-width joiners hold emoji sequences together
+        assert_eq!(sanitize("Family 👨\u{200d}👩"), "Family 👨\u{200d}👩");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Short", 10), "Short");
+        assert_eq!(truncate("Exactly ten", 11), "Exactly ten");
+        assert_eq!(truncate("Rather long title", 10), "Rather lo…");
+        assert_eq!(truncate("Too long title", 8), "Too lon…");
+        // Wide characters take two columns
+        assert_eq!(truncate("日本語のタイトル", 7), "日本語…");
+        assert_eq!(truncate("🚀🚀🚀🚀", 6), "🚀🚀…");
+    }
+}
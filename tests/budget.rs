@@ -0,0 +1,109 @@
+//! Performance budgets, measured like `benches/status.rs` but failing when
+//! exceeded.  The budgets are for release builds, and are loosened for
+//! unoptimized ones.  The tests needing jj are ignored by default; run them
+//! all as
+//!
+//!   cargo test --release --features fixtures --test budget -- --include-ignored
+
+use std::time::Duration;
+use std::time::Instant;
+
+use jr::commands::submit::SubmitOptions;
+use jr::diff_utils::normalize_diff;
+use jr::fixtures;
+use jr::fixtures::Shape;
+use jr::stack::Stack;
+
+/// Commits in the stacks measured.
+const STACK_SIZE: usize = 50;
+
+/// Comparing the diffs of a whole stack, so that status time is dominated by
+/// jj, git and GitHub.
+const COMPARE_DIFFS_BUDGET: Duration = Duration::from_millis(20);
+
+/// Loading a stack as `jr status` does, jj and git included.
+const LOAD_BUDGET: Duration = Duration::from_secs(5);
+
+/// Working out what `jr submit` would do for a stack without PRs.
+const SUBMIT_PLAN_BUDGET: Duration = Duration::from_secs(20);
+
+/// Runs to take the fastest of, so that a busy machine doesn't fail them.
+const RUNS: usize = 3;
+
+/// How much slower than a release build an unoptimized one may be.
+const DEBUG_SLOWDOWN: u32 = 20;
+
+/// A budget for this build.
+fn budget(release: Duration) -> Duration {
+    if cfg!(debug_assertions) {
+        release * DEBUG_SLOWDOWN
+    } else {
+        release
+    }
+}
+
+/// The fastest of [`RUNS`] runs of `f`.
+async fn fastest<F: AsyncFnMut()>(mut f: F) -> Duration {
+    let mut fastest = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        f().await;
+        fastest = fastest.min(start.elapsed());
+    }
+    fastest
+}
+
+#[tokio::test]
+async fn test_compare_diffs_budget() {
+    let diffs = fixtures::synthetic_diffs(STACK_SIZE);
+    let elapsed = fastest(async || {
+        let matching = diffs
+            .iter()
+            .filter(|(commit_diff, pr_diff)| normalize_diff(commit_diff) == normalize_diff(pr_diff))
+            .count();
+        assert_eq!(matching, STACK_SIZE);
+    })
+    .await;
+    assert!(
+        elapsed < budget(COMPARE_DIFFS_BUDGET),
+        "Comparing diffs took {elapsed:?}, over the budget of {:?}",
+        budget(COMPARE_DIFFS_BUDGET)
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_fixture_budgets() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture = fixtures::build(dir.path(), &Shape::linear(STACK_SIZE))
+        .await
+        .unwrap();
+    let app = fixture.offline_app("budget/").unwrap();
+    let commits = app.jj.get_stack_ancestors("@-").await.unwrap();
+    assert_eq!(commits.len(), STACK_SIZE);
+
+    let elapsed = fastest(async || {
+        let stack = Stack::load(commits.clone(), &app.config, &app.jj, &app.gh, &app.git)
+            .await
+            .unwrap();
+        assert_eq!(stack.commits.len(), STACK_SIZE);
+    })
+    .await;
+    assert!(
+        elapsed < budget(LOAD_BUDGET),
+        "Loading the stack took {elapsed:?}, over the budget of {:?}",
+        budget(LOAD_BUDGET)
+    );
+
+    let elapsed = fastest(async || {
+        app.cmd_submit("::@-", &SubmitOptions::default(), &mut std::io::sink())
+            .await
+            .unwrap();
+    })
+    .await;
+    assert!(
+        elapsed < budget(SUBMIT_PLAN_BUDGET),
+        "Planning the submit took {elapsed:?}, over the budget of {:?}",
+        budget(SUBMIT_PLAN_BUDGET)
+    );
+}
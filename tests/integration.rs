@@ -18,6 +18,8 @@ use std::sync::LazyLock;
 
 use futures_util::future;
 use jr::clients::git::GitClient;
+use jr::commands::create::CreateOptions;
+use jr::commands::status::StatusOptions;
 use log::debug;
 use serde::Deserialize;
 use tracing::instrument;
@@ -109,9 +111,9 @@ async fn setup(temp_path: &std::path::Path) -> anyhow::Result<()> {
     future::try_join_all(delete_futures).await?;
 
     // Update git repo again because we deleted remote branches
-    utils::jj_git_fetch(&temp_path).await?;
+    utils::jj_git_fetch(temp_path).await?;
 
-    utils::jj_new(&temp_path, "master").await?;
+    utils::jj_new(temp_path, "master").await?;
 
     utils::create_jj_commit(temp_path, "Alpha", "alpha", "alpha\n").await?;
     utils::create_jj_commit(temp_path, "Beta", "beta", "beta\n").await?;
@@ -159,11 +161,15 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     let github = jr::clients::github::GithubClient::new(
         TEST_CONFIG.github_token.clone(),
         test_dir.path().into(),
+        jr::clients::github::GithubHost::default(),
+        "origin",
     )
     .await?;
     let app = jr::App::new(config, github, test_dir.path().into());
+    let create_options = CreateOptions::default();
+    let status_options = StatusOptions::default();
 
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ? [CHGID]
     ? [CHGID] Gamma
@@ -177,7 +183,7 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     debug!("Updating PR for alpha");
     let mut out = Vec::new();
     let res = app
-        .cmd_update("description(Alpha)", "message", &mut out)
+        .cmd_update("description(Alpha)", Some("message"), false, &mut out)
         .await;
     assert_snapshot_filtered!(res.err().unwrap(), INSTA_FILTERS, @"PR branch [BRANCH] does not exist. Use 'jr create' to create a new PR.");
 
@@ -193,11 +199,12 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     // Create PR for Alpha
 
     debug!("Creating PR for alpha");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_create("description(Alpha)", out));
+    let (out, _) =
+        run_and_capture!(|out, _| app.cmd_create("description(Alpha)", &create_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Created PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ? [CHGID]
     ? [CHGID] Gamma
@@ -212,7 +219,11 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     debug!("Recreating PR for alpha");
     let mut out = Vec::new();
     let res = app
-        .cmd_create("description(Alpha) & ~remote_bookmarks()", &mut out)
+        .cmd_create(
+            "description(Alpha) & ~remote_bookmarks()",
+            &create_options,
+            &mut out,
+        )
         .await;
     assert_snapshot_filtered!(res.err().unwrap(), INSTA_FILTERS, @"PR branch already exists: [BRANCH]");
 
@@ -224,7 +235,8 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     let res = app
         .cmd_update(
             "description(Alpha) & ~remote_bookmarks()",
-            "message",
+            Some("message"),
+            false,
             &mut out,
         )
         .await;
@@ -245,18 +257,21 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
 
     debug!("Creating PR for gamma");
     let mut out = Vec::new();
-    let res = app.cmd_create("description(Gamma)", &mut out).await;
-    insta::assert_snapshot!(res.err().unwrap(), @"Parent commit has no PR branch. Create parent PR first (bottom-up).");
+    let res = app
+        .cmd_create("description(Gamma)", &create_options, &mut out)
+        .await;
+    insta::assert_snapshot!(res.err().unwrap(), @"Parent commit has no PR branch. Create parent PR first (bottom-up). (override with --override parent-up-to-date)");
 
     // -------------------------------------------------------------------------
     // Create PR for Beta
 
     debug!("Creating PR for beta");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_create("description(Beta)", out));
+    let (out, _) =
+        run_and_capture!(|out, _| app.cmd_create("description(Beta)", &create_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Created PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ? [CHGID]
     ? [CHGID] Gamma
@@ -270,11 +285,12 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     // Create PR for Gamma
 
     debug!("Creating PR for gamma");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_create("description(Gamma)", out));
+    let (out, _) =
+        run_and_capture!(|out, _| app.cmd_create("description(Gamma)", &create_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Created PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ? [CHGID]
     ✓ [CHGID] Gamma
@@ -293,7 +309,7 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     tokio::fs::write(test_dir.path().join("alpha"), "alpha1\n").await?;
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ↻ [CHGID] Gamma
       https://github.com/[USER]/[REPO]/[PRID]
@@ -322,13 +338,14 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     debug!("Updating alpha");
     let (out, _) = run_and_capture!(|out, _| app.cmd_update(
         "description(Alpha) & ~remote_bookmarks()",
-        "Update alpha",
+        Some("Update alpha"),
+        false,
         out
     ));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Updated PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ↻ [CHGID] Gamma
       https://github.com/[USER]/[REPO]/[PRID]
@@ -346,7 +363,7 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     let res = app
         .cmd_restack("description(Gamma) & ~remote_bookmarks()", &mut out)
         .await;
-    assert_snapshot_filtered!(res.err().unwrap(), INSTA_FILTERS, @"Cannot update PR: parent PR needs restacking. Its base branch has been updated. Run 'jr restack' on the parent first.");
+    assert_snapshot_filtered!(res.err().unwrap(), INSTA_FILTERS, @"Cannot update PR: parent PR needs restacking. Its base branch has been updated. Run 'jr restack' on the parent first. (override with --override parent-up-to-date)");
 
     // -------------------------------------------------------------------------
     // Restack Beta
@@ -357,7 +374,7 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Updated PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Gettings status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ↻ [CHGID] Gamma
       https://github.com/[USER]/[REPO]/[PRID]
@@ -376,7 +393,7 @@ async fn test_stacked_workflow() -> anyhow::Result<()> {
     assert_snapshot_filtered!(out, INSTA_FILTERS, @"Updated PR: https://github.com/[USER]/[REPO]/[PRID]");
 
     debug!("Getting status");
-    let (out, _) = run_and_capture!(|out, _| app.cmd_status(out));
+    let (out, _) = run_and_capture!(|out, _| app.cmd_status(&status_options, out));
     assert_snapshot_filtered!(out, INSTA_FILTERS, @r"
     ✓ [CHGID] Gamma
       https://github.com/[USER]/[REPO]/[PRID]
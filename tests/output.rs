@@ -0,0 +1,517 @@
+//! Golden tests for user-visible output, rendered from stack snapshots so
+//! they don't need jj or GitHub.
+
+use std::collections::HashMap;
+
+use fluent_bundle::FluentArgs;
+use insta::assert_snapshot;
+use jr::clients::git::CommitId;
+use jr::clients::jujutsu::JujutsuChangeId;
+use jr::clients::jujutsu::JujutsuCommit;
+use jr::clients::jujutsu::JujutsuCommitMessage;
+use jr::commands::dashboard::Dashboard;
+use jr::commands::dashboard::DashboardCommit;
+use jr::commands::dashboard::DashboardPr;
+use jr::commands::dashboard::DashboardStack;
+use jr::commands::dashboard::write_dashboard;
+use jr::commands::status::Hidden;
+use jr::commands::status::write_forest;
+use jr::commands::status::write_highlighted;
+use jr::commands::status::write_status;
+use jr::commit::CommitInfo;
+use jr::commit::SyncStatus;
+use jr::i18n;
+use jr::model::Account;
+use jr::model::BranchRef;
+use jr::model::CiState;
+use jr::model::Milestone;
+use jr::model::PullRequest;
+use jr::model::PullRequestState;
+use jr::model::ReviewDecision;
+use jr::model::TimelineEvent;
+use jr::stack::Stack;
+
+#[ctor::ctor]
+fn init() {
+    colored::control::set_override(false);
+}
+
+/// The state of a commit relative to its PR.
+#[derive(Clone, Copy)]
+enum State {
+    /// No PR yet.
+    NoPr,
+    /// PR matches the commit and contains its base.
+    Synced,
+    /// PR matches the commit but not its base.
+    StaleBase,
+    /// Commit differs from its PR.
+    Changed,
+}
+
+fn commit(n: usize, title: &str, state: State) -> CommitInfo {
+    let change_id = ["kkkk", "llll", "mmmm", "nnnn", "oooo"][n].repeat(8);
+    let pr_branch = format!("test/{}", &change_id[..8]);
+    let has_pr = !matches!(state, State::NoPr);
+    let pr = has_pr.then(|| PullRequest {
+        number: n as u64 + 1,
+        node_id: format!("PR_{n}"),
+        url: format!("https://github.com/o/r/pull/{}", n + 1),
+        state: PullRequestState::Open,
+        draft: false,
+        title: title.to_string(),
+        body: None,
+        base: BranchRef {
+            name: "main".to_string(),
+            sha: "base".to_string(),
+        },
+        head: BranchRef {
+            name: pr_branch.clone(),
+            sha: "head".to_string(),
+        },
+        mergeable: None,
+        review_decision: None,
+        milestone: None,
+        user: None,
+        requested_reviewers: vec![],
+        created_at: None,
+        merged_at: None,
+        labels: vec![],
+    });
+    let pr_diff = match state {
+        State::NoPr => None,
+        State::Changed => Some("old diff".to_string()),
+        State::Synced | State::StaleBase => Some("diff".to_string()),
+    };
+    CommitInfo {
+        commit: JujutsuCommit {
+            change_id: JujutsuChangeId(change_id),
+            commit_id: CommitId(format!("{n:040x}")),
+            message: JujutsuCommitMessage {
+                title: (!title.is_empty()).then(|| title.to_string()),
+                body: None,
+            },
+            parent_change_ids: vec![],
+            divergent: false,
+        },
+        commit_diff: "diff".to_string(),
+        commit_diff_norm: "diff".to_string(),
+        pr_branch,
+        pr_tip: has_pr.then(|| CommitId("head".to_string())),
+        pr_diff_norm: pr_diff.clone(),
+        pr,
+        pr_diff,
+        base_branch: "main".to_string(),
+        base_tip: Some(CommitId("base".to_string())),
+        pr_contains_base: !matches!(state, State::StaleBase),
+    }
+}
+
+/// Render the status of a stack given from parent to child, with the top
+/// commit current.
+fn status(commits: Vec<CommitInfo>, hidden: Hidden) -> String {
+    let current = commits.last().unwrap().commit.change_id.clone();
+    let mut out = vec![];
+    write_status(
+        &Stack::new(commits),
+        &current,
+        hidden,
+        &HashMap::new(),
+        &mut out,
+    )
+    .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_status_no_prs() {
+    let out = status(
+        vec![
+            commit(0, "Alpha", State::NoPr),
+            commit(1, "Beta", State::NoPr),
+            commit(2, "", State::NoPr),
+        ],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @r#"
+    ? mmmm
+    ? llll Beta
+    ? kkkk Alpha
+    "#);
+}
+
+#[test]
+fn test_status_partially_created() {
+    let out = status(
+        vec![
+            commit(0, "Alpha", State::Synced),
+            commit(1, "Beta", State::Synced),
+            commit(2, "Gamma", State::NoPr),
+        ],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @r#"
+    ? mmmm Gamma
+    ✓ llll Beta
+      https://github.com/o/r/pull/2
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    "#);
+}
+
+#[test]
+fn test_status_changed_propagates_restack() {
+    let out = status(
+        vec![
+            commit(0, "Alpha", State::Synced),
+            commit(1, "Beta", State::Changed),
+            commit(2, "Gamma", State::Synced),
+            commit(3, "Delta", State::Synced),
+        ],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @r#"
+    ↻ nnnn Delta
+      https://github.com/o/r/pull/4
+    ↻ mmmm Gamma
+      https://github.com/o/r/pull/3
+    ✗ llll Beta
+      https://github.com/o/r/pull/2
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    "#);
+}
+
+#[test]
+fn test_status_stale_base() {
+    let out = status(
+        vec![
+            commit(0, "Alpha", State::StaleBase),
+            commit(1, "Beta", State::Changed),
+        ],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @r#"
+    ✗ llll Beta
+      https://github.com/o/r/pull/2
+    ↻ kkkk Alpha
+      https://github.com/o/r/pull/1
+    "#);
+}
+
+#[test]
+fn test_status_milestone_and_depth() {
+    let mut top = commit(4, "Epsilon", State::Synced);
+    top.pr.as_mut().unwrap().milestone = Some(Milestone {
+        number: 1,
+        title: "v1.0".to_string(),
+    });
+    let synced = Hidden {
+        count: 1,
+        top: Some(SyncStatus::Synced),
+    };
+    let out = status(vec![commit(3, "Delta", State::Synced), top.clone()], synced);
+    assert_snapshot!(out, @r#"
+    ✓ oooo Epsilon
+      https://github.com/o/r/pull/5 (milestone: v1.0)
+    ✓ nnnn Delta
+      https://github.com/o/r/pull/4
+    … 1 more synced commit below
+    "#);
+    // A change below the commits shown still needs them restacked
+    let changed = Hidden {
+        count: 3,
+        top: Some(SyncStatus::Changed),
+    };
+    let out = status(vec![commit(3, "Delta", State::Synced), top], changed);
+    assert_snapshot!(out, @r#"
+    ↻ oooo Epsilon
+      https://github.com/o/r/pull/5 (milestone: v1.0)
+    ↻ nnnn Delta
+      https://github.com/o/r/pull/4
+    … 3 more commits below, not all in sync
+    "#);
+    let unchecked = Hidden {
+        count: 3,
+        top: None,
+    };
+    let out = status(vec![commit(3, "Delta", State::Changed)], unchecked);
+    assert_snapshot!(out, @r#"
+    ✗ nnnn Delta
+      https://github.com/o/r/pull/4
+    … 3 more commits below (not checked)
+    "#);
+}
+
+#[test]
+fn test_status_ready_before_parent() {
+    let mut parent = commit(0, "Alpha", State::Synced);
+    parent.pr.as_mut().unwrap().draft = true;
+    let out = status(
+        vec![parent, commit(1, "Beta", State::Synced)],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @"
+    ✓ llll Beta
+      https://github.com/o/r/pull/2 (ready for review before its draft parent)
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    ");
+}
+
+#[test]
+fn test_status_conflicts() {
+    let mut conflicted = commit(1, "Beta", State::Synced);
+    conflicted.pr.as_mut().unwrap().mergeable = Some(false);
+    let out = status(
+        vec![commit(0, "Alpha", State::Synced), conflicted],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @"
+    ✓ llll Beta
+      https://github.com/o/r/pull/2 (⚠ conflicts with base)
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    ");
+}
+
+#[test]
+fn test_status_watch_highlights_changes() {
+    let before = status(vec![commit(0, "Alpha", State::NoPr)], Hidden::default());
+    let after = status(vec![commit(0, "Alpha", State::Synced)], Hidden::default());
+    let mut out = vec![];
+    write_highlighted(None, &before, &mut out).unwrap();
+    write_highlighted(Some(&before), &after, &mut out).unwrap();
+    assert_snapshot!(String::from_utf8(out).unwrap(), @"
+      ? kkkk Alpha
+    ▌ ✓ kkkk Alpha
+    ▌   https://github.com/o/r/pull/1
+    ");
+}
+
+#[test]
+fn test_status_divergent() {
+    let mut divergent = commit(1, "Beta", State::Synced);
+    divergent.commit.divergent = true;
+    let out = status(
+        vec![commit(0, "Alpha", State::Synced), divergent],
+        Hidden::default(),
+    );
+    assert_snapshot!(out, @"
+    ⑂ llll Beta
+      divergent change; resolve it before updating this PR
+      https://github.com/o/r/pull/2
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    ");
+}
+
+#[test]
+fn test_status_branching() {
+    let (alpha, beta) = (
+        commit(0, "Alpha", State::Synced),
+        commit(1, "Beta", State::Synced),
+    );
+    let main = Stack::new(vec![
+        alpha.clone(),
+        beta.clone(),
+        commit(2, "Gamma", State::Changed),
+    ]);
+    let sibling = Stack::new(vec![alpha, beta, commit(3, "Delta", State::StaleBase)]);
+    let current = main.commits[1].commit.change_id.clone();
+    let mut out = vec![];
+    write_forest(
+        &[(main, Hidden::default()), (sibling, Hidden::default())],
+        &current,
+        &HashMap::new(),
+        &mut out,
+    )
+    .unwrap();
+    assert_snapshot!(String::from_utf8(out).unwrap(), @r#"
+    ↻ nnnn Delta
+      https://github.com/o/r/pull/4
+      ╰─ branches from llll
+    ✗ mmmm Gamma
+      https://github.com/o/r/pull/3
+    ✓ llll Beta
+      https://github.com/o/r/pull/2
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+    "#);
+}
+
+#[test]
+fn test_status_external_changes() {
+    let event = |event: &str, date: &str| TimelineEvent {
+        event: event.to_string(),
+        actor: Some(Account {
+            login: "alice".to_string(),
+        }),
+        created_at: Some(date.parse().unwrap()),
+    };
+    let commits = vec![commit(0, "Alpha", State::Synced)];
+    let current = commits[0].commit.change_id.clone();
+    let external_changes = HashMap::from([(
+        1,
+        vec![
+            event("base_ref_changed", "2024-01-02T12:00:00Z"),
+            event("ready_for_review", "2024-01-03T12:00:00Z"),
+        ],
+    )]);
+    let mut out = vec![];
+    write_status(
+        &Stack::new(commits),
+        &current,
+        Hidden::default(),
+        &external_changes,
+        &mut out,
+    )
+    .unwrap();
+    assert_snapshot!(String::from_utf8(out).unwrap(), @"
+    ✓ kkkk Alpha
+      https://github.com/o/r/pull/1
+        ! Externally modified: alice changed the base branch to main on 2024-01-02
+        ! Externally modified: alice marked it ready for review on 2024-01-03
+    ");
+}
+
+#[test]
+fn test_dashboard() {
+    let now = "2024-01-10T12:00:00Z".parse().unwrap();
+    let pr = |number, draft, review_decision, ci, created_at: &str| {
+        Some(DashboardPr {
+            number,
+            url: format!("https://github.com/o/r/pull/{number}"),
+            draft,
+            review_decision,
+            ci,
+            created_at: Some(created_at.parse().unwrap()),
+        })
+    };
+    let commit = |change_id: &str, title: &str, status, pr| DashboardCommit {
+        change_id: change_id.to_string(),
+        title: title.to_string(),
+        status,
+        pr,
+    };
+    let dashboard = Dashboard {
+        stacks: vec![
+            DashboardStack {
+                commits: vec![
+                    commit(
+                        "kkkk",
+                        "Alpha",
+                        SyncStatus::Synced,
+                        pr(
+                            1,
+                            false,
+                            Some(ReviewDecision::Approved),
+                            CiState::Success,
+                            "2024-01-02T12:00:00Z",
+                        ),
+                    ),
+                    commit(
+                        "llll",
+                        "Beta",
+                        SyncStatus::Changed,
+                        pr(
+                            12,
+                            false,
+                            Some(ReviewDecision::ChangesRequested),
+                            CiState::Failure,
+                            "2024-01-10T07:00:00Z",
+                        ),
+                    ),
+                    commit("mmmm", "Gamma", SyncStatus::Unknown, None),
+                ],
+            },
+            DashboardStack {
+                commits: vec![commit(
+                    "nnnn",
+                    "Delta",
+                    SyncStatus::Synced,
+                    pr(3, true, None, CiState::Pending, "2024-01-10T11:30:00Z"),
+                )],
+            },
+        ],
+    };
+    let mut out = vec![];
+    write_dashboard(&dashboard, now, &mut out).unwrap();
+    assert_snapshot!(String::from_utf8(out).unwrap(), @"
+    Stack mmmm (3 commits)
+    ? mmmm                                           Gamma
+    ✗ llll    #12 changes requested CI failed    5h  Beta
+    ✓ kkkk     #1 approved          CI passed    8d  Alpha
+
+    Stack nnnn (1 commit)
+    ✓ nnnn     #3 draft             CI running  30m  Delta
+    ");
+}
+
+#[test]
+fn test_command_messages() {
+    let message = |id: &str, args: &[(&str, &str)]| {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, *value);
+        }
+        i18n::format(id, &fluent_args)
+    };
+    let url = ("url", "https://github.com/o/r/pull/1");
+    let branch = ("branch", "test/klmnopqr");
+    let revision = ("revision", "@");
+    let out = [
+        message("pr-created", &[url]),
+        message("pr-updated", &[url]),
+        message("pr-branch-exists", &[branch]),
+        message("pr-branch-missing", &[branch]),
+        message("pr-not-open", &[branch]),
+        message("pr-empty-description", &[]),
+        message("create-on-trunk", &[revision]),
+        message("pr-on-trunk-update", &[revision]),
+        message("restack-local-changes", &[]),
+        message("restack-base-unchanged", &[]),
+        message("update-no-changes", &[]),
+        message("update-commit-unchanged", &[]),
+        message("parent-no-pr", &[]),
+        message("parent-needs-restack", &[]),
+        message("parent-out-of-date", &[]),
+        message("status-on-trunk", &[]),
+    ]
+    .join("\n");
+    assert_snapshot!(out, @r#"
+    Created PR: https://github.com/o/r/pull/1
+    Updated PR: https://github.com/o/r/pull/1
+    PR branch already exists: test/klmnopqr
+    PR branch test/klmnopqr does not exist. Use 'jr create' to create a new PR.
+    No open PR found for branch test/klmnopqr. The PR may have been closed or merged.
+    Cannot create PR with empty description
+    Revision @ is on trunk, so there's no change to create a PR for. Start a stack with `jj new`, then run 'jr create'.
+    Revision @ is on trunk, so there's no PR to update; it may already have been merged. Run 'jr status' to see your stack.
+    Cannot restack: commit has local changes.
+    Use 'jr update -m "<message>"' to update with your changes.
+    Base hasn't changed; no need to restack
+    No changes detected
+    Commit unchanged; use 'jr restack' instead
+    Parent commit has no PR branch. Create parent PR first (bottom-up).
+    Cannot update PR: parent PR needs restacking. Its base branch has been updated. Run 'jr restack' on the parent first.
+    Cannot update PR: parent PR is out of date. Update parent PRs first (starting from the bottom of the stack).
+    You're on trunk — no stack here. Start one with `jj new`.
+    "#);
+}
+
+#[test]
+fn test_tutorial_messages() {
+    let out = i18n::format("tutorial-create", &FluentArgs::new());
+    assert_snapshot!(out, @r#"
+    Describe a commit, then create its PR:
+
+        jj new -m "Add feature"
+        jr create
+
+    jr pushes a PR branch named after the change ID and opens a draft PR.
+    Create PRs from the bottom of the stack up; a commit's parent must have
+    a PR first.  In the sandbox, jr says what it would do, and the tutorial
+    plays GitHub's part:
+    "#);
+}